@@ -0,0 +1,1254 @@
+use crate::cli::{
+    CorrelateArgs, EffectArgs, ModelArgs, OutputFormat, RecommendArgs, RecommendTopic, WhatifArgs,
+};
+use crate::custom;
+use crate::daily::{self, DailyPoint};
+use crate::layout;
+use crate::milestones::{read_milestones, Milestone};
+use crate::phases;
+use crate::profiles;
+use crate::schema::{canonical_metric, column_name};
+use crate::units::DisplayUnits;
+use crate::wizard::{detect_timestamp_format, parse_timestamp};
+use crate::{metric_range, read_entries, AppError, GlobalOptions, MetricSummary, METRIC_NAMES};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use colored::*;
+use csv::ReaderBuilder;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const WORKOUT: &str = "workout_today";
+const MIN_DAYS: usize = 14; // Fewer days than this gives meaningless fits
+const BASELINE_DAYS: usize = 30; // "Current" values are the recent average
+const SLEEP: &str = "sleep_hours";
+const RESTED_METRICS: [&str; 2] = ["energy", "focus"]; // What "slept well" is judged by
+const MIN_CORRELATION_DAYS: usize = 7; // Fewer shared days than this says nothing
+const TOP_LINKS: usize = 10;
+const MIN_BIN_DAYS: usize = 3; // Sleep durations seen less often than this aren't compared
+
+// Daily value of a metric, with workouts counted as 0/1
+pub fn daily_value(point: &DailyPoint, name: &str) -> Option<f64> {
+    if name == WORKOUT {
+        Some(if point.workout { 1.0 } else { 0.0 })
+    } else {
+        point.values.get(name).copied()
+    }
+}
+
+// Pearson correlation of paired values (None when either side never varies)
+pub fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    (var_x > 0.0 && var_y > 0.0).then(|| cov / (var_x * var_y).sqrt())
+}
+
+// --- Daily series: one value per date, for joining metrics with each other or outside data ---
+pub type Series = BTreeMap<NaiveDate, f64>;
+
+// Every metric (and workouts, and numeric custom metrics) as a series labelled with its
+// column name
+pub fn metric_series(points: &[DailyPoint]) -> Vec<(String, Series)> {
+    METRIC_NAMES
+        .iter()
+        .copied()
+        .chain(std::iter::once(WORKOUT))
+        .chain(custom::numeric_columns())
+        .map(|name| {
+            let series = points
+                .iter()
+                .filter_map(|p| Some((p.date, daily_value(p, name)?)))
+                .collect();
+            (column_name(name).to_string(), series)
+        })
+        .collect()
+}
+
+// Correlation over the days both series have a value, with the number of such days
+pub fn correlation(a: &Series, b: &Series) -> Option<(f64, usize)> {
+    let pairs: Vec<(f64, f64)> = a
+        .iter()
+        .filter_map(|(date, x)| Some((*x, *b.get(date)?)))
+        .collect();
+    if pairs.len() < MIN_CORRELATION_DAYS {
+        return None;
+    }
+    Some((pearson(&pairs)?, pairs.len()))
+}
+
+// Every pair within one set of series, strongest first
+pub fn pair_links(series: &[(String, Series)]) -> Vec<(String, String, f64, usize)> {
+    let mut links = Vec::new();
+    for (i, (a, a_values)) in series.iter().enumerate() {
+        for (b, b_values) in &series[i + 1..] {
+            if let Some((r, days)) = correlation(a_values, b_values) {
+                links.push((a.clone(), b.clone(), r, days));
+            }
+        }
+    }
+    links.sort_by(|x, y| y.2.abs().total_cmp(&x.2.abs()));
+    links
+}
+
+// --- Ordinary least squares fit of y on several predictors ---
+pub struct Fit {
+    pub intercept: f64,
+    pub coefficients: Vec<f64>,
+    pub standard_errors: Vec<f64>, // Intercept first, then one per coefficient
+    pub r_squared: f64,
+    pub n: usize,
+}
+
+// Gauss-Jordan inverse; None when the matrix is singular (a predictor never varies,
+// or duplicates another)
+fn invert(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let k = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let identity = (0..k).map(|j| if i == j { 1.0 } else { 0.0 });
+            row.iter().copied().chain(identity).collect()
+        })
+        .collect();
+    for col in 0..k {
+        let pivot = (col..k).max_by(|&p, &q| a[p][col].abs().total_cmp(&a[q][col].abs()))?;
+        if a[pivot][col].abs() < 1e-9 {
+            return None;
+        }
+        a.swap(col, pivot);
+        let pivot_row: Vec<f64> = a[col].iter().map(|v| v / a[col][col]).collect();
+        for (i, row) in a.iter_mut().enumerate() {
+            if i == col {
+                row.clone_from(&pivot_row);
+            } else {
+                let factor = row[col];
+                for (value, p) in row.iter_mut().zip(&pivot_row) {
+                    *value -= factor * p;
+                }
+            }
+        }
+    }
+    Some(a.into_iter().map(|row| row[k..].to_vec()).collect())
+}
+
+// Rows with a leading 1 for the intercept
+fn design_matrix(rows: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    rows.iter()
+        .map(|row| std::iter::once(1.0).chain(row.iter().copied()).collect())
+        .collect()
+}
+
+// X'WX for per-row weights (all 1 for least squares)
+fn weighted_cross(design: &[Vec<f64>], weights: impl Iterator<Item = f64>) -> Vec<Vec<f64>> {
+    let k = design.first().map_or(0, Vec::len);
+    let mut m = vec![vec![0.0; k]; k];
+    for (x, w) in design.iter().zip(weights) {
+        for i in 0..k {
+            for j in 0..k {
+                m[i][j] += w * x[i] * x[j];
+            }
+        }
+    }
+    m
+}
+
+pub fn least_squares(rows: &[Vec<f64>], ys: &[f64]) -> Option<Fit> {
+    let n = ys.len();
+    let k = rows.first()?.len() + 1; // +1 for the intercept
+    if n <= k {
+        return None;
+    }
+    // Normal equations: b = (X'X)^-1 X'y
+    let design = design_matrix(rows);
+    let inverse = invert(&weighted_cross(&design, std::iter::repeat(1.0)))?;
+    let xty: Vec<f64> = (0..k)
+        .map(|i| design.iter().zip(ys).map(|(x, y)| x[i] * y).sum())
+        .collect();
+    let b: Vec<f64> = inverse
+        .iter()
+        .map(|row| row.iter().zip(&xty).map(|(a, v)| a * v).sum())
+        .collect();
+
+    let mean_y = ys.iter().sum::<f64>() / n as f64;
+    let total: f64 = ys.iter().map(|y| (y - mean_y).powi(2)).sum();
+    let residual: f64 = design
+        .iter()
+        .zip(ys)
+        .map(|(x, y)| {
+            let predicted: f64 = x.iter().zip(&b).map(|(xi, bi)| xi * bi).sum();
+            (y - predicted).powi(2)
+        })
+        .sum();
+    let variance = residual / (n - k) as f64;
+    Some(Fit {
+        intercept: b[0],
+        coefficients: b[1..].to_vec(),
+        standard_errors: (0..k).map(|i| (variance * inverse[i][i]).sqrt()).collect(),
+        r_squared: if total > 0.0 {
+            1.0 - residual / total
+        } else {
+            0.0
+        },
+        n,
+    })
+}
+
+// --- Logistic regression for a yes/no outcome, by Newton's method ---
+// r_squared holds McFadden's pseudo r²; None when the fit doesn't settle, which
+// happens when the predictors separate the yes and no days perfectly.
+pub fn logistic(rows: &[Vec<f64>], ys: &[f64]) -> Option<Fit> {
+    let n = ys.len();
+    let k = rows.first()?.len() + 1;
+    if n <= k {
+        return None;
+    }
+    let design = design_matrix(rows);
+    let probabilities = |b: &[f64]| -> Vec<f64> {
+        design
+            .iter()
+            .map(|x| {
+                let z: f64 = x.iter().zip(b).map(|(xi, bi)| xi * bi).sum();
+                1.0 / (1.0 + (-z).exp())
+            })
+            .collect()
+    };
+    let mut b = vec![0.0; k];
+    let mut converged = false;
+    for _ in 0..50 {
+        let p = probabilities(&b);
+        let inverse = invert(&weighted_cross(&design, p.iter().map(|p| p * (1.0 - p))))?;
+        let gradient: Vec<f64> = (0..k)
+            .map(|i| {
+                design
+                    .iter()
+                    .zip(ys.iter().zip(&p))
+                    .map(|(x, (y, p))| x[i] * (y - p))
+                    .sum()
+            })
+            .collect();
+        let step: Vec<f64> = inverse
+            .iter()
+            .map(|row| row.iter().zip(&gradient).map(|(a, g)| a * g).sum())
+            .collect();
+        for (bi, s) in b.iter_mut().zip(&step) {
+            *bi += s;
+        }
+        if b.iter().any(|bi| bi.abs() > 30.0) {
+            return None;
+        }
+        if step.iter().all(|s| s.abs() < 1e-8) {
+            converged = true;
+            break;
+        }
+    }
+    if !converged {
+        return None;
+    }
+    let p = probabilities(&b);
+    let inverse = invert(&weighted_cross(&design, p.iter().map(|p| p * (1.0 - p))))?;
+    let log_likelihood = |p: &mut dyn Iterator<Item = f64>| -> f64 {
+        ys.iter()
+            .zip(p)
+            .map(|(y, p)| y * p.max(1e-12).ln() + (1.0 - y) * (1.0 - p).max(1e-12).ln())
+            .sum()
+    };
+    let fitted = log_likelihood(&mut p.iter().copied());
+    let mean_y = ys.iter().sum::<f64>() / n as f64;
+    let null = log_likelihood(&mut std::iter::repeat(mean_y));
+    Some(Fit {
+        intercept: b[0],
+        coefficients: b[1..].to_vec(),
+        standard_errors: (0..k).map(|i| inverse[i][i].sqrt()).collect(),
+        r_squared: if null < 0.0 { 1.0 - fitted / null } else { 0.0 },
+        n,
+    })
+}
+
+// --- `whatif` command: estimate how other metrics move if some change ---
+pub fn run_whatif(options: &GlobalOptions, args: &WhatifArgs) -> Result<(), AppError> {
+    let units = DisplayUnits::from_config(&options.config);
+    let mut scenario: Vec<(&'static str, f64)> = Vec::new();
+    for setting in &args.settings {
+        let Some((name, value)) = setting.split_once('=') else {
+            return Err(AppError::UsageError(
+                "--set takes <metric>=<value>, e.g. sleep_hours=8".to_string(),
+            ));
+        };
+        let metric = if name == WORKOUT {
+            Some(WORKOUT)
+        } else {
+            canonical_metric(name)
+        };
+        let Some(metric) = metric else {
+            return Err(AppError::UsageError(format!(
+                "Unknown metric '{}' (choose from {}, {})",
+                name,
+                METRIC_NAMES.join(", "),
+                WORKOUT
+            )));
+        };
+        // Values are given in the metric's display unit, like everywhere else
+        let (min, max) = metric_range(metric);
+        let (min, max) = (units.convert(metric, min), units.convert(metric, max));
+        match value.parse::<f64>() {
+            Ok(v) if (min..=max).contains(&v) => {
+                scenario.push((metric, units.to_stored(metric, v)))
+            }
+            _ => {
+                return Err(AppError::UsageError(format!(
+                    "{} must be a number from {} to {}",
+                    name, min, max
+                )));
+            }
+        }
+    }
+    let points = daily::load(options)?;
+    let recent = &points[points.len().saturating_sub(BASELINE_DAYS)..];
+    let baseline = |name: &str| {
+        let values: Vec<f64> = recent.iter().filter_map(|p| daily_value(p, name)).collect();
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    };
+
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " What if... ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    for (metric, value) in &scenario {
+        println!(
+            "{} = {} (recently {})",
+            column_name(metric).bold(),
+            units.convert(metric, *value),
+            baseline(metric).map_or("n/a".to_string(), |b| format!(
+                "{:.1}",
+                units.convert(metric, b)
+            ))
+        );
+    }
+    for (metric, value) in &scenario {
+        let observed: Vec<f64> = points
+            .iter()
+            .filter_map(|p| daily_value(p, metric))
+            .collect();
+        let low = observed.iter().copied().fold(f64::MAX, f64::min);
+        let high = observed.iter().copied().fold(f64::MIN, f64::max);
+        if !observed.is_empty() && (*value < low || *value > high) {
+            eprintln!(
+                "Warning: {} is outside anything logged so far; the estimates extrapolate.",
+                column_name(metric)
+            );
+        }
+    }
+    println!("{}", layout::rule('-').cyan());
+
+    let mut predicted_any = false;
+    for target in METRIC_NAMES
+        .iter()
+        .filter(|m| scenario.iter().all(|(s, _)| s != *m))
+    {
+        // Days where the target and every scenario metric were recorded
+        let (rows, ys): (Vec<Vec<f64>>, Vec<f64>) = points
+            .iter()
+            .filter_map(|p| {
+                let row: Option<Vec<f64>> =
+                    scenario.iter().map(|(m, _)| daily_value(p, m)).collect();
+                Some((row?, daily_value(p, target)?))
+            })
+            .unzip();
+        if ys.len() < MIN_DAYS {
+            continue;
+        }
+        let Some(fit) = least_squares(&rows, &ys) else {
+            continue;
+        };
+        let Some(current) = baseline(target) else {
+            continue;
+        };
+        let shift: f64 = scenario
+            .iter()
+            .zip(&fit.coefficients)
+            .filter_map(|((m, v), b)| baseline(m).map(|base| b * (v - base)))
+            .sum();
+        let (min, max) = metric_range(target);
+        let predicted = (current + shift).clamp(min, max);
+        let delta = units.convert(target, predicted) - units.convert(target, current);
+        let delta_text = format!("{:+.1}", delta);
+        let delta_text = if delta.abs() < 0.1 {
+            delta_text.dimmed()
+        } else if delta > 0.0 {
+            delta_text.green()
+        } else {
+            delta_text.red()
+        };
+        layout::row(&format!(
+            "predicted {:<14} {:>5.1} -> {:>5.1}  {}  {}",
+            column_name(target),
+            units.convert(target, current),
+            units.convert(target, predicted),
+            delta_text,
+            format!("(r² {:.2}, {} days)", fit.r_squared, fit.n).dimmed()
+        ));
+        predicted_any = true;
+    }
+    if !predicted_any {
+        println!(
+            "{}",
+            format!(
+                "Not enough history: need at least {} days with these metrics recorded.",
+                MIN_DAYS
+            )
+            .dimmed()
+        );
+        return Ok(());
+    }
+    println!();
+    println!(
+        "{}",
+        "Rough estimates from a linear fit on your daily averages. They show association, not cause."
+            .dimmed()
+    );
+    Ok(())
+}
+
+// --- `recommend sleep`: the sleep duration after which energy and focus run highest ---
+pub fn run_recommend(options: &GlobalOptions, args: &RecommendArgs) -> Result<(), AppError> {
+    let RecommendTopic::Sleep = args.topic;
+    let wake_text = args.wake.clone().or_else(|| options.config.wake_time.clone());
+    let wake = match wake_text
+        .as_deref()
+        .map(|t| NaiveTime::parse_from_str(t, "%H:%M"))
+    {
+        Some(Ok(time)) => Some(time),
+        Some(Err(_)) => {
+            return Err(AppError::UsageError(
+                "Wake time must be HH:MM, e.g. 07:00".to_string(),
+            ));
+        }
+        None => None,
+    };
+
+    // Sleep is logged with the morning entry, so it pairs with that same day's ratings
+    let units = DisplayUnits::from_config(&options.config);
+    let days: Vec<(f64, f64)> = daily::load(options)?
+        .iter()
+        .filter_map(|p| {
+            let sleep = daily_value(p, SLEEP)?;
+            let rested: Vec<f64> = RESTED_METRICS
+                .iter()
+                .filter_map(|m| daily_value(p, m))
+                .collect();
+            (!rested.is_empty()).then(|| (sleep, rested.iter().sum::<f64>() / rested.len() as f64))
+        })
+        .collect();
+
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Sleep recommendation ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    if days.len() < MIN_DAYS {
+        println!(
+            "{}",
+            format!(
+                "Not enough history: need at least {} days with sleep and energy or focus recorded ({} so far).",
+                MIN_DAYS,
+                days.len()
+            )
+            .dimmed()
+        );
+        return Ok(());
+    }
+
+    // Average next-day score for each half-hour of sleep
+    let mut bins: BTreeMap<i64, Vec<f64>> = BTreeMap::new();
+    for (sleep, score) in &days {
+        bins.entry((sleep * 2.0).round() as i64)
+            .or_default()
+            .push(*score);
+    }
+    let sleep_unit = units.unit(SLEEP).to_string();
+    println!(
+        "Average {} by sleep the night before ({} days):",
+        RESTED_METRICS
+            .iter()
+            .map(|m| column_name(m))
+            .collect::<Vec<_>>()
+            .join(" and "),
+        days.len()
+    );
+    let mut best_bin: Option<(f64, f64)> = None;
+    for (half_hours, scores) in &bins {
+        let sleep = *half_hours as f64 / 2.0;
+        let mean = scores.iter().sum::<f64>() / scores.len() as f64;
+        let line = format!(
+            "  {:>6.1} {:<8} {:>4.1}  {:<10}  ({} days)",
+            units.convert(SLEEP, sleep),
+            sleep_unit,
+            mean,
+            "#".repeat(mean.round() as usize),
+            scores.len()
+        );
+        if scores.len() < MIN_BIN_DAYS {
+            layout::row(&line.dimmed().to_string());
+            continue;
+        }
+        layout::row(&line);
+        if best_bin.is_none_or(|(_, best)| mean > best) {
+            best_bin = Some((sleep, mean));
+        }
+    }
+    println!("{}", layout::rule('-').cyan());
+
+    // A hump-shaped fit gives a smoother answer than the single best bucket
+    let rows: Vec<Vec<f64>> = days.iter().map(|(s, _)| vec![*s, s * s]).collect();
+    let ys: Vec<f64> = days.iter().map(|(_, y)| *y).collect();
+    let low = days.iter().map(|(s, _)| *s).fold(f64::MAX, f64::min);
+    let high = days.iter().map(|(s, _)| *s).fold(f64::MIN, f64::max);
+    let fitted = least_squares(&rows, &ys).and_then(|fit| {
+        let (b, c) = (fit.coefficients[0], fit.coefficients[1]);
+        let peak = -b / (2.0 * c);
+        (c < 0.0 && (low..=high).contains(&peak)).then_some((peak, fit.r_squared))
+    });
+    let optimum = match (fitted, best_bin) {
+        (Some((peak, r_squared)), _) => {
+            println!(
+                "Apparent optimum: {} {} {}",
+                format!("{:.1}", units.convert(SLEEP, peak)).bold().green(),
+                sleep_unit,
+                format!("(curve fit, r² {:.2})", r_squared).dimmed()
+            );
+            peak
+        }
+        (None, Some((sleep, _))) => {
+            println!(
+                "Apparent optimum: {} {} {}",
+                format!("{:.1}", units.convert(SLEEP, sleep)).bold().green(),
+                sleep_unit,
+                "(best-rated amount; no clear peak in the curve)".dimmed()
+            );
+            sleep
+        }
+        (None, None) => {
+            println!(
+                "{}",
+                format!(
+                    "No sleep amount has been seen on {} or more days yet.",
+                    MIN_BIN_DAYS
+                )
+                .dimmed()
+            );
+            return Ok(());
+        }
+    };
+
+    match wake {
+        Some(wake) => {
+            // Round to the nearest quarter hour; nobody sets a bedtime of 22:47
+            let minutes = ((optimum * 60.0 / 15.0).round() * 15.0) as i64;
+            let bedtime = wake - Duration::minutes(minutes);
+            println!(
+                "Be asleep by {} to wake at {}",
+                bedtime.format("%H:%M").to_string().bold().yellow(),
+                wake.format("%H:%M")
+            );
+        }
+        None => println!(
+            "{}",
+            "Set wake_time = \"07:00\" in config.toml (or pass --wake) for a bedtime.".dimmed()
+        ),
+    }
+    println!();
+    println!(
+        "{}",
+        "Based on your own history only. It shows association, not cause.".dimmed()
+    );
+    Ok(())
+}
+
+// --- External daily data joined by date (weather, pollen, anything with a date column) ---
+// Numeric columns become series; several rows on one date are averaged.
+fn read_external(path: &Path, on: &str) -> Result<Vec<(String, Series)>, AppError> {
+    let mut rdr = ReaderBuilder::new().flexible(true).from_path(path)?;
+    let headers: Vec<String> = rdr
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let Some(date_col) = headers.iter().position(|h| h.eq_ignore_ascii_case(on)) else {
+        return Err(AppError::UsageError(format!(
+            "{} has no '{}' column (columns: {}). Pick one with --on <column>.",
+            path.display(),
+            on,
+            headers.join(", ")
+        )));
+    };
+    let mut rows = Vec::new();
+    for result in rdr.records() {
+        match result {
+            Ok(record) => rows.push(record),
+            Err(e) => eprintln!("Warning: Skipping corrupted CSV record: {}", e),
+        }
+    }
+    let samples: Vec<&str> = rows
+        .iter()
+        .filter_map(|r| r.get(date_col))
+        .filter(|v| !v.trim().is_empty())
+        .take(20)
+        .collect();
+    let Some(format) = detect_timestamp_format(&samples) else {
+        return Err(AppError::UsageError(format!(
+            "Can't read the dates in column '{}' of {}",
+            on,
+            path.display()
+        )));
+    };
+
+    let mut columns = Vec::new();
+    for (index, header) in headers.iter().enumerate() {
+        if index == date_col {
+            continue;
+        }
+        let mut sums: BTreeMap<NaiveDate, (f64, usize)> = BTreeMap::new();
+        let mut numeric = true;
+        for row in &rows {
+            let value = row.get(index).unwrap_or("").trim();
+            if value.is_empty() {
+                continue;
+            }
+            let Ok(value) = value.parse::<f64>() else {
+                numeric = false; // Text columns (conditions, notes) can't be correlated
+                break;
+            };
+            // Calendar dates stay as written; full timestamps go through UTC like our own
+            let text = row.get(date_col).unwrap_or("").trim();
+            let date = NaiveDate::parse_from_str(text, format)
+                .ok()
+                .or_else(|| parse_timestamp(text, format).map(|ts| ts.date_naive()));
+            if let Some(date) = date {
+                let sum = sums.entry(date).or_default();
+                sum.0 += value;
+                sum.1 += 1;
+            }
+        }
+        if numeric && !sums.is_empty() {
+            let series = sums
+                .into_iter()
+                .map(|(date, (sum, n))| (date, sum / n as f64))
+                .collect();
+            columns.push((header.clone(), series));
+        }
+    }
+    Ok(columns)
+}
+
+// Another profile's daily values, joined by date like an outside CSV: each metric it has
+// logged, labelled e.g. `trial:focus`
+fn profile_series(options: &GlobalOptions, name: &str) -> Result<Vec<(String, Series)>, AppError> {
+    let data_file = profiles::joined_data_file(options, name)?;
+    let points = daily::daily_points(read_entries(&data_file)?);
+    if points.is_empty() {
+        return Err(AppError::UsageError(format!(
+            "Profile '{}' has nothing logged yet ({})",
+            name, data_file
+        )));
+    }
+    Ok(metric_series(&points)
+        .into_iter()
+        .filter(|(_, series)| !series.is_empty())
+        .map(|(column, series)| (format!("{}:{}", name, column), series))
+        .collect())
+}
+
+// --- `correlate` command: how metrics move together, optionally with outside data ---
+pub fn run_correlate(options: &GlobalOptions, args: &CorrelateArgs) -> Result<(), AppError> {
+    let on = &args.on;
+    let external = match (&args.with, &args.with_profile) {
+        (Some(path), _) => {
+            let columns = read_external(Path::new(path), on)?;
+            if columns.is_empty() {
+                return Err(AppError::UsageError(format!(
+                    "{} has no numeric columns besides '{}'",
+                    path, on
+                )));
+            }
+            Some((path.clone(), columns))
+        }
+        (_, Some(name)) => Some((format!("profile {}", name), profile_series(options, name)?)),
+        (None, None) => None,
+    };
+    let points = daily::load(options)?;
+    let metrics = metric_series(&points);
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Correlations ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    if let (Some(first), Some(last)) = (points.first(), points.last()) {
+        if let Some(note) = phases::mixed_note(&options.data_file(), first.date, last.date)? {
+            println!("{}", note.yellow());
+        }
+    }
+
+    let Some((source, external)) = external else {
+        let links = pair_links(&metrics);
+        if links.is_empty() {
+            println!("{}", "Not enough days to compare metrics.".dimmed());
+            return Ok(());
+        }
+        for (a, b, r, days) in links.iter().take(TOP_LINKS) {
+            layout::row(&format!(
+                "  {:<14} ~ {:<14} r = {:+.2} {}",
+                a,
+                b,
+                r,
+                format!("({} days)", days).dimmed()
+            ));
+        }
+        println!();
+        println!("{}", "Correlation shows association, not cause.".dimmed());
+        return Ok(());
+    };
+
+    for (column, values) in &external {
+        println!(
+            "{} {}",
+            column.bold(),
+            format!("({} days in {})", values.len(), source).dimmed()
+        );
+        let mut links: Vec<(&String, f64, usize)> = metrics
+            .iter()
+            .filter_map(|(metric, series)| {
+                let (r, days) = correlation(values, series)?;
+                Some((metric, r, days))
+            })
+            .collect();
+        links.sort_by(|x, y| y.1.abs().total_cmp(&x.1.abs()));
+        if links.is_empty() {
+            println!(
+                "  {}",
+                format!(
+                    "Fewer than {} days overlap with your data.",
+                    MIN_CORRELATION_DAYS
+                )
+                .dimmed()
+            );
+        }
+        for (metric, r, days) in links {
+            layout::row(&format!(
+                "  ~ {:<14} r = {:+.2} {}",
+                metric,
+                r,
+                format!("({} days)", days).dimmed()
+            ));
+        }
+    }
+    println!("{}", layout::rule('-').cyan());
+
+    // How much of each metric the outside columns explain together
+    println!("{}", format!(" Explained by {} together ", source).bold());
+    for (metric, series) in &metrics {
+        let (rows, ys): (Vec<Vec<f64>>, Vec<f64>) = series
+            .iter()
+            .filter_map(|(date, y)| {
+                let row: Option<Vec<f64>> = external
+                    .iter()
+                    .map(|(_, values)| values.get(date).copied())
+                    .collect();
+                Some((row?, *y))
+            })
+            .unzip();
+        if ys.len() < MIN_DAYS {
+            continue;
+        }
+        if let Some(fit) = least_squares(&rows, &ys) {
+            layout::row(&format!(
+                "  {:<14} r² {:.2} {}",
+                metric,
+                fit.r_squared,
+                format!("({} days)", fit.n).dimmed()
+            ));
+        }
+    }
+    println!();
+    println!("{}", "Correlation shows association, not cause.".dimmed());
+    Ok(())
+}
+
+// --- `friction` command: how long logging takes and how often answers are redone ---
+pub fn run_friction(options: &GlobalOptions) -> Result<(), AppError> {
+    let mut months: BTreeMap<String, (u32, u32, u32)> = BTreeMap::new(); // seconds, edits, entries
+    for entry in read_entries(&options.data_file())? {
+        let (Some(seconds), Some(ts)) = (entry.session_seconds, entry.parsed_timestamp()) else {
+            continue;
+        };
+        let month = months.entry(ts.format("%Y-%m").to_string()).or_default();
+        month.0 += seconds;
+        month.1 += entry.session_edits.unwrap_or(0);
+        month.2 += 1;
+    }
+
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Logging friction ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    if months.is_empty() {
+        println!(
+            "{}",
+            "No session data yet. Set session_meta = true in config.toml to record it.".dimmed()
+        );
+        return Ok(());
+    }
+    layout::row(&format!(
+        "{:<8} {:>7} {:>9} {:>10}",
+        "Month", "Entries", "Avg secs", "Edits/log"
+    ));
+    for (month, (seconds, edits, n)) in &months {
+        layout::row(&format!(
+            "{:<8} {:>7} {:>9.0} {:>10.2}",
+            month,
+            n,
+            f64::from(*seconds) / f64::from(*n),
+            f64::from(*edits) / f64::from(*n)
+        ));
+    }
+    Ok(())
+}
+
+// --- `effect` command: each metric before vs after a milestone, with effect sizes ---
+#[derive(Serialize)]
+struct EffectRow {
+    metric: String,
+    before: Option<f64>, // Mean daily value, in display units
+    after: Option<f64>,
+    change: Option<f64>,
+    days_before: usize,
+    days_after: usize,
+    cohens_d: Option<f64>,
+}
+
+pub fn mean_sd(values: &[f64]) -> Option<(f64, f64)> {
+    let s = MetricSummary::from_values(values)?;
+    let n = values.len() as f64;
+    let variance = values.iter().map(|v| (v - s.mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+    Some((s.mean, variance.sqrt()))
+}
+
+// Standardized mean difference over the pooled standard deviation
+pub fn cohens_d(before: &[f64], after: &[f64]) -> Option<f64> {
+    if before.len() < 2 || after.len() < 2 {
+        return None;
+    }
+    let ((m1, s1), (m2, s2)) = (mean_sd(before)?, mean_sd(after)?);
+    let (n1, n2) = (before.len() as f64, after.len() as f64);
+    let pooled = (((n1 - 1.0) * s1.powi(2) + (n2 - 1.0) * s2.powi(2)) / (n1 + n2 - 2.0)).sqrt();
+    (pooled > 0.0).then(|| (m2 - m1) / pooled)
+}
+
+// Conventional reading of |d|
+fn effect_label(d: f64) -> &'static str {
+    match d.abs() {
+        d if d < 0.2 => "negligible",
+        d if d < 0.5 => "small",
+        d if d < 0.8 => "medium",
+        _ => "large",
+    }
+}
+
+// A milestone by date (any date works) or by its label: exact first, then a unique part
+fn find_milestone(milestones: &[Milestone], query: &str) -> Result<Milestone, AppError> {
+    if let Ok(date) = NaiveDate::parse_from_str(query, "%Y-%m-%d") {
+        return Ok(milestones
+            .iter()
+            .find(|m| m.date == date)
+            .cloned()
+            .unwrap_or(Milestone {
+                date,
+                label: date.to_string(),
+            }));
+    }
+    let query = query.to_lowercase();
+    if let Some(exact) = milestones.iter().find(|m| m.label.to_lowercase() == query) {
+        return Ok(exact.clone());
+    }
+    let matches: Vec<&Milestone> = milestones
+        .iter()
+        .filter(|m| m.label.to_lowercase().contains(&query))
+        .collect();
+    match matches.as_slice() {
+        [one] => Ok((*one).clone()),
+        [] => Err(AppError::UsageError(format!(
+            "No milestone matches '{}' (see `annotate`, or give a date)",
+            query
+        ))),
+        several => Err(AppError::UsageError(format!(
+            "'{}' matches several milestones: {}",
+            query,
+            several
+                .iter()
+                .map(|m| format!("{} {}", m.date, m.label))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+pub fn run_effect(options: &GlobalOptions, args: &EffectArgs) -> Result<(), AppError> {
+    if args.window == 0 {
+        return Err(AppError::UsageError(
+            "--window must be at least one day".to_string(),
+        ));
+    }
+    let milestone = find_milestone(&read_milestones(&options.data_file())?, &args.milestone)?;
+    let units = DisplayUnits::from_config(&options.config);
+    let points = daily::load(options)?;
+    let window = args.window as i64;
+    let in_window = |range: std::ops::Range<i64>| -> Vec<&DailyPoint> {
+        points
+            .iter()
+            .filter(|p| range.contains(&(p.date - milestone.date).num_days()))
+            .collect()
+    };
+    // The milestone's own day counts as "after"
+    let (before_days, after_days) = (in_window(-window..0), in_window(0..window));
+
+    let rows: Vec<EffectRow> = METRIC_NAMES
+        .iter()
+        .copied()
+        .chain(std::iter::once(WORKOUT))
+        .map(|name| {
+            let values = |days: &[&DailyPoint]| -> Vec<f64> {
+                days.iter()
+                    .filter_map(|p| daily_value(p, name))
+                    .map(|v| {
+                        if name == WORKOUT {
+                            v
+                        } else {
+                            units.convert(name, v)
+                        }
+                    })
+                    .collect()
+            };
+            let (before, after) = (values(&before_days), values(&after_days));
+            let before_mean = mean_sd(&before).map(|(m, _)| m);
+            let after_mean = mean_sd(&after).map(|(m, _)| m);
+            EffectRow {
+                metric: column_name(name).to_string(),
+                before: before_mean,
+                after: after_mean,
+                change: before_mean.zip(after_mean).map(|(b, a)| a - b),
+                days_before: before.len(),
+                days_after: after.len(),
+                cohens_d: cohens_d(&before, &after),
+            }
+        })
+        .collect();
+
+    match options.format {
+        OutputFormat::Json => {
+            let report = serde_json::json!({
+                "milestone": milestone,
+                "window_days": window,
+                "metrics": rows,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+            for row in &rows {
+                wtr.serialize(row)?;
+            }
+            wtr.flush()?;
+            return Ok(());
+        }
+        OutputFormat::Table | OutputFormat::Parquet => {}
+    }
+
+    println!("{}", layout::rule('=').cyan());
+    println!(
+        "{}",
+        format!(" Effect of \"{}\" ({}) ", milestone.label, milestone.date)
+            .bold()
+            .cyan()
+    );
+    println!("{}", layout::rule('=').cyan());
+    println!(
+        "{} days before vs the {} days from it on ({} and {} days logged)",
+        window,
+        window,
+        before_days.len(),
+        after_days.len()
+    );
+    println!();
+    layout::row(&format!(
+        "{:<14} {:>6} {:>6} {:>7} {:>6}  Effect",
+        "Metric", "Before", "After", "Change", "d"
+    ));
+    let shown = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.2}", v));
+    for row in &rows {
+        let change = row.change.map_or("-".to_string(), |c| format!("{:+.2}", c));
+        let effect = match row.cohens_d {
+            Some(d) if d.abs() >= 0.5 => effect_label(d).bold().to_string(),
+            Some(d) => effect_label(d).to_string(),
+            None => "too few days".dimmed().to_string(),
+        };
+        layout::row(&format!(
+            "{:<14} {:>6} {:>6} {:>7} {:>6}  {}",
+            row.metric,
+            shown(row.before),
+            shown(row.after),
+            change,
+            row.cohens_d
+                .map_or("-".to_string(), |d| format!("{:+.2}", d)),
+            effect
+        ));
+    }
+    println!();
+    println!(
+        "{}",
+        "d is the change in standard deviations; it shows a difference, not its cause.".dimmed()
+    );
+    Ok(())
+}
+
+// --- `model` command: regression of one variable on several others ---
+#[derive(Serialize)]
+struct TermRow {
+    term: String,
+    coefficient: f64,
+    std_error: f64,
+    ci_low: f64, // 95% confidence interval
+    ci_high: f64,
+    odds_ratio: Option<f64>, // Logistic models only: exp(coefficient)
+}
+
+// Two-sided 95% critical value of Student's t (Cornish-Fisher expansion around 1.96)
+fn t_critical(df: usize) -> f64 {
+    let z: f64 = 1.959964;
+    let df = df.max(1) as f64;
+    z + (z.powi(3) + z) / (4.0 * df)
+        + (5.0 * z.powi(5) + 16.0 * z.powi(3) + 3.0 * z) / (96.0 * df.powi(2))
+}
+
+// A metric (in its display unit), workouts as 0/1, or a column of the --with file
+fn model_variable(
+    name: &str,
+    points: &[DailyPoint],
+    units: &DisplayUnits,
+    external: &[(String, Series)],
+) -> Result<(String, Series), AppError> {
+    let metric = if name == WORKOUT {
+        Some(WORKOUT)
+    } else {
+        canonical_metric(name).or_else(|| custom::numeric_columns().find(|c| *c == name))
+    };
+    if let Some(metric) = metric {
+        let series = points
+            .iter()
+            .filter_map(|p| {
+                let value = daily_value(p, metric)?;
+                let shown = if metric == WORKOUT {
+                    value
+                } else {
+                    units.convert(metric, value)
+                };
+                Some((p.date, shown))
+            })
+            .collect();
+        return Ok((column_name(metric).to_string(), series));
+    }
+    external
+        .iter()
+        .find(|(column, _)| column.eq_ignore_ascii_case(name))
+        .cloned()
+        .ok_or_else(|| {
+            AppError::UsageError(format!(
+                "Unknown variable '{}': use a metric ({}, {}) or a numeric column of the --with file",
+                name,
+                METRIC_NAMES.join(", "),
+                WORKOUT
+            ))
+        })
+}
+
+pub fn run_model(options: &GlobalOptions, args: &ModelArgs) -> Result<(), AppError> {
+    // An unquoted ~ on its own reaches us already expanded to the home directory
+    let home = std::env::var("HOME").ok();
+    let formula = args
+        .formula
+        .iter()
+        .map(|word| {
+            if Some(word) == home.as_ref() {
+                "~"
+            } else {
+                word.as_str()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let usage = || {
+        AppError::UsageError(
+            "Write the model as: model \"energy ~ sleep_hours + workout_today\"".to_string(),
+        )
+    };
+    let (outcome, predictors) = formula.split_once('~').ok_or_else(usage)?;
+    let predictors: Vec<&str> = predictors.split('+').map(str::trim).collect();
+    if outcome.trim().is_empty() || predictors.iter().any(|p| p.is_empty()) {
+        return Err(usage());
+    }
+
+    let external = match &args.with {
+        Some(path) => read_external(Path::new(path), &args.on)?,
+        None => Vec::new(),
+    };
+    let units = DisplayUnits::from_config(&options.config);
+    let points = daily::load(options)?;
+    let (outcome, ys_by_date) = model_variable(outcome.trim(), &points, &units, &external)?;
+    let predictors: Vec<(String, Series)> = predictors
+        .iter()
+        .map(|name| model_variable(name, &points, &units, &external))
+        .collect::<Result<_, _>>()?;
+    if predictors.iter().any(|(name, _)| *name == outcome) {
+        return Err(AppError::UsageError(format!(
+            "{} can't predict itself",
+            outcome
+        )));
+    }
+
+    // Days on which the outcome and every predictor have a value
+    let (rows, ys): (Vec<Vec<f64>>, Vec<f64>) = ys_by_date
+        .iter()
+        .filter_map(|(date, y)| {
+            let row: Option<Vec<f64>> = predictors
+                .iter()
+                .map(|(_, values)| values.get(date).copied())
+                .collect();
+            Some((row?, *y))
+        })
+        .unzip();
+    if ys.len() < MIN_DAYS {
+        return Err(AppError::UsageError(format!(
+            "Only {} days have all of these; a model needs at least {}",
+            ys.len(),
+            MIN_DAYS
+        )));
+    }
+    // A yes/no outcome (like workouts) gets a logistic model
+    let binary = ys.iter().all(|y| *y == 0.0 || *y == 1.0);
+    let fit = if binary {
+        logistic(&rows, &ys)
+    } else {
+        least_squares(&rows, &ys)
+    };
+    let Some(fit) = fit else {
+        return Err(AppError::UsageError(
+            "Can't fit this model: a predictor never varies, duplicates another, or predicts the outcome perfectly"
+                .to_string(),
+        ));
+    };
+    let critical = if binary {
+        1.959964
+    } else {
+        t_critical(fit.n - fit.standard_errors.len())
+    };
+    let terms: Vec<TermRow> = std::iter::once("(intercept)".to_string())
+        .chain(predictors.iter().map(|(name, _)| name.clone()))
+        .zip(std::iter::once(fit.intercept).chain(fit.coefficients.iter().copied()))
+        .zip(&fit.standard_errors)
+        .map(|((term, coefficient), se)| TermRow {
+            term,
+            coefficient,
+            std_error: *se,
+            ci_low: coefficient - critical * se,
+            ci_high: coefficient + critical * se,
+            odds_ratio: binary.then(|| coefficient.exp()),
+        })
+        .collect();
+
+    match options.format {
+        OutputFormat::Json => {
+            let report = serde_json::json!({
+                "outcome": outcome,
+                "model": if binary { "logistic" } else { "linear" },
+                "days": fit.n,
+                "r_squared": fit.r_squared,
+                "terms": terms,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+            for term in &terms {
+                wtr.serialize(term)?;
+            }
+            wtr.flush()?;
+            return Ok(());
+        }
+        OutputFormat::Table | OutputFormat::Parquet => {}
+    }
+
+    let formula = format!(
+        "{} ~ {}",
+        outcome,
+        predictors
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(" + ")
+    );
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", format!(" Model: {} ", formula).bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    println!(
+        "{} regression on {} days, {} {:.2}",
+        if binary { "Logistic" } else { "Linear" },
+        fit.n,
+        if binary { "pseudo r²" } else { "r²" },
+        fit.r_squared
+    );
+    println!();
+    let mut header = format!("{:<14} {:>8}  {:<18}", "Term", "Coef", "95% CI");
+    if binary {
+        header.push_str(&format!(" {:>6}", "Odds"));
+    }
+    layout::row(&header.bold().to_string());
+    for term in &terms {
+        let mut line = format!(
+            "{:<14} {:>+8.3}  [{:>+7.3}, {:>+7.3}]",
+            term.term, term.coefficient, term.ci_low, term.ci_high
+        );
+        if let Some(odds) = term.odds_ratio {
+            line.push_str(&format!(" {:>6.2}", odds));
+        }
+        // An interval that excludes zero is a link worth noticing
+        let clear = term.term != "(intercept)" && (term.ci_low > 0.0 || term.ci_high < 0.0);
+        layout::row(&if clear { line.bold().to_string() } else { line });
+    }
+    println!();
+    println!(
+        "{}",
+        if binary {
+            format!(
+                "Coefficients are log-odds of {} per unit of each term, the others held fixed.",
+                outcome
+            )
+        } else {
+            format!(
+                "Coefficients are the change in {} per unit of each term, the others held fixed.",
+                outcome
+            )
+        }
+        .dimmed()
+    );
+    println!(
+        "{}",
+        "Bold terms have an interval that excludes zero. They show association, not cause."
+            .dimmed()
+    );
+    Ok(())
+}
@@ -0,0 +1,288 @@
+use crate::cli::ReviewArgs;
+use crate::constraints::{self, Violation};
+use crate::edit::{ask_value, label};
+use crate::import::{replace_entries, save_sorted};
+use crate::layout;
+use crate::replay;
+use crate::schema::column_name;
+use crate::trash::move_to_trash;
+use crate::units::DisplayUnits;
+use crate::{read_entries, AppError, EntryKind, GlobalOptions, LogEntry, METRIC_NAMES, THEME};
+use colored::*;
+use dialoguer::{Input, Select};
+
+const GAP_DAYS: i64 = 14; // Longer breaks between logged days are worth a note
+
+// --- Something in the data that would skew the statistics ---
+enum Anomaly {
+    Impossible(Violation),
+    // A second start of the same day, or the same answers logged twice
+    Duplicate { timestamp: String, of: String },
+    Gap { timestamp: String, days: i64 },
+}
+
+impl Anomaly {
+    fn timestamp(&self) -> &str {
+        match self {
+            Anomaly::Impossible(v) => &v.timestamp,
+            Anomaly::Duplicate { timestamp, .. } | Anomaly::Gap { timestamp, .. } => timestamp,
+        }
+    }
+}
+
+// Everything but when and how the entry was logged
+fn same_answers(a: &LogEntry, b: &LogEntry) -> bool {
+    METRIC_NAMES.iter().all(|m| a.metric(m) == b.metric(m))
+        && a.workout_today == b.workout_today
+        && a.remarks == b.remarks
+}
+
+// In file order, so the review pages through the history once
+fn find_all(entries: &[LogEntry]) -> Vec<Anomaly> {
+    let mut found = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        found.extend(
+            constraints::check(entry)
+                .into_iter()
+                .map(Anomaly::Impossible),
+        );
+        let Some(date) = entry.parsed_timestamp().map(|ts| ts.date_naive()) else {
+            continue;
+        };
+        let previous = entries[..i]
+            .iter()
+            .rev()
+            .find_map(|e| Some((e, e.parsed_timestamp()?.date_naive())));
+        let Some((previous, previous_date)) = previous else {
+            continue;
+        };
+        let starts_day = matches!(
+            entry.entry_kind,
+            Some(EntryKind::FirstOfDay | EntryKind::Backfill)
+        );
+        if previous_date == date && (starts_day || same_answers(previous, entry)) {
+            found.push(Anomaly::Duplicate {
+                timestamp: entry.timestamp.clone(),
+                of: previous.timestamp.clone(),
+            });
+        } else if (date - previous_date).num_days() > GAP_DAYS {
+            found.push(Anomaly::Gap {
+                timestamp: entry.timestamp.clone(),
+                days: (date - previous_date).num_days(),
+            });
+        }
+    }
+    found
+}
+
+// Only anomalies in entries from `source` (a provenance kind) when one is given
+fn find(entries: &[LogEntry], source: Option<&str>) -> Vec<Anomaly> {
+    find_all(entries)
+        .into_iter()
+        .filter(|a| {
+            source.is_none_or(|source| {
+                entries
+                    .iter()
+                    .any(|e| e.timestamp == a.timestamp() && e.has_provenance(source))
+            })
+        })
+        .collect()
+}
+
+// After an import: point at `review` when the imported entries look off
+pub fn hint_after_import(data_file: &str) -> Result<(), AppError> {
+    let found = find(&read_entries(data_file)?, Some("import")).len();
+    if found > 0 {
+        println!(
+            "{}",
+            format!(
+                "{} possible problems in imported entries: run `review --source import` to go through them",
+                found
+            )
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+fn answers(entry: &LogEntry, units: &DisplayUnits) -> String {
+    let mut parts: Vec<String> = METRIC_NAMES
+        .iter()
+        .filter_map(|m| {
+            let value = entry.metric(m)?;
+            Some(format!("{} {}", column_name(m), units.convert(m, value)))
+        })
+        .collect();
+    if entry.workout_today {
+        parts.push("workout".to_string());
+    }
+    if parts.is_empty() {
+        "no answers".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+fn add_note(entry: &mut LogEntry) -> Result<bool, AppError> {
+    let note: String = replay::answer("Note", || {
+        Input::with_theme(&**THEME)
+            .with_prompt("Note")
+            .allow_empty(true)
+            .interact_text()
+    })?;
+    let note = note.trim();
+    if note.is_empty() {
+        return Ok(false);
+    }
+    entry.remarks = if entry.remarks.trim().is_empty() {
+        note.to_string()
+    } else {
+        format!("{}; {}", entry.remarks, note)
+    };
+    Ok(true)
+}
+
+// --- `review` command: page through anomalies and fix or annotate each one ---
+// Nothing is written until the end; deleted duplicates go to the trash.
+pub fn run_review(options: &GlobalOptions, args: &ReviewArgs) -> Result<(), AppError> {
+    let data_file = options.data_file();
+    let units = DisplayUnits::from_config(&options.config);
+    let original = read_entries(&data_file)?;
+    let mut entries = original.clone();
+    let anomalies = find(&entries, args.source.as_deref());
+    if anomalies.is_empty() {
+        println!("{}", "Nothing looks wrong.".green());
+        return Ok(());
+    }
+
+    let mut removed: Vec<LogEntry> = Vec::new();
+    let mut changed = 0;
+    for (n, anomaly) in anomalies.iter().enumerate() {
+        // An earlier answer may have deleted the entry
+        let Some(index) = entries
+            .iter()
+            .position(|e| e.timestamp == anomaly.timestamp())
+        else {
+            continue;
+        };
+        let problem = match anomaly {
+            Anomaly::Impossible(v) => format!("{} = {}: {}", v.column, v.value, v.problem),
+            Anomaly::Duplicate { of, .. } => format!("same day as {}", of),
+            Anomaly::Gap { days, .. } => format!("first entry after {} days without one", days),
+        };
+        println!();
+        println!(
+            "{}",
+            format!(" {}/{} {} ", n + 1, anomalies.len(), problem)
+                .bold()
+                .cyan()
+        );
+        println!("{}", layout::rule('-').cyan());
+        layout::row(&format!(
+            "{}  {}",
+            label(&entries[index]),
+            answers(&entries[index], &units)
+        ));
+        if let Anomaly::Duplicate { of, .. } = anomaly {
+            if let Some(other) = entries.iter().find(|e| &e.timestamp == of) {
+                layout::row(
+                    &format!("{}  {}", label(other), answers(other, &units))
+                        .dimmed()
+                        .to_string(),
+                );
+            }
+        }
+
+        // A row that still breaks a constraint can't be saved until its values are fixed
+        let metric = match anomaly {
+            Anomaly::Impossible(v) => METRIC_NAMES.iter().find(|m| **m == v.column),
+            _ => None,
+        };
+        let can_note = constraints::check(&entries[index]).is_empty();
+        let mut items: Vec<&str> = Vec::new();
+        if metric.is_some() {
+            items.extend(["Enter the right value", "Clear the value"]);
+        }
+        if matches!(anomaly, Anomaly::Duplicate { .. }) {
+            items.push("Delete this entry");
+        }
+        if can_note {
+            items.push("Add a note to the remarks");
+        }
+        items.extend(["Leave it", "Stop reviewing"]);
+        let choice = replay::answer("What should happen?", || {
+            Select::with_theme(&**THEME)
+                .with_prompt("What should happen?")
+                .items(&items)
+                .default(0)
+                .interact()
+        })?;
+
+        match items[choice] {
+            "Delete this entry" => {
+                removed.push(entries.remove(index));
+                continue;
+            }
+            "Stop reviewing" => break,
+            _ => {}
+        }
+        let entry = &mut entries[index];
+        let fixed = match (items[choice], metric) {
+            ("Enter the right value", Some(metric)) => match ask_value(&units, metric, None)? {
+                Some(value) => entry.set_metric(metric, value),
+                None => false,
+            },
+            ("Clear the value", Some(metric)) => entry.clear_metric(metric),
+            ("Add a note to the remarks", _) => add_note(entry)?,
+            _ => false,
+        };
+        if fixed {
+            entry.add_provenance("review");
+            changed += 1;
+        }
+    }
+
+    println!();
+    if changed == 0 && removed.is_empty() {
+        println!("{}", "Nothing changed.".dimmed());
+        return Ok(());
+    }
+    if options.dry_run {
+        println!(
+            "{}",
+            format!(
+                "Dry run: would change {} and delete {} entries",
+                changed,
+                removed.len()
+            )
+            .yellow()
+        );
+        return Ok(());
+    }
+    // Only the reviewed entries are written back, onto the file as it is now
+    let updated: Vec<LogEntry> = entries
+        .into_iter()
+        .filter(|e| !original.contains(e))
+        .collect();
+    let deleted = removed.len();
+    if removed.is_empty() {
+        save_sorted(&data_file, "review", |entries| {
+            replace_entries(entries, &updated)
+        })?;
+    } else {
+        let id = move_to_trash(&data_file, removed, "review", |entries| {
+            replace_entries(entries, &updated)
+        })?;
+        println!(
+            "{}",
+            format!("Deleted {} (undo with: trash restore {})", deleted, id).dimmed()
+        );
+    }
+    println!(
+        "{}",
+        format!("Reviewed: {} changed, {} deleted", changed, deleted)
+            .bold()
+            .green()
+    );
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use crate::cli::AuditArgs;
+use crate::{AppError, GlobalOptions, LogEntry};
+use chrono::Utc;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+// --- One data modification: what ran, when, and the rows it changed ---
+// Rows are stored as JSON objects so old records stay readable if the schema grows.
+#[derive(Serialize, Deserialize)]
+struct AuditRecord {
+    at: String,
+    action: String,
+    #[serde(default)]
+    details: String,
+    #[serde(default)]
+    changes: Vec<Change>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Change {
+    timestamp: String,
+    before: Option<Value>, // None for added rows
+    after: Option<Value>,  // None for removed rows
+}
+
+// The audit trail lives next to the data file: daily_metrics.csv -> daily_metrics.audit.jsonl
+fn audit_file(data_file: &str) -> PathBuf {
+    Path::new(data_file).with_extension("audit.jsonl")
+}
+
+// day_count is renumbered on every rewrite, so it is left out of the comparison
+fn row_values(entries: &[LogEntry]) -> BTreeMap<String, Value> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let mut value = serde_json::to_value(entry).ok()?;
+            value.as_object_mut()?.remove("day_count");
+            Some((entry.timestamp.clone(), value))
+        })
+        .collect()
+}
+
+// --- Rows that differ between two versions of the data, keyed by timestamp ---
+pub fn diff_entries(before: &[LogEntry], after: &[LogEntry]) -> Vec<Change> {
+    let mut before = row_values(before);
+    let after = row_values(after);
+    let mut changes = Vec::new();
+    for (timestamp, new) in after {
+        let old = before.remove(&timestamp);
+        if old.as_ref() != Some(&new) {
+            changes.push(Change {
+                timestamp,
+                before: old,
+                after: Some(new),
+            });
+        }
+    }
+    changes.extend(before.into_iter().map(|(timestamp, old)| Change {
+        timestamp,
+        before: Some(old),
+        after: None,
+    }));
+    changes
+}
+
+// --- Append a record to the audit trail (nothing is written for a no-op) ---
+pub fn record(
+    data_file: &str,
+    action: &str,
+    details: &str,
+    changes: Vec<Change>,
+) -> Result<(), AppError> {
+    if changes.is_empty() && details.is_empty() {
+        return Ok(());
+    }
+    let record = AuditRecord {
+        at: Utc::now().to_rfc3339(),
+        action: action.to_string(),
+        details: details.to_string(),
+        changes,
+    };
+    let line = serde_json::to_string(&record)
+        .map_err(|e| AppError::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_file(data_file))?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+fn field_changes(before: &Value, after: &Value) -> Vec<String> {
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return Vec::new();
+    };
+    after
+        .iter()
+        .filter(|(key, value)| before.get(*key) != Some(value))
+        .map(|(key, value)| {
+            format!(
+                "{} {} -> {}",
+                key,
+                before.get(key).unwrap_or(&Value::Null),
+                value
+            )
+        })
+        .collect()
+}
+
+// --- `audit` command: show the modification history, newest last ---
+pub fn run_audit(options: &GlobalOptions, args: &AuditArgs) -> Result<(), AppError> {
+    let limit = args.last;
+
+    let path = audit_file(&options.data_file());
+    if !path.exists() {
+        println!("{}", "No modifications recorded yet.".dimmed());
+        return Ok(());
+    }
+    let mut records = Vec::new();
+    for line in fs::read_to_string(&path)?.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<AuditRecord>(line) {
+            Ok(record) => records.push(record),
+            Err(e) => eprintln!("Warning: Skipping unreadable audit record: {}", e),
+        }
+    }
+    let skip = limit.map_or(0, |n| records.len().saturating_sub(n));
+
+    for record in records.iter().skip(skip) {
+        println!(
+            "{} {} {}",
+            record.at.dimmed(),
+            record.action.bold().cyan(),
+            record.details
+        );
+        for change in &record.changes {
+            match (&change.before, &change.after) {
+                (None, Some(_)) => println!("  {} {}", "+".green(), change.timestamp),
+                (Some(_), None) => println!("  {} {}", "-".red(), change.timestamp),
+                (Some(before), Some(after)) => {
+                    println!("  {} {}", "~".yellow(), change.timestamp);
+                    for line in field_changes(before, after) {
+                        println!("      {}", line);
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use crate::config::BackupConfig;
+use crate::store;
+use crate::AppError;
+use chrono::Local;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const BACKUPS_DIR: &str = "backups"; // Next to the data file
+const KEEP: usize = 10; // Snapshots kept per data file unless `backup.keep` says otherwise
+
+// --- Copies of the data file taken before every write ---
+// daily_metrics.csv -> backups/daily_metrics-20250314-213005.csv, or one per day
+// (backups/daily_metrics-20250314.csv) with `daily = true`. Older copies are rotated out.
+#[derive(Clone, Copy)]
+struct Policy {
+    keep: usize, // 0 turns backups off
+    daily: bool,
+}
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+pub fn install(config: Option<&BackupConfig>) {
+    let _ = POLICY.set(Policy {
+        keep: config.and_then(|c| c.keep).unwrap_or(KEEP),
+        daily: config.and_then(|c| c.daily).unwrap_or(false),
+    });
+}
+
+fn backups_dir(file: &Path) -> PathBuf {
+    file.parent().unwrap_or(Path::new("")).join(BACKUPS_DIR)
+}
+
+// daily_metrics.csv + "20250314" -> daily_metrics-20250314.csv
+fn backup_name(file: &Path, stamp: &str) -> String {
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    match file.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, stamp, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, stamp),
+    }
+}
+
+// Copies of this file, oldest first (the stamps sort by time)
+fn backups_of(file: &Path) -> Vec<PathBuf> {
+    let Ok(listing) = fs::read_dir(backups_dir(file)) else {
+        return Vec::new();
+    };
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    let prefix = format!("{}-", stem);
+    let suffix = file
+        .extension()
+        .map_or(String::new(), |ext| format!(".{}", ext.to_string_lossy()));
+    let mut found: Vec<PathBuf> = listing
+        .filter_map(|item| {
+            let path = item.ok()?.path();
+            let name = path.file_name()?.to_str()?;
+            let stamp = name.strip_prefix(&prefix)?.strip_suffix(&suffix)?;
+            let is_stamp = matches!(stamp.len(), 8 | 15)
+                && stamp.chars().all(|c| c.is_ascii_digit() || c == '-');
+            is_stamp.then_some(path)
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+fn snapshot_file(file: &Path, policy: Policy) -> Result<(), AppError> {
+    let stamp = if policy.daily {
+        Local::now().format("%Y%m%d")
+    } else {
+        Local::now().format("%Y%m%d-%H%M%S")
+    }
+    .to_string();
+    let dir = backups_dir(file);
+    let target = dir.join(backup_name(file, &stamp));
+    // Several writes in one command (or one day) keep the state from before the first
+    if !target.exists() {
+        fs::create_dir_all(&dir)?;
+        fs::copy(file, &target)?;
+    }
+    let copies = backups_of(file);
+    for old in &copies[..copies.len().saturating_sub(policy.keep)] {
+        fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+// Called by the store before it appends or rewrites; every file holding entries is
+// copied (all of them when the data is split per year)
+pub fn snapshot(data_file: &str) -> Result<(), AppError> {
+    let Some(policy) = POLICY.get().copied().filter(|p| p.keep > 0) else {
+        return Ok(());
+    };
+    for file in store::files(data_file) {
+        snapshot_file(&file, policy)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use crate::analysis::mean_sd;
+use crate::audit;
+use crate::cli::{BaselineAction, BaselineArgs};
+use crate::daily::{self, DailyPoint};
+use crate::edit::ask_value;
+use crate::layout;
+use crate::schema::column_name;
+use crate::units::DisplayUnits;
+use crate::{metric_range, AppError, GlobalOptions, LogEntry, METRIC_NAMES};
+use colored::*;
+use csv::{ReaderBuilder, WriterBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const SEED_DAYS: usize = 14; // The seed baseline stands in until this many days are logged
+const SEED_SPREAD: f64 = 0.15; // Assumed day-to-day spread of a seeded metric, share of its range
+const MIN_SPREAD: f64 = 0.05; // Floor for a very steady metric, so every change isn't "unusual"
+const ALERT_SPREADS: f64 = 2.0; // Answers this many spreads from typical get a note
+
+// --- Typical values entered from memory, for the first weeks before there is data ---
+// Kept beside the data in stored units, one row per metric.
+#[derive(Serialize, Deserialize)]
+struct BaselineRow {
+    metric: String,
+    value: f64,
+}
+
+fn baseline_file(data_file: &str) -> PathBuf {
+    Path::new(data_file).with_extension("baseline.csv")
+}
+
+pub fn read_baseline(data_file: &str) -> Result<BTreeMap<String, f64>, AppError> {
+    let path = baseline_file(data_file);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let mut rdr = ReaderBuilder::new().from_path(path)?;
+    let rows = rdr.deserialize().collect::<Result<Vec<BaselineRow>, _>>()?;
+    Ok(rows.into_iter().map(|r| (r.metric, r.value)).collect())
+}
+
+fn write_baseline(data_file: &str, baseline: &BTreeMap<String, f64>) -> Result<(), AppError> {
+    let mut wtr = WriterBuilder::new().from_path(baseline_file(data_file))?;
+    for (metric, value) in baseline {
+        wtr.serialize(BaselineRow {
+            metric: metric.clone(),
+            value: *value,
+        })?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Move a metric's seed value onto a new scale (`metric scale`)
+pub fn rescale(
+    data_file: &str,
+    metric: &str,
+    convert: impl Fn(f64) -> f64,
+) -> Result<(), AppError> {
+    let mut baseline = read_baseline(data_file)?;
+    let Some(value) = baseline.get_mut(metric) else {
+        return Ok(());
+    };
+    *value = (convert(*value) * 10.0).round() / 10.0;
+    write_baseline(data_file, &baseline)
+}
+
+// The seed values while fewer than SEED_DAYS days are logged; None once the data takes over
+pub fn seed(
+    options: &GlobalOptions,
+    points: &[DailyPoint],
+) -> Result<Option<BTreeMap<String, f64>>, AppError> {
+    if points.len() >= SEED_DAYS {
+        return Ok(None);
+    }
+    let baseline = read_baseline(&options.data_file())?;
+    Ok((!baseline.is_empty()).then_some(baseline))
+}
+
+// Typical value and day-to-day spread of a metric, from the seed or the logged days
+fn reference(
+    points: &[DailyPoint],
+    seed: Option<&BTreeMap<String, f64>>,
+    metric: &str,
+) -> Option<(f64, f64)> {
+    let (min, max) = metric_range(metric);
+    let width = (max - min).min(10.0); // Open-ended ranges get a rating's width
+    let (typical, spread) = match seed {
+        Some(seed) => (*seed.get(metric)?, width * SEED_SPREAD),
+        None if points.len() >= SEED_DAYS => {
+            let values: Vec<f64> = points
+                .iter()
+                .filter_map(|p| p.values.get(metric).copied())
+                .collect();
+            mean_sd(&values)?
+        }
+        None => return None,
+    };
+    Some((typical, spread.max(width * MIN_SPREAD)))
+}
+
+// After logging: point out answers far from the usual value (the seed one at first).
+// The day being logged is left out, so it can't make itself look normal.
+pub fn alert(options: &GlobalOptions, entry: &LogEntry) -> Result<(), AppError> {
+    let today = entry.parsed_timestamp().map(|ts| ts.date_naive());
+    let points: Vec<DailyPoint> = daily::load(options)?
+        .into_iter()
+        .filter(|p| Some(p.date) != today)
+        .collect();
+    let seed = seed(options, &points)?;
+    let units = DisplayUnits::from_config(&options.config);
+    for metric in METRIC_NAMES {
+        let Some(value) = entry.metric(metric) else {
+            continue;
+        };
+        let Some((typical, spread)) = reference(&points, seed.as_ref(), metric) else {
+            continue;
+        };
+        if (value - typical).abs() <= ALERT_SPREADS * spread {
+            continue;
+        }
+        println!(
+            "{}",
+            format!(
+                " Unusually {} {}: {} (typically {:.1}{})",
+                if value < typical { "low" } else { "high" },
+                column_name(metric),
+                units.convert(metric, value),
+                units.convert(metric, typical),
+                if seed.is_some() {
+                    ", from your baseline"
+                } else {
+                    ""
+                }
+            )
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+fn show(options: &GlobalOptions, baseline: &BTreeMap<String, f64>) -> Result<(), AppError> {
+    let units = DisplayUnits::from_config(&options.config);
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Seed baseline ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    if baseline.is_empty() {
+        println!(
+            "{}",
+            "No baseline yet. Enter your typical values with: baseline set".dimmed()
+        );
+        return Ok(());
+    }
+    for (metric, value) in baseline {
+        layout::row(&format!(
+            "{:<14} {:>6} {}",
+            column_name(metric),
+            units.convert(metric, *value),
+            units.unit(metric).dimmed()
+        ));
+    }
+    let days = daily::load(options)?.len();
+    println!();
+    if days < SEED_DAYS {
+        println!(
+            "{}",
+            format!(
+                "Used by stats and alerts until {} days are logged ({} so far).",
+                SEED_DAYS, days
+            )
+            .dimmed()
+        );
+    } else {
+        println!(
+            "{}",
+            format!("Not used any more: {} days of real data are logged.", days).dimmed()
+        );
+    }
+    Ok(())
+}
+
+// --- `baseline` command: show, set or clear the seed baseline ---
+pub fn run_baseline(options: &GlobalOptions, args: &BaselineArgs) -> Result<(), AppError> {
+    let data_file = options.data_file();
+    let mut baseline = read_baseline(&data_file)?;
+    match args.action {
+        BaselineAction::Show => show(options, &baseline),
+        BaselineAction::Set => {
+            let units = DisplayUnits::from_config(&options.config);
+            println!(
+                "{}",
+                "Your typical value for each metric (Enter keeps the one shown):".bold()
+            );
+            for metric in METRIC_NAMES {
+                if !options.config.metric_enabled(metric) {
+                    continue;
+                }
+                if let Some(value) = ask_value(&units, metric, baseline.get(metric).copied())? {
+                    baseline.insert(metric.to_string(), value);
+                }
+            }
+            if baseline.is_empty() {
+                println!("{}", "Nothing entered.".dimmed());
+                return Ok(());
+            }
+            if options.dry_run {
+                println!(
+                    "{}",
+                    format!("Dry run: would save {} typical values", baseline.len()).yellow()
+                );
+                return Ok(());
+            }
+            write_baseline(&data_file, &baseline)?;
+            let details: Vec<String> = baseline
+                .iter()
+                .map(|(metric, value)| format!("{} {}", column_name(metric), value))
+                .collect();
+            audit::record(&data_file, "baseline set", &details.join(", "), Vec::new())?;
+            println!("{}", "Baseline saved.".green());
+            Ok(())
+        }
+        BaselineAction::Clear => {
+            if baseline.is_empty() {
+                println!("{}", "No baseline to clear.".dimmed());
+                return Ok(());
+            }
+            if options.dry_run {
+                println!("{}", "Dry run: would clear the baseline".yellow());
+                return Ok(());
+            }
+            std::fs::remove_file(baseline_file(&data_file))?;
+            audit::record(&data_file, "baseline clear", "", Vec::new())?;
+            println!("{}", "Baseline cleared.".green());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,313 @@
+// --- Standardized questionnaires that can be switched on alongside the daily metrics ---
+// Each bundle keeps its published wording, answer scale and scoring. Answers are stored
+// next to the data file (daily_metrics.csv -> daily_metrics.who5.csv) with the bundle
+// version, so a later revision never rescores old answers.
+use crate::cli::{BundleAction, BundleArgs};
+use crate::layout;
+use crate::replay;
+use crate::{AppError, GlobalOptions, THEME};
+use chrono::{DateTime, NaiveDate, Utc};
+use colored::*;
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use dialoguer::Select;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+pub struct Bundle {
+    pub id: &'static str,
+    pub version: u32,
+    pub title: &'static str,
+    preamble: &'static str,
+    every_days: i64, // How often the bundle is asked during logging
+    items: &'static [&'static str],
+    choices: &'static [(&'static str, u8)], // Answer label and its item score
+    score: fn(&[u8]) -> f64,
+    score_range: (f64, f64),
+    interpret: fn(f64) -> Option<&'static str>,
+}
+
+// WHO-5 Well-Being Index (WHO, 1998): raw 0-25, reported as a 0-100 percentage
+const WHO5: Bundle = Bundle {
+    id: "who5",
+    version: 1,
+    title: "WHO-5 Well-Being Index",
+    preamble: "Over the last two weeks...",
+    every_days: 14,
+    items: &[
+        "I have felt cheerful and in good spirits",
+        "I have felt calm and relaxed",
+        "I have felt active and vigorous",
+        "I woke up feeling fresh and rested",
+        "My daily life has been filled with things that interest me",
+    ],
+    choices: &[
+        ("All of the time", 5),
+        ("Most of the time", 4),
+        ("More than half of the time", 3),
+        ("Less than half of the time", 2),
+        ("Some of the time", 1),
+        ("At no time", 0),
+    ],
+    score: |answers| answers.iter().map(|a| f64::from(*a)).sum::<f64>() * 4.0,
+    score_range: (0.0, 100.0),
+    interpret: |score| {
+        Some(if score <= 28.0 {
+            "Very low; the WHO suggests talking to a doctor about depression"
+        } else if score < 52.0 {
+            "Below 52: poor wellbeing"
+        } else {
+            "Adequate wellbeing"
+        })
+    },
+};
+
+// Sleep Hygiene Index (Mastin et al., 2006): 13-65, lower means better habits
+const SLEEP_HYGIENE: Bundle = Bundle {
+    id: "sleep-hygiene",
+    version: 1,
+    title: "Sleep Hygiene Index",
+    preamble: "How often do these apply to you?",
+    every_days: 7,
+    items: &[
+        "I take daytime naps lasting two or more hours",
+        "I go to bed at different times from day to day",
+        "I get out of bed at different times from day to day",
+        "I exercise to the point of sweating within 1 hour of going to bed",
+        "I stay in bed longer than I should two or three times a week",
+        "I use alcohol, tobacco or caffeine within 4 hours of going to bed or after going to bed",
+        "I do something that may wake me up before bedtime (e.g. video games, internet, cleaning)",
+        "I go to bed feeling stressed, angry, upset or nervous",
+        "I use my bed for things other than sleeping or sex (e.g. TV, reading, eating, studying)",
+        "I sleep on an uncomfortable bed (e.g. poor mattress or pillow, wrong blankets)",
+        "I sleep in an uncomfortable bedroom (e.g. too bright, stuffy, hot, cold or noisy)",
+        "I do important work before bedtime (e.g. pay bills, schedule, study)",
+        "I think, plan or worry when I am in bed",
+    ],
+    choices: &[
+        ("Always", 5),
+        ("Frequently", 4),
+        ("Sometimes", 3),
+        ("Rarely", 2),
+        ("Never", 1),
+    ],
+    score: |answers| answers.iter().map(|a| f64::from(*a)).sum(),
+    score_range: (13.0, 65.0),
+    interpret: |_| None, // No published cut-offs; compare with your own history
+};
+
+pub const BUNDLES: [&Bundle; 2] = [&WHO5, &SLEEP_HYGIENE];
+
+pub fn find(id: &str) -> Option<&'static Bundle> {
+    BUNDLES.iter().copied().find(|b| b.id == id)
+}
+
+// Bundles switched on for the selected profile (a profile's list replaces the top-level one)
+fn enabled(options: &GlobalOptions) -> Vec<&'static Bundle> {
+    let profile = options
+        .profile
+        .as_ref()
+        .and_then(|name| options.config.profile.get(name))
+        .and_then(|p| p.bundles.as_ref());
+    profile
+        .unwrap_or(&options.config.bundles)
+        .iter()
+        .filter_map(|id| find(id)) // Unknown ids are rejected when the config loads
+        .collect()
+}
+
+fn answers_file(data_file: &str, bundle: &Bundle) -> PathBuf {
+    Path::new(data_file).with_extension(format!("{}.csv", bundle.id))
+}
+
+// --- One completed questionnaire ---
+struct Response {
+    timestamp: DateTime<Utc>,
+    version: u32,
+    score: f64,
+}
+
+fn read_responses(path: &Path) -> Result<Vec<Response>, AppError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut rdr = ReaderBuilder::new().flexible(true).from_path(path)?;
+    let mut responses = Vec::new();
+    for result in rdr.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                eprintln!("Warning: Skipping corrupted CSV record: {}", e);
+                continue;
+            }
+        };
+        // timestamp, version, item_1..item_n, score
+        let parsed = (|| {
+            Some(Response {
+                timestamp: DateTime::parse_from_rfc3339(record.get(0)?)
+                    .ok()?
+                    .with_timezone(&Utc),
+                version: record.get(1)?.parse().ok()?,
+                score: record.get(record.len() - 1)?.parse().ok()?,
+            })
+        })();
+        match parsed {
+            Some(response) => responses.push(response),
+            None => eprintln!("Warning: Skipping unreadable row in {}", path.display()),
+        }
+    }
+    Ok(responses)
+}
+
+fn append_response(
+    path: &Path,
+    bundle: &Bundle,
+    answers: &[u8],
+    score: f64,
+) -> Result<(), AppError> {
+    let file_exists = path.exists();
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut wtr = WriterBuilder::new().from_writer(file);
+    if !file_exists {
+        let mut header = StringRecord::from(vec!["timestamp", "version"]);
+        for i in 1..=bundle.items.len() {
+            header.push_field(&format!("item_{}", i));
+        }
+        header.push_field("score");
+        wtr.write_record(&header)?;
+    }
+    let mut row = vec![Utc::now().to_rfc3339(), bundle.version.to_string()];
+    row.extend(answers.iter().map(u8::to_string));
+    row.push(score.to_string());
+    wtr.write_record(&row)?;
+    wtr.flush()?;
+    Ok(())
+}
+
+// --- Ask every item of a bundle and store the scored result ---
+fn administer(options: &GlobalOptions, bundle: &Bundle) -> Result<(), AppError> {
+    println!("{}", layout::rule('-').cyan());
+    println!(
+        "{} {}",
+        bundle.title.bold(),
+        format!("(v{})", bundle.version).dimmed()
+    );
+    println!("{}", bundle.preamble);
+    let labels: Vec<&str> = bundle.choices.iter().map(|(label, _)| *label).collect();
+    let mut answers = Vec::new();
+    for item in bundle.items {
+        let choice = replay::answer(item, || {
+            Select::with_theme(&**THEME)
+                .with_prompt(*item)
+                .items(&labels)
+                .default(labels.len() / 2)
+                .interact()
+        })?;
+        answers.push(bundle.choices[choice].1);
+    }
+    let score = (bundle.score)(&answers);
+    if options.dry_run {
+        println!("{}", " Dry run: answers not saved".bold().yellow());
+    } else {
+        let path = answers_file(&options.data_file(), bundle);
+        append_response(&path, bundle, &answers, score)?;
+    }
+    println!(
+        "{} score: {} {}",
+        bundle.title,
+        format!("{}", score).bold().yellow(),
+        format!("({}-{})", bundle.score_range.0, bundle.score_range.1).dimmed()
+    );
+    if let Some(meaning) = (bundle.interpret)(score) {
+        println!("{}", meaning.dimmed());
+    }
+    Ok(())
+}
+
+// --- Called from the first log of the day: ask any enabled bundle that is due ---
+pub fn ask_due(options: &GlobalOptions, today: NaiveDate) -> Result<(), AppError> {
+    for bundle in enabled(options) {
+        let responses = read_responses(&answers_file(&options.data_file(), bundle))?;
+        let due = responses
+            .iter()
+            .map(|r| r.timestamp.date_naive())
+            .max()
+            .is_none_or(|last| (today - last).num_days() >= bundle.every_days);
+        if due {
+            administer(options, bundle)?;
+        }
+    }
+    Ok(())
+}
+
+// --- `bundle` command: list, take or review the questionnaires ---
+pub fn run_bundle(options: &GlobalOptions, args: &BundleArgs) -> Result<(), AppError> {
+    let lookup = |id: &str| -> Result<&'static Bundle, AppError> {
+        find(id).ok_or_else(|| {
+            AppError::UsageError(format!(
+                "Unknown bundle '{}' (choose from {})",
+                id,
+                BUNDLES.map(|b| b.id).join(", ")
+            ))
+        })
+    };
+    let id = args.id.as_deref().unwrap_or_default();
+    match args.action {
+        BundleAction::List => list(options),
+        BundleAction::Take => administer(options, lookup(id)?),
+        BundleAction::Scores => scores(options, lookup(id)?),
+    }
+}
+
+fn list(options: &GlobalOptions) -> Result<(), AppError> {
+    let active = enabled(options);
+    for bundle in BUNDLES {
+        let on = active.iter().any(|b| b.id == bundle.id);
+        println!(
+            "{} {} {} {}",
+            if on { "*".green() } else { " ".normal() },
+            format!("{:<14}", bundle.id).bold(),
+            bundle.title,
+            format!(
+                "(v{}, {} items, every {} days)",
+                bundle.version,
+                bundle.items.len(),
+                bundle.every_days
+            )
+            .dimmed()
+        );
+    }
+    println!();
+    println!(
+        "{}",
+        "Enable with bundles = [\"who5\"] in config.toml (or under [profile.<name>]).".dimmed()
+    );
+    Ok(())
+}
+
+fn scores(options: &GlobalOptions, bundle: &Bundle) -> Result<(), AppError> {
+    let responses = read_responses(&answers_file(&options.data_file(), bundle))?;
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", format!(" {} ", bundle.title).bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    if responses.is_empty() {
+        println!(
+            "{}",
+            format!("No answers yet. Run: bundle take {}", bundle.id).dimmed()
+        );
+        return Ok(());
+    }
+    for response in &responses {
+        let meaning = if response.version == bundle.version {
+            (bundle.interpret)(response.score).unwrap_or("")
+        } else {
+            "older version"
+        };
+        layout::row(&format!(
+            "{}  {:>5}  {}",
+            response.timestamp.format("%Y-%m-%d"),
+            response.score,
+            meaning.dimmed()
+        ));
+    }
+    Ok(())
+}
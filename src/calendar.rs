@@ -0,0 +1,176 @@
+use crate::cli::CalendarArgs;
+use crate::layout;
+use crate::schema::column_name;
+use crate::units::DisplayUnits;
+use crate::{group_by_day, read_entries, AppError, GlobalOptions, LogEntry, METRIC_NAMES};
+use chrono::{Datelike, Duration, Local, NaiveDate, Utc};
+use colored::*;
+use console::{Key, Term};
+use std::collections::BTreeMap;
+
+// --- Month grid: one compact glyph per day ---
+//   ★ logged with a workout   ● logged   · missed   (blank) still to come
+// Compact mode (Termux) sticks to ASCII: * o .
+fn glyphs() -> [&'static str; 3] {
+    if layout::compact() {
+        ["*", "o", "."]
+    } else {
+        ["★", "●", "·"]
+    }
+}
+
+fn glyph(date: NaiveDate, today: NaiveDate, day: Option<&Vec<LogEntry>>) -> ColoredString {
+    let [workout, logged, missed] = glyphs();
+    match day {
+        Some(entries) if entries.iter().any(|e| e.workout_today) => workout.yellow(),
+        Some(_) => logged.green(),
+        None if date <= today => missed.dimmed(),
+        None => " ".normal(),
+    }
+}
+
+fn grid_lines(
+    first: NaiveDate,
+    selected: NaiveDate,
+    today: NaiveDate,
+    days: &BTreeMap<NaiveDate, Vec<LogEntry>>,
+) -> Vec<String> {
+    let mut lines = vec![
+        format!(" {}", first.format("%B %Y"))
+            .bold()
+            .cyan()
+            .to_string(),
+        "  Mo  Tu  We  Th  Fr  Sa  Su".dimmed().to_string(),
+    ];
+    let mut line = "    ".repeat(first.weekday().num_days_from_monday() as usize);
+    let mut date = first;
+    while date.month() == first.month() {
+        let number = format!("{:>3}", date.day());
+        let number = if date == selected {
+            number.reversed().to_string()
+        } else {
+            number
+        };
+        line.push_str(&format!(
+            "{}{}",
+            number,
+            glyph(date, today, days.get(&date))
+        ));
+        if date.weekday().num_days_from_monday() == 6 {
+            lines.push(std::mem::take(&mut line));
+        }
+        date += Duration::days(1);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    let [workout, logged, missed] = glyphs();
+    lines.push(format!(
+        " {} workout  {} logged  {} missed",
+        workout.yellow(),
+        logged.green(),
+        missed.dimmed()
+    ));
+    lines
+}
+
+// One line per entry: local time, each recorded metric, workout and remarks
+fn entry_lines(date: NaiveDate, day: Option<&Vec<LogEntry>>, units: &DisplayUnits) -> Vec<String> {
+    let mut lines = vec![layout::rule('-').cyan().to_string()];
+    let Some(entries) = day else {
+        lines.push(
+            format!("{} nothing logged", date.format("%a %Y-%m-%d"))
+                .dimmed()
+                .to_string(),
+        );
+        return lines;
+    };
+    lines.push(format!("{}", date.format("%a %Y-%m-%d")).bold().to_string());
+    for entry in entries {
+        let time = entry
+            .parsed_timestamp()
+            .map(|ts| ts.with_timezone(&Local).format("%H:%M").to_string())
+            .unwrap_or_default();
+        let mut parts: Vec<String> = METRIC_NAMES
+            .iter()
+            .filter_map(|m| {
+                let value = units.convert(m, entry.metric(m)?);
+                Some(format!("{} {}", column_name(m), value))
+            })
+            .collect();
+        if entry.workout_today {
+            parts.push("workout".to_string());
+        }
+        lines.push(layout::fit(&format!(
+            "{}  {}",
+            time.yellow(),
+            parts.join(", ")
+        )));
+        if !entry.remarks.trim().is_empty() {
+            lines.push(layout::fit(&format!("       \"{}\"", entry.remarks)));
+        }
+    }
+    lines
+}
+
+// --- `calendar [YYYY-MM]`: month grid, arrow keys move through the days ---
+pub fn run_calendar(options: &GlobalOptions, args: &CalendarArgs) -> Result<(), AppError> {
+    let today = Utc::now().date_naive();
+    let first = match &args.month {
+        None => today.with_day(1),
+        Some(month) => NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok(),
+    };
+    let Some(first) = first else {
+        return Err(AppError::UsageError(format!(
+            "Can't read month '{}': use YYYY-MM",
+            args.month.as_deref().unwrap_or_default()
+        )));
+    };
+    let units = DisplayUnits::from_config(&options.config);
+    let days = group_by_day(read_entries(&options.data_file())?);
+    let in_month = |date: NaiveDate| date.month() == first.month() && date.year() == first.year();
+    let mut selected = if in_month(today) { today } else { first };
+
+    // Piped output gets the grid alone
+    let term = Term::stdout();
+    if !term.is_term() {
+        for line in grid_lines(first, first - Duration::days(1), today, &days) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    term.hide_cursor()?;
+    let mut drawn = 0;
+    let result = loop {
+        let mut lines = grid_lines(first, selected, today, &days);
+        lines.extend(entry_lines(selected, days.get(&selected), &units));
+        let hint = if layout::compact() {
+            "arrows move, q to quit"
+        } else {
+            "←↑↓→ move, q to quit"
+        };
+        lines.push(hint.dimmed().to_string());
+        term.clear_last_lines(drawn)?;
+        for line in &lines {
+            term.write_line(line)?;
+        }
+        drawn = lines.len();
+
+        let step = match term.read_key() {
+            Ok(Key::ArrowLeft) => -1,
+            Ok(Key::ArrowRight) => 1,
+            Ok(Key::ArrowUp) => -7,
+            Ok(Key::ArrowDown) => 7,
+            Ok(Key::Char('q')) | Ok(Key::Escape) | Ok(Key::Enter) => break Ok(()),
+            Ok(_) => 0,
+            Err(e) => break Err(e),
+        };
+        let next = selected + Duration::days(step);
+        if in_month(next) {
+            selected = next;
+        }
+    };
+    term.show_cursor()?;
+    Ok(result?)
+}
@@ -0,0 +1,165 @@
+use crate::defaults::LogDefaults;
+use crate::replay;
+use crate::units::DisplayUnits;
+use crate::{AppError, LogEntry, THEME};
+use chrono::{DateTime, Local};
+use colored::*;
+use dialoguer::{Confirm, Select};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// --- Cancelling a question while logging ---
+// console reads keys in raw mode and raises SIGINT itself when Ctrl+C is pressed. While
+// a session is open a no-op handler keeps that from killing the process: the prompt
+// returns an error instead, the answers so far go to a draft, and the user decides what
+// happens to the entry. The next `log` offers to pick the draft up again.
+#[cfg(unix)]
+extern "C" fn ignore_sigint(_: libc::c_int) {}
+
+pub fn draft_file(data_file: &str) -> PathBuf {
+    Path::new(data_file).with_extension("draft.toml")
+}
+
+// Once the entry is saved or thrown away its draft is no longer needed
+pub fn discard_draft(data_file: &str) {
+    let _ = fs::remove_file(draft_file(data_file));
+}
+
+// Some(answers) when a draft from a cancelled `log` exists and the user resumes it;
+// declining deletes the draft
+pub fn offer_draft(data_file: &str, units: &DisplayUnits) -> Result<Option<LogDefaults>, AppError> {
+    let path = draft_file(data_file);
+    let Ok(metadata) = fs::metadata(&path) else {
+        return Ok(None);
+    };
+    let saved = metadata
+        .modified()
+        .map(|time| {
+            DateTime::<Local>::from(time)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| "earlier".to_string());
+    let prompt = format!("Resume the unfinished entry from {}?", saved);
+    let resume = replay::answer(&prompt, || {
+        Confirm::with_theme(&**THEME)
+            .with_prompt(&prompt)
+            .default(true)
+            .interact()
+    })?;
+    if !resume {
+        discard_draft(data_file);
+        println!("{}", "Draft deleted, starting over.".dimmed());
+        return Ok(None);
+    }
+    LogDefaults::load(&path, units).map(Some)
+}
+
+pub struct PromptSession<'a> {
+    units: &'a DisplayUnits,
+    draft_file: PathBuf,
+    pub answered: LogDefaults, // Everything answered so far, in case a draft is saved
+}
+
+impl<'a> PromptSession<'a> {
+    pub fn start(data_file: &str, units: &'a DisplayUnits) -> PromptSession<'a> {
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(
+                libc::SIGINT,
+                ignore_sigint as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+        PromptSession {
+            units,
+            draft_file: draft_file(data_file),
+            answered: LogDefaults::default(),
+        }
+    }
+
+    // Ask one question: Some(answer), or None when the user chose to skip it.
+    // Saving a draft or discarding ends the session with DialogCancelled. `label` names
+    // the question in --record/--replay sessions.
+    pub fn ask<T: Serialize + DeserializeOwned>(
+        &self,
+        label: &str,
+        mut prompt: impl FnMut() -> dialoguer::Result<T>,
+    ) -> Result<Option<T>, AppError> {
+        loop {
+            let answered = replay::answer(label, &mut prompt);
+            // Saved straight away, so even a second Ctrl+C keeps the answers
+            match answered {
+                Ok(answer) => return Ok(Some(answer)),
+                Err(AppError::DialogCancelled) => {
+                    eprintln!();
+                    self.save_draft()?;
+                }
+                // A replay that stopped matching keeps the answers so far as well
+                Err(e) => {
+                    self.save_draft()?;
+                    return Err(e);
+                }
+            }
+            const CANCELLED: &str = "Cancelled. What now? (Esc to answer again)";
+            let choice = replay::answer(CANCELLED, || {
+                Select::with_theme(&**THEME)
+                    .with_prompt(CANCELLED)
+                    .items(&[
+                        "Skip this question",
+                        "Save a draft and quit",
+                        "Discard the entry",
+                    ])
+                    .default(0)
+                    .interact_opt()
+            });
+            match choice {
+                Ok(None) => continue,
+                Ok(Some(0)) => return Ok(None),
+                Ok(Some(2)) => {
+                    let _ = fs::remove_file(&self.draft_file);
+                    println!("{}", "Entry discarded.".dimmed());
+                    return Err(AppError::DialogCancelled);
+                }
+                Ok(Some(_)) | Err(AppError::DialogCancelled) => return Err(self.quit_with_draft()),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    // Keep a finished but unsaved entry (the review was cancelled) as the draft
+    pub fn cancel_entry(&mut self, entry: &LogEntry) -> AppError {
+        let mut answered = LogDefaults::default();
+        answered.fill_from(entry);
+        answered.remarks = Some(entry.remarks.clone());
+        self.answered = answered;
+        match self.save_draft() {
+            Ok(()) => self.quit_with_draft(),
+            Err(e) => e,
+        }
+    }
+
+    fn save_draft(&self) -> Result<(), AppError> {
+        self.answered.save(&self.draft_file, self.units)
+    }
+
+    fn quit_with_draft(&self) -> AppError {
+        println!(
+            "{}",
+            format!("Draft saved to {}", self.draft_file.display()).yellow()
+        );
+        println!("{}", "Run `log` again to resume it.".dimmed());
+        AppError::DialogCancelled
+    }
+}
+
+// Ctrl+C stops the program again once the questions are over
+impl Drop for PromptSession<'_> {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::analysis::daily_value;
+use crate::cli::{ChallengeAction, ChallengeArgs, OutputFormat};
+use crate::config::{edit_config, Challenge, Config};
+use crate::custom;
+use crate::daily::{self, DailyPoint};
+use crate::layout;
+use crate::schema::{canonical_metric, column_name};
+use crate::units::DisplayUnits;
+use crate::{AppError, GlobalOptions};
+use chrono::{Duration, NaiveDate, Utc};
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use toml_edit::{value, Array, ArrayOfTables, Item, Table};
+
+// --- Named challenges (`[[challenges]]` in the config), next to the goal ---
+// A challenge runs for a fixed number of days from its start date. A day counts towards
+// it when the challenge's metrics were logged that day (any entry when it names none).
+#[derive(Serialize)]
+struct Progress {
+    name: String,
+    start: NaiveDate,
+    end: NaiveDate,
+    days: i64,
+    status: &'static str, // upcoming, active or finished
+    day: Option<i64>,     // Day of the challenge today, while it runs
+    days_logged: usize,
+    averages: BTreeMap<String, f64>, // Per metric, over the days that counted
+}
+
+// Built-in metrics by their stored name; custom ones as they are
+fn metric(name: &str) -> Option<&'static str> {
+    canonical_metric(name).or_else(|| custom::numeric_columns().find(|c| *c == name))
+}
+
+fn progress(
+    challenge: &Challenge,
+    points: &[DailyPoint],
+    units: &DisplayUnits,
+    today: NaiveDate,
+) -> Progress {
+    // Checked as a date when the config was loaded
+    let start = NaiveDate::parse_from_str(&challenge.start, "%Y-%m-%d").unwrap_or(today);
+    let end = start + Duration::days(challenge.days - 1);
+    let metrics: Vec<&str> = challenge.metrics.iter().filter_map(|m| metric(m)).collect();
+    let counted: Vec<&DailyPoint> = points
+        .iter()
+        .filter(|p| (start..=end).contains(&p.date))
+        .filter(|p| metrics.iter().all(|m| daily_value(p, m).is_some()))
+        .collect();
+    let averages = metrics
+        .iter()
+        .filter(|_| !counted.is_empty())
+        .map(|m| {
+            let sum: f64 = counted.iter().filter_map(|p| daily_value(p, m)).sum();
+            let mean = units.convert(m, sum / counted.len() as f64);
+            (column_name(m).to_string(), (mean * 100.0).round() / 100.0)
+        })
+        .collect();
+    let (status, day) = if today < start {
+        ("upcoming", None)
+    } else if today <= end {
+        ("active", Some((today - start).num_days() + 1))
+    } else {
+        ("finished", None)
+    };
+    Progress {
+        name: challenge.name.clone(),
+        start,
+        end,
+        days: challenge.days,
+        status,
+        day,
+        days_logged: counted.len(),
+        averages,
+    }
+}
+
+fn all_progress(options: &GlobalOptions) -> Result<Vec<Progress>, AppError> {
+    if options.config.challenges.is_empty() {
+        return Ok(Vec::new());
+    }
+    let units = DisplayUnits::from_config(&options.config);
+    let points = daily::load(options)?;
+    let today = Utc::now().date_naive();
+    Ok(options
+        .config
+        .challenges
+        .iter()
+        .map(|c| progress(c, &points, &units, today))
+        .collect())
+}
+
+fn averages_text(progress: &Progress) -> String {
+    progress
+        .averages
+        .iter()
+        .map(|(metric, mean)| format!("{} {:.1}", metric, mean))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// --- Lines for the log banner: one per running challenge ---
+pub fn banner(options: &GlobalOptions) -> Result<Vec<String>, AppError> {
+    Ok(all_progress(options)?
+        .iter()
+        .filter_map(|p| {
+            let day = p.day?;
+            Some(if layout::compact() {
+                format!("{} {}/{}", p.name, day, p.days)
+            } else {
+                format!(
+                    "Challenge: {} (day {} / {}, {} days logged)",
+                    p.name, day, p.days, p.days_logged
+                )
+            })
+        })
+        .collect())
+}
+
+// --- Called after the first entry of a day: challenges that ended since the last one ---
+pub fn check_finished(
+    options: &GlobalOptions,
+    previous_entry: Option<NaiveDate>,
+) -> Result<(), AppError> {
+    let Some(previous) = previous_entry else {
+        return Ok(());
+    };
+    let today = Utc::now().date_naive();
+    for p in all_progress(options)? {
+        if !(previous < p.end && p.end <= today) {
+            continue;
+        }
+        println!(
+            "{}",
+            format!(
+                " Challenge \"{}\" complete: {} of {} days logged ",
+                p.name, p.days_logged, p.days
+            )
+            .bold()
+            .green()
+        );
+        if !p.averages.is_empty() {
+            println!("  {}", averages_text(&p));
+        }
+        println!(
+            "{}",
+            format!(
+                "Start the next one with: {} challenge start \"<name>\" --days {}",
+                env!("CARGO_PKG_NAME"),
+                p.days
+            )
+            .dimmed()
+        );
+    }
+    Ok(())
+}
+
+// --- `challenge` command: list the challenges, or start one today ---
+pub fn run_challenge(options: &GlobalOptions, args: &ChallengeArgs) -> Result<(), AppError> {
+    match args.action {
+        ChallengeAction::List => list(options),
+        ChallengeAction::Start => start(options, args),
+    }
+}
+
+fn list(options: &GlobalOptions) -> Result<(), AppError> {
+    let challenges = all_progress(options)?;
+    match options.format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&challenges)?);
+            return Ok(());
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+            wtr.write_record([
+                "name",
+                "start",
+                "end",
+                "days",
+                "status",
+                "day",
+                "days_logged",
+                "averages",
+            ])?;
+            for p in &challenges {
+                wtr.write_record([
+                    p.name.clone(),
+                    p.start.to_string(),
+                    p.end.to_string(),
+                    p.days.to_string(),
+                    p.status.to_string(),
+                    p.day.map_or(String::new(), |d| d.to_string()),
+                    p.days_logged.to_string(),
+                    averages_text(p),
+                ])?;
+            }
+            wtr.flush()?;
+            return Ok(());
+        }
+        OutputFormat::Table | OutputFormat::Parquet => {}
+    }
+
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Challenges ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    if challenges.is_empty() {
+        println!(
+            "{}",
+            "No challenges yet. Start one with: challenge start \"<name>\" --days 30".dimmed()
+        );
+        return Ok(());
+    }
+    for p in &challenges {
+        let status = match p.day {
+            Some(day) => format!("day {} / {}", day, p.days).yellow(),
+            None if p.status == "upcoming" => format!("starts {}", p.start).dimmed(),
+            None => "finished".green(),
+        };
+        layout::row(&format!("{} {}", p.name.bold(), status));
+        layout::row(&format!(
+            "  {} to {}, {} of {} days logged",
+            p.start, p.end, p.days_logged, p.days
+        ));
+        if !p.averages.is_empty() {
+            layout::row(&format!("  {}", averages_text(p)));
+        }
+    }
+    Ok(())
+}
+
+fn start(options: &GlobalOptions, args: &ChallengeArgs) -> Result<(), AppError> {
+    let Some(name) = args
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+    else {
+        return Err(AppError::UsageError(
+            "Usage: challenge start \"<name>\" [--days N] [--metric <metric>]...".to_string(),
+        ));
+    };
+    if args.days <= 0 {
+        return Err(AppError::UsageError(
+            "--days must be at least one day".to_string(),
+        ));
+    }
+    if options
+        .config
+        .challenges
+        .iter()
+        .any(|c| c.name.eq_ignore_ascii_case(name))
+    {
+        return Err(AppError::UsageError(format!(
+            "There is already a challenge called '{}'",
+            name
+        )));
+    }
+    if let Some(unknown) = args.metrics.iter().find(|m| metric(m).is_none()) {
+        return Err(AppError::UsageError(format!(
+            "Unknown metric '{}': use a metric or a numeric [custom] one",
+            unknown
+        )));
+    }
+    let today = Utc::now().date_naive();
+    let end = today + Duration::days(args.days - 1);
+    if options.dry_run {
+        println!(
+            "{}",
+            format!(
+                "Dry run: would start \"{}\" today, running to {}",
+                name, end
+            )
+            .yellow()
+        );
+        return Ok(());
+    }
+
+    let config_path = options.config_path();
+    edit_config(&config_path, |doc| {
+        let challenges = doc
+            .entry("challenges")
+            .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+            .as_array_of_tables_mut()
+            .ok_or_else(|| {
+                AppError::ConfigError(format!(
+                    "{}: challenges must be a list of [[challenges]] tables",
+                    config_path.display()
+                ))
+            })?;
+        let mut table = Table::new();
+        table.insert("name", value(name));
+        table.insert("start", value(today.to_string()));
+        table.insert("days", value(args.days));
+        if !args.metrics.is_empty() {
+            table.insert("metrics", value(args.metrics.iter().collect::<Array>()));
+        }
+        challenges.push(table);
+        Ok(())
+    })?;
+    // The new section has to pass the same checks as one written by hand
+    Config::load(&config_path)?;
+    println!(
+        "{}",
+        format!(" Challenge \"{}\" starts today and runs to {}", name, end)
+            .bold()
+            .green()
+    );
+    println!("{}", format!("Saved to {}", config_path.display()).dimmed());
+    Ok(())
+}
@@ -0,0 +1,730 @@
+// --- Command line: one subcommand per feature, logging when none is given ---
+// Global flags (--user, --profile, --data-file, --remote, --config, --goal-days,
+// --exclude-source, --phase, --format, --dry-run, --read-only, --wide, --compact,
+// --no-color, --portable, --json, --record, --replay) are taken out by GlobalOptions::parse first, so they work
+// anywhere on the line.
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+
+const GLOBAL_OPTIONS: &str = "\
+Global options (accepted anywhere):
+      --user <name>             Use this person's files on a shared machine
+      --profile <name>          Use a named profile with its own data file and goal
+      --data-file <path>        Read and write this data file (.jsonl, .msgpack or CSV)
+      --remote <user@host:path> Keep the data file on another machine, over ssh
+      --config <path>           Read settings from this file instead of config.toml
+      --goal-days <days>        Length of the logging goal, overriding the config
+      --exclude-source <kind>   Leave these entries out of analyses (e.g. import)
+      --phase <n|current>       Analyse only one goal phase (1 is the first)
+      --format <format>         table, json or csv (view, stats, export, effect, model, ...),
+                                or parquet (export)
+      --dry-run                 Show what a writing command would change
+      --read-only               Refuse every command that modifies the data
+      --wide                    Don't cut tables to the terminal width
+      --compact                 Short numeric prompts for small screens
+      --no-color                Plain text without colour codes
+      --portable                Keep config and data next to the executable
+                                (also on when a portable.txt file is there)
+      --json                    Report errors as JSON on stderr, with a stable code
+                                (and print `schema` and `features` as JSON)
+      --record <file>           Save every answer given to the prompts
+      --replay <file>           Answer the prompts from a recorded session
+
+Each option can also be set with a DML_<OPTION> environment variable, e.g. DML_DATA_FILE,
+DML_PROFILE or DML_NO_COLOR=1 (DML_EXCLUDE_SOURCES takes a comma-separated list).
+DML_THEME and DML_TIMEZONE replace those config settings. Flags win over the
+environment, and the environment over the config file.";
+
+#[derive(Parser)]
+#[command(
+    name = "daily_metrics_logger",
+    version,
+    about = "Log daily self-ratings and see how they relate",
+    after_help = GLOBAL_OPTIONS
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    #[command(about = "Log an entry (the default when no command is given)")]
+    Log(LogArgs),
+    #[command(about = "Set up the data file, tracked metrics, goal and time zone")]
+    Init,
+    #[command(about = "Walk through the main features on sample data")]
+    Tour,
+    #[command(about = "Summary statistics of the daily values")]
+    Stats,
+    #[command(about = "Log in one line, e.g. quick s3 z2 e7 st6 f8 i7 w1 \"good day\"")]
+    Quick(QuickArgs),
+    #[command(about = "Show the most recent entries as a table")]
+    View(ViewArgs),
+    #[command(about = "Write every entry as CSV, JSON or Parquet")]
+    Export(ExportArgs),
+    #[command(about = "Change the answers of a logged entry")]
+    Edit(EditArgs),
+    #[command(about = "Mark a date with a milestone, e.g. annotate 2024-05-10 \"new job\"")]
+    Annotate(AnnotateArgs),
+    #[command(about = "Typical values to compare against until 14 days are logged")]
+    Baseline(BaselineArgs),
+    #[command(about = "Go through impossible values, duplicate days and long gaps")]
+    Review(ReviewArgs),
+    #[command(about = "Average of a metric by hour of day")]
+    ByHour {
+        #[arg(default_value = "energy")]
+        metric: String,
+    },
+    #[command(about = "How ratings change between entries on the same day")]
+    Intraday,
+    #[command(about = "Compare several people's exported files (read-only)")]
+    Compare { files: Vec<String> },
+    #[command(about = "Progress report: text, a template or a static site")]
+    Report(ReportArgs),
+    #[command(about = "Month grid of logged days")]
+    Calendar(CalendarArgs),
+    #[command(about = "Today's entries at a glance")]
+    Today(TodayArgs),
+    #[command(about = "Import from Exist.io, Bearable, Toggl, Clockify, Google Forms or any CSV")]
+    Import(ImportArgs),
+    #[command(about = "Write scheduled reports, post metric reminders and import watched files")]
+    Daemon(DaemonArgs),
+    #[command(about = "Show what each metric means")]
+    Describe(DescribeArgs),
+    #[command(about = "Columns of the data and exports: names, types, ranges and units")]
+    Schema,
+    #[command(about = "Optional parts this binary was built with (cargo features)")]
+    Features,
+    #[command(about = "Bring an older CSV data file up to the current column layout")]
+    Migrate(MigrateArgs),
+    #[command(about = "List the profiles, or add one with its own config: profile new <name>")]
+    Profile(ProfileArgs),
+    #[command(about = "Rename a metric, move a rating to another scale, or turn one off")]
+    Metric(MetricArgs),
+    #[command(about = "Move entries to the trash")]
+    Delete(DeleteArgs),
+    #[command(about = "List, restore or empty deleted entries")]
+    Trash(TrashArgs),
+    #[command(about = "History of changes to the data file")]
+    Audit(AuditArgs),
+    #[command(about = "Standardized questionnaires (WHO-5, sleep hygiene)")]
+    Bundle(BundleArgs),
+    #[command(about = "Generate realistic sample data")]
+    Generate(GenerateArgs),
+    #[command(about = "Predict ratings if some metrics changed")]
+    Whatif(WhatifArgs),
+    #[command(
+        about = "Regression of one metric on others, e.g. model energy ~ sleep_hours + workout_today"
+    )]
+    Model(ModelArgs),
+    #[command(about = "How long logging takes, month by month")]
+    Friction,
+    #[command(about = "Keyword trends and the tone of the remarks next to the metrics")]
+    RemarksAnalysis(RemarksArgs),
+    #[command(about = "Each metric before vs after a milestone, with effect sizes")]
+    Effect(EffectArgs),
+    #[command(about = "Strongest links between metrics (or an external CSV or another profile)")]
+    Correlate(CorrelateArgs),
+    #[command(about = "The week's most notable changes, new links and broken streaks")]
+    Insights(InsightsArgs),
+    #[command(about = "Named challenges: their progress, or start one")]
+    Challenge(ChallengeArgs),
+    #[command(about = "Suggestions from your own data, e.g. a bedtime")]
+    Recommend(RecommendArgs),
+    #[command(about = "Post a notification (Termux or Windows) when today isn't logged")]
+    Remind(RemindArgs),
+    #[command(about = "Send daily summaries to an OpenTelemetry collector")]
+    Otlp(OtlpArgs),
+    #[cfg(feature = "test-support")]
+    #[command(about = "Property-based checks of the CSV reader/writer")]
+    Fuzz(FuzzArgs),
+}
+
+#[derive(Args, Default)]
+pub struct LogArgs {
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Pre-fill the prompts from a TOML file"
+    )]
+    pub defaults: Option<String>,
+    #[arg(
+        short,
+        long,
+        help = "Don't prompt: use the defaults file and the previous entry"
+    )]
+    pub yes: bool,
+    #[arg(
+        long,
+        help = "Exit with code 3, without prompting, when today is already logged"
+    )]
+    pub if_missing: bool,
+    #[arg(long, help = "Log another reading on a day that already has one")]
+    pub follow_up: bool,
+    #[arg(
+        long,
+        conflicts_with = "follow_up",
+        help = "Replace today's last entry (it goes to the trash)"
+    )]
+    pub correction: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "csv",
+        help = "How --dry-run shows the entry it would append"
+    )]
+    pub preview: ExportFormat,
+}
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+// Global `--format`: coloured text by default, or data for scripts and dashboards
+#[derive(Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+    Parquet, // Binary and typed, so only `export` writes it
+}
+
+impl OutputFormat {
+    // How commands that list entries write them; None for the coloured table
+    pub fn rows(self) -> Option<ExportFormat> {
+        match self {
+            OutputFormat::Table | OutputFormat::Parquet => None,
+            OutputFormat::Json => Some(ExportFormat::Json),
+            OutputFormat::Csv => Some(ExportFormat::Csv),
+        }
+    }
+}
+
+// `export` takes its format from the global --format flag
+#[derive(Args)]
+pub struct ExportArgs {
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Write here instead of stdout"
+    )]
+    pub output: Option<String>,
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        value_delimiter = ',',
+        help = "Only these columns, in this order, e.g. timestamp,energy,focus"
+    )]
+    pub fields: Vec<String>,
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        value_delimiter = ',',
+        help = "Leave these columns out, e.g. remarks"
+    )]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct RemindArgs {
+    #[arg(
+        long,
+        help = "Windows: add a daily Task Scheduler entry that runs `remind`"
+    )]
+    pub install: bool,
+    #[arg(
+        long,
+        value_name = "HH:MM",
+        help = "When the scheduled reminder runs (reminder_time in config.toml, else 21:00)"
+    )]
+    pub at: Option<String>,
+}
+
+#[derive(Args)]
+pub struct QuickArgs {
+    #[arg(
+        required = true,
+        help = "sh sq s z m e st f i w followed by a value; other words become the remarks (after -- when they start with '-')"
+    )]
+    pub answers: Vec<String>,
+    #[arg(long, help = "Log another reading on a day that already has one")]
+    pub follow_up: bool,
+    #[arg(
+        long,
+        conflicts_with = "follow_up",
+        help = "Replace today's last entry (it goes to the trash)"
+    )]
+    pub correction: bool,
+}
+
+#[derive(Args)]
+pub struct ViewArgs {
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 10,
+        help = "How many entries to show"
+    )]
+    pub last: usize,
+}
+
+#[derive(Args)]
+pub struct EditArgs {
+    #[arg(help = "`last` or a full timestamp (pick from recent entries when left out)")]
+    pub entry: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ReviewArgs {
+    #[arg(
+        long,
+        value_name = "KIND",
+        help = "Only entries from this source, e.g. import"
+    )]
+    pub source: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Period {
+    Week,
+    Month,
+}
+
+#[derive(Args)]
+pub struct RemarksArgs {
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 10,
+        help = "How many keywords to list"
+    )]
+    pub top: usize,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "month",
+        help = "Count keywords per week or month"
+    )]
+    pub by: Period,
+}
+
+#[derive(Args)]
+pub struct AnnotateArgs {
+    #[arg(help = "YYYY-MM-DD, today or yesterday (lists the milestones when left out)")]
+    pub date: Option<String>,
+    #[arg(
+        trailing_var_arg = true,
+        help = "What happened, e.g. \"started new medication\""
+    )]
+    pub label: Vec<String>,
+    #[arg(
+        long,
+        value_name = "DATE",
+        conflicts_with = "date",
+        help = "Remove the milestone on this date"
+    )]
+    pub remove: Option<String>,
+}
+
+#[derive(Args)]
+pub struct BaselineArgs {
+    #[arg(value_enum, default_value = "show")]
+    pub action: BaselineAction,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum BaselineAction {
+    Show,
+    Set, // Enter the typical value of each tracked metric
+    Clear,
+}
+
+#[derive(Args)]
+pub struct ProfileArgs {
+    #[arg(value_enum, default_value = "list")]
+    pub action: ProfileAction,
+    #[arg(help = "Name of the new profile, e.g. partner")]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ProfileAction {
+    List,
+    New, // Copy config.toml to config_<name>.toml, minus the data file and goal start
+}
+
+#[derive(Args)]
+pub struct ChallengeArgs {
+    #[arg(value_enum, default_value = "list")]
+    pub action: ChallengeAction,
+    #[arg(help = "Name of the new challenge, e.g. \"30-day focus sprint\"")]
+    pub name: Option<String>,
+    #[arg(
+        long,
+        default_value_t = 30,
+        help = "Length of the new challenge in days"
+    )]
+    pub days: i64,
+    #[arg(
+        long = "metric",
+        value_name = "METRIC",
+        help = "A metric the challenge is about (repeatable)"
+    )]
+    pub metrics: Vec<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ChallengeAction {
+    List,
+    Start, // Add a challenge starting today to the config
+}
+
+#[derive(Args)]
+pub struct MigrateArgs {
+    #[arg(
+        long,
+        value_name = "OLD=NEW",
+        help = "Read column OLD as NEW, e.g. --rename brainfry=zonkedness"
+    )]
+    pub rename: Vec<String>,
+    #[arg(long, help = "Drop columns that aren't part of any layout")]
+    pub drop_unknown: bool,
+}
+
+#[derive(Args)]
+pub struct EffectArgs {
+    #[arg(help = "The milestone's label (or part of it), or a date YYYY-MM-DD")]
+    pub milestone: String,
+    #[arg(
+        long,
+        value_name = "DAYS",
+        default_value_t = 14,
+        help = "Days compared on each side"
+    )]
+    pub window: u32,
+}
+
+#[derive(Args)]
+pub struct InsightsArgs {
+    #[arg(
+        long,
+        value_name = "DAYS",
+        default_value_t = 7,
+        help = "Days that count as this week"
+    )]
+    pub days: u32,
+    #[arg(
+        long,
+        help = "Post the top three as a notification (Termux or Windows) instead of printing"
+    )]
+    pub send: bool,
+}
+
+#[derive(Args)]
+pub struct ModelArgs {
+    #[arg(required = true, help = "outcome ~ predictor + predictor ...")]
+    pub formula: Vec<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Daily CSV with more predictors, e.g. a caffeine column"
+    )]
+    pub with: Option<String>,
+    #[arg(
+        long,
+        value_name = "COLUMN",
+        default_value = "date",
+        help = "Date column of the --with file"
+    )]
+    pub on: String,
+}
+
+#[derive(Args)]
+pub struct ReportArgs {
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["copy", "template", "style"],
+        help = "Write a static site (index.html with charts) to this directory"
+    )]
+    pub bundle: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "style",
+        help = "Render this Handlebars template instead of the text report"
+    )]
+    pub template: Option<String>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "A built-in template: minimal, detailed, coach or printable"
+    )]
+    pub style: Option<String>,
+    #[arg(long, help = "Also put the report on the clipboard")]
+    pub copy: bool,
+}
+
+#[derive(Args)]
+pub struct CalendarArgs {
+    #[arg(value_name = "YYYY-MM", help = "The month to show (this month when left out)")]
+    pub month: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TodayArgs {
+    #[arg(long, help = "Show the summary as a QR code (JSON) instead")]
+    pub qr: bool,
+    #[arg(long, help = "Also put the summary on the clipboard")]
+    pub copy: bool,
+}
+
+// Exactly one source per run
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("source")
+        .required(true)
+        .args(["exist", "bearable", "toggl", "clockify", "google_forms", "wizard"])
+))]
+pub struct ImportArgs {
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Exist.io export (CSV, or JSON from the API)"
+    )]
+    pub exist: Option<String>,
+    #[arg(long, value_name = "FILE", help = "Bearable CSV export")]
+    pub bearable: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Toggl time entries CSV, summed into daily work_hours"
+    )]
+    pub toggl: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Clockify time entries CSV, summed into daily work_hours"
+    )]
+    pub clockify: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Google Forms responses CSV (asks which question is which metric)"
+    )]
+    pub google_forms: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Any CSV, with its columns matched to metrics step by step"
+    )]
+    pub wizard: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["google_forms", "wizard"],
+        help = "TOML file mapping the export's attributes to metrics"
+    )]
+    pub mapping: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DaemonArgs {
+    #[arg(
+        long,
+        help = "Write today's reports and import waiting files, then exit (for cron)"
+    )]
+    pub once: bool,
+}
+
+#[derive(Args)]
+pub struct DescribeArgs {
+    #[arg(value_name = "METRIC", help = "Only these metrics (all when left out)")]
+    pub metrics: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct MetricArgs {
+    #[command(subcommand)]
+    pub action: MetricAction,
+}
+
+#[derive(Subcommand)]
+pub enum MetricAction {
+    #[command(about = "Give a metric's column a new name (the old one stays an alias)")]
+    Rename { old: String, new: String },
+    #[command(about = "Move a rating to another scale, e.g. metric scale focus 1 5")]
+    Scale {
+        metric: String,
+        min: u8,
+        max: u8,
+        #[arg(
+            long,
+            conflicts_with = "keep",
+            help = "Rewrite earlier values on the new scale"
+        )]
+        rescale: bool,
+        #[arg(
+            long,
+            help = "Keep earlier values as entered; analyses convert them"
+        )]
+        keep: bool,
+    },
+    #[command(about = "Ask the metric again while logging")]
+    On { metric: String },
+    #[command(about = "Stop asking the metric while logging (its data is kept)")]
+    Off { metric: String },
+}
+
+#[derive(Args)]
+pub struct DeleteArgs {
+    #[arg(value_name = "ENTRY", help = "last, a date (YYYY-MM-DD) or a full timestamp")]
+    pub selector: String,
+}
+
+#[derive(Args)]
+pub struct TrashArgs {
+    #[arg(value_enum, default_value = "list")]
+    pub action: TrashAction,
+    #[arg(
+        required_if_eq("action", "restore"),
+        help = "Id of the trash item to restore"
+    )]
+    pub id: Option<u64>,
+    #[arg(short, long, help = "Empty the trash without asking")]
+    pub yes: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum TrashAction {
+    List,
+    Restore, // Put one trash item's entries back
+    Empty,
+}
+
+#[derive(Args)]
+pub struct AuditArgs {
+    #[arg(long, value_name = "N", help = "Only the last N changes")]
+    pub last: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct BundleArgs {
+    #[arg(value_enum, default_value = "list")]
+    pub action: BundleAction,
+    #[arg(
+        required_if_eq_any([("action", "take"), ("action", "scores")]),
+        help = "The questionnaire, e.g. who5"
+    )]
+    pub id: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum BundleAction {
+    List,
+    Take,   // Answer the questionnaire now
+    Scores, // Past scores
+}
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    #[arg(
+        long,
+        default_value_t = 90,
+        value_parser = clap::value_parser!(i64).range(1..),
+        help = "Days of history, ending today"
+    )]
+    pub days: i64,
+    #[arg(long, help = "Seed for the same data every time (random when left out)")]
+    pub seed: Option<u64>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write here instead of the (empty) data file"
+    )]
+    pub output: Option<String>,
+}
+
+#[derive(Args)]
+pub struct WhatifArgs {
+    #[arg(
+        long = "set",
+        value_name = "METRIC=VALUE",
+        required = true,
+        help = "A changed value, e.g. sleep_hours=8 (repeatable)"
+    )]
+    pub settings: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct CorrelateArgs {
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "with_profile",
+        help = "Daily CSV with more columns to correlate, e.g. weather"
+    )]
+    pub with: Option<String>,
+    #[arg(
+        long,
+        value_name = "COLUMN",
+        default_value = "date",
+        help = "Date column of the --with file"
+    )]
+    pub on: String,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Another profile's log, joined by date"
+    )]
+    pub with_profile: Option<String>,
+}
+
+#[derive(Args)]
+pub struct RecommendArgs {
+    #[arg(value_enum)]
+    pub topic: RecommendTopic,
+    #[arg(
+        long,
+        value_name = "HH:MM",
+        help = "When you get up (wake_time in config.toml)"
+    )]
+    pub wake: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum RecommendTopic {
+    Sleep, // A bedtime from the sleep duration with the best next day
+}
+
+#[derive(Args)]
+pub struct OtlpArgs {
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "all",
+        value_parser = clap::value_parser!(i64).range(1..),
+        help = "Send the last N days (7 by default)"
+    )]
+    pub days: Option<i64>,
+    #[arg(long, help = "Send every day")]
+    pub all: bool,
+}
+
+#[cfg(feature = "test-support")]
+#[derive(Args)]
+pub struct FuzzArgs {
+    #[arg(
+        long,
+        default_value_t = 200,
+        help = "How many random files to check"
+    )]
+    pub cases: usize,
+    #[arg(long, help = "Seed to reproduce a run (random when left out)")]
+    pub seed: Option<u64>,
+}
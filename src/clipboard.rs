@@ -0,0 +1,80 @@
+use crate::AppError;
+
+// Set in the environment of the helper process that keeps the text on a Linux clipboard
+#[cfg(all(feature = "clipboard", target_os = "linux"))]
+const HELPER_ENV: &str = "DML_CLIPBOARD_HELPER";
+
+// --- Put plain text on the system clipboard (used by `--copy`) ---
+#[cfg(all(feature = "clipboard", not(target_os = "linux")))]
+pub fn copy_to_clipboard(text: &str) -> Result<(), AppError> {
+    let mut clipboard = arboard::Clipboard::new()?;
+    clipboard.set_text(strip_ansi(text))?;
+    Ok(())
+}
+
+// X11 and Wayland only hand the text out while its owner runs, so a detached copy of this
+// program holds it until something else is copied
+#[cfg(all(feature = "clipboard", target_os = "linux"))]
+pub fn copy_to_clipboard(text: &str) -> Result<(), AppError> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    arboard::Clipboard::new()?; // Fails here, not in the helper, when there is no display
+    let mut helper = Command::new(std::env::current_exe()?)
+        .env(HELPER_ENV, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = helper.stdin.take() {
+        stdin.write_all(strip_ansi(text).as_bytes())?;
+    }
+    Ok(())
+}
+
+// Run as the clipboard helper when started by copy_to_clipboard: returns false otherwise
+#[cfg(all(feature = "clipboard", target_os = "linux"))]
+pub fn serve_helper() -> bool {
+    use arboard::SetExtLinux;
+    use std::io::Read;
+    if std::env::var_os(HELPER_ENV).is_none() {
+        return false;
+    }
+    let mut text = String::new();
+    if std::io::stdin().read_to_string(&mut text).is_ok() {
+        if let Ok(mut clipboard) = arboard::Clipboard::new() {
+            let _ = clipboard.set().wait().text(text); // Returns once the text is replaced
+        }
+    }
+    true
+}
+
+#[cfg(not(all(feature = "clipboard", target_os = "linux")))]
+pub fn serve_helper() -> bool {
+    false
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn copy_to_clipboard(_text: &str) -> Result<(), AppError> {
+    Err(AppError::UsageError(
+        "This build has no clipboard support (rebuild with --features clipboard)".to_string(),
+    ))
+}
+
+// Remove terminal colour codes so pasted text stays clean
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip everything up to and including the final letter of the escape sequence
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,705 @@
+use crate::bundles;
+use crate::constraints;
+use crate::daily::{Aggregate, FlagAggregate};
+use crate::push;
+use crate::schema::metric_info;
+use crate::units::conversion_factor;
+use crate::{env_setting, AppError, ENV_PREFIX, METRIC_NAMES};
+use chrono::{NaiveDate, NaiveTime};
+use directories::ProjectDirs;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml_edit::{DocumentMut, Item, Table, TableLike};
+
+pub const CONFIG_FILE: &str = "config.toml"; // Lives next to the data (per user when --user is set)
+const APP_NAME: &str = "daily-metric-logger"; // Folder name in the platform directories
+pub const WORKOUT_QUESTION: &str = "workout_today"; // How sections name the workout question
+pub const THEMES: [&str; 2] = ["colorful", "simple"]; // Prompt styles for `theme`
+
+// ~/.config/daily-metric-logger/config.toml (~/Library/Application Support/... on macOS),
+// read when the data directory has no config of its own, so one file configures the
+// logger wherever it's run from
+pub fn user_config_file() -> Option<PathBuf> {
+    let dirs = ProjectDirs::from("", "", APP_NAME)?;
+    Some(dirs.config_dir().join(CONFIG_FILE))
+}
+
+// ~/.local/share/daily-metric-logger (or $XDG_DATA_HOME), ~/Library/Application
+// Support/daily-metric-logger on macOS: where the data lives when nothing says otherwise
+pub fn platform_data_dir() -> Option<PathBuf> {
+    ProjectDirs::from("", "", APP_NAME).map(|dirs| dirs.data_dir().to_path_buf())
+}
+
+// --- Portable mode: config and data beside the executable (a USB stick, a synced folder) ---
+// On with --portable or DML_PORTABLE, or when this file sits next to the program
+pub const PORTABLE_MARKER: &str = "portable.txt";
+
+static PORTABLE: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+// Called once while the global options are read, before any path is worked out
+pub fn install_portable(requested: bool) -> Result<(), String> {
+    let dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let dir = match dir {
+        Some(dir) if requested || dir.join(PORTABLE_MARKER).exists() => Some(dir),
+        None if requested => {
+            return Err("--portable: can't tell where the executable is".to_string())
+        }
+        _ => None,
+    };
+    let _ = PORTABLE.set(dir);
+    Ok(())
+}
+
+// The executable's folder in portable mode
+pub fn portable_dir() -> Option<&'static Path> {
+    PORTABLE.get()?.as_deref()
+}
+
+// Expand a leading `~` so config paths like "~/reports" work (Windows has USERPROFILE)
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            PathBuf::from(home).join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+// A profile can have a whole config of its own beside config.toml (`profile new`)
+pub fn profile_config_file(profile: &str) -> String {
+    format!("config_{}.toml", profile)
+}
+
+// --- Settings read from config.toml ---
+// Every key is optional; anything missing falls back to the built-in defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub goal_days: Option<i64>,
+    pub goal_start: Option<String>, // YYYY-MM-DD the current goal phase began
+    pub data_file: Option<String>,  // Relative to the data directory; may start with ~
+    pub remote: Option<String>,     // user@host[:path]: the data file lives on that machine
+    pub default_sleep_hours: Option<f64>, // Pre-filled answer to the sleep question (8)
+    pub theme: Option<String>,      // Prompt style: colorful (the default) or simple
+    pub read_only: Option<bool>,    // Refuse writing commands, e.g. for an archived dataset
+    pub compact: Option<bool>,      // Short numeric prompts for phones (on by default in Termux)
+    pub session_meta: Option<bool>, // Record how long each log took and how many answers were redone
+    pub review: Option<bool>, // Show the answers for a last check before saving (on by default)
+    pub wake_time: Option<String>, // HH:MM, used by `recommend sleep` to suggest a bedtime
+    pub reminder_time: Option<String>, // HH:MM for `remind --install` and the summary after `log`
+    pub summary: Option<SummaryConfig>, // Parts of the block shown after `log`
+    pub timezone: Option<String>, // IANA name, e.g. "Europe/Berlin": local times are shown in it
+    pub yearly_files: Option<bool>, // One data file per year: daily_metrics_2025.csv, ...
+    pub backup: Option<BackupConfig>, // Copies in backups/ before every write
+    pub bundles: Vec<String>, // Questionnaire bundles asked while logging, e.g. ["who5"]
+    pub workout_aggregate: Option<String>, // How several same-day workout answers combine (any)
+    pub profile: BTreeMap<String, ProfileConfig>, // `[profile.<name>]` sections
+    pub report: BTreeMap<String, ScheduledReport>, // `report.weekly = { ... }`, run by `daemon`
+    pub reminder: BTreeMap<String, MetricReminder>, // `reminder.<metric> = { ... }`, posted by `daemon`
+    pub watch: Option<WatchFolder>, // Folder the daemon imports new export files from
+    pub otlp: Option<OtlpConfig>,   // OpenTelemetry collector for the `otlp` command
+    pub push: Option<PushConfig>,   // statsd or Graphite server each new entry is sent to
+    pub metric: BTreeMap<String, MetricConfig>, // `[metric.<name>]` schema overrides
+    pub custom: BTreeMap<String, CustomMetric>, // `[custom.<name>]` metrics of your own
+    pub sections: Vec<QuestionSection>, // `[[sections]]`: the order of the questions while logging
+    pub challenges: Vec<Challenge>, // `[[challenges]]`: named runs shown in the banner
+}
+
+// --- Overrides applied when a profile is selected with --profile ---
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub goal_days: Option<i64>,
+    pub goal_start: Option<String>,
+    pub data_file: Option<String>,
+    pub read_only: Option<bool>,
+    pub bundles: Option<Vec<String>>, // Replaces the top-level list for this profile
+}
+
+// --- Per-metric schema details shown by `describe` ---
+// Anything left out keeps the built-in definition.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricConfig {
+    pub description: Option<String>,
+    pub low: Option<String>,          // What the bottom of the scale means
+    pub high: Option<String>,         // What the top of the scale means
+    pub unit: Option<String>,         // Unit the values are stored in
+    pub display_unit: Option<String>, // Convert to this unit when showing values (e.g. lbs)
+    pub name: Option<String>,         // Column name after `metric rename`
+    pub aliases: Vec<String>,         // Former names that still resolve to this metric
+    pub aggregate: Option<String>,    // How several entries on one day combine (mean, sum, ...)
+    pub enabled: Option<bool>,        // false: never asked while logging (the column stays)
+    pub scale: Option<[f64; 2]>,      // Rating scale after `metric scale`, e.g. [1, 5]
+    pub prompt: Option<String>,       // The question while logging, e.g. "Mental sharpness"
+}
+
+// --- A named challenge alongside the goal, e.g. a focus sprint ---
+// [[challenges]]
+// name = "30-day focus sprint"
+// start = "2025-06-01"
+// days = 30
+// metrics = ["focus"]  # A day counts when these were logged (any entry when left out)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Challenge {
+    pub name: String,
+    pub start: String, // YYYY-MM-DD
+    pub days: i64,
+    #[serde(default)]
+    pub metrics: Vec<String>, // Metrics (or their names) and numeric custom metrics
+}
+
+// --- Questions asked together while logging, under a header ---
+// [[sections]]
+// title = "Sleep"
+// metrics = ["sleep_hours", "sleep_quality", "sleepiness"]
+// Sections come in the order they're listed; anything left out follows in the usual order.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuestionSection {
+    pub title: String,
+    pub metrics: Vec<String>, // Metrics (or their names), custom metrics and workout_today
+}
+
+// --- A metric of your own, stored in a column after the built-in ones ---
+// [custom.caffeine]
+// prompt = "Cups of coffee so far?"
+// type = "float"
+// range = [0, 12]
+// ask = "entry"
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomMetric {
+    pub prompt: Option<String>, // The question while logging (the name when unset)
+    #[serde(rename = "type")]
+    pub kind: CustomKind,
+    pub range: Option<[f64; 2]>, // Allowed values of a scale (1-10 when unset) or float
+    #[serde(default)]
+    pub ask: AskWhen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomKind {
+    Scale, // Whole numbers, like the built-in ratings
+    Float,
+    Bool,
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AskWhen {
+    Daily, // With the day's first entry only, like sleep
+    #[default]
+    Entry, // With every entry, like the ratings
+}
+
+// --- A report the daemon writes on a schedule ---
+// report.weekly = { day = "sun", time = "20:00", format = "html", output = "~/reports" }
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduledReport {
+    pub day: Option<ScheduleDay>, // Weekday for weekly reports, day of month for monthly ones
+    pub time: Option<String>,     // HH:MM local time, defaults to 20:00
+    pub format: Option<String>,   // text, html, bundle, or a built-in style name
+    pub output: String,           // Directory the reports are written to
+}
+
+// --- A reminder for one metric, posted by the daemon apart from the daily one ---
+// reminder.weight = { day = "mon", time = "08:00" }
+// reminder.water = { every_hours = 3, from = "08:00", until = "22:00" }
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetricReminder {
+    pub day: Option<String>,      // Only on this weekday (every day when unset)
+    pub time: Option<String>,     // HH:MM local time, defaults to 09:00
+    pub every_hours: Option<u32>, // Repeat through the day instead of one `time`
+    pub from: Option<String>,     // First repeat, HH:MM (08:00)
+    pub until: Option<String>,    // No repeats after this, HH:MM (22:00)
+    pub message: Option<String>,  // Notification text instead of "Time to log <metric>"
+}
+
+// --- A folder the daemon imports new export files from ---
+// watch = { folder = "~/wearable", format = "exist", mapping = "wearable.toml" }
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchFolder {
+    pub folder: String,          // Checked about once a minute
+    pub format: String,          // exist, bearable, toggl or clockify, like the `import` flags
+    pub mapping: Option<String>, // Mapping file (relative paths are next to the data)
+}
+
+// --- What the block after a logged entry shows; every part is on unless set to false ---
+// summary = { insight = false }
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SummaryConfig {
+    pub streak: Option<bool>,   // Days logged in a row
+    pub averages: Option<bool>, // Today's answers next to the averages so far
+    pub insight: Option<bool>,  // The strongest link between two metrics
+    pub reminder: Option<bool>, // When tomorrow's reminder comes (needs reminder_time)
+}
+
+// --- How many copies of the data file backups/ keeps ---
+// backup = { keep = 30, daily = true }
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupConfig {
+    pub keep: Option<usize>, // Copies kept, 10 by default; 0 turns backups off
+    pub daily: Option<bool>, // One copy per day (before its first write) instead of per write
+}
+
+// --- OpenTelemetry collector the `otlp` command sends daily summaries to ---
+// otlp = { endpoint = "http://localhost:4318", headers = { Authorization = "Bearer ..." } }
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OtlpConfig {
+    pub endpoint: String, // OTLP/HTTP base URL; /v1/metrics is appended
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>, // Extra request headers, e.g. for auth
+    pub service_name: Option<String>, // Resource service.name (daily_metrics)
+}
+
+// --- statsd or Graphite server each new entry is pushed to ---
+// push = { protocol = "graphite", address = "graphite.lan:2003", prefix = "personal" }
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PushConfig {
+    pub protocol: String, // statsd (UDP gauges) or graphite (plaintext over TCP)
+    pub address: String,  // host:port
+    pub prefix: Option<String>, // First part of every path, "personal" by default
+}
+
+// `day = "sun"` or `day = 1`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ScheduleDay {
+    Name(String),
+    Number(i64),
+}
+
+impl Config {
+    // Metrics are asked unless `[metric.<name>] enabled = false`
+    pub fn metric_enabled(&self, metric: &str) -> bool {
+        self.metric
+            .get(metric)
+            .and_then(|m| m.enabled)
+            .unwrap_or(true)
+    }
+
+    // The question asked while logging, when `[metric.<name>] prompt` rewords it
+    pub fn metric_prompt(&self, metric: &str) -> Option<&str> {
+        self.metric.get(metric).and_then(|m| m.prompt.as_deref())
+    }
+
+    // Load the config file, treating a missing file as an empty config
+    pub fn load(path: &Path) -> Result<Config, AppError> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let text = fs::read_to_string(path)?;
+        let config: Config = toml::from_str(&text)
+            .map_err(|e| AppError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        config.validate(path)?;
+        Ok(config)
+    }
+
+    // DML_THEME and DML_TIMEZONE replace the file's values, e.g. in a container
+    pub fn apply_env(&mut self) -> Result<(), String> {
+        if let Some(theme) = env_setting("THEME") {
+            if !THEMES.contains(&theme.as_str()) {
+                return Err(format!(
+                    "{}THEME must be one of {}, got '{}'",
+                    ENV_PREFIX,
+                    THEMES.join(", "),
+                    theme
+                ));
+            }
+            self.theme = Some(theme);
+        }
+        if let Some(zone) = env_setting("TIMEZONE") {
+            if zone.parse::<chrono_tz::Tz>().is_err() {
+                return Err(format!(
+                    "{}TIMEZONE '{}' is not a known time zone name (e.g. Europe/Berlin)",
+                    ENV_PREFIX, zone
+                ));
+            }
+            self.timezone = Some(zone);
+        }
+        Ok(())
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), AppError> {
+        let goals = std::iter::once(("goal_days".to_string(), self.goal_days)).chain(
+            self.profile
+                .iter()
+                .map(|(name, p)| (format!("profile.{}.goal_days", name), p.goal_days)),
+        );
+        if let Some(name) = self
+            .metric
+            .keys()
+            .find(|name| !METRIC_NAMES.contains(&name.as_str()))
+        {
+            return Err(AppError::ConfigError(format!(
+                "{}: unknown metric '{}' in [metric.{}]",
+                path.display(),
+                name,
+                name
+            )));
+        }
+        // Every name a metric has gone by must point at only that metric
+        let mut seen: BTreeMap<&str, &str> = METRIC_NAMES.iter().map(|m| (*m, *m)).collect();
+        for (metric, settings) in &self.metric {
+            for name in settings.name.iter().chain(&settings.aliases) {
+                if let Some(other) = seen.insert(name, metric).filter(|other| other != metric) {
+                    return Err(AppError::ConfigError(format!(
+                        "{}: name '{}' is used by both {} and {}",
+                        path.display(),
+                        name,
+                        other,
+                        metric
+                    )));
+                }
+            }
+        }
+        for (name, settings) in &self.metric {
+            if let Some(aggregate) = settings
+                .aggregate
+                .as_deref()
+                .filter(|a| Aggregate::parse(a).is_none())
+            {
+                return Err(AppError::ConfigError(format!(
+                    "{}: metric.{}.aggregate '{}' is not one of {}",
+                    path.display(),
+                    name,
+                    aggregate,
+                    Aggregate::NAMES.join(", ")
+                )));
+            }
+            if settings
+                .prompt
+                .as_ref()
+                .is_some_and(|p| p.trim().is_empty())
+            {
+                return Err(AppError::ConfigError(format!(
+                    "{}: metric.{}.prompt is empty",
+                    path.display(),
+                    name
+                )));
+            }
+        }
+        for (name, settings) in &self.metric {
+            let Some([min, max]) = settings.scale else {
+                continue;
+            };
+            let problem = if !constraints::is_rating(name) {
+                Some("only 1-10 ratings can change scale")
+            } else if !(0.0..=f64::from(u8::MAX)).contains(&min) || max > f64::from(u8::MAX) {
+                Some("must be between 0 and 255")
+            } else if min >= max {
+                Some("needs the lower end first")
+            } else if min.fract() != 0.0 || max.fract() != 0.0 {
+                Some("must be whole numbers")
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                return Err(AppError::ConfigError(format!(
+                    "{}: metric.{}.scale {}",
+                    path.display(),
+                    name,
+                    problem
+                )));
+            }
+        }
+        if let Some(aggregate) = self
+            .workout_aggregate
+            .as_deref()
+            .filter(|a| FlagAggregate::parse(a).is_none())
+        {
+            return Err(AppError::ConfigError(format!(
+                "{}: workout_aggregate '{}' is not one of {}",
+                path.display(),
+                aggregate,
+                FlagAggregate::NAMES.join(", ")
+            )));
+        }
+        for name in self.metric.keys() {
+            // The stored unit may come from the built-in definition
+            let Some(info) = metric_info(self, name) else {
+                continue;
+            };
+            if let Some(display) = &info.display_unit {
+                let unit = info.unit.as_deref().unwrap_or("");
+                if conversion_factor(unit, display).is_none() {
+                    return Err(AppError::ConfigError(format!(
+                        "{}: metric.{}.display_unit '{}' cannot be converted from unit '{}'",
+                        path.display(),
+                        name,
+                        display,
+                        unit
+                    )));
+                }
+            }
+        }
+        let starts = std::iter::once(("goal_start".to_string(), &self.goal_start)).chain(
+            self.profile
+                .iter()
+                .map(|(name, p)| (format!("profile.{}.goal_start", name), &p.goal_start)),
+        );
+        for (key, start) in starts {
+            if let Some(start) = start {
+                if NaiveDate::parse_from_str(start, "%Y-%m-%d").is_err() {
+                    return Err(AppError::ConfigError(format!(
+                        "{}: {} must be a YYYY-MM-DD date, got '{}'",
+                        path.display(),
+                        key,
+                        start
+                    )));
+                }
+            }
+        }
+        let bundle_lists = std::iter::once(("bundles".to_string(), &self.bundles)).chain(
+            self.profile.iter().filter_map(|(name, p)| {
+                Some((format!("profile.{}.bundles", name), p.bundles.as_ref()?))
+            }),
+        );
+        for (key, ids) in bundle_lists {
+            if let Some(id) = ids.iter().find(|id| bundles::find(id).is_none()) {
+                return Err(AppError::ConfigError(format!(
+                    "{}: unknown bundle '{}' in {} (choose from {})",
+                    path.display(),
+                    id,
+                    key,
+                    bundles::BUNDLES.map(|b| b.id).join(", ")
+                )));
+            }
+        }
+        if let Some(push) = &self.push {
+            if !push::PROTOCOLS.contains(&push.protocol.as_str()) {
+                return Err(AppError::ConfigError(format!(
+                    "{}: push.protocol '{}' is not one of {}",
+                    path.display(),
+                    push.protocol,
+                    push::PROTOCOLS.join(", ")
+                )));
+            }
+        }
+        let sleep_range = constraints::constraint("sleep_hours").and_then(|c| c.range);
+        if let (Some(hours), Some((min, max))) = (self.default_sleep_hours, sleep_range) {
+            if !(min..=max).contains(&hours) {
+                return Err(AppError::ConfigError(format!(
+                    "{}: default_sleep_hours must be between {} and {}, got {}",
+                    path.display(),
+                    min,
+                    max,
+                    hours
+                )));
+            }
+        }
+        if let Some(theme) = self.theme.as_deref().filter(|t| !THEMES.contains(t)) {
+            return Err(AppError::ConfigError(format!(
+                "{}: theme '{}' is not one of {}",
+                path.display(),
+                theme,
+                THEMES.join(", ")
+            )));
+        }
+        if let Some(zone) = &self.timezone {
+            if zone.parse::<chrono_tz::Tz>().is_err() {
+                return Err(AppError::ConfigError(format!(
+                    "{}: timezone '{}' is not a known time zone name (e.g. Europe/Berlin)",
+                    path.display(),
+                    zone
+                )));
+            }
+        }
+        for (key, time) in [
+            ("wake_time", &self.wake_time),
+            ("reminder_time", &self.reminder_time),
+        ] {
+            if let Some(time) = time {
+                if NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                    return Err(AppError::ConfigError(format!(
+                        "{}: {} must be HH:MM, got '{}'",
+                        path.display(),
+                        key,
+                        time
+                    )));
+                }
+            }
+        }
+        for (name, custom) in &self.custom {
+            let problem = if let Err(e) = crate::validate_name("metric", name) {
+                Some(e)
+            } else if constraints::constraint(name).is_some() || seen.contains_key(name.as_str()) {
+                Some("is already the name of a built-in column".to_string())
+            } else {
+                match (custom.kind, custom.range) {
+                    (CustomKind::Bool | CustomKind::Text, Some(_)) => {
+                        Some("range only applies to scale and float metrics".to_string())
+                    }
+                    (_, Some([min, max])) if min >= max => {
+                        Some("range needs the lower end first".to_string())
+                    }
+                    (CustomKind::Scale, Some([min, max]))
+                        if min.fract() != 0.0 || max.fract() != 0.0 =>
+                    {
+                        Some("a scale's range must be whole numbers".to_string())
+                    }
+                    _ => None,
+                }
+            };
+            if let Some(problem) = problem {
+                return Err(AppError::ConfigError(format!(
+                    "{}: custom.{}: {}",
+                    path.display(),
+                    name,
+                    problem
+                )));
+            }
+        }
+        let mut placed: Vec<&str> = Vec::new();
+        for section in &self.sections {
+            if section.title.trim().is_empty() {
+                return Err(AppError::ConfigError(format!(
+                    "{}: every [[sections]] needs a title",
+                    path.display()
+                )));
+            }
+            for name in &section.metrics {
+                let question = match seen.get(name.as_str()) {
+                    Some(metric) => *metric,
+                    None if self.custom.contains_key(name) || name == WORKOUT_QUESTION => {
+                        name.as_str()
+                    }
+                    None => {
+                        return Err(AppError::ConfigError(format!(
+                            "{}: section '{}': unknown metric '{}'",
+                            path.display(),
+                            section.title,
+                            name
+                        )))
+                    }
+                };
+                if placed.contains(&question) {
+                    return Err(AppError::ConfigError(format!(
+                        "{}: section '{}': {} is already in a section",
+                        path.display(),
+                        section.title,
+                        name
+                    )));
+                }
+                placed.push(question);
+            }
+        }
+        for (i, challenge) in self.challenges.iter().enumerate() {
+            let problem = if challenge.name.trim().is_empty() {
+                Some("needs a name".to_string())
+            } else if self.challenges[..i]
+                .iter()
+                .any(|c| c.name.eq_ignore_ascii_case(&challenge.name))
+            {
+                Some("the name is used twice".to_string())
+            } else if NaiveDate::parse_from_str(&challenge.start, "%Y-%m-%d").is_err() {
+                Some(format!(
+                    "start must be a YYYY-MM-DD date, got '{}'",
+                    challenge.start
+                ))
+            } else if challenge.days <= 0 {
+                Some("days must be a positive number".to_string())
+            } else {
+                challenge
+                    .metrics
+                    .iter()
+                    .find(|name| {
+                        !seen.contains_key(name.as_str())
+                            && !self.custom.get(*name).is_some_and(|c| {
+                                matches!(c.kind, CustomKind::Scale | CustomKind::Float)
+                            })
+                    })
+                    .map(|name| format!("unknown or non-numeric metric '{}'", name))
+            };
+            if let Some(problem) = problem {
+                return Err(AppError::ConfigError(format!(
+                    "{}: challenge '{}': {}",
+                    path.display(),
+                    challenge.name,
+                    problem
+                )));
+            }
+        }
+        for (key, goal) in goals {
+            if matches!(goal, Some(days) if days <= 0) {
+                return Err(AppError::ConfigError(format!(
+                    "{}: {} must be a positive number",
+                    path.display(),
+                    key
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+// --- Change config.toml in place, keeping the user's comments and layout ---
+pub fn edit_config(
+    path: &Path,
+    edit: impl FnOnce(&mut DocumentMut) -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    let text = if path.exists() {
+        fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+    let mut doc: DocumentMut = text
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("{}: {}", path.display(), e)))?;
+    edit(&mut doc)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, doc.to_string())?;
+    Ok(())
+}
+
+// Table at a dotted path such as ["profile", "work"], created if missing
+pub fn table_at<'a>(doc: &'a mut DocumentMut, keys: &[&str]) -> Option<&'a mut dyn TableLike> {
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for (depth, key) in keys.iter().enumerate() {
+        table = table
+            .entry(key)
+            .or_insert_with(|| {
+                // Only the innermost table gets its own [header]
+                let mut new = Table::new();
+                new.set_implicit(depth + 1 < keys.len());
+                Item::Table(new)
+            })
+            .as_table_like_mut()?;
+    }
+    Some(table)
+}
+
+// Set a key, keeping any comment around an existing value
+pub fn set_value(table: &mut dyn TableLike, key: &str, new: Item) {
+    match table.get_mut(key) {
+        Some(item) => {
+            let decor = item.as_value().map(|v| v.decor().clone());
+            *item = new;
+            if let (Some(decor), Some(value)) = (decor, item.as_value_mut()) {
+                *value.decor_mut() = decor;
+            }
+        }
+        None => {
+            table.insert(key, new);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::config::Config;
+use crate::scales;
+use crate::{AppError, LogEntry};
+use chrono::DateTime;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::OnceLock;
+
+// --- Column constraints: what every written row must satisfy ---
+// The interactive prompts, defaults files and importers each check their own input
+// first; this table is the last line of defence before anything reaches the CSV.
+#[derive(Clone, Copy)]
+pub enum ColumnType {
+    Timestamp, // RFC 3339
+    Integer,
+    Decimal,
+    Boolean,
+    Text,
+}
+
+impl ColumnType {
+    pub fn name(self) -> &'static str {
+        match self {
+            ColumnType::Timestamp => "timestamp",
+            ColumnType::Integer => "integer",
+            ColumnType::Decimal => "decimal",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Text => "text",
+        }
+    }
+}
+
+pub struct Constraint {
+    pub column: &'static str,
+    pub kind: ColumnType,
+    pub range: Option<(f64, f64)>, // Inclusive; booleans use 0/1 for imports
+    pub nullable: bool,
+}
+
+const fn column(
+    column: &'static str,
+    kind: ColumnType,
+    range: Option<(f64, f64)>,
+    nullable: bool,
+) -> Constraint {
+    Constraint {
+        column,
+        kind,
+        range,
+        nullable,
+    }
+}
+
+const RATING: Option<(f64, f64)> = Some((1.0, 10.0));
+const FLAG: Option<(f64, f64)> = Some((0.0, 1.0));
+const NON_NEGATIVE: Option<(f64, f64)> = Some((0.0, f64::MAX));
+
+pub const CONSTRAINTS: [Constraint; 17] = [
+    column("timestamp", ColumnType::Timestamp, None, false),
+    // day_count 0 means "not numbered yet"
+    column("day_count", ColumnType::Integer, NON_NEGATIVE, false),
+    column("sleep_hours", ColumnType::Decimal, Some((0.0, 12.0)), true),
+    column("sleep_quality", ColumnType::Decimal, RATING, true),
+    column("sleepiness", ColumnType::Integer, RATING, true),
+    column("zonkedness", ColumnType::Integer, RATING, true),
+    column("mid_vibes", ColumnType::Integer, RATING, true),
+    column("energy", ColumnType::Integer, RATING, true),
+    column("strength", ColumnType::Integer, RATING, true),
+    column("focus", ColumnType::Integer, RATING, true),
+    column("intelligence", ColumnType::Integer, RATING, true),
+    column("workout_today", ColumnType::Boolean, FLAG, false),
+    column("remarks", ColumnType::Text, None, true),
+    column("session_seconds", ColumnType::Integer, None, true),
+    column("session_edits", ColumnType::Integer, None, true),
+    column("provenance", ColumnType::Text, None, true),
+    column("entry_kind", ColumnType::Text, None, true),
+];
+
+pub fn constraint(column: &str) -> Option<&'static Constraint> {
+    CONSTRAINTS.iter().find(|c| c.column == column)
+}
+
+// --- Rating scales changed with `metric scale` (`[metric.<name>] scale = [1, 5]`) ---
+static SCALES: OnceLock<BTreeMap<&'static str, (f64, f64)>> = OnceLock::new();
+
+// Only 1-10 ratings can move to another scale
+pub fn is_rating(column: &str) -> bool {
+    constraint(column).is_some_and(|c| c.range == RATING)
+}
+
+// A column's range under a config, before or without it being installed
+pub fn configured_range(config: &Config, column: &str) -> Option<(f64, f64)> {
+    config
+        .metric
+        .get(column)
+        .and_then(|m| m.scale)
+        .filter(|_| is_rating(column))
+        .map(|[min, max]| (min, max))
+        .or(constraint(column)?.range)
+}
+
+// Called once at startup, after the config is loaded (scales were validated then) and the
+// scale changes are read
+pub fn install_scales(config: &Config) {
+    let scales = CONSTRAINTS
+        .iter()
+        .filter_map(|c| {
+            let range = scales::held_range(c.column).or_else(|| configured_range(config, c.column));
+            Some((c.column, range?))
+        })
+        .collect();
+    let _ = SCALES.set(scales);
+}
+
+// A column's inclusive range: its scale when one is configured
+pub fn range(column: &str) -> Option<(f64, f64)> {
+    match SCALES.get() {
+        Some(scales) => scales.get(column).copied(),
+        None => constraint(column)?.range,
+    }
+}
+
+// --- One value that broke its column's constraint ---
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub timestamp: String, // Identifies the row
+    pub column: &'static str,
+    pub value: String,
+    pub problem: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} = {}: {}",
+            self.timestamp, self.column, self.value, self.problem
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Violations(pub Vec<Violation>);
+
+impl fmt::Display for Violations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = self.0.iter().map(|v| v.to_string()).collect();
+        write!(f, "{}", lines.join("; "))
+    }
+}
+
+impl Constraint {
+    fn check(&self, value: &Value) -> Option<String> {
+        if value.is_null() || value.as_str() == Some("") {
+            return (!self.nullable).then(|| "a value is required".to_string());
+        }
+        let ok_type = match self.kind {
+            ColumnType::Timestamp => value
+                .as_str()
+                .is_some_and(|s| DateTime::parse_from_rfc3339(s).is_ok()),
+            ColumnType::Integer => value.is_i64() || value.is_u64(),
+            // serde_json writes non-finite floats as null, so a number here is finite
+            ColumnType::Decimal => value.is_number(),
+            ColumnType::Boolean => value.is_boolean(),
+            ColumnType::Text => value.is_string(),
+        };
+        if !ok_type {
+            return Some(match self.kind {
+                ColumnType::Timestamp => "not an RFC 3339 timestamp".to_string(),
+                ColumnType::Integer => "not a whole number".to_string(),
+                ColumnType::Decimal => "not a finite number".to_string(),
+                ColumnType::Boolean => "not true or false".to_string(),
+                ColumnType::Text => "not text".to_string(),
+            });
+        }
+        let (min, max) = range(self.column)?;
+        let number = value.as_f64()?;
+        if (min..=max).contains(&number) {
+            None
+        } else if max == f64::MAX {
+            Some(format!("must be at least {}", min))
+        } else {
+            Some(format!("must be between {} and {}", min, max))
+        }
+    }
+}
+
+// --- Every constraint an entry breaks ---
+pub fn check(entry: &LogEntry) -> Vec<Violation> {
+    let row = serde_json::to_value(entry).unwrap_or(Value::Null);
+    let mut violations: Vec<Violation> = CONSTRAINTS
+        .iter()
+        .filter_map(|c| {
+            let value = row.get(c.column).unwrap_or(&Value::Null);
+            let problem = c.check(value)?;
+            Some(Violation {
+                timestamp: entry.timestamp.clone(),
+                column: c.column,
+                value: match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                },
+                problem,
+            })
+        })
+        .collect();
+    // NaN and infinity serialize as null, which a nullable column would accept
+    for (column, value) in [
+        ("sleep_hours", entry.sleep_hours),
+        ("sleep_quality", entry.sleep_quality),
+    ] {
+        if value.is_some_and(|v| !v.is_finite()) {
+            violations.push(Violation {
+                timestamp: entry.timestamp.clone(),
+                column,
+                value: value.unwrap_or_default().to_string(),
+                problem: "not a finite number".to_string(),
+            });
+        }
+    }
+    violations
+}
+
+pub fn validate(entries: &[LogEntry]) -> Result<(), AppError> {
+    let violations: Vec<Violation> = entries.iter().flat_map(check).collect();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::ValidationError(Violations(violations)))
+    }
+}
+
+// --- Validate only the rows a rewrite adds or changes ---
+// Rows already on disk are left alone, so one old out-of-range value doesn't block
+// every later edit, import or restore.
+pub fn validate_changes(before: &[LogEntry], after: &[LogEntry]) -> Result<(), AppError> {
+    let existing: HashMap<&str, &LogEntry> =
+        before.iter().map(|e| (e.timestamp.as_str(), e)).collect();
+    let changed: Vec<LogEntry> = after
+        .iter()
+        .filter(|e| {
+            existing.get(e.timestamp.as_str()).is_none_or(|old| {
+                // day_count is renumbered on every rewrite
+                let renumbered = LogEntry {
+                    day_count: old.day_count,
+                    ..(*e).clone()
+                };
+                renumbered != **old
+            })
+        })
+        .cloned()
+        .collect();
+    validate(&changed)
+}
@@ -0,0 +1,241 @@
+use crate::cancel::PromptSession;
+use crate::config::{AskWhen, Config, CustomKind, CustomMetric};
+use crate::constraints::ColumnType;
+use crate::replay;
+use crate::{AppError, LogEntry, THEME};
+use csv::StringRecord;
+use dialoguer::{Confirm, Input};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+// --- Metrics of your own, declared with `[custom.<name>]` in the config ---
+// Each one is a column after the built-in ones, in name order. Values are kept as the
+// text they're stored as: numbers as typed, yes/no as true/false.
+static CUSTOM: OnceLock<Vec<(String, CustomMetric)>> = OnceLock::new();
+
+// Called once at startup, before any entry is read or written
+pub fn install(config: &Config) {
+    let metrics = config
+        .custom
+        .iter()
+        .map(|(name, metric)| (name.clone(), metric.clone()))
+        .collect();
+    let _ = CUSTOM.set(metrics);
+}
+
+pub fn metrics() -> &'static [(String, CustomMetric)] {
+    CUSTOM.get().map_or(&[], Vec::as_slice)
+}
+
+// Column names, in the order they follow the built-in columns
+pub fn columns() -> impl Iterator<Item = &'static str> {
+    metrics().iter().map(|(name, _)| name.as_str())
+}
+
+pub fn find(name: &str) -> Option<&'static CustomMetric> {
+    metrics().iter().find(|(n, _)| n == name).map(|(_, m)| m)
+}
+
+fn numeric(metric: &CustomMetric) -> bool {
+    matches!(metric.kind, CustomKind::Scale | CustomKind::Float)
+}
+
+// Scales and floats, which analyses take alongside the built-in metrics
+pub fn numeric_columns() -> impl Iterator<Item = &'static str> {
+    metrics()
+        .iter()
+        .filter(|(_, metric)| numeric(metric))
+        .map(|(name, _)| name.as_str())
+}
+
+pub fn number(entry: &LogEntry, name: &str) -> Option<f64> {
+    entry.custom.get(name)?.parse().ok()
+}
+
+// Store a number in a numeric column (scales rounded, floats to two decimals), returning
+// false when there is no such column
+pub fn set_number(entry: &mut LogEntry, name: &str, value: f64) -> bool {
+    let Some(metric) = find(name).filter(|m| numeric(m)) else {
+        return false;
+    };
+    let stored = match metric.kind {
+        CustomKind::Scale => (value.round() as i64).to_string(),
+        _ => ((value * 100.0).round() / 100.0).to_string(),
+    };
+    entry.custom.insert(name.to_string(), stored);
+    true
+}
+
+// The type the column has in the schema and in typed exports
+pub fn column_type(kind: CustomKind) -> ColumnType {
+    match kind {
+        CustomKind::Scale => ColumnType::Integer,
+        CustomKind::Float => ColumnType::Decimal,
+        CustomKind::Bool => ColumnType::Boolean,
+        CustomKind::Text => ColumnType::Text,
+    }
+}
+
+// Allowed values of a numeric metric (a scale is 1-10 unless configured)
+pub fn range(metric: &CustomMetric) -> Option<(f64, f64)> {
+    match (metric.kind, metric.range) {
+        (CustomKind::Scale | CustomKind::Float, Some([min, max])) => Some((min, max)),
+        (CustomKind::Scale, None) => Some((1.0, 10.0)),
+        _ => None,
+    }
+}
+
+// An answer in the form it's stored, or why it doesn't fit
+pub fn parse(metric: &CustomMetric, text: &str) -> Result<String, String> {
+    let text = text.trim();
+    let in_range = |value: f64| match range(metric) {
+        Some((min, max)) if !(min..=max).contains(&value) => {
+            Err(format!("Please enter a number between {} and {}", min, max))
+        }
+        _ => Ok(()),
+    };
+    match metric.kind {
+        CustomKind::Text => Ok(text.to_string()),
+        CustomKind::Bool => match text.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Ok("true".to_string()),
+            "false" | "no" | "n" | "0" => Ok("false".to_string()),
+            _ => Err("Please answer yes or no".to_string()),
+        },
+        CustomKind::Scale => {
+            let value = text
+                .parse::<i64>()
+                .map_err(|_| "Please enter a whole number".to_string())?;
+            in_range(value as f64)?;
+            Ok(value.to_string())
+        }
+        CustomKind::Float => {
+            let value = text
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| "Please enter a number (e.g. 2.5)".to_string())?;
+            in_range(value)?;
+            Ok(value.to_string())
+        }
+    }
+}
+
+fn prompt(name: &str, metric: &CustomMetric) -> String {
+    let question = metric.prompt.as_deref().unwrap_or(name);
+    match range(metric) {
+        Some((min, max)) => format!("{} ({}-{})", question, min, max),
+        None => question.to_string(),
+    }
+}
+
+// The question itself; an empty answer leaves the value out
+fn interact(name: &str, metric: &CustomMetric, current: Option<&str>) -> dialoguer::Result<String> {
+    if metric.kind == CustomKind::Bool {
+        let mut confirm = Confirm::with_theme(&**THEME).with_prompt(prompt(name, metric));
+        if let Some(current) = current {
+            confirm = confirm.default(current == "true");
+        }
+        return confirm.interact().map(|yes| yes.to_string());
+    }
+    let mut input = Input::<String>::with_theme(&**THEME)
+        .with_prompt(prompt(name, metric))
+        .allow_empty(true);
+    if let Some(current) = current {
+        input = input.default(current.to_string());
+    }
+    input
+        .validate_with(|text: &String| -> Result<(), String> {
+            if text.trim().is_empty() {
+                return Ok(());
+            }
+            parse(metric, text).map(|_| ())
+        })
+        .interact_text()
+}
+
+fn stored(metric: &CustomMetric, text: &str) -> Result<Option<String>, AppError> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    parse(metric, text).map(Some).map_err(AppError::UsageError)
+}
+
+// Whether a new entry asks this question: daily ones only with the day's first entry
+pub fn asked(name: &str, first_of_day: bool) -> bool {
+    find(name).is_some_and(|metric| metric.ask != AskWhen::Daily || first_of_day)
+}
+
+// One custom question for a new entry, None when it isn't asked or is left empty
+pub fn ask(
+    session: &PromptSession,
+    name: &str,
+    first_of_day: bool,
+) -> Result<Option<String>, AppError> {
+    let Some(metric) = find(name).filter(|_| asked(name, first_of_day)) else {
+        return Ok(None);
+    };
+    match session.ask(name, || interact(name, metric, None))? {
+        Some(text) => stored(metric, &text),
+        None => Ok(None),
+    }
+}
+
+// One question again, from the review before saving
+pub fn ask_again(name: &str, entry: &mut LogEntry) -> Result<(), AppError> {
+    let Some(metric) = find(name) else {
+        return Ok(());
+    };
+    let current = entry.custom.get(name).cloned();
+    let text = replay::answer(name, || interact(name, metric, current.as_deref()))?;
+    match stored(metric, &text)? {
+        Some(value) => entry.custom.insert(name.to_string(), value),
+        None => entry.custom.remove(name),
+    };
+    Ok(())
+}
+
+// --- CSV columns ---
+
+// An entry's values in column order, empty where there's no answer
+pub fn record_values(entry: &LogEntry) -> Vec<String> {
+    columns()
+        .map(|name| entry.custom.get(name).cloned().unwrap_or_default())
+        .collect()
+}
+
+// The values in a CSV row's custom columns (found by header name)
+pub fn read_values(headers: &StringRecord, record: &StringRecord) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .zip(record.iter())
+        .filter(|(header, value)| {
+            !value.trim().is_empty() && metrics().iter().any(|(name, _)| name == header)
+        })
+        .map(|(header, value)| (header.to_string(), value.to_string()))
+        .collect()
+}
+
+// Values as JSON numbers and booleans, keyed by column like the built-in fields
+pub fn json_values(entry: &LogEntry) -> impl Iterator<Item = (String, Value)> + '_ {
+    metrics().iter().filter_map(|(name, metric)| {
+        let text = entry.custom.get(name)?;
+        let value = match column_type(metric.kind) {
+            ColumnType::Integer => text.parse::<i64>().ok().map(Value::from),
+            ColumnType::Decimal => text.parse::<f64>().ok().map(Value::from),
+            ColumnType::Boolean => text.parse::<bool>().ok().map(Value::from),
+            _ => None,
+        };
+        Some((
+            name.clone(),
+            value.unwrap_or_else(|| Value::from(text.as_str())),
+        ))
+    })
+}
+
+// The number of custom columns a header ends with, when they're exactly the declared ones
+pub fn trailing_columns(header: &[&str]) -> Option<usize> {
+    let count = metrics().len();
+    let tail = header.get(header.len().checked_sub(count)?..)?;
+    (count > 0 && tail.iter().copied().eq(columns())).then_some(count)
+}
@@ -0,0 +1,591 @@
+use crate::cli::DaemonArgs;
+use crate::clipboard::strip_ansi;
+use crate::config::{expand_home, MetricReminder, ScheduleDay, ScheduledReport, WatchFolder};
+use crate::custom;
+use crate::import::{import_file, FILE_FORMATS};
+use crate::milestones::read_milestones;
+use crate::report::{format_text_report, write_bundle};
+use crate::schema::{canonical_metric, column_name};
+use crate::store;
+use crate::templates::{render_style, ReportContext, STYLES};
+use crate::termux;
+use crate::units::DisplayUnits;
+use crate::{AppError, GlobalOptions, LogEntry, METRIC_NAMES};
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveTime, Utc, Weekday};
+use colored::*;
+use csv::{ReaderBuilder, WriterBuilder};
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::SystemTime;
+
+const DEFAULT_TIME: &str = "20:00";
+const MAX_SLEEP_SECS: i64 = 60; // Wake up regularly so clock changes and suspends are noticed
+const SETTLE_SECS: u64 = 30; // Leave files alone until they stop changing (still being copied)
+const NUDGE_TIME: &str = "09:00";
+const NUDGE_FROM: &str = "08:00";
+const NUDGE_UNTIL: &str = "22:00";
+
+// --- When a scheduled report runs ---
+#[derive(Debug, Clone, Copy)]
+enum Schedule {
+    Daily,
+    Weekly(Weekday),
+    Monthly(u32), // Day of month, 1-28 so every month has it
+}
+
+#[derive(Debug)]
+struct Job {
+    name: String,
+    schedule: Schedule,
+    time: NaiveTime,
+    format: String,
+    output: PathBuf,
+}
+
+impl Job {
+    fn from_config(name: &str, report: &ScheduledReport) -> Result<Job, AppError> {
+        let invalid = |msg: String| AppError::ConfigError(format!("report.{}: {}", name, msg));
+
+        let schedule = match (name, &report.day) {
+            ("daily", None) => Schedule::Daily,
+            ("daily", Some(_)) => return Err(invalid("daily reports take no 'day'".to_string())),
+            ("weekly", None) => Schedule::Weekly(Weekday::Sun),
+            ("weekly", Some(ScheduleDay::Name(day))) => Schedule::Weekly(
+                day.parse::<Weekday>()
+                    .map_err(|_| invalid(format!("'{}' is not a weekday", day)))?,
+            ),
+            ("monthly", None) => Schedule::Monthly(1),
+            ("monthly", Some(ScheduleDay::Number(day))) if (1..=28).contains(day) => {
+                Schedule::Monthly(*day as u32)
+            }
+            ("weekly", Some(_)) => return Err(invalid("'day' must be a weekday name".to_string())),
+            ("monthly", Some(_)) => {
+                return Err(invalid("'day' must be a number from 1 to 28".to_string()))
+            }
+            _ => {
+                return Err(AppError::ConfigError(format!(
+                    "unknown report schedule 'report.{}' (use daily, weekly or monthly)",
+                    name
+                )))
+            }
+        };
+
+        let time_text = report.time.as_deref().unwrap_or(DEFAULT_TIME);
+        let time = NaiveTime::parse_from_str(time_text, "%H:%M")
+            .map_err(|_| invalid(format!("'{}' is not a HH:MM time", time_text)))?;
+
+        let format = report.format.clone().unwrap_or_else(|| "text".to_string());
+        let known = ["text", "html", "bundle"].contains(&format.as_str())
+            || STYLES.iter().any(|(style, _, _)| *style == format);
+        if !known {
+            return Err(invalid(format!(
+                "unknown format '{}' (text, html, bundle or a style name)",
+                format
+            )));
+        }
+
+        Ok(Job {
+            name: name.to_string(),
+            schedule,
+            time,
+            format,
+            output: expand_home(&report.output),
+        })
+    }
+
+    fn matches(&self, date: NaiveDate) -> bool {
+        match self.schedule {
+            Schedule::Daily => true,
+            Schedule::Weekly(day) => date.weekday() == day,
+            Schedule::Monthly(day) => date.day() == day,
+        }
+    }
+
+    // First run strictly after `after` (a month always contains the scheduled day)
+    fn next_run(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        (0..=31)
+            .map(|offset| after.date_naive() + Duration::days(offset))
+            .filter(|date| self.matches(*date))
+            .filter_map(|date| {
+                date.and_time(self.time)
+                    .and_local_timezone(Local)
+                    .earliest()
+            })
+            .find(|run| *run > after)
+    }
+
+    // Reports cover the period ending on the run date: one day, seven days or one month
+    fn period_start(&self, run_date: NaiveDate) -> NaiveDate {
+        match self.schedule {
+            Schedule::Daily => run_date,
+            Schedule::Weekly(_) => run_date - Duration::days(6),
+            Schedule::Monthly(_) => {
+                run_date
+                    .checked_sub_months(Months::new(1))
+                    .unwrap_or(run_date)
+                    + Duration::days(1)
+            }
+        }
+    }
+}
+
+fn log_line(message: &str) {
+    println!(
+        "{} {}",
+        format!("[{}]", Local::now().format("%Y-%m-%d %H:%M:%S")).dimmed(),
+        message
+    );
+}
+
+// --- Write one report covering the job's period, returning where it went ---
+fn write_report(
+    options: &GlobalOptions,
+    job: &Job,
+    run_date: NaiveDate,
+) -> Result<PathBuf, AppError> {
+    let start = job.period_start(run_date);
+    let entries: Vec<_> = options
+        .analysis_entries()?
+        .into_iter()
+        .filter(|e| {
+            e.parsed_timestamp()
+                .map(|ts| (start..=run_date).contains(&ts.date_naive()))
+                .unwrap_or(false)
+        })
+        .collect();
+
+    let milestones = read_milestones(&options.data_file())?;
+
+    let stem = format!("{}-{}", job.name, run_date.format("%Y-%m-%d"));
+    if job.format == "bundle" {
+        let dir = job.output.join(stem);
+        if !options.dry_run {
+            write_bundle(&dir, entries, &milestones)?;
+        }
+        return Ok(dir.join("index.html"));
+    }
+
+    let units = DisplayUnits::from_config(&options.config);
+    let (text, extension) = match job.format.as_str() {
+        "text" => (
+            strip_ansi(&format_text_report(entries, &units, &milestones)?),
+            "txt",
+        ),
+        "html" => (
+            render_style(
+                "printable",
+                &ReportContext::build(entries, options.goal_days, &units, &milestones),
+            )?,
+            "html",
+        ),
+        style => {
+            let html = STYLES.iter().any(|(name, _, html)| *name == style && *html);
+            (
+                render_style(
+                    style,
+                    &ReportContext::build(entries, options.goal_days, &units, &milestones),
+                )?,
+                if html { "html" } else { "txt" },
+            )
+        }
+    };
+    let path = job.output.join(format!("{}.{}", stem, extension));
+    if !options.dry_run {
+        fs::create_dir_all(&job.output)?;
+        fs::write(&path, text)?;
+    }
+    Ok(path)
+}
+
+fn run_job(options: &GlobalOptions, job: &Job, run_date: NaiveDate) {
+    match write_report(options, job, run_date) {
+        Ok(path) if options.dry_run => log_line(&format!(
+            "Would write {} report to {} (dry run)",
+            job.name,
+            path.display()
+        )),
+        Ok(path) => log_line(&format!(
+            "Wrote {} report to {}",
+            job.name,
+            path.display().to_string().green()
+        )),
+        // Keep running: the next scheduled report may well succeed
+        Err(e) => log_line(&format!(
+            "{} {} report failed: {}",
+            "Error:".red(),
+            job.name,
+            e
+        )),
+    }
+}
+
+// --- Reminders for single metrics (`reminder.<metric>` in the config) ---
+// A reminder is skipped when the metric already has a value since the one before it
+// (since the start of the day for once-a-day reminders), so it only nudges when needed.
+struct Nudge {
+    metric: String, // The stored name: a built-in metric or a custom one
+    day: Option<Weekday>,
+    times: Vec<NaiveTime>,
+    every_hours: Option<u32>,
+    message: String,
+}
+
+impl Nudge {
+    fn from_config(name: &str, reminder: &MetricReminder) -> Result<Nudge, AppError> {
+        let invalid = |msg: String| AppError::ConfigError(format!("reminder.{}: {}", name, msg));
+        let metric = match canonical_metric(name) {
+            Some(metric) => metric.to_string(),
+            None if custom::find(name).is_some() => name.to_string(),
+            None => {
+                return Err(invalid(format!(
+                    "unknown metric (use one of {}, or declare [custom.{}])",
+                    METRIC_NAMES.join(", "),
+                    name
+                )))
+            }
+        };
+        let time = |text: Option<&String>, default: &str| {
+            let text = text.map_or(default, String::as_str);
+            NaiveTime::parse_from_str(text, "%H:%M")
+                .map_err(|_| invalid(format!("'{}' is not a HH:MM time", text)))
+        };
+        let day = reminder
+            .day
+            .as_ref()
+            .map(|day| {
+                day.parse::<Weekday>()
+                    .map_err(|_| invalid(format!("'{}' is not a weekday", day)))
+            })
+            .transpose()?;
+        let times = match reminder.every_hours {
+            Some(hours) if (1..=12).contains(&hours) => {
+                if reminder.time.is_some() {
+                    return Err(invalid(
+                        "use either 'time' or 'every_hours', not both".to_string(),
+                    ));
+                }
+                let (from, until) = (
+                    time(reminder.from.as_ref(), NUDGE_FROM)?,
+                    time(reminder.until.as_ref(), NUDGE_UNTIL)?,
+                );
+                let times: Vec<NaiveTime> = (0..24)
+                    .map(|i| from + Duration::hours(i64::from(i * hours)))
+                    .take_while(|t| *t >= from && *t <= until)
+                    .collect();
+                if times.is_empty() {
+                    return Err(invalid("'from' must be before 'until'".to_string()));
+                }
+                times
+            }
+            Some(_) => return Err(invalid("'every_hours' must be from 1 to 12".to_string())),
+            None if reminder.from.is_some() || reminder.until.is_some() => {
+                return Err(invalid(
+                    "'from' and 'until' only go with 'every_hours'".to_string(),
+                ))
+            }
+            None => vec![time(reminder.time.as_ref(), NUDGE_TIME)?],
+        };
+        let message = reminder
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("Time to log {}", column_name(&metric)));
+        Ok(Nudge {
+            metric,
+            day,
+            times,
+            every_hours: reminder.every_hours,
+            message,
+        })
+    }
+
+    fn next_run(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        (0..=7)
+            .map(|offset| after.date_naive() + Duration::days(offset))
+            .filter(|date| self.day.is_none_or(|day| date.weekday() == day))
+            .flat_map(|date| self.times.iter().map(move |time| date.and_time(*time)))
+            .filter_map(|run| run.and_local_timezone(Local).earliest())
+            .find(|run| *run > after)
+    }
+
+    fn logged(&self, entry: &LogEntry) -> bool {
+        entry.metric(&self.metric).is_some() || entry.custom.contains_key(&self.metric)
+    }
+
+    // Whether the metric has a value since the previous reminder (or since midnight)
+    fn answered(&self, options: &GlobalOptions, run: DateTime<Local>) -> Result<bool, AppError> {
+        let since = match self.every_hours {
+            Some(hours) => run - Duration::hours(i64::from(hours)),
+            None => run
+                .date_naive()
+                .and_time(NaiveTime::MIN)
+                .and_local_timezone(Local)
+                .earliest()
+                .unwrap_or(run),
+        }
+        .with_timezone(&Utc);
+        let entries = store::open(&options.data_file())
+            .read_range(since.date_naive(), Utc::now().date_naive())?;
+        Ok(entries
+            .iter()
+            .any(|e| e.parsed_timestamp().is_some_and(|ts| ts >= since) && self.logged(e)))
+    }
+
+    fn run(&self, options: &GlobalOptions, run: DateTime<Local>) {
+        let id = format!("daily-metrics-{}", self.metric);
+        let result = self.answered(options, run).and_then(|answered| {
+            if answered || options.dry_run {
+                return Ok(answered);
+            }
+            termux::post(&id, column_name(&self.metric), &self.message).map(|_| false)
+        });
+        let name = column_name(&self.metric);
+        match result {
+            Ok(true) => log_line(&format!("{} already logged; no reminder needed", name)),
+            Ok(false) if options.dry_run => {
+                log_line(&format!("Would remind about {} (dry run)", name))
+            }
+            Ok(false) => log_line(&format!("Reminded about {}", name.green())),
+            Err(e) => log_line(&format!(
+                "{} reminder for {} failed: {}",
+                "Error:".red(),
+                name,
+                e
+            )),
+        }
+    }
+}
+
+// --- Watch folder: import export files as they appear ---
+// Imported files are listed next to the data file (daily_metrics.watched.csv) with
+// their modification time, so a file that is replaced later is imported again.
+struct Watcher {
+    folder: PathBuf,
+    format: String,
+    mapping: Option<String>,
+    seen_file: PathBuf,
+    seen: BTreeSet<(String, u64)>, // File name and modification time (seconds)
+}
+
+impl Watcher {
+    fn from_config(options: &GlobalOptions, watch: &WatchFolder) -> Result<Watcher, AppError> {
+        if !FILE_FORMATS.contains(&watch.format.as_str()) {
+            return Err(AppError::ConfigError(format!(
+                "watch: unknown format '{}' (choose from {})",
+                watch.format,
+                FILE_FORMATS.join(", ")
+            )));
+        }
+        let folder = expand_home(&watch.folder);
+        if !folder.is_dir() {
+            return Err(AppError::ConfigError(format!(
+                "watch: folder '{}' does not exist",
+                folder.display()
+            )));
+        }
+        let mapping = watch.mapping.as_ref().map(|path| {
+            options
+                .data_dir()
+                .join(expand_home(path))
+                .to_string_lossy()
+                .into_owned()
+        });
+        let seen_file = Path::new(&options.data_file()).with_extension("watched.csv");
+        let mut seen = BTreeSet::new();
+        if seen_file.exists() {
+            let mut rdr = ReaderBuilder::new().from_path(&seen_file)?;
+            for record in rdr.records() {
+                let record = record?;
+                if let (Some(name), Some(Ok(modified))) =
+                    (record.get(0), record.get(1).map(str::parse))
+                {
+                    seen.insert((name.to_string(), modified));
+                }
+            }
+        }
+        Ok(Watcher {
+            folder,
+            format: watch.format.clone(),
+            mapping,
+            seen_file,
+            seen,
+        })
+    }
+
+    // Files not imported yet that have stopped changing, oldest first
+    fn new_files(&self) -> Result<Vec<(PathBuf, String, u64)>, AppError> {
+        let now = SystemTime::now();
+        let mut files = Vec::new();
+        for dir_entry in fs::read_dir(&self.folder)? {
+            let dir_entry = dir_entry?;
+            let meta = dir_entry.metadata()?;
+            let name = dir_entry.file_name().to_string_lossy().into_owned();
+            if !meta.is_file() || name.starts_with('.') {
+                continue;
+            }
+            let modified = meta.modified()?;
+            let settled = now
+                .duration_since(modified)
+                .is_ok_and(|age| age.as_secs() >= SETTLE_SECS);
+            let stamp = modified
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            if settled && !self.seen.contains(&(name.clone(), stamp)) {
+                files.push((dir_entry.path(), name, stamp));
+            }
+        }
+        files.sort_by_key(|(_, _, stamp)| *stamp);
+        Ok(files)
+    }
+
+    fn remember(&mut self, name: String, stamp: u64) -> Result<(), AppError> {
+        let file_exists = self.seen_file.exists();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.seen_file)?;
+        let mut wtr = WriterBuilder::new().from_writer(file);
+        if !file_exists {
+            wtr.write_record(["file", "modified", "imported_at"])?;
+        }
+        wtr.write_record([
+            name.as_str(),
+            &stamp.to_string(),
+            &Local::now().to_rfc3339(),
+        ])?;
+        wtr.flush()?;
+        self.seen.insert((name, stamp));
+        Ok(())
+    }
+
+    // A failed file is logged and retried on the next check
+    fn check(&mut self, options: &GlobalOptions) {
+        let files = match self.new_files() {
+            Ok(files) => files,
+            Err(e) => {
+                log_line(&format!("{} watch folder: {}", "Error:".red(), e));
+                return;
+            }
+        };
+        for (path, name, stamp) in files {
+            log_line(&format!("Importing {} ({})", path.display(), self.format));
+            let result = import_file(options, &self.format, &path, self.mapping.clone());
+            match result {
+                Ok(()) if options.dry_run => {} // Dry runs import the file again next time
+                Ok(()) => {
+                    if let Err(e) = self.remember(name, stamp) {
+                        log_line(&format!(
+                            "{} {}: {}",
+                            "Error:".red(),
+                            self.seen_file.display(),
+                            e
+                        ));
+                    }
+                }
+                Err(e) => log_line(&format!("{} importing {}: {}", "Error:".red(), name, e)),
+            }
+        }
+    }
+}
+
+// --- `daemon` command: reports, reminders and the watch folder, in the foreground ---
+pub fn run_daemon(options: &GlobalOptions, args: &DaemonArgs) -> Result<(), AppError> {
+    let jobs = options
+        .config
+        .report
+        .iter()
+        .map(|(name, report)| Job::from_config(name, report))
+        .collect::<Result<Vec<_>, _>>()?;
+    let nudges = options
+        .config
+        .reminder
+        .iter()
+        .map(|(name, reminder)| Nudge::from_config(name, reminder))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut watcher = match &options.config.watch {
+        // Importing rewrites the data file, which read-only mode forbids
+        Some(_) if options.read_only && !options.dry_run => {
+            return Err(AppError::UsageError(
+                "The watch folder imports data and is disabled in read-only mode (try --dry-run)"
+                    .to_string(),
+            ));
+        }
+        Some(watch) => Some(Watcher::from_config(options, watch)?),
+        None => None,
+    };
+    if jobs.is_empty() && nudges.is_empty() && watcher.is_none() {
+        return Err(AppError::UsageError(
+            [
+                "No scheduled reports, reminders or watch folder configured. Add e.g. to config.toml:",
+                "  report.weekly = { day = \"sun\", format = \"html\", output = \"~/reports\" }",
+                "  reminder.weight = { day = \"mon\", time = \"08:00\" }",
+                "  watch = { folder = \"~/wearable\", format = \"exist\", mapping = \"wearable.toml\" }",
+            ]
+            .join("\n"),
+        ));
+    }
+
+    // --once writes every configured report for today, imports waiting files and exits
+    // (reminders are left out: they belong to a time of day)
+    if args.once {
+        let today = Local::now().date_naive();
+        for job in &jobs {
+            run_job(options, job, today);
+        }
+        if let Some(watcher) = &mut watcher {
+            watcher.check(options);
+        }
+        return Ok(());
+    }
+
+    log_line(&format!(
+        "Daemon started with {} scheduled report(s), {} reminder(s){}. Press Ctrl+C to stop.",
+        jobs.len(),
+        nudges.len(),
+        watcher.as_ref().map_or(String::new(), |w| format!(
+            ", watching {}",
+            w.folder.display()
+        ))
+    ));
+    let mut last = Local::now();
+    loop {
+        let next_report = jobs.iter().filter_map(|job| job.next_run(last)).min();
+        let next_nudge = nudges.iter().filter_map(|nudge| nudge.next_run(last)).min();
+        let next = next_report.into_iter().chain(next_nudge).min();
+        if let Some(next) = next_report {
+            log_line(&format!(
+                "Next report due {}",
+                next.format("%a %Y-%m-%d %H:%M")
+            ));
+        }
+
+        loop {
+            if let Some(watcher) = &mut watcher {
+                watcher.check(options);
+            }
+            let remaining = next.map_or(Duration::seconds(MAX_SLEEP_SECS), |next| {
+                next - Local::now()
+            });
+            if next.is_some() && remaining <= Duration::zero() {
+                break;
+            }
+            let secs = remaining.num_seconds().clamp(1, MAX_SLEEP_SECS);
+            thread::sleep(std::time::Duration::from_secs(secs as u64));
+        }
+
+        // Run everything that came due since the last check (several jobs may share a time)
+        let now = Local::now();
+        for job in &jobs {
+            if let Some(run) = job.next_run(last).filter(|run| *run <= now) {
+                run_job(options, job, run.date_naive());
+            }
+        }
+        for nudge in &nudges {
+            if let Some(run) = nudge.next_run(last).filter(|run| *run <= now) {
+                nudge.run(options, run);
+            }
+        }
+        last = now;
+    }
+}
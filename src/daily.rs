@@ -0,0 +1,381 @@
+// --- Daily summary layer: one value per metric per day ---
+// Several entries on one day are combined per metric (`aggregate` in [metric.<name>],
+// the mean by default; `workout_aggregate` for the workout answer, any by default). Summaries of the whole data file are cached next to it
+// (daily_metrics.csv -> daily_metrics.daily.csv) and rebuilt whenever the data
+// file, the aggregation settings or the custom metrics change.
+use crate::config::Config;
+use crate::custom;
+use crate::phases;
+use crate::scales;
+use crate::store;
+use crate::{group_by_day, read_entries, AppError, GlobalOptions, LogEntry, METRIC_NAMES};
+use chrono::NaiveDate;
+use csv::{ReaderBuilder, WriterBuilder};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::UNIX_EPOCH;
+
+// How several same-day values of a metric combine
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Aggregate {
+    Mean,
+    Min,
+    Max,
+    First,
+    Last,
+    Sum, // e.g. sleep_hours when naps are logged as their own entries
+}
+
+impl Aggregate {
+    pub const NAMES: [&'static str; 6] = ["mean", "min", "max", "first", "last", "sum"];
+
+    pub fn parse(name: &str) -> Option<Aggregate> {
+        match name {
+            "mean" => Some(Aggregate::Mean),
+            "min" => Some(Aggregate::Min),
+            "max" => Some(Aggregate::Max),
+            "first" => Some(Aggregate::First),
+            "last" => Some(Aggregate::Last),
+            "sum" => Some(Aggregate::Sum),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Aggregate::Mean => "mean",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::First => "first",
+            Aggregate::Last => "last",
+            Aggregate::Sum => "sum",
+        }
+    }
+
+    // Values are in the order they were logged
+    fn combine(self, values: &[f64]) -> Option<f64> {
+        if values.is_empty() {
+            return None;
+        }
+        Some(match self {
+            Aggregate::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Aggregate::Min => values.iter().copied().fold(f64::MAX, f64::min),
+            Aggregate::Max => values.iter().copied().fold(f64::MIN, f64::max),
+            Aggregate::First => values[0],
+            Aggregate::Last => values[values.len() - 1],
+            Aggregate::Sum => values.iter().sum(),
+        })
+    }
+}
+
+// The same for yes/no answers (workout_today): any entry, every entry, or first/last
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FlagAggregate {
+    Any,
+    All,
+    First,
+    Last,
+}
+
+impl FlagAggregate {
+    pub const NAMES: [&'static str; 4] = ["any", "all", "first", "last"];
+
+    pub fn parse(name: &str) -> Option<FlagAggregate> {
+        match name {
+            "any" => Some(FlagAggregate::Any),
+            "all" => Some(FlagAggregate::All),
+            "first" => Some(FlagAggregate::First),
+            "last" => Some(FlagAggregate::Last),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            FlagAggregate::Any => "any",
+            FlagAggregate::All => "all",
+            FlagAggregate::First => "first",
+            FlagAggregate::Last => "last",
+        }
+    }
+
+    // Never called with an empty day
+    fn combine(self, flags: &[bool]) -> bool {
+        match self {
+            FlagAggregate::Any => flags.iter().any(|f| *f),
+            FlagAggregate::All => flags.iter().all(|f| *f),
+            FlagAggregate::First => flags.first().copied().unwrap_or(false),
+            FlagAggregate::Last => flags.last().copied().unwrap_or(false),
+        }
+    }
+}
+
+struct Aggregation {
+    metrics: BTreeMap<&'static str, Aggregate>,
+    workout: FlagAggregate,
+}
+
+static AGGREGATION: OnceLock<Aggregation> = OnceLock::new();
+
+// Called once at startup, after the config is loaded (values were validated then)
+pub fn install_aggregates(config: &Config) {
+    let metrics = METRIC_NAMES
+        .iter()
+        .map(|metric| {
+            let aggregate = config
+                .metric
+                .get(*metric)
+                .and_then(|m| m.aggregate.as_deref())
+                .and_then(Aggregate::parse)
+                .unwrap_or(Aggregate::Mean);
+            (*metric, aggregate)
+        })
+        .collect();
+    let workout = config
+        .workout_aggregate
+        .as_deref()
+        .and_then(FlagAggregate::parse)
+        .unwrap_or(FlagAggregate::Any);
+    let _ = AGGREGATION.set(Aggregation { metrics, workout });
+}
+
+pub fn aggregate(metric: &str) -> Aggregate {
+    AGGREGATION
+        .get()
+        .and_then(|a| a.metrics.get(metric).copied())
+        .unwrap_or(Aggregate::Mean)
+}
+
+fn workout_aggregate() -> FlagAggregate {
+    AGGREGATION.get().map_or(FlagAggregate::Any, |a| a.workout)
+}
+
+// The built-in metrics, then the numeric `[custom.<name>]` ones (always averaged)
+fn columns() -> impl Iterator<Item = &'static str> {
+    METRIC_NAMES
+        .iter()
+        .copied()
+        .chain(custom::numeric_columns())
+}
+
+fn value(entry: &LogEntry, column: &str) -> Option<f64> {
+    entry
+        .metric(column)
+        .or_else(|| custom::number(entry, column))
+}
+
+// --- One day's combined values ---
+pub struct DailyPoint {
+    pub date: NaiveDate,
+    pub entries: usize,
+    pub values: BTreeMap<&'static str, f64>,
+    pub workout: bool,
+}
+
+pub fn daily_points(entries: Vec<LogEntry>) -> Vec<DailyPoint> {
+    group_by_day(entries)
+        .into_iter()
+        .map(|(date, mut day)| {
+            day.sort_by_key(|e| e.parsed_timestamp());
+            let flags: Vec<bool> = day.iter().map(|e| e.workout_today).collect();
+            let mut values = BTreeMap::new();
+            for metric in columns() {
+                let logged: Vec<f64> = day.iter().filter_map(|e| value(e, metric)).collect();
+                if let Some(value) = aggregate(metric).combine(&logged) {
+                    values.insert(metric, value);
+                }
+            }
+            DailyPoint {
+                date,
+                entries: day.len(),
+                values,
+                workout: workout_aggregate().combine(&flags),
+            }
+        })
+        .collect()
+}
+
+// --- Cached summaries of the selected data file ---
+fn cache_file(data_file: &str) -> PathBuf {
+    Path::new(data_file).with_extension("daily.csv")
+}
+
+// Changes whenever the data file (every year's file, when split) or the aggregation
+// settings do
+fn fingerprint(data_file: &str) -> Option<String> {
+    let files = store::files(data_file);
+    if files.is_empty() {
+        return None;
+    }
+    let mut sources = Vec::new();
+    for file in files {
+        let meta = fs::metadata(file).ok()?;
+        let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        sources.push(format!("{} {}", meta.len(), modified.as_nanos()));
+    }
+    let aggregates: Vec<&str> = METRIC_NAMES.iter().map(|m| aggregate(m).name()).collect();
+    let custom: Vec<&str> = custom::numeric_columns().collect();
+    Some(format!(
+        "# source {} {} {} {}",
+        sources.join(" "),
+        aggregates.join(","),
+        workout_aggregate().name(),
+        custom.join(",")
+    ))
+}
+
+fn read_cache(path: &Path, expected: &str) -> Option<Vec<DailyPoint>> {
+    let text = fs::read_to_string(path).ok()?;
+    let (first_line, _) = text.split_once('\n')?;
+    if first_line != expected {
+        return None;
+    }
+    let mut rdr = ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(text.as_bytes());
+    let mut points = Vec::new();
+    for record in rdr.records() {
+        // date, entries, workout_today, then one column per metric
+        let record = record.ok()?;
+        let mut values = BTreeMap::new();
+        for (metric, value) in columns().zip(record.iter().skip(3)) {
+            if !value.is_empty() {
+                values.insert(metric, value.parse().ok()?);
+            }
+        }
+        points.push(DailyPoint {
+            date: NaiveDate::parse_from_str(record.get(0)?, "%Y-%m-%d").ok()?,
+            entries: record.get(1)?.parse().ok()?,
+            workout: record.get(2)?.parse().ok()?,
+            values,
+        });
+    }
+    Some(points)
+}
+
+fn write_cache(path: &Path, fingerprint: &str, points: &[DailyPoint]) -> Result<(), AppError> {
+    let mut wtr = WriterBuilder::new().from_writer(Vec::new());
+    let mut header = vec!["date", "entries", "workout_today"];
+    header.extend(columns());
+    wtr.write_record(&header)?;
+    for point in points {
+        let mut row = vec![
+            point.date.format("%Y-%m-%d").to_string(),
+            point.entries.to_string(),
+            point.workout.to_string(),
+        ];
+        row.extend(columns().map(|m| point.values.get(m).map_or(String::new(), |v| v.to_string())));
+        wtr.write_record(&row)?;
+    }
+    let body = wtr
+        .into_inner()
+        .map_err(|e| AppError::IoError(e.into_error()))?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(
+        &tmp_path,
+        format!("{}\n{}", fingerprint, String::from_utf8_lossy(&body)),
+    )?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+// Daily summaries of the whole data file, from the cache when it is current. Values kept
+// on an older rating scale are converted after the cache, which holds them as entered.
+pub fn load(options: &GlobalOptions) -> Result<Vec<DailyPoint>, AppError> {
+    let mut points = scales::normalized(load_as_entered(options)?);
+    if let Some(days) = phases::selected_days(options)? {
+        points.retain(|p| days.contains(&p.date));
+    }
+    Ok(points)
+}
+
+fn load_as_entered(options: &GlobalOptions) -> Result<Vec<DailyPoint>, AppError> {
+    // Excluded sources change the result, so those runs neither read nor write the cache
+    if !options.exclude_sources.is_empty() {
+        return Ok(daily_points(options.analysis_entries()?));
+    }
+    let data_file = options.data_file();
+    let cache = cache_file(&data_file);
+    let fingerprint = fingerprint(&data_file);
+    if let Some(points) = fingerprint
+        .as_deref()
+        .and_then(|expected| read_cache(&cache, expected))
+    {
+        return Ok(points);
+    }
+    let points = daily_points(read_entries(&data_file)?);
+    // The cache is only an optimisation: read-only setups and unwritable directories skip it
+    if let (Some(fingerprint), false) = (fingerprint, options.read_only) {
+        let _ = write_cache(&cache, &fingerprint, &points);
+    }
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{MemoryStore, MetricStore};
+    use chrono::{TimeZone, Utc};
+
+    fn entry_at(day: u32, hour: u32, energy: u8, workout: bool) -> LogEntry {
+        let timestamp = Utc.with_ymd_and_hms(2025, 3, day, hour, 0, 0).unwrap();
+        let mut entry = LogEntry::empty_at(timestamp);
+        entry.energy = Some(energy);
+        entry.workout_today = workout;
+        entry
+    }
+
+    #[test]
+    fn same_day_entries_are_combined() {
+        let store = MemoryStore::default();
+        for entry in [
+            entry_at(1, 8, 4, false),
+            entry_at(1, 20, 7, true),
+            entry_at(2, 8, 6, false),
+        ] {
+            store.append(&entry).unwrap();
+        }
+        let points = daily_points(store.all_entries().unwrap());
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].entries, 2);
+        assert_eq!(points[0].values.get("energy"), Some(&5.5)); // The mean by default
+        assert!(points[0].workout); // Any entry with a workout
+        assert_eq!(points[1].values.get("energy"), Some(&6.0));
+        assert!(!points[1].workout);
+        assert!(!points[1].values.contains_key("focus"));
+    }
+
+    #[test]
+    fn aggregates_combine_in_logged_order() {
+        let values = [4.0, 9.0, 5.0];
+        assert_eq!(Aggregate::Mean.combine(&values), Some(6.0));
+        assert_eq!(Aggregate::Min.combine(&values), Some(4.0));
+        assert_eq!(Aggregate::Max.combine(&values), Some(9.0));
+        assert_eq!(Aggregate::First.combine(&values), Some(4.0));
+        assert_eq!(Aggregate::Last.combine(&values), Some(5.0));
+        assert_eq!(Aggregate::Sum.combine(&values), Some(18.0));
+        assert_eq!(Aggregate::Mean.combine(&[]), None);
+        assert!(!FlagAggregate::All.combine(&[true, false]));
+        assert!(FlagAggregate::Last.combine(&[false, true]));
+    }
+
+    #[test]
+    fn fingerprint_changes_with_the_data_file() {
+        let dir = std::env::temp_dir().join(format!("dml-fingerprint-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("daily_metrics.csv").to_string_lossy().into_owned();
+        assert_eq!(fingerprint(&path), None); // Nothing to cache yet
+
+        let store = store::open(&path);
+        store.append(&entry_at(1, 8, 4, false)).unwrap();
+        let first = fingerprint(&path).unwrap();
+        assert!(first.starts_with("# source "));
+        assert_eq!(fingerprint(&path), Some(first.clone()));
+
+        store.append(&entry_at(1, 20, 7, true)).unwrap();
+        assert_ne!(fingerprint(&path), Some(first));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::schema::{canonical_metric, column_name};
+use crate::units::DisplayUnits;
+use crate::{metric_range, AppError, LogEntry, METRIC_NAMES};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// --- Preset answers for `log --defaults <file>` ---
+// The file is TOML in the same units the prompts use, and metrics may go by any name:
+//   sleep_hours = 7.5
+//   energy = 6
+//   workout_today = true
+//   remarks = "sensor day"
+#[derive(Default, Clone)]
+pub struct LogDefaults {
+    values: BTreeMap<&'static str, f64>, // Stored units
+    pub workout: Option<bool>,
+    pub remarks: Option<String>,
+}
+
+impl LogDefaults {
+    pub fn load(path: &Path, units: &DisplayUnits) -> Result<LogDefaults, AppError> {
+        let error = |msg: String| AppError::ConfigError(format!("{}: {}", path.display(), msg));
+        let text = fs::read_to_string(path)?;
+        let table: toml::Table = toml::from_str(&text).map_err(|e| error(e.to_string()))?;
+        let mut defaults = LogDefaults::default();
+        for (key, value) in table {
+            match key.as_str() {
+                "workout_today" => {
+                    let workout = value
+                        .as_bool()
+                        .ok_or_else(|| error("workout_today must be true or false".to_string()))?;
+                    defaults.workout = Some(workout);
+                }
+                "remarks" => {
+                    let remarks = value
+                        .as_str()
+                        .ok_or_else(|| error("remarks must be a string".to_string()))?;
+                    defaults.remarks = Some(remarks.to_string());
+                }
+                name => {
+                    let metric = canonical_metric(name).ok_or_else(|| {
+                        error(format!(
+                            "unknown metric '{}' (choose from {}, workout_today, remarks)",
+                            name,
+                            METRIC_NAMES.join(", ")
+                        ))
+                    })?;
+                    let number = value
+                        .as_float()
+                        .or_else(|| value.as_integer().map(|i| i as f64))
+                        .ok_or_else(|| error(format!("{} must be a number", name)))?;
+                    let stored = units.to_stored(metric, number);
+                    let (min, max) = metric_range(metric);
+                    if !(min..=max).contains(&stored) {
+                        return Err(error(format!(
+                            "{} = {} is outside {} to {}",
+                            name,
+                            number,
+                            units.convert(metric, min),
+                            units.convert(metric, max)
+                        )));
+                    }
+                    defaults.values.insert(metric, stored);
+                }
+            }
+        }
+        Ok(defaults)
+    }
+
+    pub fn get(&self, metric: &str) -> Option<f64> {
+        self.values.get(metric).copied()
+    }
+
+    pub fn set(&mut self, metric: &'static str, value: f64) {
+        self.values.insert(metric, value);
+    }
+
+    // Write the values back out in the format `load` reads (used for log drafts)
+    pub fn save(&self, path: &Path, units: &DisplayUnits) -> Result<(), AppError> {
+        let mut table = toml::Table::new();
+        for (metric, value) in &self.values {
+            let shown = units.convert(metric, *value);
+            let shown = if shown.fract() == 0.0 {
+                toml::Value::Integer(shown as i64) // Ratings read back as written
+            } else {
+                toml::Value::Float(shown)
+            };
+            table.insert(column_name(metric).to_string(), shown);
+        }
+        if let Some(workout) = self.workout {
+            table.insert("workout_today".to_string(), toml::Value::Boolean(workout));
+        }
+        if let Some(remarks) = &self.remarks {
+            table.insert("remarks".to_string(), toml::Value::String(remarks.clone()));
+        }
+        let text = toml::to_string(&table)
+            .map_err(|e| AppError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    // Fill anything the file left out from an earlier entry (remarks are never copied)
+    pub fn fill_from(&mut self, entry: &LogEntry) {
+        for metric in METRIC_NAMES {
+            if let Some(value) = entry.metric(metric) {
+                self.values.entry(metric).or_insert(value);
+            }
+        }
+        self.workout.get_or_insert(entry.workout_today);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::cli::EditArgs;
+use crate::custom;
+use crate::import::{replace_entries, save_sorted};
+use crate::layout;
+use crate::replay;
+use crate::schema::column_name;
+use crate::units::DisplayUnits;
+use crate::{metric_range, read_entries, AppError, GlobalOptions, LogEntry, METRIC_NAMES, THEME};
+use chrono::{DateTime, Local, Utc};
+use colored::*;
+use dialoguer::{Confirm, Input, Select};
+
+const PICK_FROM: usize = 10; // Recent entries offered when no entry is named
+
+pub fn label(entry: &LogEntry) -> String {
+    let time = entry
+        .parsed_timestamp()
+        .map(|ts| {
+            ts.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| entry.timestamp.clone());
+    if entry.remarks.trim().is_empty() {
+        time
+    } else {
+        format!("{}  \"{}\"", time, entry.remarks)
+    }
+}
+
+// Index of the entry to edit: `last`, an exact timestamp, or picked from the recent ones
+fn select(entries: &[LogEntry], selector: Option<&str>) -> Result<usize, AppError> {
+    match selector {
+        Some("last") => Ok(entries.len() - 1),
+        Some(text) => {
+            let found = DateTime::parse_from_rfc3339(text).ok().and_then(|ts| {
+                let ts = ts.with_timezone(&Utc);
+                entries
+                    .iter()
+                    .position(|e| e.parsed_timestamp() == Some(ts))
+            });
+            found.ok_or_else(|| {
+                AppError::UsageError(format!(
+                    "No entry at '{}': use last or a full timestamp (see `view`)",
+                    text
+                ))
+            })
+        }
+        None => {
+            let start = entries.len().saturating_sub(PICK_FROM);
+            let labels: Vec<String> = entries[start..].iter().map(label).collect();
+            let choice = replay::answer("Which entry?", || {
+                Select::with_theme(&**THEME)
+                    .with_prompt("Which entry?")
+                    .items(&labels)
+                    .default(labels.len() - 1)
+                    .interact()
+            })?;
+            Ok(start + choice)
+        }
+    }
+}
+
+// Ask for a new value, keeping the current one when the answer is left empty
+pub fn ask_value(
+    units: &DisplayUnits,
+    metric: &str,
+    current: Option<f64>,
+) -> Result<Option<f64>, AppError> {
+    let (min, max) = metric_range(metric);
+    let (min, max) = (units.convert(metric, min), units.convert(metric, max));
+    let shown = current.map_or("-".to_string(), |v| units.convert(metric, v).to_string());
+    let answer: String = replay::answer(metric, || {
+        Input::with_theme(&**THEME)
+            .with_prompt(format!("{} [{}]", column_name(metric), shown))
+            .allow_empty(true)
+            .validate_with(|input: &String| -> Result<(), String> {
+                if input.trim().is_empty() {
+                    return Ok(());
+                }
+                match input.trim().parse::<f64>() {
+                    Ok(value) if (min..=max).contains(&value) => Ok(()),
+                    Ok(_) => Err(format!("Please enter a value between {} and {}", min, max)),
+                    Err(_) => Err("Please enter a number (or nothing to keep it)".to_string()),
+                }
+            })
+            .interact_text()
+    })?;
+    Ok(match answer.trim() {
+        "" => current,
+        value => value.parse().ok().map(|v| units.to_stored(metric, v)),
+    })
+}
+
+// --- `edit` command: re-answer one logged entry, keeping its timestamp ---
+pub fn run_edit(options: &GlobalOptions, args: &EditArgs) -> Result<(), AppError> {
+    let data_file = options.data_file();
+    let entries = read_entries(&data_file)?;
+    if entries.is_empty() {
+        println!("{}", "No entries to edit.".dimmed());
+        return Ok(());
+    }
+    let index = select(&entries, args.entry.as_deref())?;
+    let units = DisplayUnits::from_config(&options.config);
+
+    let before = entries[index].clone();
+    println!("{}", format!(" Editing {} ", label(&before)).bold().cyan());
+    println!("{}", layout::rule('-').cyan());
+    println!("{}", "Press Enter to keep a value.".dimmed());
+    let mut edited = before.clone();
+    let mut changes = Vec::new();
+    for metric in METRIC_NAMES {
+        let current = before.metric(metric);
+        let Some(value) = ask_value(&units, metric, current)? else {
+            continue;
+        };
+        if current != Some(value) && edited.set_metric(metric, value) {
+            changes.push(format!(
+                "{}: {} -> {}",
+                column_name(metric),
+                current.map_or("-".to_string(), |v| units.convert(metric, v).to_string()),
+                units.convert(metric, value)
+            ));
+        }
+    }
+    edited.workout_today = replay::answer("workout_today", || {
+        Confirm::with_theme(&**THEME)
+            .with_prompt("Worked out that day?")
+            .default(before.workout_today)
+            .interact()
+    })?;
+    if edited.workout_today != before.workout_today {
+        changes.push(format!(
+            "workout_today: {} -> {}",
+            before.workout_today, edited.workout_today
+        ));
+    }
+    edited.remarks = replay::answer("remarks", || {
+        Input::with_theme(&**THEME)
+            .with_prompt("Remarks")
+            .default(before.remarks.clone())
+            .allow_empty(true)
+            .interact_text()
+    })?;
+    if edited.remarks != before.remarks {
+        changes.push(format!(
+            "remarks: \"{}\" -> \"{}\"",
+            before.remarks, edited.remarks
+        ));
+    }
+
+    if changes.is_empty() {
+        println!("{}", "Nothing changed.".dimmed());
+        return Ok(());
+    }
+    for change in &changes {
+        println!(" {}", change);
+    }
+    if options.dry_run {
+        println!("{}", " Dry run: entry not changed".bold().yellow());
+        return Ok(());
+    }
+    edited.add_provenance("edit");
+    save_sorted(&data_file, "edit", |entries| {
+        replace_entries(entries, std::slice::from_ref(&edited))
+    })?;
+    println!("{}", " Entry updated".bold().green());
+    Ok(())
+}
+
+// --- Review a new entry before it is saved: Some(entry), or None when discarded ---
+// Any answer can be picked from the list and given again; the rest are kept. Only the
+// metrics that were asked are listed, so ones turned off in the config stay empty.
+pub fn review(
+    units: &DisplayUnits,
+    metrics: &[&'static str],
+    mut entry: LogEntry,
+) -> Result<Option<LogEntry>, AppError> {
+    loop {
+        let mut items = vec!["Save the entry".bold().green().to_string()];
+        for &metric in metrics {
+            let value = entry
+                .metric(metric)
+                .map_or("-".to_string(), |v| units.convert(metric, v).to_string());
+            let line = format!("{}: {} {}", column_name(metric), value, units.unit(metric));
+            items.push(line.trim_end().to_string());
+        }
+        let workout = if entry.workout_today { "yes" } else { "no" };
+        items.push(format!("workout_today: {}", workout));
+        items.push(format!("remarks: \"{}\"", entry.remarks));
+        for name in custom::columns() {
+            let value = entry.custom.get(name).map_or("-", String::as_str);
+            items.push(format!("{}: {}", name, value));
+        }
+        items.push("Discard the entry".red().to_string());
+
+        println!();
+        const PROMPT: &str = "Save, or pick an answer to change";
+        let choice = replay::answer(PROMPT, || {
+            Select::with_theme(&**THEME)
+                .with_prompt(PROMPT)
+                .items(&items)
+                .default(0)
+                .max_length(items.len())
+                .interact()
+        })?;
+        let field = choice.wrapping_sub(1);
+        if choice == 0 {
+            return Ok(Some(entry));
+        } else if choice == items.len() - 1 {
+            return Ok(None);
+        } else if let Some(&metric) = metrics.get(field) {
+            if let Some(value) = ask_value(units, metric, entry.metric(metric))? {
+                entry.set_metric(metric, value);
+            }
+        } else if field == metrics.len() {
+            entry.workout_today = replay::answer("workout_today", || {
+                Confirm::with_theme(&**THEME)
+                    .with_prompt("Did you (or will you) workout today?")
+                    .default(entry.workout_today)
+                    .interact()
+            })?;
+        } else if field == metrics.len() + 1 {
+            entry.remarks = replay::answer("remarks", || {
+                Input::with_theme(&**THEME)
+                    .with_prompt("Any remarks?")
+                    .default(entry.remarks.clone())
+                    .allow_empty(true)
+                    .interact_text()
+            })?;
+        } else if let Some(name) = custom::columns().nth(field - metrics.len() - 2) {
+            custom::ask_again(name, &mut entry)?;
+        }
+    }
+}
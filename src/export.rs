@@ -0,0 +1,230 @@
+use crate::cli::{ExportArgs, ExportFormat, OutputFormat};
+#[cfg(feature = "parquet")]
+use crate::constraints::{ColumnType, CONSTRAINTS};
+use crate::custom;
+use crate::schema::{canonical_metric, column_name};
+use crate::store::entry_record;
+use crate::{csv_header, read_entries, AppError, GlobalOptions, LogEntry, METRIC_NAMES};
+#[cfg(feature = "parquet")]
+use chrono::DateTime;
+use colored::*;
+use csv::{StringRecord, WriterBuilder};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::{IsTerminal, Write};
+
+// JSON objects use the same (possibly renamed) column names as the CSV header
+pub fn json_entry(entry: &LogEntry) -> Result<Value, serde_json::Error> {
+    let Value::Object(mut fields) = serde_json::to_value(entry)? else {
+        return Ok(Value::Null);
+    };
+    fields.remove("custom");
+    let renamed: Map<String, Value> = fields
+        .into_iter()
+        .map(
+            |(key, value)| match METRIC_NAMES.iter().find(|m| **m == key) {
+                Some(metric) => (column_name(metric).to_string(), value),
+                None => (key, value),
+            },
+        )
+        .chain(custom::json_values(entry))
+        .collect();
+    Ok(Value::Object(renamed))
+}
+
+pub fn render(entries: &[LogEntry], format: ExportFormat) -> Result<String, AppError> {
+    let all: Vec<usize> = (0..csv_header().len()).collect();
+    render_columns(entries, format, &all)
+}
+
+// Only the given columns (positions in csv_header()), in that order
+fn render_columns(
+    entries: &[LogEntry],
+    format: ExportFormat,
+    columns: &[usize],
+) -> Result<String, AppError> {
+    let header = csv_header();
+    let pick = |record: &StringRecord| -> StringRecord {
+        columns
+            .iter()
+            .map(|&i| record.get(i).unwrap_or(""))
+            .collect()
+    };
+    match format {
+        ExportFormat::Csv => {
+            let mut wtr = WriterBuilder::new().has_headers(false).from_writer(vec![]);
+            wtr.write_record(&pick(&header))?;
+            for entry in entries {
+                wtr.write_record(&pick(&entry_record(entry)?))?;
+            }
+            let bytes = wtr
+                .into_inner()
+                .map_err(|e| AppError::IoError(e.into_error()))?;
+            Ok(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        ExportFormat::Json => {
+            let keep: Vec<&str> = columns.iter().filter_map(|&i| header.get(i)).collect();
+            let rows = entries
+                .iter()
+                .map(|entry| {
+                    let Value::Object(mut fields) = json_entry(entry)? else {
+                        return Ok(Value::Null);
+                    };
+                    fields.retain(|key, _| keep.contains(&key.as_str()));
+                    Ok(Value::Object(fields))
+                })
+                .collect::<Result<Vec<_>, serde_json::Error>>()?;
+            Ok(serde_json::to_string_pretty(&rows)? + "\n")
+        }
+    }
+}
+
+// --- Parquet: the same columns with real types, for Polars, pandas or DuckDB ---
+// Each column gets the type from its constraint, so nothing has to be guessed on load.
+#[cfg(feature = "parquet")]
+fn parquet_bytes(entries: &[LogEntry], columns: &[usize]) -> Result<Vec<u8>, AppError> {
+    use arrow_array::{
+        ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray,
+        TimestampMicrosecondArray,
+    };
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    let header = csv_header();
+    let records = entries
+        .iter()
+        .map(entry_record)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut fields = Vec::new();
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+    for &i in columns {
+        let cells = records.iter().map(|r| r.get(i).filter(|c| !c.is_empty()));
+        // Custom columns come after the built-in ones and can always be empty
+        let (kind, nullable) = match CONSTRAINTS.get(i) {
+            Some(constraint) => (constraint.kind, constraint.nullable),
+            None => (
+                custom::column_type(custom::metrics()[i - CONSTRAINTS.len()].1.kind),
+                true,
+            ),
+        };
+        let array: ArrayRef = match kind {
+            ColumnType::Timestamp => Arc::new(
+                cells
+                    .map(|c| Some(DateTime::parse_from_rfc3339(c?).ok()?.timestamp_micros()))
+                    .collect::<TimestampMicrosecondArray>()
+                    .with_timezone("UTC"),
+            ),
+            ColumnType::Integer => Arc::new(
+                cells
+                    .map(|c| c?.parse::<i64>().ok())
+                    .collect::<Int64Array>(),
+            ),
+            ColumnType::Decimal => Arc::new(
+                cells
+                    .map(|c| c?.parse::<f64>().ok())
+                    .collect::<Float64Array>(),
+            ),
+            ColumnType::Boolean => Arc::new(
+                cells
+                    .map(|c| c?.parse::<bool>().ok())
+                    .collect::<BooleanArray>(),
+            ),
+            ColumnType::Text => Arc::new(cells.collect::<StringArray>()),
+        };
+        let data_type = match kind {
+            ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            _ => array.data_type().clone(),
+        };
+        // A required column can still hold a value that didn't parse (left empty)
+        let nullable = nullable || array.null_count() > 0;
+        fields.push(Field::new(header.get(i).unwrap_or(""), data_type, nullable));
+        arrays.push(array);
+    }
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+        .map_err(std::io::Error::other)?;
+    let mut bytes = Vec::new();
+    let mut writer =
+        ArrowWriter::try_new(&mut bytes, batch.schema(), None).map_err(std::io::Error::other)?;
+    writer.write(&batch).map_err(std::io::Error::other)?;
+    writer.close().map_err(std::io::Error::other)?;
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn parquet_bytes(_entries: &[LogEntry], _columns: &[usize]) -> Result<Vec<u8>, AppError> {
+    Err(AppError::UsageError(
+        "This build has no Parquet support (rebuild with --features parquet)".to_string(),
+    ))
+}
+
+// Column positions from --fields (in the order given, else all) minus --exclude.
+// Metrics can be named by their current name or any alias.
+fn selected_columns(args: &ExportArgs) -> Result<Vec<usize>, AppError> {
+    let header = csv_header();
+    let position = |name: &str| {
+        let name = name.trim();
+        let column = canonical_metric(name).map_or(name, column_name);
+        header.iter().position(|h| h == column).ok_or_else(|| {
+            AppError::UsageError(format!(
+                "Unknown column '{}' (columns: {})",
+                name,
+                header.iter().collect::<Vec<_>>().join(", ")
+            ))
+        })
+    };
+    let wanted: Vec<usize> = if args.fields.is_empty() {
+        (0..header.len()).collect()
+    } else {
+        args.fields
+            .iter()
+            .map(|f| position(f))
+            .collect::<Result<_, _>>()?
+    };
+    let excluded = args
+        .exclude
+        .iter()
+        .map(|f| position(f))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut columns = Vec::new();
+    for i in wanted {
+        if !excluded.contains(&i) && !columns.contains(&i) {
+            columns.push(i);
+        }
+    }
+    if columns.is_empty() {
+        return Err(AppError::UsageError(
+            "--fields and --exclude leave no columns to export".to_string(),
+        ));
+    }
+    Ok(columns)
+}
+
+// --- `export` command: every entry as CSV (the default), JSON or Parquet, to stdout or a file ---
+pub fn run_export(options: &GlobalOptions, args: &ExportArgs) -> Result<(), AppError> {
+    let columns = selected_columns(args)?;
+    let entries = read_entries(&options.data_file())?;
+    let bytes = if options.format == OutputFormat::Parquet {
+        if args.output.is_none() && std::io::stdout().is_terminal() {
+            return Err(AppError::UsageError(
+                "Parquet is binary: write it to a file with -o metrics.parquet, or pipe it"
+                    .to_string(),
+            ));
+        }
+        parquet_bytes(&entries, &columns)?
+    } else {
+        let format = options.format.rows().unwrap_or(ExportFormat::Csv);
+        render_columns(&entries, format, &columns)?.into_bytes()
+    };
+    match &args.output {
+        Some(path) => {
+            fs::write(path, bytes)?;
+            eprintln!(
+                "{}",
+                format!("Exported {} entries to {}", entries.len(), path).green()
+            );
+        }
+        None => std::io::stdout().write_all(&bytes)?,
+    }
+    Ok(())
+}
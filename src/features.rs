@@ -0,0 +1,88 @@
+use crate::cli::OutputFormat;
+use crate::layout;
+use crate::{AppError, GlobalOptions};
+use colored::*;
+use serde::Serialize;
+
+// --- Optional parts of the build (cargo features in Cargo.toml) ---
+// Integrations that pull in large dependencies are features, so a build without them
+// stays small; the commands that need one say which feature to rebuild with.
+#[derive(Serialize)]
+struct Feature {
+    name: &'static str,
+    enabled: bool,
+    about: &'static str,
+}
+
+const FEATURES: [Feature; 4] = [
+    Feature {
+        name: "parquet",
+        enabled: cfg!(feature = "parquet"),
+        about: "export --format parquet",
+    },
+    Feature {
+        name: "clipboard",
+        enabled: cfg!(feature = "clipboard"),
+        about: "--copy for today and report",
+    },
+    Feature {
+        name: "qr",
+        enabled: cfg!(feature = "qr"),
+        about: "today --qr",
+    },
+    Feature {
+        name: "test-support",
+        enabled: cfg!(feature = "test-support"),
+        about: "the fuzz command",
+    },
+];
+
+// --- `features` command: what this binary was built with ---
+pub fn run_features(options: &GlobalOptions) -> Result<(), AppError> {
+    if options.format == OutputFormat::Csv {
+        let mut wtr = csv::Writer::from_writer(std::io::stdout());
+        for feature in &FEATURES {
+            wtr.serialize(feature)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+    if options.json || options.format == OutputFormat::Json {
+        let features = serde_json::json!({
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "features": FEATURES,
+        });
+        println!("{}", serde_json::to_string_pretty(&features)?);
+        return Ok(());
+    }
+
+    println!("{}", layout::rule('=').cyan());
+    println!(
+        "{}",
+        format!(" Features (version {}) ", env!("CARGO_PKG_VERSION"))
+            .bold()
+            .cyan()
+    );
+    println!("{}", layout::rule('=').cyan());
+    layout::row(
+        &format!("{:<14} {:<6} Adds", "Feature", "Built")
+            .bold()
+            .to_string(),
+    );
+    for feature in &FEATURES {
+        let built = if feature.enabled {
+            format!("{:<6}", "yes").green()
+        } else {
+            format!("{:<6}", "no").dimmed()
+        };
+        layout::row(&format!("{:<14} {} {}", feature.name, built, feature.about));
+    }
+    if FEATURES.iter().any(|f| !f.enabled) {
+        println!();
+        println!(
+            "{}",
+            "Rebuild with `cargo build --features <name>` to add one.".dimmed()
+        );
+    }
+    Ok(())
+}
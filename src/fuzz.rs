@@ -0,0 +1,319 @@
+// --- Property-based checks for the CSV data layer (`--features test-support`) ---
+// `fuzz` generates random valid entries and random malformed files, then checks
+// that valid data round-trips exactly and that bad input is skipped, never fatal.
+use crate::cli::FuzzArgs;
+use crate::layout;
+use crate::store::{self, MemoryStore, MetricStore};
+use crate::{read_entries, write_entries, AppError, EntryKind, GlobalOptions, LogEntry};
+use chrono::{DateTime, Duration, FixedOffset, TimeZone, Utc};
+use colored::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+const REMARK_PIECES: [&str; 12] = [
+    "fine",
+    ",",
+    "\"",
+    "\"\"",
+    "\n",
+    "\r\n",
+    " ",
+    "naïve café",
+    "🙂",
+    "a,b\nc",
+    "'",
+    "\t",
+];
+
+const PROVENANCES: [&str; 5] = [
+    "",
+    "interactive",
+    "defaults",
+    "import:exist.io",
+    "interactive+import:bearable+edit",
+];
+
+fn random_timestamp(rng: &mut StdRng) -> String {
+    let base = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let ts = base + Duration::seconds(rng.gen_range(0..200_000_000));
+    let offset = FixedOffset::east_opt(rng.gen_range(-12..=14) * 3600).unwrap();
+    match rng.gen_range(0..3) {
+        0 => ts.to_rfc3339(),
+        1 => ts.with_timezone(&offset).to_rfc3339(),
+        _ => (ts + Duration::nanoseconds(rng.gen_range(0..1_000_000_000))).to_rfc3339(),
+    }
+}
+
+// Most values present, some left empty
+fn maybe<T>(rng: &mut StdRng, make: impl FnOnce(&mut StdRng) -> T) -> Option<T> {
+    if rng.gen_bool(0.85) {
+        Some(make(rng))
+    } else {
+        None
+    }
+}
+
+fn random_entry(rng: &mut StdRng) -> LogEntry {
+    let timestamp = random_timestamp(rng);
+    let mut entry = LogEntry::empty_at(
+        DateTime::parse_from_rfc3339(&timestamp)
+            .map(|ts| ts.with_timezone(&Utc))
+            .unwrap_or_default(),
+    );
+    entry.timestamp = timestamp;
+    entry.day_count = rng.gen_range(1..2000);
+    entry.sleep_hours = maybe(rng, |r| r.gen_range(0..=48) as f32 / 4.0);
+    entry.sleep_quality = maybe(rng, |r| r.gen_range(2..=20) as f32 / 2.0);
+    entry.sleepiness = maybe(rng, |r| r.gen_range(1..=10));
+    entry.zonkedness = maybe(rng, |r| r.gen_range(1..=10));
+    entry.mid_vibes = maybe(rng, |r| r.gen_range(1..=10));
+    entry.energy = maybe(rng, |r| r.gen_range(1..=10));
+    entry.strength = maybe(rng, |r| r.gen_range(1..=10));
+    entry.focus = maybe(rng, |r| r.gen_range(1..=10));
+    entry.intelligence = maybe(rng, |r| r.gen_range(1..=10));
+    entry.workout_today = rng.gen();
+    entry.session_seconds = maybe(rng, |r| r.gen_range(0..3600));
+    entry.session_edits = maybe(rng, |r| r.gen_range(0..20));
+    entry.entry_kind = [
+        None,
+        Some(EntryKind::FirstOfDay),
+        Some(EntryKind::FollowUp),
+        Some(EntryKind::Backfill),
+        Some(EntryKind::Correction),
+    ]
+    .choose(rng)
+    .copied()
+    .flatten();
+    entry.provenance = PROVENANCES
+        .choose(rng)
+        .copied()
+        .unwrap_or_default()
+        .to_string();
+    entry.remarks = (0..rng.gen_range(0..5))
+        .filter_map(|_| REMARK_PIECES.choose(rng).copied())
+        .collect();
+    entry
+}
+
+// Entries compare through JSON so float formatting quirks show up as differences
+fn same(a: &LogEntry, b: &LogEntry) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+// --- Ways of breaking a well-formed data line ---
+fn mutate_line(rng: &mut StdRng, line: &str) -> String {
+    let mut fields: Vec<String> = line.split(',').map(str::to_string).collect();
+    match rng.gen_range(0..8) {
+        0 => {
+            fields.truncate(rng.gen_range(0..fields.len()));
+        }
+        1 => fields.push("extra".to_string()),
+        2 => {
+            let i = rng.gen_range(0..fields.len());
+            fields[i] = ["abc", "-1", "300", "1e999", "NaN", "", " 5 "]
+                .choose(rng)
+                .unwrap()
+                .to_string();
+        }
+        3 => {
+            fields[0] = ["yesterday", "2024-13-40T99:00:00Z", ""]
+                .choose(rng)
+                .unwrap()
+                .to_string()
+        }
+        4 => {
+            let i = rng.gen_range(0..fields.len());
+            fields[i].insert(0, '"'); // Unbalanced quote
+        }
+        5 => return String::from_utf8_lossy(&[0xff, 0xfe, b',', 0x00]).into_owned(),
+        6 => fields.reverse(),
+        _ => return String::new(),
+    }
+    fields.join(",")
+}
+
+struct Outcome {
+    failures: Vec<String>,
+    skipped_rows: usize,
+}
+
+fn check_round_trip(rng: &mut StdRng, dir: &Path, case: usize, outcome: &mut Outcome) {
+    let path = dir.join(format!("valid-{}.csv", case));
+    let path_str = path.to_string_lossy().into_owned();
+    let entries: Vec<LogEntry> = (0..rng.gen_range(1..20))
+        .map(|_| random_entry(rng))
+        .collect();
+
+    if let Err(e) = write_entries(&path_str, &entries) {
+        outcome
+            .failures
+            .push(format!("case {}: write failed: {}", case, e));
+        return;
+    }
+    match read_entries(&path_str) {
+        Ok(read) if read.len() != entries.len() => outcome.failures.push(format!(
+            "case {}: wrote {} entries, read back {} ({})",
+            case,
+            entries.len(),
+            read.len(),
+            path.display()
+        )),
+        Ok(read) => {
+            if let Some((i, _)) = entries
+                .iter()
+                .zip(&read)
+                .enumerate()
+                .find(|(_, (a, b))| !same(a, b))
+            {
+                outcome.failures.push(format!(
+                    "case {}: entry {} changed on round trip ({})",
+                    case,
+                    i,
+                    path.display()
+                ));
+            }
+        }
+        Err(e) => outcome
+            .failures
+            .push(format!("case {}: read failed: {}", case, e)),
+    }
+
+    // The logging flow's quick reader must agree on the date range
+    match store::open(&path_str).info() {
+        Ok(info) => {
+            let expected_first = entries
+                .iter()
+                .filter_map(|e| e.parsed_timestamp())
+                .map(|ts| ts.date_naive())
+                .min();
+            if info.first_entry_date != expected_first {
+                outcome.failures.push(format!(
+                    "case {}: info first date {:?}, expected {:?} ({})",
+                    case,
+                    info.first_entry_date,
+                    expected_first,
+                    path.display()
+                ));
+            }
+            // The CSV scan reads raw columns; it must match the typed scan other stores use
+            let memory = MemoryStore::default();
+            let typed = memory
+                .write_all(&entries)
+                .and_then(|_| memory.info())
+                .map(|m| (m.last_entry_date, m.workout_logged_today));
+            if let Ok(typed) = typed {
+                if typed != (info.last_entry_date, info.workout_logged_today) {
+                    outcome.failures.push(format!(
+                        "case {}: info {:?} differs from the in-memory store's {:?} ({})",
+                        case,
+                        (info.last_entry_date, info.workout_logged_today),
+                        typed,
+                        path.display()
+                    ));
+                }
+            }
+        }
+        Err(e) => outcome
+            .failures
+            .push(format!("case {}: info failed: {}", case, e)),
+    }
+}
+
+fn check_malformed(rng: &mut StdRng, dir: &Path, case: usize, outcome: &mut Outcome) {
+    let path = dir.join(format!("malformed-{}.csv", case));
+    let path_str = path.to_string_lossy().into_owned();
+    let entries: Vec<LogEntry> = (0..rng.gen_range(1..10))
+        .map(|_| random_entry(rng))
+        .collect();
+    if write_entries(&path_str, &entries).is_err() {
+        return;
+    }
+    let Ok(text) = fs::read_to_string(&path) else {
+        return;
+    };
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    for _ in 0..rng.gen_range(1..4) {
+        let i = rng.gen_range(0..lines.len());
+        lines[i] = mutate_line(rng, &lines[i]);
+    }
+    if fs::write(&path, lines.join("\n")).is_err() {
+        return;
+    }
+
+    // Malformed input may lose rows but must never panic or abort the read
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        (
+            read_entries(&path_str).map(|e| e.len()),
+            store::open(&path_str).info().is_ok(),
+        )
+    }));
+    match result {
+        Ok((Ok(read), true)) => outcome.skipped_rows += entries.len().saturating_sub(read),
+        Ok((Ok(_), false)) => outcome.failures.push(format!(
+            "case {}: info gave up on a malformed file ({})",
+            case,
+            path.display()
+        )),
+        Ok((Err(e), _)) => outcome.failures.push(format!(
+            "case {}: read_entries gave up on a malformed file: {} ({})",
+            case,
+            e,
+            path.display()
+        )),
+        Err(_) => outcome.failures.push(format!(
+            "case {}: panic while reading a malformed file ({})",
+            case,
+            path.display()
+        )),
+    }
+}
+
+// --- `fuzz` command ---
+pub fn run_fuzz(_options: &GlobalOptions, args: &FuzzArgs) -> Result<(), AppError> {
+    let cases = args.cases;
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // Failing cases are left on disk for inspection
+    let dir: PathBuf = std::env::temp_dir().join(format!("dml-fuzz-{}", seed));
+    fs::create_dir_all(&dir)?;
+
+    // Reader warnings for deliberately broken rows are expected; keep panics quiet too
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let mut outcome = Outcome {
+        failures: Vec::new(),
+        skipped_rows: 0,
+    };
+    for case in 0..cases {
+        check_round_trip(&mut rng, &dir, case, &mut outcome);
+        check_malformed(&mut rng, &dir, case, &mut outcome);
+    }
+    panic::set_hook(default_hook);
+
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " CSV fuzzing ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    println!("Seed: {}  Cases: {}", seed, cases);
+    println!(
+        "Malformed rows skipped: {}",
+        outcome.skipped_rows.to_string().dimmed()
+    );
+    if outcome.failures.is_empty() {
+        fs::remove_dir_all(&dir)?;
+        println!("{}", "All properties held.".bold().green());
+        return Ok(());
+    }
+    for failure in &outcome.failures {
+        println!("{} {}", "FAIL".red().bold(), failure);
+    }
+    println!(
+        "{}",
+        format!("Re-run with --seed {} to reproduce.", seed).dimmed()
+    );
+    std::process::exit(1);
+}
@@ -0,0 +1,195 @@
+use crate::cli::GenerateArgs;
+use crate::constraints;
+use crate::{read_entries, write_entries, AppError, EntryKind, GlobalOptions, LogEntry};
+use chrono::{Datelike, Duration, NaiveTime, Utc, Weekday};
+use colored::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+use std::path::Path;
+
+const MISSING_DAY_CHANCE: f64 = 0.08;
+const REMARKS: [&str; 6] = [
+    "coffee late",
+    "long meeting day",
+    "went for a walk",
+    "bad night, neighbours",
+    "felt great after the gym",
+    "travel day",
+];
+
+// Standard normal sample (Box-Muller)
+fn normal(rng: &mut StdRng, sd: f64) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos() * sd
+}
+
+fn rating(value: f64) -> Option<u8> {
+    Some(value.round().clamp(1.0, 10.0) as u8)
+}
+
+// --- Fake history with the shape of real data ---
+// A slow upward trend, weekend effects, sleep driving energy and focus,
+// occasional follow-up entries and some days with nothing logged.
+fn generate_entries(days: i64, seed: u64) -> Vec<LogEntry> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let now = Utc::now();
+    let today = now.date_naive();
+    let first = today - Duration::days(days - 1);
+    let mut entries = Vec::new();
+
+    for offset in 0..days {
+        let date = first + Duration::days(offset);
+        if rng.gen_bool(MISSING_DAY_CHANCE) {
+            continue;
+        }
+        let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        let trend = offset as f64 / days as f64; // Up to one point of improvement by today
+
+        let sleep =
+            (7.1 + if weekend { 0.7 } else { 0.0 } + normal(&mut rng, 0.8)).clamp(3.0, 11.5);
+        let sleep = (sleep * 4.0).round() / 4.0;
+        let quality = (5.5 + 0.9 * (sleep - 7.0) + normal(&mut rng, 1.2)).clamp(1.0, 10.0);
+        let quality = (quality * 2.0).round() / 2.0;
+        let workout = rng.gen_bool(if weekend { 0.3 } else { 0.45 });
+
+        // Most days have one morning entry; some get afternoon and evening follow-ups
+        let roll: f64 = rng.gen();
+        let count = if roll < 0.7 {
+            1
+        } else if roll < 0.95 {
+            2
+        } else {
+            3
+        };
+        for (slot, hour) in [8, 14, 21].into_iter().take(count).enumerate() {
+            let minute = rng.gen_range(0..60);
+            let Some(time) = NaiveTime::from_hms_opt(hour, minute, 0) else {
+                continue;
+            };
+            let timestamp = date.and_time(time).and_utc();
+            // Today's later slots haven't happened yet, and rows must stay in time order
+            if timestamp > now {
+                break;
+            }
+            let afternoon_dip = if slot == 1 { -0.8 } else { 0.0 };
+            let evening_tired = if slot == 2 { 2.0 } else { 0.0 };
+
+            let energy = 5.0
+                + 0.7 * (sleep - 7.0)
+                + 0.3 * (quality - 5.5)
+                + trend
+                + if workout { 0.5 } else { 0.0 }
+                + afternoon_dip
+                - evening_tired * 0.5
+                + normal(&mut rng, 1.0);
+            let zonked = 4.0 + if weekend { -0.8 } else { 0.6 } - 0.3 * (sleep - 7.0)
+                + evening_tired * 0.7
+                + normal(&mut rng, 1.0);
+            let focus = 5.0 + 0.5 * (energy - 5.0) - 0.3 * (zonked - 4.0)
+                + trend * 0.5
+                + normal(&mut rng, 0.9);
+
+            let mut entry = LogEntry::empty_at(timestamp);
+            entry.provenance = "generate".to_string();
+            entry.entry_kind = Some(if slot == 0 {
+                EntryKind::FirstOfDay
+            } else {
+                EntryKind::FollowUp
+            });
+            entry.sleep_hours = Some(sleep as f32);
+            entry.sleep_quality = Some(quality as f32);
+            entry.sleepiness =
+                rating(5.5 - 0.8 * (sleep - 7.0) + evening_tired + normal(&mut rng, 1.0));
+            entry.zonkedness = rating(zonked);
+            entry.mid_vibes = rating(
+                5.5 + 0.4 * (energy - 5.0)
+                    + if weekend { 0.6 } else { 0.0 }
+                    + normal(&mut rng, 1.0),
+            );
+            entry.energy = rating(energy);
+            entry.strength =
+                rating(5.0 + if workout { 0.8 } else { 0.0 } + trend + normal(&mut rng, 1.0));
+            entry.focus = rating(focus);
+            entry.intelligence = rating(2.0 + 0.6 * focus + normal(&mut rng, 0.8));
+            entry.workout_today = workout;
+            if rng.gen_bool(0.1) {
+                entry.remarks = REMARKS[rng.gen_range(0..REMARKS.len())].to_string();
+            }
+            entries.push(entry);
+        }
+    }
+    // day_count counts from the first day that actually has data
+    if let Some(start) = entries.first().and_then(|e| e.parsed_timestamp()) {
+        let start = start.date_naive();
+        for entry in &mut entries {
+            if let Some(ts) = entry.parsed_timestamp() {
+                entry.day_count = (ts.date_naive() - start).num_days() + 1;
+            }
+        }
+    }
+    entries
+}
+
+// --- `generate` command: write realistic sample data ---
+pub fn run_generate(options: &GlobalOptions, args: &GenerateArgs) -> Result<(), AppError> {
+    // Never mix fake rows into real history: the data file is only used while it's empty
+    let target = match args.output.clone() {
+        Some(path) => path,
+        None => {
+            let data_file = options.data_file();
+            if !read_entries(&data_file)?.is_empty() {
+                return Err(AppError::UsageError(format!(
+                    "{} already has entries. Use --output <file> (or --data-file) for sample data.",
+                    data_file
+                )));
+            }
+            options.require_writable("generate")?;
+            data_file
+        }
+    };
+
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let entries = generate_entries(args.days, seed);
+    constraints::validate(&entries)?;
+    if options.dry_run {
+        println!(
+            "{}",
+            format!(
+                " Dry run: would write {} sample entries to {}",
+                entries.len(),
+                target
+            )
+            .bold()
+            .yellow()
+        );
+        return Ok(());
+    }
+    if let Some(parent) = Path::new(&target).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    write_entries(&target, &entries)?;
+    println!(
+        "{}",
+        format!(
+            " Wrote {} sample entries over {} days to {}",
+            entries.len(),
+            args.days,
+            target
+        )
+        .bold()
+        .green()
+    );
+    println!(
+        "{}",
+        format!(
+            "Seed {} (pass --seed {} to get the same data again)",
+            seed, seed
+        )
+        .dimmed()
+    );
+    Ok(())
+}
@@ -0,0 +1,257 @@
+use crate::analysis::{daily_value, metric_series, pair_links};
+use crate::cli::{MetricAction, MetricArgs};
+use crate::config::{edit_config, set_value, table_at, Config};
+use crate::daily::{self, DailyPoint};
+use crate::layout;
+use crate::phases;
+use crate::replay;
+use crate::schema::{check_new_name, column_name, run_metric};
+use crate::units::DisplayUnits;
+use crate::{AppError, GlobalOptions, METRIC_NAMES, THEME};
+use chrono::{Duration, NaiveDate, Utc};
+use colored::*;
+use dialoguer::{Confirm, Input, Select};
+use toml_edit::value;
+
+const WORKOUT: &str = "workout_today";
+const TOP_CORRELATIONS: usize = 3;
+
+// Longest run of consecutive dates whose day passes `keep`
+fn longest_streak(points: &[DailyPoint], keep: impl Fn(&DailyPoint) -> bool) -> usize {
+    let mut best = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for point in points {
+        if !keep(point) {
+            run = 0;
+            previous = None;
+            continue;
+        }
+        run = match previous {
+            Some(date) if point.date - date == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        previous = Some(point.date);
+        best = best.max(run);
+    }
+    best
+}
+
+// --- Summary of a finished goal phase: averages, streaks, strongest links ---
+fn print_summary(options: &GlobalOptions, start: NaiveDate) -> Result<(), AppError> {
+    let units = DisplayUnits::from_config(&options.config);
+    let points: Vec<DailyPoint> = daily::load(options)?
+        .into_iter()
+        .filter(|p| p.date >= start)
+        .collect();
+    let entry_count: usize = points.iter().map(|p| p.entries).sum();
+    let Some(last) = points.last() else {
+        return Ok(());
+    };
+
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Goal complete! ".bold().green());
+    println!("{}", layout::rule('=').cyan());
+    println!(
+        "{} to {}: {} days logged, {} entries",
+        start.format("%Y-%m-%d"),
+        last.date.format("%Y-%m-%d"),
+        points.len().to_string().yellow(),
+        entry_count
+    );
+    println!("{}", layout::rule('-').cyan());
+
+    println!("{}", " Averages ".bold());
+    for metric in METRIC_NAMES {
+        let values: Vec<f64> = points
+            .iter()
+            .filter_map(|p| daily_value(p, metric))
+            .collect();
+        if values.is_empty() {
+            continue;
+        }
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        layout::row(&format!(
+            "  {:<14} {:>5.1} {}",
+            column_name(metric),
+            units.convert(metric, mean),
+            units.unit(metric).dimmed()
+        ));
+    }
+    let workouts = points.iter().filter(|p| p.workout).count();
+    println!("  {:<14} {} of {} days", WORKOUT, workouts, points.len());
+    println!("{}", layout::rule('-').cyan());
+
+    println!("{}", " Best streaks ".bold());
+    println!(
+        "  Days logged in a row:  {}",
+        longest_streak(&points, |_| true).to_string().yellow()
+    );
+    println!(
+        "  Workout days in a row: {}",
+        longest_streak(&points, |p| p.workout).to_string().yellow()
+    );
+    println!("{}", layout::rule('-').cyan());
+
+    // Every pair of metrics (and workouts), strongest first
+    let links = pair_links(&metric_series(&points));
+    println!("{}", " Strongest links ".bold());
+    if links.is_empty() {
+        println!("  {}", "Not enough days to compare metrics.".dimmed());
+    }
+    for (a, b, r, days) in links.iter().take(TOP_CORRELATIONS) {
+        println!(
+            "  {} ~ {}  r = {:+.2} {}",
+            a,
+            b,
+            r,
+            format!("({} days)", days).dimmed()
+        );
+    }
+    println!("{}", layout::rule('=').cyan());
+    Ok(())
+}
+
+// --- Start the next goal phase: new length, optional metric renames, saved to config.toml ---
+fn start_next_phase(options: &GlobalOptions) -> Result<(), AppError> {
+    const GOAL_PROMPT: &str = "How many days should the next goal be?";
+    let goal_days: i64 = replay::answer(GOAL_PROMPT, || {
+        Input::with_theme(&**THEME)
+            .with_prompt(GOAL_PROMPT)
+            .default(options.goal_days)
+            .validate_with(|days: &i64| -> Result<(), String> {
+                if *days > 0 {
+                    Ok(())
+                } else {
+                    Err("Please enter a positive number of days".to_string())
+                }
+            })
+            .interact_text()
+    })?;
+
+    const RENAME_PROMPT: &str = "Rename any metrics for the next phase?";
+    let rename = replay::answer(RENAME_PROMPT, || {
+        Confirm::with_theme(&**THEME)
+            .with_prompt(RENAME_PROMPT)
+            .default(false)
+            .interact()
+    })?;
+    // Names only change on disk during this run, so each metric is offered once
+    let mut remaining: Vec<&str> = if rename {
+        METRIC_NAMES.to_vec()
+    } else {
+        Vec::new()
+    };
+    let mut taken: Vec<String> = Vec::new();
+    while !remaining.is_empty() {
+        let mut items: Vec<String> = remaining
+            .iter()
+            .map(|m| column_name(m).to_string())
+            .collect();
+        items.push("Done".to_string());
+        let choice = replay::answer("Metric to rename", || {
+            Select::with_theme(&**THEME)
+                .with_prompt("Metric to rename")
+                .items(&items)
+                .default(items.len() - 1)
+                .interact()
+        })?;
+        if choice == remaining.len() {
+            break;
+        }
+        let metric = remaining.remove(choice);
+        let prompt = format!("New name for {}", column_name(metric));
+        let new: String = replay::answer(&prompt, || {
+            Input::with_theme(&**THEME)
+                .with_prompt(&prompt)
+                .validate_with(|name: &String| -> Result<(), String> {
+                    if taken.contains(name) {
+                        return Err(format!("'{}' was just given to another metric", name));
+                    }
+                    check_new_name(metric, name)
+                })
+                .interact_text()
+        })?;
+        let rename = MetricAction::Rename {
+            old: metric.to_string(),
+            new: new.clone(),
+        };
+        run_metric(options, &MetricArgs { action: rename })?;
+        taken.push(new);
+    }
+
+    let today = Utc::now().date_naive().format("%Y-%m-%d").to_string();
+    let config_path = options.config_path();
+    edit_config(&config_path, |doc| {
+        let keys: Vec<&str> = match &options.profile {
+            Some(name) if !options.has_own_config() => vec!["profile", name],
+            _ => Vec::new(),
+        };
+        let settings = table_at(doc, &keys).ok_or_else(|| {
+            AppError::ConfigError(format!(
+                "{}: [profile] must be a table",
+                config_path.display()
+            ))
+        })?;
+        set_value(settings, "goal_days", value(goal_days));
+        set_value(settings, "goal_start", value(today.as_str()));
+        Ok(())
+    })?;
+    // The question set is frozen as the config now stands, renames included
+    let config = Config::load(&config_path)?;
+    phases::record(
+        &options.data_file(),
+        &config,
+        Utc::now().date_naive(),
+        goal_days,
+    )?;
+    println!(
+        "{}",
+        format!(" New goal: {} days starting {}", goal_days, today)
+            .bold()
+            .green()
+    );
+    println!("{}", format!("Saved to {}", config_path.display()).dimmed());
+    Ok(())
+}
+
+// --- Called after the first entry of a day: celebrate the goal and offer the next one ---
+// `previous_day` is the goal-phase day of the last entry before this one, if any.
+pub fn check_goal(
+    options: &GlobalOptions,
+    phase_start: NaiveDate,
+    goal_day: i64,
+    previous_day: Option<i64>,
+) -> Result<(), AppError> {
+    if goal_day < options.goal_days {
+        return Ok(());
+    }
+    let just_reached = previous_day.is_none_or(|day| day < options.goal_days);
+    if just_reached {
+        print_summary(options, phase_start)?;
+    } else {
+        println!(
+            "{}",
+            format!(
+                "Day {} of a {}-day goal; still counting.",
+                goal_day,
+                goal_day - options.goal_days
+            )
+            .dimmed()
+        );
+    }
+    // Today's entry is already saved, so a cancelled prompt just means "not now"
+    let next = match replay::answer("Start a new goal phase?", || {
+        Confirm::with_theme(&**THEME)
+            .with_prompt("Start a new goal phase?")
+            .default(just_reached)
+            .interact()
+    }) {
+        Err(AppError::DialogCancelled) => false,
+        next => next?,
+    };
+    if next {
+        start_next_phase(options)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,776 @@
+use crate::anomalies;
+use crate::cli::ImportArgs;
+use crate::audit;
+use crate::constraints;
+use crate::custom;
+use crate::layout;
+use crate::schema::canonical_metric;
+use crate::store;
+use crate::wizard;
+use crate::{
+    metric_range, read_entries, AppError, EntryKind, GlobalOptions, LogEntry,
+    METRIC_NAMES,
+};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use colored::*;
+use csv::ReaderBuilder;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+// Imported values for each day, keyed by metric name
+pub type DailyValues = BTreeMap<NaiveDate, BTreeMap<String, f64>>;
+
+// Importers may also mark workouts: any value >= 0.5 counts as "worked out that day"
+const WORKOUT_COLUMN: &str = "workout_today";
+
+// --- Mapping file: source attribute -> metric column ---
+// [attributes]
+// mood = "mid_vibes"
+// sleep = { metric = "sleep_hours", scale = 0.016667 }  # minutes -> hours
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Mapping {
+    attributes: BTreeMap<String, MappingTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MappingTarget {
+    Metric(String),
+    Scaled {
+        metric: String,
+        #[serde(default = "default_scale")]
+        scale: f64,
+    },
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+impl Mapping {
+    fn load(path: Option<&String>) -> Result<Mapping, AppError> {
+        let Some(path) = path else {
+            return Ok(Mapping::default());
+        };
+        let text = fs::read_to_string(path)?;
+        let mut mapping: Mapping =
+            toml::from_str(&text).map_err(|e| AppError::ConfigError(format!("{}: {}", path, e)))?;
+        for target in mapping.attributes.values_mut() {
+            let (MappingTarget::Metric(metric) | MappingTarget::Scaled { metric, .. }) = target;
+            // Renamed metrics are stored under their original name internally
+            match canonical_metric(metric) {
+                Some(canonical) => *metric = canonical.to_string(),
+                None if metric == WORKOUT_COLUMN => {}
+                None => {
+                    return Err(AppError::ConfigError(format!(
+                        "{}: unknown metric '{}' (choose from {}, {})",
+                        path,
+                        metric,
+                        METRIC_NAMES.join(", "),
+                        WORKOUT_COLUMN
+                    )))
+                }
+            }
+        }
+        Ok(mapping)
+    }
+
+    // Add mappings for keys the user's mapping file does not mention
+    fn with_defaults(mut self, defaults: &[(&str, &str, f64)]) -> Mapping {
+        for (key, metric, scale) in defaults {
+            self.attributes
+                .entry(key.to_string())
+                .or_insert_with(|| MappingTarget::Scaled {
+                    metric: metric.to_string(),
+                    scale: *scale,
+                });
+        }
+        self
+    }
+
+    // Metric and scale factor for a source attribute; attributes named like a metric map to it
+    fn resolve(&self, attribute: &str) -> Option<(&str, f64)> {
+        match self.attributes.get(attribute) {
+            Some(MappingTarget::Metric(metric)) => Some((metric.as_str(), 1.0)),
+            Some(MappingTarget::Scaled { metric, scale }) => Some((metric.as_str(), *scale)),
+            None => canonical_metric(attribute).map(|m| (m, 1.0)),
+        }
+    }
+}
+
+// --- `import` command ---
+pub fn run_import(options: &GlobalOptions, args: &ImportArgs) -> Result<(), AppError> {
+    // The guided importers ask for their mapping interactively (clap allows one source)
+    if let Some(file) = &args.google_forms {
+        return wizard::run_forms_import(options, Path::new(file));
+    }
+    if let Some(file) = &args.wizard {
+        return wizard::run_wizard_import(options, Path::new(file));
+    }
+    let files = [
+        ("exist", &args.exist),
+        ("bearable", &args.bearable),
+        ("toggl", &args.toggl),
+        ("clockify", &args.clockify),
+    ];
+    for (format, file) in files {
+        if let Some(file) = file {
+            return import_file(options, format, Path::new(file), args.mapping.clone());
+        }
+    }
+    Ok(())
+}
+
+// Export formats `import_file` understands (the daemon's watch folder uses the same names)
+pub const FILE_FORMATS: [&str; 4] = ["exist", "bearable", "toggl", "clockify"];
+
+// --- Merge one export file, in one of FILE_FORMATS, into the data file ---
+pub fn import_file(
+    options: &GlobalOptions,
+    format: &str,
+    path: &Path,
+    mapping_file: Option<String>,
+) -> Result<(), AppError> {
+    let mapping = Mapping::load(mapping_file.as_ref())?;
+    match format {
+        "exist" => {
+            let raw = read_exist_export(path)?;
+            merge_daily_values(options, apply_mapping(raw, &mapping), "Exist.io")?;
+        }
+        "bearable" => {
+            let values = read_bearable_export(path, mapping)?;
+            merge_daily_values(options, values, "Bearable")?;
+        }
+        "toggl" => {
+            let values = read_time_entries(path, &TOGGL_COLUMNS)?;
+            merge_daily_values(options, values, "Toggl")?;
+        }
+        "clockify" => {
+            let values = read_time_entries(path, &CLOCKIFY_COLUMNS)?;
+            merge_daily_values(options, values, "Clockify")?;
+        }
+        other => {
+            return Err(AppError::ImportError(format!(
+                "unknown import format '{}' (choose from {})",
+                other,
+                FILE_FORMATS.join(", ")
+            )))
+        }
+    }
+    Ok(())
+}
+
+// Raw attribute values per day, before mapping
+type RawValues = BTreeMap<NaiveDate, BTreeMap<String, f64>>;
+
+// --- Exist.io export parsing (API JSON, or CSV in wide or long layout) ---
+fn read_exist_export(path: &Path) -> Result<RawValues, AppError> {
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        read_exist_json(path)
+    } else {
+        read_exist_csv(path)
+    }
+}
+
+// JSON as returned by the attributes API: a list (or `results` list) of
+// `{ "name": ..., "values": [{ "date": ..., "value": ... }] }`
+fn read_exist_json(path: &Path) -> Result<RawValues, AppError> {
+    let json: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let attributes = match &json {
+        Value::Array(items) => items.as_slice(),
+        Value::Object(map) => map
+            .get("results")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default(),
+        _ => &[],
+    };
+
+    let mut raw = RawValues::new();
+    for attribute in attributes {
+        let Some(name) = attribute
+            .get("name")
+            .or_else(|| attribute.get("attribute"))
+            .and_then(Value::as_str)
+        else {
+            continue;
+        };
+        let values = attribute.get("values").and_then(Value::as_array);
+        for point in values.into_iter().flatten() {
+            let date = point
+                .get("date")
+                .and_then(Value::as_str)
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+            let value = point.get("value").and_then(Value::as_f64);
+            if let (Some(date), Some(value)) = (date, value) {
+                raw.entry(date).or_default().insert(name.to_string(), value);
+            }
+        }
+    }
+    Ok(raw)
+}
+
+// CSV with a `date` column plus either one column per attribute, or
+// `attribute`/`name` and `value` columns (one row per attribute per day)
+fn read_exist_csv(path: &Path) -> Result<RawValues, AppError> {
+    let mut rdr = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let headers: Vec<String> = rdr
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let Some(date_col) = column("date") else {
+        return Err(AppError::ImportError(
+            "expected a 'date' column".to_string(),
+        ));
+    };
+    let long_format = column("value").zip(column("attribute").or_else(|| column("name")));
+
+    let mut raw = RawValues::new();
+    for result in rdr.records() {
+        let record = match result {
+            Ok(rec) => rec,
+            Err(e) => {
+                eprintln!("Warning: Skipping corrupted CSV record: {}", e);
+                continue;
+            }
+        };
+        let Some(date) = record
+            .get(date_col)
+            .and_then(|d| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").ok())
+        else {
+            eprintln!("Warning: Skipping row with unreadable date.");
+            continue;
+        };
+        let day = raw.entry(date).or_default();
+        match long_format {
+            Some((value_col, name_col)) => {
+                let name = record.get(name_col).unwrap_or("").trim();
+                if let Some(value) = record.get(value_col).and_then(|v| v.trim().parse().ok()) {
+                    day.insert(name.to_string(), value);
+                }
+            }
+            None => {
+                for (i, header) in headers.iter().enumerate() {
+                    if i == date_col {
+                        continue;
+                    }
+                    if let Some(value) = record.get(i).and_then(|v| v.trim().parse().ok()) {
+                        day.insert(header.clone(), value);
+                    }
+                }
+            }
+        }
+    }
+    Ok(raw)
+}
+
+fn apply_mapping(raw: RawValues, mapping: &Mapping) -> DailyValues {
+    let mut unmapped: Vec<String> = Vec::new();
+    let mut values = DailyValues::new();
+    for (date, attributes) in raw {
+        for (attribute, value) in attributes {
+            match mapping.resolve(&attribute) {
+                Some((metric, scale)) => {
+                    values
+                        .entry(date)
+                        .or_default()
+                        .insert(metric.to_string(), value * scale);
+                }
+                None if !unmapped.contains(&attribute) => unmapped.push(attribute),
+                None => {}
+            }
+        }
+    }
+    if !unmapped.is_empty() {
+        eprintln!(
+            "{}",
+            format!(
+                "Ignoring attributes without a metric mapping: {}",
+                unmapped.join(", ")
+            )
+            .yellow()
+        );
+    }
+    values
+}
+
+// --- Bearable export parsing ---
+// One row per rating/factor: date, category, rating/amount, detail. Ratings
+// on Bearable's 1-5 scales are doubled to this tool's 1-10 scale.
+const BEARABLE_DEFAULTS: [(&str, &str, f64); 4] = [
+    ("Mood", "mid_vibes", 2.0),
+    ("Energy", "energy", 2.0),
+    ("Sleep", "sleep_hours", 1.0),
+    ("Sleep quality", "sleep_quality", 2.0),
+];
+
+// Boolean factors that count as a workout unless the mapping file says otherwise
+const WORKOUT_FACTORS: [&str; 5] = ["exercise", "workout", "gym", "sport", "training"];
+
+fn read_bearable_export(path: &Path, mapping: Mapping) -> Result<DailyValues, AppError> {
+    let mapping = mapping.with_defaults(&BEARABLE_DEFAULTS);
+    let mut rdr = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let headers: Vec<String> = rdr
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_ascii_lowercase())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (Some(date_col), Some(category_col)) = (column("date"), column("category")) else {
+        return Err(AppError::ImportError(
+            "expected 'date' and 'category' columns in the Bearable export".to_string(),
+        ));
+    };
+    let amount_col = column("rating/amount")
+        .or_else(|| column("rating"))
+        .or_else(|| column("amount"));
+    let detail_col = column("detail");
+
+    // (sum, count) per day and metric, so several readings in a day are averaged
+    let mut sums: BTreeMap<NaiveDate, BTreeMap<String, (f64, usize)>> = BTreeMap::new();
+    let mut unmapped: Vec<String> = Vec::new();
+    for result in rdr.records() {
+        let record = match result {
+            Ok(rec) => rec,
+            Err(e) => {
+                eprintln!("Warning: Skipping corrupted CSV record: {}", e);
+                continue;
+            }
+        };
+        // Dates are ISO timestamps; the day is the first ten characters
+        let Some(date) = record
+            .get(date_col)
+            .and_then(|d| d.trim().get(..10))
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        else {
+            eprintln!("Warning: Skipping row with unreadable date.");
+            continue;
+        };
+        let category = record.get(category_col).unwrap_or("").trim();
+        let detail = detail_col.and_then(|i| record.get(i)).unwrap_or("").trim();
+        let amount = amount_col.and_then(|i| record.get(i)).unwrap_or("").trim();
+
+        // "Category:Detail" mappings are more specific than plain "Category" ones
+        let specific_key = format!("{}:{}", category, detail);
+        let target = mapping
+            .resolve(&specific_key)
+            .or_else(|| mapping.resolve(detail).filter(|_| !detail.is_empty()))
+            .or_else(|| mapping.resolve(category));
+        let (metric, scale, value) = match target {
+            Some((metric, scale)) if metric == WORKOUT_COLUMN => (metric.to_string(), scale, 1.0),
+            Some((metric, scale)) => match parse_amount(amount) {
+                Some(value) => (metric.to_string(), scale, value),
+                None => continue, // Mapped but empty rating
+            },
+            None if category.eq_ignore_ascii_case("factors")
+                && WORKOUT_FACTORS
+                    .iter()
+                    .any(|f| detail.to_ascii_lowercase().contains(f)) =>
+            {
+                (WORKOUT_COLUMN.to_string(), 1.0, 1.0)
+            }
+            None => {
+                let key = if detail.is_empty() {
+                    category.to_string()
+                } else {
+                    specific_key
+                };
+                if !unmapped.contains(&key) {
+                    unmapped.push(key);
+                }
+                continue;
+            }
+        };
+        let slot = sums.entry(date).or_default().entry(metric).or_default();
+        slot.0 += value * scale;
+        slot.1 += 1;
+    }
+
+    if !unmapped.is_empty() {
+        eprintln!(
+            "{}",
+            format!(
+                "Ignoring Bearable entries without a metric mapping: {}",
+                unmapped.join(", ")
+            )
+            .yellow()
+        );
+    }
+    Ok(sums
+        .into_iter()
+        .map(|(date, metrics)| {
+            let means = metrics
+                .into_iter()
+                .map(|(metric, (sum, count))| (metric, sum / count as f64))
+                .collect();
+            (date, means)
+        })
+        .collect())
+}
+
+// Ratings are plain numbers; durations may be written as H:MM
+fn parse_amount(amount: &str) -> Option<f64> {
+    if let Some((hours, minutes)) = amount.split_once(':') {
+        let hours: f64 = hours.trim().parse().ok()?;
+        let minutes: f64 = minutes.trim().parse().ok()?;
+        return Some(hours + minutes / 60.0);
+    }
+    amount.parse().ok()
+}
+
+// --- Time tracker exports (Toggl Track, Clockify): hours worked per day ---
+// Detailed reports list one time entry per row; the entries are summed by the day they
+// started into the `work_hours` custom metric, which has to be declared in the config.
+const WORK_HOURS: &str = "work_hours";
+
+struct TimeColumns {
+    date: &'static str,
+    durations: &'static [&'static str], // The first one present is used
+}
+
+const TOGGL_COLUMNS: TimeColumns = TimeColumns {
+    date: "start date",
+    durations: &["duration"],
+};
+
+const CLOCKIFY_COLUMNS: TimeColumns = TimeColumns {
+    date: "start date",
+    durations: &["duration (decimal)", "duration (h)"],
+};
+
+// Decimal hours, or H:MM[:SS]
+fn parse_duration(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if !text.contains(':') {
+        return text.parse().ok().filter(|h: &f64| h.is_finite());
+    }
+    let mut parts = text.split(':').map(|p| p.trim().parse::<f64>());
+    let hours = parts.next()?.ok()?;
+    let minutes = parts.next()?.ok()?;
+    let seconds = parts.next().transpose().ok()?.unwrap_or(0.0);
+    parts
+        .next()
+        .is_none()
+        .then_some(hours + minutes / 60.0 + seconds / 3600.0)
+}
+
+fn read_time_entries(path: &Path, columns: &TimeColumns) -> Result<DailyValues, AppError> {
+    if !custom::numeric_columns().any(|c| c == WORK_HOURS) {
+        return Err(AppError::ImportError(format!(
+            "time entries are imported into a numeric '{}' metric; add it to the config first:\n\n[custom.{}]\ntype = \"float\"\nrange = [0, 24]",
+            WORK_HOURS, WORK_HOURS
+        )));
+    }
+    let mut rdr = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let headers: Vec<String> = rdr
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_ascii_lowercase())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (Some(date_col), Some(duration_col)) = (
+        column(columns.date),
+        columns.durations.iter().find_map(|name| column(name)),
+    ) else {
+        return Err(AppError::ImportError(format!(
+            "expected '{}' and '{}' columns in the time entries (export a detailed report as CSV)",
+            columns.date,
+            columns.durations.join("' or '")
+        )));
+    };
+    let mut rows = Vec::new();
+    for result in rdr.records() {
+        match result {
+            Ok(record) => rows.push(record),
+            Err(e) => eprintln!("Warning: Skipping corrupted CSV record: {}", e),
+        }
+    }
+    // Date layouts follow the account's settings, so they're detected like the wizard does
+    let dates: Vec<&str> = rows.iter().filter_map(|r| r.get(date_col)).collect();
+    let Some(format) = wizard::detect_timestamp_format(&dates) else {
+        return Err(AppError::ImportError(format!(
+            "could not recognise the dates in the '{}' column",
+            columns.date
+        )));
+    };
+
+    let mut hours = DailyValues::new();
+    for record in &rows {
+        let date = record
+            .get(date_col)
+            .and_then(|d| wizard::parse_timestamp(d, format));
+        let duration = record.get(duration_col).and_then(parse_duration);
+        let (Some(date), Some(duration)) = (date, duration) else {
+            eprintln!("Warning: Skipping time entry with unreadable date or duration.");
+            continue;
+        };
+        *hours
+            .entry(date.date_naive())
+            .or_default()
+            .entry(WORK_HOURS.to_string())
+            .or_default() += duration;
+    }
+    for day in hours.values_mut() {
+        for total in day.values_mut() {
+            *total = (*total * 100.0).round() / 100.0;
+        }
+    }
+    Ok(hours)
+}
+
+// --- Merge imported daily values into the data file ---
+// Days that already have entries only get their empty metrics filled in (logged
+// answers are never overwritten); other days get a new midday entry.
+pub fn merge_daily_values(
+    options: &GlobalOptions,
+    values: DailyValues,
+    source: &str,
+) -> Result<(), AppError> {
+    let data_file = options.data_file();
+
+    // Drop values outside the metric's valid range
+    let mut skipped = 0;
+    let days = values.len();
+    let values: DailyValues = values
+        .into_iter()
+        .map(|(date, metrics)| {
+            let metrics = metrics
+                .into_iter()
+                .filter(|(metric, value)| {
+                    let (min, max) = match custom::find(metric) {
+                        Some(custom) => custom::range(custom).unwrap_or((f64::MIN, f64::MAX)),
+                        None => metric_range(metric),
+                    };
+                    let ok = (min..=max).contains(value);
+                    if !ok {
+                        eprintln!(
+                            "Warning: {} {} = {} is outside {}..={}, skipping.",
+                            date, metric, value, min, max
+                        );
+                        skipped += 1;
+                    }
+                    ok
+                })
+                .collect();
+            (date, metrics)
+        })
+        .collect();
+
+    // Counted on the entries as read; the merge is done again on the file when saving
+    let (created, filled) = fill_days(&mut read_entries(&data_file)?, &values, source);
+    println!("{}", format!(" {} import ", source).bold().cyan());
+    println!("{}", layout::rule('-').cyan());
+    println!("Days in import:          {}", days.to_string().yellow());
+    println!("New entries:             {}", created.to_string().green());
+    println!("Values filled in:        {}", filled.to_string().green());
+    if skipped > 0 {
+        println!("Out-of-range values:     {}", skipped.to_string().red());
+    }
+    if created == 0 && filled == 0 {
+        println!("{}", "Nothing to change.".dimmed());
+        return Ok(());
+    }
+    if options.dry_run {
+        println!(
+            "{}",
+            format!("Dry run: {} was not modified.", data_file).yellow()
+        );
+        return Ok(());
+    }
+
+    save_sorted(&data_file, &format!("import {}", source), |entries| {
+        fill_days(entries, &values, source);
+    })?;
+    println!("{}", format!("Merged into {}", data_file).bold().green());
+    anomalies::hint_after_import(&data_file)
+}
+
+// Fill in or add each day's values, returning (entries created, values filled in)
+fn fill_days(entries: &mut Vec<LogEntry>, values: &DailyValues, source: &str) -> (usize, usize) {
+    let mut filled = 0;
+    let mut new_entries: Vec<LogEntry> = Vec::new();
+    for (date, metrics) in values {
+        if metrics.is_empty() {
+            continue;
+        }
+
+        let mut same_day: Vec<&mut LogEntry> = entries
+            .iter_mut()
+            .filter(|e| e.parsed_timestamp().map(|ts| ts.date_naive()) == Some(*date))
+            .collect();
+        if same_day.is_empty() {
+            let mut entry = LogEntry::empty_at(
+                date.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap())
+                    .and_utc(),
+            );
+            entry.provenance = import_provenance(source);
+            entry.entry_kind = Some(EntryKind::Backfill);
+            for (metric, value) in metrics {
+                if metric == WORKOUT_COLUMN {
+                    entry.workout_today = *value >= 0.5;
+                } else if !custom::set_number(&mut entry, metric, *value) {
+                    entry.set_metric(metric, *value);
+                }
+            }
+            new_entries.push(entry);
+        } else {
+            for entry in same_day.iter_mut() {
+                let before = filled;
+                for (metric, value) in metrics {
+                    if metric.as_str() == WORKOUT_COLUMN {
+                        // Like the logging flow, a workout only ever turns a day to "yes"
+                        if *value >= 0.5 && !entry.workout_today {
+                            entry.workout_today = true;
+                            filled += 1;
+                        }
+                    } else if custom::find(metric).is_some() {
+                        if !entry.custom.contains_key(metric.as_str())
+                            && custom::set_number(entry, metric, *value)
+                        {
+                            filled += 1;
+                        }
+                    } else if entry.metric(metric).is_none() && entry.set_metric(metric, *value) {
+                        filled += 1;
+                    }
+                }
+                if filled > before {
+                    entry.add_provenance(&import_provenance(source));
+                }
+            }
+        }
+    }
+    let created = new_entries.len();
+    entries.extend(new_entries);
+    (created, filled)
+}
+
+// --- Add imported entries that carry their own timestamps ---
+// Rows whose timestamp is already in the file are treated as duplicates.
+pub fn merge_entries(
+    options: &GlobalOptions,
+    imported: Vec<LogEntry>,
+    source: &str,
+) -> Result<(), AppError> {
+    let data_file = options.data_file();
+    let entries = read_entries(&data_file)?;
+    let total = imported.len();
+    let imported: Vec<LogEntry> = imported
+        .into_iter()
+        .map(|mut e| {
+            if e.provenance.is_empty() {
+                e.provenance = import_provenance(source);
+            }
+            e.entry_kind.get_or_insert(EntryKind::Backfill);
+            e
+        })
+        .collect();
+    let fresh = new_timestamps(&entries, &imported);
+
+    println!("{}", format!(" {} import ", source).bold().cyan());
+    println!("{}", layout::rule('-').cyan());
+    println!("Rows in import:          {}", total.to_string().yellow());
+    println!(
+        "New entries:             {}",
+        fresh.len().to_string().green()
+    );
+    if fresh.len() < total {
+        println!(
+            "Already present:         {}",
+            (total - fresh.len()).to_string().dimmed()
+        );
+    }
+    if fresh.is_empty() {
+        println!("{}", "Nothing to change.".dimmed());
+        return Ok(());
+    }
+    if options.dry_run {
+        println!(
+            "{}",
+            format!("Dry run: {} was not modified.", data_file).yellow()
+        );
+        return Ok(());
+    }
+
+    save_sorted(&data_file, &format!("import {}", source), |entries| {
+        let fresh = new_timestamps(entries, &imported);
+        entries.extend(fresh);
+    })?;
+    println!("{}", format!("Merged into {}", data_file).bold().green());
+    anomalies::hint_after_import(&data_file)
+}
+
+// The entries of `added` whose timestamp isn't in `entries` yet
+pub fn new_timestamps(entries: &[LogEntry], added: &[LogEntry]) -> Vec<LogEntry> {
+    let existing: HashSet<Option<DateTime<Utc>>> =
+        entries.iter().map(|e| e.parsed_timestamp()).collect();
+    added
+        .iter()
+        .filter(|e| !existing.contains(&e.parsed_timestamp()))
+        .cloned()
+        .collect()
+}
+
+// Provenance tag for imported values, e.g. "Google Forms" -> "import:google-forms"
+fn import_provenance(source: &str) -> String {
+    format!("import:{}", source.to_lowercase().replace(' ', "-"))
+}
+
+// Put `updated` in place of the entries with the same timestamps (entries deleted in the
+// meantime stay deleted)
+pub fn replace_entries(entries: &mut [LogEntry], updated: &[LogEntry]) {
+    for entry in entries.iter_mut() {
+        if let Some(new) = updated.iter().find(|u| u.timestamp == entry.timestamp) {
+            *entry = new.clone();
+        }
+    }
+}
+
+// Apply `change` to the entries and keep the file chronological: the reader treats the
+// last row as the latest day. The file is read and written under its lock, so `change`
+// sees rows added since the caller read them. Every rewrite is recorded in the audit
+// trail under `action`.
+pub fn save_sorted(
+    data_file: &str,
+    action: &str,
+    mut change: impl FnMut(&mut Vec<LogEntry>),
+) -> Result<(), AppError> {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    store::open(data_file).modify(&mut |entries| {
+        before = entries.clone();
+        change(entries);
+        entries.sort_by_key(|e| e.parsed_timestamp().unwrap_or(DateTime::<Utc>::MIN_UTC));
+        renumber_days(entries);
+        constraints::validate_changes(&before, entries)?;
+        after = entries.clone();
+        Ok(())
+    })?;
+    audit::record(data_file, action, "", audit::diff_entries(&before, &after))
+}
+
+// day_count is derived from the earliest entry, so renumber everything once
+// imported rows may have moved the first day earlier
+fn renumber_days(entries: &mut [LogEntry]) {
+    let Some(first) = entries
+        .iter()
+        .filter_map(|e| e.parsed_timestamp())
+        .map(|ts| ts.date_naive())
+        .min()
+    else {
+        return;
+    };
+    for entry in entries.iter_mut() {
+        if let Some(ts) = entry.parsed_timestamp() {
+            entry.day_count = (ts.date_naive() - first).num_days() + 1;
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::config::{edit_config, set_value, table_at};
+use crate::layout;
+use crate::replay;
+use crate::schema::column_name;
+use crate::store;
+use crate::{read_entries, write_entries, AppError, GlobalOptions, METRIC_NAMES, THEME};
+use colored::*;
+use dialoguer::{Confirm, Input, MultiSelect};
+use std::fs;
+use std::path::Path;
+use toml_edit::value;
+
+// The answer is a path from the current directory, like --data-file; the config
+// resolves relative paths against the data directory instead
+fn config_path_of(options: &GlobalOptions, data_file: &str) -> Result<String, AppError> {
+    let path = Path::new(data_file);
+    let data_dir = options.data_dir();
+    let stored = if let Ok(inside) = path.strip_prefix(&data_dir) {
+        inside.to_path_buf()
+    } else if path.is_relative() {
+        std::env::current_dir()?.join(path)
+    } else {
+        path.to_path_buf()
+    };
+    Ok(stored.to_string_lossy().into_owned())
+}
+
+// --- `init` command: set up the data file and config.toml step by step ---
+// Existing settings are the defaults, so running it again changes them in place.
+pub fn run_init(options: &GlobalOptions) -> Result<(), AppError> {
+    let config_path = options.config_path();
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Daily Metrics Logger setup ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    if config_path.exists() {
+        println!(
+            "{}",
+            format!(
+                "Updating {}; press Enter to keep a setting.",
+                config_path.display()
+            )
+            .dimmed()
+        );
+    }
+
+    let data_file: String = replay::answer("Data file", || {
+        Input::with_theme(&**THEME)
+            .with_prompt("Data file")
+            .default(options.data_file())
+            .interact_text()
+    })?;
+
+    let names: Vec<&str> = METRIC_NAMES.iter().map(|m| column_name(m)).collect();
+    let enabled: Vec<bool> = METRIC_NAMES
+        .iter()
+        .map(|m| options.config.metric_enabled(m))
+        .collect();
+    const METRICS_PROMPT: &str = "Metrics to track (Space to toggle, Enter to confirm)";
+    let tracked = replay::answer(METRICS_PROMPT, || {
+        MultiSelect::with_theme(&**THEME)
+            .with_prompt(METRICS_PROMPT)
+            .items(&names)
+            .defaults(&enabled)
+            .interact()
+    })?;
+    if tracked.is_empty() {
+        return Err(AppError::UsageError(
+            "Pick at least one metric to track".to_string(),
+        ));
+    }
+
+    let goal_days: i64 = replay::answer("Goal length in days", || {
+        Input::with_theme(&**THEME)
+            .with_prompt("Goal length in days")
+            .default(options.goal_days)
+            .validate_with(|days: &i64| -> Result<(), String> {
+                if *days > 0 {
+                    Ok(())
+                } else {
+                    Err("Please enter a positive number of days".to_string())
+                }
+            })
+            .interact_text()
+    })?;
+
+    let mut zone_input = Input::with_theme(&**THEME)
+        .with_prompt("Time zone, e.g. Europe/Berlin (empty for the system's)")
+        .allow_empty(true);
+    if let Some(zone) = &options.config.timezone {
+        zone_input = zone_input.default(zone.clone());
+    }
+    let timezone: String = replay::answer("timezone", || {
+        zone_input
+            .validate_with(|zone: &String| -> Result<(), String> {
+                if zone.trim().is_empty() || zone.trim().parse::<chrono_tz::Tz>().is_ok() {
+                    Ok(())
+                } else {
+                    Err("Unknown time zone: use a name like America/New_York".to_string())
+                }
+            })
+            .interact_text()
+    })?;
+    let timezone = timezone.trim();
+
+    println!("{}", layout::rule('-').cyan());
+    println!(" Config:    {}", config_path.display());
+    println!(" Data file: {}", data_file);
+    println!(
+        " Metrics:   {}",
+        tracked
+            .iter()
+            .map(|&i| names[i])
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    println!(" Goal:      {} days", goal_days);
+    println!(
+        " Time zone: {}",
+        if timezone.is_empty() {
+            "system"
+        } else {
+            timezone
+        }
+    );
+    if options.dry_run {
+        println!("{}", "Dry run: nothing written.".yellow());
+        return Ok(());
+    }
+    let confirmed = replay::answer("Write these settings?", || {
+        Confirm::with_theme(&**THEME)
+            .with_prompt("Write these settings?")
+            .default(true)
+            .interact()
+    })?;
+    if !confirmed {
+        println!("{}", "Nothing written.".dimmed());
+        return Ok(());
+    }
+
+    edit_config(&config_path, |doc| {
+        let root = doc.as_table_mut();
+        // The default location stays implicit, so the data can move with the config
+        if data_file != options.data_file() || root.contains_key("data_file") {
+            set_value(
+                root,
+                "data_file",
+                value(config_path_of(options, &data_file)?),
+            );
+        }
+        set_value(root, "goal_days", value(goal_days));
+        if timezone.is_empty() {
+            root.remove("timezone");
+        } else {
+            set_value(root, "timezone", value(timezone));
+        }
+        for (i, metric) in METRIC_NAMES.iter().enumerate() {
+            let on = tracked.contains(&i);
+            if on && options.config.metric_enabled(metric) {
+                continue;
+            }
+            let settings = table_at(doc, &["metric", metric]).ok_or_else(|| {
+                AppError::ConfigError(format!(
+                    "{}: [metric] must be a table",
+                    config_path.display()
+                ))
+            })?;
+            if on {
+                settings.remove("enabled");
+            } else {
+                set_value(settings, "enabled", value(false));
+            }
+        }
+        Ok(())
+    })?;
+
+    if let Some(dir) = Path::new(&data_file)
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+    {
+        fs::create_dir_all(dir)?;
+    }
+    if store::files(&data_file).is_empty() {
+        // A new file (say .jsonl instead of .csv) can start with the entries logged so far
+        let existing = read_entries(&options.data_file())?;
+        let carry_over = !existing.is_empty()
+            && replay::answer("Copy the entries?", || {
+                Confirm::with_theme(&**THEME)
+                    .with_prompt(format!(
+                        "Copy the {} entries from {}?",
+                        existing.len(),
+                        options.data_file()
+                    ))
+                    .default(true)
+                    .interact()
+            })?;
+        write_entries(&data_file, if carry_over { &existing } else { &[] })?;
+    }
+    println!(
+        "{}",
+        format!(
+            "Saved {}. Run `log` for your first entry.",
+            config_path.display()
+        )
+        .bold()
+        .green()
+    );
+    Ok(())
+}
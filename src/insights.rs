@@ -0,0 +1,252 @@
+use crate::analysis::{cohens_d, correlation, daily_value, mean_sd, metric_series, pair_links};
+use crate::cli::{InsightsArgs, OutputFormat};
+use crate::daily::{self, DailyPoint};
+use crate::layout;
+use crate::schema::column_name;
+use crate::termux;
+use crate::units::DisplayUnits;
+use crate::{AppError, GlobalOptions, METRIC_NAMES};
+use chrono::{Duration, NaiveDate, Utc};
+use colored::*;
+use serde::Serialize;
+
+// --- Insights: the most notable things about the last week ---
+// Three kinds of finding, each with a score so they can be ranked together: a metric
+// that moved away from the weeks before (scored by its effect size), a link between two
+// metrics that has only just appeared (by how much stronger it got), and a logging
+// streak that ended (by its length). Run `insights --send` weekly from cron or
+// termux-job-scheduler for a digest of the top three.
+const BASELINE_DAYS: i64 = 28; // The week is compared with the four weeks before it
+const MIN_WEEK_DAYS: usize = 3;
+const MIN_BASELINE_DAYS: usize = 7;
+const MIN_EFFECT: f64 = 0.5; // A "medium" change or more
+const LINK_DAYS: i64 = 30; // Links are found over the last month...
+const MIN_LINK: f64 = 0.5;
+const MIN_LINK_GAIN: f64 = 0.3; // ...and count as new when this much stronger than before
+const LINK_WEIGHT: f64 = 2.5; // A gain of 0.4 ranks with an effect size of 1
+const MIN_STREAK: usize = 5;
+const STREAK_WEIGHT: f64 = 0.1; // A 10-day streak ranks with an effect size of 1
+const SENT: usize = 3;
+const DIGEST_ID: &str = "daily-metrics-insights"; // Each digest replaces the last one
+
+#[derive(Serialize)]
+struct Insight {
+    kind: &'static str, // trend, link or streak
+    score: f64,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct Insights {
+    from: NaiveDate,
+    to: NaiveDate,
+    insights: Vec<Insight>,
+}
+
+fn in_range(points: &[DailyPoint], from: NaiveDate, to: NaiveDate) -> Vec<&DailyPoint> {
+    points
+        .iter()
+        .filter(|p| (from..=to).contains(&p.date))
+        .collect()
+}
+
+// Metrics whose week differs from the weeks before by a medium effect or more
+fn trends(
+    points: &[DailyPoint],
+    units: &DisplayUnits,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<Insight> {
+    let week = in_range(points, from, to);
+    let before = in_range(
+        points,
+        from - Duration::days(BASELINE_DAYS),
+        from - Duration::days(1),
+    );
+    METRIC_NAMES
+        .iter()
+        .filter_map(|metric| {
+            let values = |days: &[&DailyPoint]| -> Vec<f64> {
+                days.iter()
+                    .filter_map(|p| daily_value(p, metric))
+                    .map(|v| units.convert(metric, v))
+                    .collect()
+            };
+            let (now, earlier) = (values(&week), values(&before));
+            if now.len() < MIN_WEEK_DAYS || earlier.len() < MIN_BASELINE_DAYS {
+                return None;
+            }
+            let d = cohens_d(&earlier, &now).filter(|d| d.abs() >= MIN_EFFECT)?;
+            let (mean, _) = mean_sd(&now)?;
+            let (usual, _) = mean_sd(&earlier)?;
+            let unit = units.unit(metric);
+            Some(Insight {
+                kind: "trend",
+                score: d.abs(),
+                text: format!(
+                    "{} {}: {:.1}{} on average, against {:.1} the four weeks before",
+                    column_name(metric),
+                    if d > 0.0 { "up" } else { "down" },
+                    mean,
+                    if unit.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" {}", unit)
+                    },
+                    usual
+                ),
+            })
+        })
+        .collect()
+}
+
+// The strongest link of the last month that was weak (or not there) before it
+fn new_link(points: &[DailyPoint], to: NaiveDate) -> Option<Insight> {
+    let start = to - Duration::days(LINK_DAYS - 1);
+    // Days come in date order
+    let points = &points[..points.partition_point(|p| p.date <= to)];
+    let (earlier, recent) = points.split_at(points.partition_point(|p| p.date < start));
+    let (earlier, recent) = (metric_series(earlier), metric_series(recent));
+    let series = |name: &str| earlier.iter().find(|(n, _)| n == name).map(|(_, s)| s);
+    pair_links(&recent)
+        .into_iter()
+        .filter(|(.., r, _)| r.abs() >= MIN_LINK)
+        .filter_map(|(a, b, r, _)| {
+            let before = correlation(series(&a)?, series(&b)?).map(|(r, _)| r);
+            let gain = r.abs() - before.map_or(0.0, f64::abs);
+            (gain >= MIN_LINK_GAIN).then_some((a, b, r, before, gain))
+        })
+        .max_by(|x, y| x.4.total_cmp(&y.4))
+        .map(|(a, b, r, before, gain)| Insight {
+            kind: "link",
+            score: gain * LINK_WEIGHT,
+            text: format!(
+                "{} and {} {} together this month (r {:+.2}, {})",
+                a,
+                b,
+                if r > 0.0 {
+                    "rise and fall"
+                } else {
+                    "move in opposite directions"
+                },
+                r,
+                before.map_or("too few days before to compare".to_string(), |r| {
+                    format!("was {:+.2}", r)
+                })
+            ),
+        })
+}
+
+// A run of logged days that ended with a missed day in the week. Today doesn't count
+// as missed: it can still be logged.
+fn broken_streak(points: &[DailyPoint], from: NaiveDate, to: NaiveDate) -> Option<Insight> {
+    let mut best: Option<(usize, NaiveDate)> = None;
+    let mut run = 0;
+    for (i, point) in points.iter().enumerate() {
+        let continues = i > 0 && points[i - 1].date == point.date - Duration::days(1);
+        run = if continues { run + 1 } else { 1 };
+        let missed = point.date + Duration::days(1);
+        let ended = points.get(i + 1).is_none_or(|next| next.date != missed);
+        if ended && run >= MIN_STREAK && (from..to).contains(&missed) {
+            best = best.max(Some((run, missed)));
+        }
+    }
+    best.map(|(length, missed)| Insight {
+        kind: "streak",
+        score: length as f64 * STREAK_WEIGHT,
+        text: format!(
+            "Your {}-day logging streak ended: nothing was logged on {}",
+            length, missed
+        ),
+    })
+}
+
+fn collect(options: &GlobalOptions, days: u32) -> Result<Insights, AppError> {
+    let units = DisplayUnits::from_config(&options.config);
+    let points = daily::load(options)?;
+    let to = Utc::now().date_naive();
+    let from = to - Duration::days(i64::from(days) - 1);
+    let mut insights = trends(&points, &units, from, to);
+    insights.extend(new_link(&points, to));
+    insights.extend(broken_streak(&points, from, to));
+    insights.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(Insights { from, to, insights })
+}
+
+// --- `insights` command: the findings of the week, most notable first ---
+pub fn run_insights(options: &GlobalOptions, args: &InsightsArgs) -> Result<(), AppError> {
+    if args.days == 0 {
+        return Err(AppError::UsageError(
+            "--days must be at least one day".to_string(),
+        ));
+    }
+    let report = collect(options, args.days)?;
+    if args.send {
+        return send(options, &report);
+    }
+    match options.format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+            for insight in &report.insights {
+                wtr.serialize(insight)?;
+            }
+            wtr.flush()?;
+            return Ok(());
+        }
+        OutputFormat::Table | OutputFormat::Parquet => {}
+    }
+
+    println!("{}", layout::rule('=').cyan());
+    println!(
+        "{}",
+        format!(" Insights: {} to {} ", report.from, report.to)
+            .bold()
+            .cyan()
+    );
+    println!("{}", layout::rule('=').cyan());
+    if report.insights.is_empty() {
+        println!("{}", "Nothing stood out this week.".dimmed());
+        return Ok(());
+    }
+    for (rank, insight) in report.insights.iter().enumerate() {
+        let number = format!("{:>2}.", rank + 1);
+        layout::row(&format!(
+            "{} {}",
+            if rank < SENT {
+                number.yellow().bold()
+            } else {
+                number.dimmed()
+            },
+            insight.text
+        ));
+    }
+    Ok(())
+}
+
+// The top three as one notification
+fn send(options: &GlobalOptions, report: &Insights) -> Result<(), AppError> {
+    if report.insights.is_empty() {
+        println!("Nothing stood out this week; no digest sent.");
+        return Ok(());
+    }
+    let title = format!("Your week to {}", report.to);
+    let content = report
+        .insights
+        .iter()
+        .take(SENT)
+        .map(|insight| format!("• {}", insight.text))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if options.dry_run {
+        println!("{}", "Dry run: would send this digest:".yellow());
+        println!("{}\n{}", title.bold(), content);
+        return Ok(());
+    }
+    termux::post(DIGEST_ID, &title, &content)?;
+    println!("Digest sent.");
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use crate::store::{self, MetricStore};
+use crate::{constraints, AppError, LogEntry};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+// --- JSON Lines storage: one entry per line, chosen by a data file ending in .jsonl ---
+// Keys are the field names, so renaming a metric never touches the file, and fields
+// added later are simply missing from older lines.
+pub fn is_jsonl(file_path: &str) -> bool {
+    Path::new(file_path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+}
+
+fn read_entries(file_path: &str) -> Result<Vec<LogEntry>, AppError> {
+    let mut entries = Vec::new();
+    if !Path::new(file_path).exists() {
+        return Ok(entries);
+    }
+    let reader = BufReader::new(File::open(file_path)?);
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<LogEntry>(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => eprintln!(
+                "Warning: Skipping corrupted JSONL record on line {}: {}",
+                number + 1,
+                e
+            ),
+        }
+    }
+    Ok(entries)
+}
+
+pub fn line(entry: &LogEntry) -> Result<String, AppError> {
+    Ok(format!("{}\n", serde_json::to_string(entry)?))
+}
+
+fn append(file_path: &str, entry: &LogEntry) -> Result<(), AppError> {
+    constraints::validate(std::slice::from_ref(entry))?;
+    if let Some(parent) = Path::new(file_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    // One write per line, so a crash can cut off at most the entry being added
+    file.write_all(line(entry)?.as_bytes())?;
+    file.sync_data()?;
+    Ok(())
+}
+
+fn write_entries(file_path: &str, entries: &[LogEntry]) -> Result<(), AppError> {
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&line(entry)?);
+    }
+    store::replace_file(file_path, text.as_bytes())
+}
+
+pub struct JsonlStore {
+    path: String,
+}
+
+impl JsonlStore {
+    pub fn new(path: &str) -> JsonlStore {
+        JsonlStore {
+            path: path.to_string(),
+        }
+    }
+}
+
+impl MetricStore for JsonlStore {
+    fn append(&self, entry: &LogEntry) -> Result<(), AppError> {
+        append(&self.path, entry)
+    }
+
+    fn all_entries(&self) -> Result<Vec<LogEntry>, AppError> {
+        read_entries(&self.path)
+    }
+
+    fn write_all(&self, entries: &[LogEntry]) -> Result<(), AppError> {
+        write_entries(&self.path, entries)
+    }
+}
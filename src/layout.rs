@@ -0,0 +1,79 @@
+// --- Terminal-aware output ---
+// Rules and table rows are cut to the terminal width so narrow screens (a phone over
+// SSH) don't wrap them into noise. `--wide` turns that off; piped output is never
+// cut and carries no colour codes, and neither does anything with `--no-color`.
+use crate::windows;
+use colored::control;
+use console::Term;
+use std::sync::OnceLock;
+
+const RULE_WIDTH: usize = 40; // Banner width on terminals at least this wide
+
+static WIDTH: OnceLock<Option<usize>> = OnceLock::new();
+static COMPACT: OnceLock<bool> = OnceLock::new();
+static SIMPLE_THEME: OnceLock<bool> = OnceLock::new();
+
+// Decide once, at startup, how wide output may be and how prompts look
+pub fn install(wide: bool, compact: bool, simple_theme: bool, no_color: bool) {
+    let _ = COMPACT.set(compact);
+    let _ = SIMPLE_THEME.set(simple_theme);
+    let term = Term::stdout();
+    if no_color {
+        // Prompts are drawn by console, the rest by colored
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+        control::set_override(false);
+    } else if term.is_term() {
+        windows::enable_colors();
+    } else {
+        control::set_override(false);
+    }
+    let width = match term.size_checked() {
+        Some((_, columns)) if !wide && term.is_term() => Some(usize::from(columns)),
+        _ => None,
+    };
+    let _ = WIDTH.set(width);
+}
+
+// Short prompts and ASCII-only glyphs for small screens (`--compact`, on by default in Termux)
+pub fn compact() -> bool {
+    COMPACT.get().copied().unwrap_or(false)
+}
+
+// Prompts without colours or box drawing (compact mode, or `theme = "simple"` in the config)
+pub fn simple_prompts() -> bool {
+    compact() || SIMPLE_THEME.get().copied().unwrap_or(false)
+}
+
+fn width() -> Option<usize> {
+    WIDTH.get().copied().flatten()
+}
+
+// Horizontal rule for banners and dividers, e.g. rule('=')
+pub fn rule(ch: char) -> String {
+    ch.to_string()
+        .repeat(width().map_or(RULE_WIDTH, |w| w.min(RULE_WIDTH)))
+}
+
+// One line cut to the terminal width (colour codes don't count towards it)
+pub fn fit(line: &str) -> String {
+    match width() {
+        Some(w) if console::measure_text_width(line) > w => {
+            let tail = if compact() { "~" } else { "…" };
+            console::truncate_str(line, w, tail).into_owned()
+        }
+        _ => line.to_string(),
+    }
+}
+
+// Print a table row
+pub fn row(line: &str) {
+    println!("{}", fit(line));
+}
+
+// Print a multi-line block (a text report) row by row
+pub fn print_block(text: &str) {
+    for line in text.lines() {
+        row(line);
+    }
+}
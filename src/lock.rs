@@ -0,0 +1,41 @@
+use crate::AppError;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::Path;
+
+// --- Advisory lock held while a data file is written ---
+// A cron `quick` and a manual `log` can otherwise interleave rows or rewrite each
+// other's changes away. The lock lives in its own file (daily_metrics.csv.lock), since
+// rewrites rename a new data file into place and a lock on the old one would be lost.
+// A rewrite holds it from reading the entries to writing them back (MetricStore::modify).
+// It's released when the guard is dropped.
+pub struct WriteLock {
+    _file: File,
+}
+
+pub fn lock_path(data_file: &str) -> String {
+    format!("{}.lock", data_file)
+}
+
+// Waits for another process that holds the lock, saying so if it has to
+pub fn write_lock(data_file: &str) -> Result<WriteLock, AppError> {
+    let path = lock_path(data_file);
+    if let Some(parent) = Path::new(&path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            eprintln!("Waiting for another process writing {}...", data_file);
+            file.lock()?;
+        }
+        Err(TryLockError::Error(e)) => return Err(e.into()),
+    }
+    Ok(WriteLock { _file: file })
+}
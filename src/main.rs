@@ -1,32 +1,249 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Local, NaiveDate, Timelike, Utc};
+use clap::{Parser, ValueEnum};
+use cli::{
+    BaselineAction, BundleAction, ChallengeAction, Cli, Command, ExportFormat, LogArgs,
+    OutputFormat, ProfileAction, TrashAction,
+};
 use colored::*; // Import colored text features
-use csv::{ReaderBuilder, StringRecord, WriterBuilder};
-use dialoguer::{theme::ColorfulTheme, Confirm, Input};
+use config::{expand_home, profile_config_file, user_config_file, Config, CONFIG_FILE};
+use csv::{StringRecord, WriterBuilder};
+use daily::{daily_points, DailyPoint};
+use defaults::LogDefaults;
+use dialoguer::theme::{ColorfulTheme, SimpleTheme, Theme};
+use dialoguer::{Confirm, Input, Select};
 use lazy_static::lazy_static;
+use remote::Remote;
+use schema::{canonical_metric, column_name, metric_info};
+use sections::Question;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
-use std::fs::{File, OpenOptions};
-use std::path::Path; // To initialize theme easily
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use units::DisplayUnits;
+
+mod analysis;
+mod anomalies;
+mod audit;
+mod backup;
+mod baseline;
+mod bundles;
+mod calendar;
+mod cancel;
+mod challenges;
+mod cli;
+mod clipboard;
+mod config;
+mod constraints;
+mod custom;
+mod daemon;
+mod daily;
+mod defaults;
+mod edit;
+mod export;
+mod features;
+#[cfg(feature = "test-support")]
+mod fuzz;
+mod generate;
+mod goals;
+mod import;
+mod init;
+mod insights;
+mod jsonl;
+mod layout;
+mod lock;
+mod migrate;
+mod milestones;
+mod msgpack;
+mod otlp;
+mod phases;
+mod profiles;
+mod push;
+mod quick;
+mod remarks;
+mod remote;
+mod replay;
+mod report;
+mod scales;
+mod schema;
+mod sections;
+mod stats;
+mod store;
+mod summary;
+mod templates;
+mod termux;
+mod today;
+mod tour;
+mod trash;
+mod units;
+mod view;
+mod windows;
+mod wizard;
 
 const DATA_FILE: &str = "daily_metrics.csv";
+const USERS_DIR: &str = "users"; // Per-user subdirectories live here on shared machines
+const DATA_FILE_ENV: &str = "DAILY_METRICS_FILE"; // Overrides the data file for every command
+const ENV_PREFIX: &str = "DML_"; // Every setting can be overridden with DML_<SETTING>
 const GOAL_DAYS: i64 = 30;
 
+// Metric columns that can be analysed by name (sleep values are floats, the rest 1-10 ratings)
+const METRIC_NAMES: [&str; 9] = [
+    "sleep_hours",
+    "sleep_quality",
+    "sleepiness",
+    "zonkedness",
+    "mid_vibes",
+    "energy",
+    "strength",
+    "focus",
+    "intelligence",
+];
+
+// --- Why an entry was logged (rows from before this column have none) ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum EntryKind {
+    FirstOfDay, // The day's first log: sleep is asked here
+    FollowUp,   // Another reading later the same day
+    Backfill,   // Added after the fact, e.g. imported daily values
+    Correction, // Replaced the day's previous last entry
+}
+
 // --- Define the structure for our log entry ---
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct LogEntry {
     timestamp: String, // Store as ISO 8601 string for simplicity in CSV
     day_count: i64,
     sleep_hours: Option<f32>, // Optional because it's asked only once a day
     sleep_quality: Option<f32>, // Optional because it's asked only once a day
-    sleepiness: u8,
-    zonkedness: u8,
-    mid_vibes: u8,
-    energy: u8,
-    strength: u8,
-    focus: u8,
-    intelligence: u8,
+    sleepiness: Option<u8>,   // Ratings are optional so imported rows can be partial
+    zonkedness: Option<u8>,
+    mid_vibes: Option<u8>,
+    energy: Option<u8>,
+    strength: Option<u8>,
+    focus: Option<u8>,
+    intelligence: Option<u8>,
     workout_today: bool,
+    #[serde(default)] // Shared exports may have remarks redacted
     remarks: String,
+    // Session metadata (`session_meta = true`): older files don't have these columns
+    #[serde(default)]
+    session_seconds: Option<u32>, // How long the questionnaire took
+    #[serde(default)]
+    session_edits: Option<u32>, // Answers re-entered after being rejected
+    // How the entry was made: "interactive", "defaults" (log --yes), "import:<source>",
+    // "generate"; later changes are joined with '+', e.g. "interactive+import:bearable"
+    #[serde(default)]
+    provenance: String,
+    #[serde(default)]
+    entry_kind: Option<EntryKind>,
+    // `[custom.<name>]` answers by name; CSV files keep them in columns after the others
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    custom: BTreeMap<String, String>,
+}
+
+impl LogEntry {
+    // Entry with no answers yet (imports fill in what they know; day_count 0 means "not numbered")
+    fn empty_at(timestamp: DateTime<Utc>) -> LogEntry {
+        LogEntry {
+            timestamp: timestamp.to_rfc3339(),
+            day_count: 0,
+            sleep_hours: None,
+            sleep_quality: None,
+            sleepiness: None,
+            zonkedness: None,
+            mid_vibes: None,
+            energy: None,
+            strength: None,
+            focus: None,
+            intelligence: None,
+            workout_today: false,
+            remarks: String::new(),
+            session_seconds: None,
+            session_edits: None,
+            provenance: String::new(),
+            entry_kind: None,
+            custom: BTreeMap::new(),
+        }
+    }
+
+    // Note another way values reached this entry (each way is listed once)
+    fn add_provenance(&mut self, how: &str) {
+        if self.provenance.is_empty() {
+            self.provenance = how.to_string();
+        } else if !self.provenance.split('+').any(|p| p == how) {
+            self.provenance = format!("{}+{}", self.provenance, how);
+        }
+    }
+
+    // True when any part of the provenance starts with `kind`, e.g. "import"
+    fn has_provenance(&self, kind: &str) -> bool {
+        self.provenance.split('+').any(|p| p.starts_with(kind))
+    }
+
+    // Look up a numeric metric by its column name
+    fn metric(&self, name: &str) -> Option<f64> {
+        match name {
+            "sleep_hours" => self.sleep_hours.map(f64::from),
+            "sleep_quality" => self.sleep_quality.map(f64::from),
+            "sleepiness" => self.sleepiness.map(f64::from),
+            "zonkedness" => self.zonkedness.map(f64::from),
+            "mid_vibes" => self.mid_vibes.map(f64::from),
+            "energy" => self.energy.map(f64::from),
+            "strength" => self.strength.map(f64::from),
+            "focus" => self.focus.map(f64::from),
+            "intelligence" => self.intelligence.map(f64::from),
+            _ => None,
+        }
+    }
+
+    // Set a numeric metric by its column name, returning false for unknown names
+    fn set_metric(&mut self, name: &str, value: f64) -> bool {
+        let rating = value.round().clamp(0.0, u8::MAX as f64) as u8;
+        let float = ((value * 100.0).round() / 100.0) as f32; // Keep converted values readable
+        match name {
+            "sleep_hours" => self.sleep_hours = Some(float),
+            "sleep_quality" => self.sleep_quality = Some(float),
+            "sleepiness" => self.sleepiness = Some(rating),
+            "zonkedness" => self.zonkedness = Some(rating),
+            "mid_vibes" => self.mid_vibes = Some(rating),
+            "energy" => self.energy = Some(rating),
+            "strength" => self.strength = Some(rating),
+            "focus" => self.focus = Some(rating),
+            "intelligence" => self.intelligence = Some(rating),
+            _ => return false,
+        }
+        true
+    }
+
+    // Empty a numeric metric, returning false for unknown names
+    fn clear_metric(&mut self, name: &str) -> bool {
+        match name {
+            "sleep_hours" => self.sleep_hours = None,
+            "sleep_quality" => self.sleep_quality = None,
+            "sleepiness" => self.sleepiness = None,
+            "zonkedness" => self.zonkedness = None,
+            "mid_vibes" => self.mid_vibes = None,
+            "energy" => self.energy = None,
+            "strength" => self.strength = None,
+            "focus" => self.focus = None,
+            "intelligence" => self.intelligence = None,
+            _ => return false,
+        }
+        true
+    }
+
+    // Parse the stored RFC 3339 timestamp
+    fn parsed_timestamp(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+// Valid range for each metric, from the column constraints
+fn metric_range(name: &str) -> (f64, f64) {
+    constraints::range(name).unwrap_or((1.0, 10.0)) // Unknown names are treated as ratings
 }
 
 // --- Define a custom error type ---
@@ -40,157 +257,1091 @@ enum AppError {
     DateParseError(#[from] chrono::ParseError),
     #[error("Dialog interaction cancelled")]
     DialogCancelled, // New variant for cancellation
+    #[error("Import error: {0}")]
+    ImportError(String),
+    #[error("Template error: {0}")]
+    TemplateError(String),
+    #[error("Config error: {0}")]
+    ConfigError(String),
+    #[cfg(feature = "clipboard")]
+    #[error("Clipboard error: {0}")]
+    ClipboardError(#[from] arboard::Error),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Number parsing error: {0}")]
+    NumberParseError(#[from] std::num::ParseFloatError),
+    #[error("Formatting error: {0}")]
+    FormatError(#[from] std::fmt::Error),
+    #[cfg(feature = "qr")]
+    #[error("QR code error: {0}")]
+    QrError(#[from] qrcode::types::QrError),
+    #[error("Validation failed: {0}")]
+    ValidationError(constraints::Violations),
+    #[error("Today is already logged")]
+    AlreadyLogged,
+    #[error("'{0}' modifies data and is disabled in read-only mode (try --dry-run)")]
+    ReadOnly(String),
+    #[error("Remote error: {0}")]
+    RemoteError(String),
+    #[error("{0}")]
+    UsageError(String),
 }
 
-// --- Helper struct to store info from existing CSV ---
-struct CsvInfo {
-    first_entry_date: Option<NaiveDate>,
-    last_entry_date: Option<NaiveDate>,
-    workout_logged_today: bool,
-    last_sleep_hours: Option<f32>,
-    last_sleep_quality: Option<f32>,
+impl AppError {
+    // Stable identifiers for `--json`: wrappers match on these, so never rename one
+    fn code(&self) -> &'static str {
+        match self {
+            AppError::CsvError(_) => "csv_error",
+            AppError::IoError(_) => "io_error",
+            AppError::DateParseError(_) => "date_parse_error",
+            AppError::DialogCancelled => "cancelled",
+            AppError::ImportError(_) => "import_failed",
+            AppError::TemplateError(_) => "template_error",
+            AppError::ConfigError(_) => "config_error",
+            #[cfg(feature = "clipboard")]
+            AppError::ClipboardError(_) => "clipboard_error",
+            AppError::JsonError(_) => "json_error",
+            AppError::NumberParseError(_) => "number_parse_error",
+            AppError::FormatError(_) => "format_error",
+            #[cfg(feature = "qr")]
+            AppError::QrError(_) => "qr_error",
+            AppError::ValidationError(_) => "validation_failed",
+            AppError::AlreadyLogged => "already_logged",
+            AppError::ReadOnly(_) => "read_only",
+            AppError::RemoteError(_) => "remote_error",
+            AppError::UsageError(_) => "usage",
+        }
+    }
+
+    // 2 for bad invocations, 3 for `log --if-missing` on a logged day, 1 otherwise
+    fn exit_code(&self) -> i32 {
+        match self {
+            AppError::ReadOnly(_) | AppError::UsageError(_) => 2,
+            AppError::AlreadyLogged => 3,
+            _ => 1,
+        }
+    }
+
+    // Print the error for people, or as one JSON object on stderr with `--json`
+    fn report(&self, json: bool) {
+        if !json {
+            if !matches!(self, AppError::AlreadyLogged) {
+                eprintln!("{} {}", "Error:".bold().red(), self);
+            }
+            return;
+        }
+        let mut error = serde_json::json!({
+            "code": self.code(),
+            "message": self.to_string(),
+            "exit_code": self.exit_code(),
+        });
+        if let AppError::ValidationError(violations) = self {
+            error["violations"] = serde_json::json!(violations.0);
+        }
+        eprintln!("{}", serde_json::json!({ "error": error }));
+    }
 }
 
-// --- Initialize the theme once ---
+// --- Initialize the theme once (plain ASCII prompts in compact mode or `theme = "simple"`) ---
 lazy_static! {
-    static ref THEME: ColorfulTheme = ColorfulTheme::default();
+    static ref THEME: Box<dyn Theme + Send + Sync> = if layout::simple_prompts() {
+        Box::new(SimpleTheme)
+    } else {
+        Box::new(ColorfulTheme::default())
+    };
 }
 
 // --- Input Validation ---
 
+// --- Global options shared by every command ---
+struct GlobalOptions {
+    user: Option<String>,          // Person on a shared machine, e.g. `--user alex`
+    profile: Option<String>,       // Named profile with its own data file, e.g. `--profile work`
+    dry_run: bool,                 // Show what writing commands would change without touching files
+    read_only: bool,               // Refuse every command that modifies the data (`--read-only`)
+    wide: bool,                    // Don't cut tables to the terminal width (`--wide`)
+    compact: bool,                 // Short numeric prompts, no box drawing (`--compact`, Termux)
+    no_color: bool,                // Plain text even on a terminal (`--no-color`)
+    data_file: Option<String>,     // Explicit data file from `--data-file` or DAILY_METRICS_FILE
+    config_file: Option<PathBuf>,  // Explicit config file from `--config`
+    remote: Option<Remote>,        // Data file on another machine (`--remote user@host:path`)
+    record: Option<String>,        // Save the answers to every prompt here (`--record`)
+    replay: Option<String>,        // Answer the prompts from this recording (`--replay`)
+    exclude_sources: Vec<String>, // Provenance kinds analyses leave out (`--exclude-source import`)
+    phase: Option<phases::Which>, // The goal phase analyses look at (`--phase 2` or `current`)
+    format: OutputFormat,         // `--format json|csv` for commands that print data
+    json: bool,                   // `--json`: errors as JSON (and `schema` as JSON)
+    goal_days: i64,               // Length of the logging goal shown in the banner
+    goal_start: Option<NaiveDate>, // Start of the current goal phase (first entry when unset)
+    config: Config,               // Loaded config.toml (defaults when the file is missing)
+}
+
+impl GlobalOptions {
+    // Pull global flags out of the argument list, returning the remaining command arguments
+    fn parse(args: Vec<String>) -> Result<(GlobalOptions, Vec<String>), String> {
+        let mut user = None;
+        let mut profile = None;
+        let mut dry_run = false;
+        let mut read_only = false;
+        let mut wide = false;
+        let mut compact = false;
+        let mut no_color = false;
+        let mut portable = false;
+        let mut json = false;
+        let mut data_file = None;
+        let mut config_file = None;
+        let mut remote = None;
+        let mut goal_days = None;
+        let mut record = None;
+        let mut replay = None;
+        let mut exclude_sources = Vec::new();
+        let mut phase = None;
+        let mut format = None;
+        let mut rest = Vec::new();
+        let mut iter = args.into_iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--user" {
+                user = Some(iter.next().ok_or("--user requires a name")?);
+            } else if let Some(name) = arg.strip_prefix("--user=") {
+                user = Some(name.to_string());
+            } else if arg == "--profile" {
+                profile = Some(iter.next().ok_or("--profile requires a name")?);
+            } else if let Some(name) = arg.strip_prefix("--profile=") {
+                profile = Some(name.to_string());
+            } else if arg == "--data-file" {
+                data_file = Some(iter.next().ok_or("--data-file requires a path")?);
+            } else if let Some(path) = arg.strip_prefix("--data-file=") {
+                data_file = Some(path.to_string());
+            } else if arg == "--config" {
+                config_file = Some(iter.next().ok_or("--config requires a path")?);
+            } else if let Some(path) = arg.strip_prefix("--config=") {
+                config_file = Some(path.to_string());
+            } else if arg == "--remote" {
+                remote = Some(iter.next().ok_or("--remote requires user@host[:path]")?);
+            } else if let Some(spec) = arg.strip_prefix("--remote=") {
+                remote = Some(spec.to_string());
+            } else if arg == "--goal-days" {
+                goal_days = Some(iter.next().ok_or("--goal-days requires a number")?);
+            } else if let Some(days) = arg.strip_prefix("--goal-days=") {
+                goal_days = Some(days.to_string());
+            } else if arg == "--record" {
+                record = Some(iter.next().ok_or("--record requires a path")?);
+            } else if let Some(path) = arg.strip_prefix("--record=") {
+                record = Some(path.to_string());
+            } else if arg == "--replay" {
+                replay = Some(iter.next().ok_or("--replay requires a path")?);
+            } else if let Some(path) = arg.strip_prefix("--replay=") {
+                replay = Some(path.to_string());
+            } else if arg == "--exclude-source" {
+                exclude_sources.push(iter.next().ok_or("--exclude-source requires a kind")?);
+            } else if let Some(kind) = arg.strip_prefix("--exclude-source=") {
+                exclude_sources.push(kind.to_string());
+            } else if arg == "--phase" {
+                phase = Some(iter.next().ok_or("--phase requires a number or current")?);
+            } else if let Some(which) = arg.strip_prefix("--phase=") {
+                phase = Some(which.to_string());
+            } else if arg == "--format" {
+                format = Some(
+                    iter.next()
+                        .ok_or("--format requires table, json, csv or parquet")?,
+                );
+            } else if let Some(name) = arg.strip_prefix("--format=") {
+                format = Some(name.to_string());
+            } else if arg == "--dry-run" {
+                dry_run = true;
+            } else if arg == "--read-only" {
+                read_only = true;
+            } else if arg == "--wide" {
+                wide = true;
+            } else if arg == "--compact" {
+                compact = true;
+            } else if arg == "--no-color" {
+                no_color = true;
+            } else if arg == "--portable" {
+                portable = true;
+            } else if arg == "--json" {
+                // Errors as JSON are reported by main(), which looks for the flag itself
+                json = true;
+            } else {
+                rest.push(arg);
+            }
+        }
+
+        // Flags win over environment variables, which win over the built-in defaults
+        config::install_portable(portable || env_flag("PORTABLE")?)?;
+        user = user.or_else(|| env_setting("USER"));
+        profile = profile.or_else(|| env_setting("PROFILE"));
+        record = record.or_else(|| env_setting("RECORD"));
+        replay = replay.or_else(|| env_setting("REPLAY"));
+        data_file = data_file.or_else(|| env_setting("DATA_FILE")).or_else(|| {
+            std::env::var(DATA_FILE_ENV)
+                .ok()
+                .filter(|p| !p.trim().is_empty())
+        });
+        if exclude_sources.is_empty() {
+            // A comma-separated list, e.g. DML_EXCLUDE_SOURCES=import,generate
+            exclude_sources = env_setting("EXCLUDE_SOURCES")
+                .map(|list| list.split(',').map(|k| k.trim().to_string()).collect())
+                .unwrap_or_default();
+        }
+        let phase = phase
+            .or_else(|| env_setting("PHASE"))
+            .map(|which| phases::Which::parse(&which))
+            .transpose()?;
+        let format = match format.or_else(|| env_setting("FORMAT")) {
+            Some(name) => OutputFormat::from_str(name.trim(), true).map_err(|_| {
+                format!("Unknown format '{}': use table, json, csv or parquet", name)
+            })?,
+            None => OutputFormat::Table,
+        };
+        dry_run = dry_run || env_flag("DRY_RUN")?;
+        read_only = read_only || env_flag("READ_ONLY")?;
+        wide = wide || env_flag("WIDE")?;
+        // DML_COMPACT=false can turn off compact mode that the config or Termux turned on
+        let compact_env = env_bool("COMPACT")?;
+        no_color = no_color || env_flag("NO_COLOR")?;
+        json = json || env_flag("JSON")?;
+        let config_file = config_file
+            .or_else(|| env_setting("CONFIG"))
+            .map(|path| expand_home(&path));
+        let goal_days = match (goal_days, env_setting("GOAL_DAYS")) {
+            (Some(value), _) => match value.parse::<i64>() {
+                Ok(days) if days > 0 => Some(days),
+                _ => return Err("--goal-days must be a positive number".to_string()),
+            },
+            (None, Some(value)) => match value.parse::<i64>() {
+                Ok(days) if days > 0 => Some(days),
+                _ => return Err(format!("{}GOAL_DAYS must be a positive number", ENV_PREFIX)),
+            },
+            (None, None) => None,
+        };
+
+        if let Some(name) = &user {
+            validate_name("user", name)?;
+        }
+        if let Some(name) = &profile {
+            validate_name("profile", name)?;
+        }
+
+        let mut options = GlobalOptions {
+            user,
+            profile,
+            dry_run,
+            read_only,
+            wide,
+            compact,
+            no_color,
+            data_file,
+            config_file,
+            remote: None,
+            record,
+            replay,
+            exclude_sources,
+            phase,
+            format,
+            json,
+            goal_days: GOAL_DAYS,
+            goal_start: None,
+            config: Config::default(),
+        };
+
+        // Config file values sit between the built-in defaults and env/flags. A profile
+        // with its own config file reads its settings from the top level of that file.
+        let mut config = Config::load(&options.config_path()).map_err(|e| e.to_string())?;
+        config.apply_env()?;
+        let own_config = options.has_own_config();
+        let profile_config = options
+            .profile
+            .as_ref()
+            .filter(|_| !own_config)
+            .and_then(|name| config.profile.get(name));
+        options.read_only = options.read_only
+            || profile_config.and_then(|p| p.read_only).unwrap_or(false)
+            || config.read_only.unwrap_or(false);
+        // Termux sessions default to compact unless the config (or DML_COMPACT) says otherwise
+        options.compact = options.compact
+            || compact_env
+                .or(config.compact)
+                .unwrap_or_else(termux::detected);
+        options.goal_days = goal_days
+            .or(profile_config.and_then(|p| p.goal_days))
+            .or(config.goal_days)
+            .unwrap_or(GOAL_DAYS);
+        // Already validated as YYYY-MM-DD when the config was loaded
+        options.goal_start = profile_config
+            .and_then(|p| p.goal_start.as_deref())
+            .or(config.goal_start.as_deref())
+            .and_then(|start| NaiveDate::parse_from_str(start, "%Y-%m-%d").ok());
+        // A remote data file is used through a local copy, which takes the data file's place
+        if let Some(spec) = remote
+            .or_else(|| env_setting("REMOTE"))
+            .or(config.remote.clone())
+        {
+            if options.data_file.is_some() {
+                return Err(
+                    "--remote replaces the data file; give its path as user@host:path".to_string(),
+                );
+            }
+            let default_file = options.default_data_file(options.profile.as_deref());
+            let default_file = Path::new(&default_file)
+                .file_name()
+                .map_or(DATA_FILE.into(), |n| n.to_string_lossy());
+            let remote = Remote::parse(&spec, &default_file)?;
+            options.data_file = Some(
+                remote
+                    .cache_file(&options.data_dir())
+                    .to_string_lossy()
+                    .into_owned(),
+            );
+            options.remote = Some(remote);
+        }
+        if options.data_file.is_none() {
+            options.data_file =
+                options.configured_data_file(&config, options.profile.as_deref(), own_config);
+        }
+
+        // chrono's Local follows TZ, so every local time shown uses the configured zone
+        if let Some(zone) = &config.timezone {
+            std::env::set_var("TZ", zone);
+        }
+        options.config = config;
+        Ok((options, rest))
+    }
+
+    // Stop a writing command early in read-only mode (dry runs are still allowed)
+    fn require_writable(&self, command: &str) -> Result<(), AppError> {
+        if self.read_only && !self.dry_run {
+            return Err(AppError::ReadOnly(command.to_string()));
+        }
+        Ok(())
+    }
+
+    // Entries for analyses: everything except the excluded provenance kinds, and only the
+    // selected phase's days with --phase
+    fn analysis_entries(&self) -> Result<Vec<LogEntry>, AppError> {
+        let mut entries = read_entries(&self.data_file())?;
+        entries.retain(|e| !self.exclude_sources.iter().any(|k| e.has_provenance(k)));
+        if let Some(days) = phases::selected_days(self)? {
+            entries.retain(|e| {
+                e.parsed_timestamp()
+                    .is_some_and(|ts| days.contains(&ts.date_naive()))
+            });
+        }
+        Ok(entries)
+    }
+
+    // Directory holding this user's files (the current directory when no user is selected)
+    fn data_dir(&self) -> PathBuf {
+        let root = root_dir();
+        match &self.user {
+            Some(name) => root.join(USERS_DIR).join(name),
+            None => root,
+        }
+    }
+
+    // Data file for the selected user and profile (the defaults keep the original file name)
+    fn data_file(&self) -> String {
+        match &self.data_file {
+            Some(path) => path.clone(),
+            None => self.default_data_file(self.profile.as_deref()),
+        }
+    }
+
+    fn default_data_file(&self, profile: Option<&str>) -> String {
+        let file_name = match profile {
+            Some(name) => format!("daily_metrics_{}.csv", name),
+            None => DATA_FILE.to_string(),
+        };
+        self.data_dir()
+            .join(file_name)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    // The data file a config names for a profile (or for no profile), if any. Relative
+    // paths are resolved against the directory the config file is in.
+    fn configured_data_file(
+        &self,
+        config: &Config,
+        profile: Option<&str>,
+        own_config: bool,
+    ) -> Option<String> {
+        let configured = match profile {
+            Some(name) if !own_config => config.profile.get(name)?.data_file.clone(),
+            _ => config.data_file.clone(),
+        };
+        let config_dir = self.config_path().parent().map(Path::to_path_buf);
+        configured.map(|path| {
+            config_dir
+                .unwrap_or_default()
+                .join(expand_home(&path))
+                .to_string_lossy()
+                .into_owned()
+        })
+    }
+
+    // A profile's own config file, e.g. config_partner.toml, used in place of config.toml
+    fn own_config_file(&self, profile: &str) -> Option<PathBuf> {
+        let path = self.data_dir().join(profile_config_file(profile));
+        path.exists().then_some(path)
+    }
+
+    // A --config file is read like config.toml, [profile.<name>] sections included
+    fn has_own_config(&self) -> bool {
+        self.config_file.is_none()
+            && self
+                .profile
+                .as_deref()
+                .is_some_and(|name| self.own_config_file(name).is_some())
+    }
+
+    // The config file settings are read from and saved to: --config, the profile's own
+    // file, config.toml beside the data, or else ~/.config/daily-metric-logger/config.toml
+    fn config_path(&self) -> PathBuf {
+        if let Some(path) = &self.config_file {
+            return path.clone();
+        }
+        if let Some(path) = self
+            .profile
+            .as_deref()
+            .and_then(|name| self.own_config_file(name))
+        {
+            return path;
+        }
+        // A new config goes to the platform config folder when the data is in the platform
+        // data folder (Windows keeps both in %APPDATA%)
+        let local = self.data_dir().join(CONFIG_FILE);
+        let platform = !data_here() && !windows::detected();
+        match user_config_file().filter(|_| config::portable_dir().is_none()) {
+            Some(user) if !local.exists() && self.user.is_none() && (platform || user.exists()) => {
+                user
+            }
+            _ => local,
+        }
+    }
+}
+
+// Where the data lives: %APPDATA% on Windows and the platform data folder elsewhere, so
+// running from another directory finds the same log. A current directory that already
+// holds the files (older setups kept everything next to where they ran it) keeps them,
+// and portable mode keeps them next to the executable.
+fn root_dir() -> PathBuf {
+    if let Some(dir) = config::portable_dir() {
+        return dir.to_path_buf();
+    }
+    if data_here() {
+        return PathBuf::new();
+    }
+    windows::app_data_dir()
+        .or_else(config::platform_data_dir)
+        .unwrap_or_default()
+}
+
+// Only the data itself counts: a config.toml here may well belong to another program
+fn data_here() -> bool {
+    [DATA_FILE, USERS_DIR]
+        .iter()
+        .any(|name| Path::new(name).exists())
+}
+
+// Read a DML_<KEY> environment variable, treating empty values as unset
+fn env_setting(key: &str) -> Option<String> {
+    std::env::var(format!("{}{}", ENV_PREFIX, key))
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+// Read a boolean DML_<KEY> environment variable (1/0, true/false, yes/no), None when unset
+fn env_bool(key: &str) -> Result<Option<bool>, String> {
+    match env_setting(key).map(|v| v.trim().to_ascii_lowercase()) {
+        None => Ok(None),
+        Some(v) if matches!(v.as_str(), "1" | "true" | "yes") => Ok(Some(true)),
+        Some(v) if matches!(v.as_str(), "0" | "false" | "no") => Ok(Some(false)),
+        Some(v) => Err(format!(
+            "{}{} must be true or false, got '{}'",
+            ENV_PREFIX, key, v
+        )),
+    }
+}
+
+fn env_flag(key: &str) -> Result<bool, String> {
+    Ok(env_bool(key)?.unwrap_or(false))
+}
+
+// User and profile names end up in paths, so keep them to a safe character set
+fn validate_name(kind: &str, name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid {} name '{}': use letters, digits, '-' or '_'",
+            kind, name
+        ))
+    }
+}
+
+// --- Entry point: dispatch on the first argument ---
+fn main() {
+    if clipboard::serve_helper() {
+        return;
+    }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // The options may fail to parse, so look for the flag directly
+    let json = args.iter().any(|a| a == "--json") || env_flag("JSON").unwrap_or(false);
+    if let Err(e) = run(args) {
+        e.report(json);
+        std::process::exit(e.exit_code());
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), AppError> {
+    let (options, args) = GlobalOptions::parse(args).map_err(AppError::UsageError)?;
+    schema::install_column_names(&options.config);
+    daily::install_aggregates(&options.config);
+    scales::install(&options.data_file());
+    constraints::install_scales(&options.config);
+    custom::install(&options.config);
+    store::install(
+        &options.data_file(),
+        options.config.yearly_files.unwrap_or(false),
+    );
+    backup::install(options.config.backup.as_ref());
+    if let Some(remote) = &options.remote {
+        remote::install(remote.clone(), &options.data_file());
+    }
+    scales::check_recorded(&options.data_file());
+    layout::install(
+        options.wide,
+        options.compact,
+        options.config.theme.as_deref() == Some("simple"),
+        options.no_color,
+    );
+    replay::install(options.record.as_deref(), options.replay.as_deref(), &args)?;
+    let cli = match Cli::try_parse_from(
+        std::iter::once(env!("CARGO_PKG_NAME").to_string()).chain(args),
+    ) {
+        Ok(cli) => cli,
+        // --help and --version print to stdout and succeed
+        Err(e) if !e.use_stderr() => e.exit(),
+        Err(e) => {
+            let message = e.render().to_string();
+            let message = message.trim_end();
+            return Err(AppError::UsageError(
+                message
+                    .strip_prefix("error: ")
+                    .unwrap_or(message)
+                    .to_string(),
+            ));
+        }
+    };
+    let command = cli.command.unwrap_or(Command::Log(LogArgs::default()));
+    let writes_data = match &command {
+        Command::Log(_) => Some("log"),
+        Command::Import(_) => Some("import"),
+        Command::Delete(_) => Some("delete"),
+        Command::Metric(_) => Some("metric"),
+        Command::Edit(_) => Some("edit"),
+        Command::Quick(_) => Some("quick"),
+        Command::Review(_) => Some("review"),
+        Command::Annotate(a) if a.date.is_some() || a.remove.is_some() => Some("annotate"),
+        Command::Baseline(b) if !matches!(b.action, BaselineAction::Show) => Some("baseline"),
+        Command::Init => Some("init"),
+        Command::Migrate(_) => Some("migrate"),
+        Command::Profile(p) if matches!(p.action, ProfileAction::New) => Some("profile new"),
+        Command::Challenge(c) if matches!(c.action, ChallengeAction::Start) => {
+            Some("challenge start")
+        }
+        Command::Trash(t) if !matches!(t.action, TrashAction::List) => Some("trash"),
+        Command::Bundle(b) if matches!(b.action, BundleAction::Take) => Some("bundle take"),
+        _ => None,
+    };
+    if let Some(command) = writes_data {
+        options.require_writable(command)?;
+        // The platform data folder doesn't exist until the first write
+        let dir = options.data_dir();
+        if !options.dry_run && !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(dir)?;
+        }
+    }
+    // Commands print JSON or CSV once they support it; the rest refuse rather than ignore it
+    let structured = matches!(
+        command,
+        Command::View(_)
+            | Command::Stats
+            | Command::Export(_)
+            | Command::RemarksAnalysis(_)
+            | Command::Effect(_)
+            | Command::Model(_)
+            | Command::Schema
+            | Command::Features
+            | Command::Insights(_)
+            | Command::Challenge(_)
+    );
+    if options.format == OutputFormat::Parquet && !matches!(command, Command::Export(_)) {
+        return Err(AppError::UsageError(
+            "--format parquet only works with export".to_string(),
+        ));
+    }
+    if options.format != OutputFormat::Table && !structured {
+        return Err(AppError::UsageError(
+            "--format json/csv works with view, stats, export, remarks-analysis, effect, model, insights, challenge, schema and features"
+                .to_string(),
+        ));
+    }
+    match command {
+        Command::Log(args) => run_log(&options, &args),
+        Command::Stats => stats::run_stats(&options),
+        Command::View(args) => view::run_view(&options, &args),
+        Command::Export(args) => export::run_export(&options, &args),
+        Command::Edit(args) => edit::run_edit(&options, &args),
+        Command::Quick(args) => quick::run_quick(&options, &args),
+        Command::Review(args) => anomalies::run_review(&options, &args),
+        Command::Annotate(args) => milestones::run_annotate(&options, &args),
+        Command::Baseline(args) => baseline::run_baseline(&options, &args),
+        Command::Init => init::run_init(&options),
+        Command::ByHour { metric } => {
+            run_by_hour(&options, canonical_metric(&metric).unwrap_or(&metric))
+        }
+        Command::Intraday => run_intraday(&options),
+        Command::Compare { files } => run_compare(&options, &files),
+        Command::Report(args) => report::run_report(&options, &args),
+        Command::Calendar(args) => calendar::run_calendar(&options, &args),
+        Command::Today(args) => today::run_today(&options, &args),
+        Command::Import(args) => import::run_import(&options, &args),
+        Command::Daemon(args) => daemon::run_daemon(&options, &args),
+        Command::Describe(args) => schema::run_describe(&options, &args),
+        Command::Metric(args) => schema::run_metric(&options, &args),
+        Command::Schema => schema::run_schema(&options),
+        Command::Features => features::run_features(&options),
+        Command::Migrate(args) => migrate::run_migrate(&options, &args),
+        Command::Profile(args) => profiles::run_profile(&options, &args),
+        Command::Delete(args) => trash::run_delete(&options, &args),
+        Command::Trash(args) => trash::run_trash(&options, &args),
+        Command::Audit(args) => audit::run_audit(&options, &args),
+        Command::Bundle(args) => bundles::run_bundle(&options, &args),
+        Command::Generate(args) => generate::run_generate(&options, &args),
+        Command::Whatif(args) => analysis::run_whatif(&options, &args),
+        Command::Model(args) => analysis::run_model(&options, &args),
+        Command::Friction => analysis::run_friction(&options),
+        Command::RemarksAnalysis(args) => remarks::run_remarks_analysis(&options, &args),
+        Command::Effect(args) => analysis::run_effect(&options, &args),
+        Command::Correlate(args) => analysis::run_correlate(&options, &args),
+        Command::Recommend(args) => analysis::run_recommend(&options, &args),
+        Command::Remind(args) => termux::run_remind(&options, &args),
+        Command::Insights(args) => insights::run_insights(&options, &args),
+        Command::Challenge(args) => challenges::run_challenge(&options, &args),
+        Command::Otlp(args) => otlp::run_otlp(&options, &args),
+        Command::Tour => tour::run_tour(&options),
+        #[cfg(feature = "test-support")]
+        Command::Fuzz(args) => fuzz::run_fuzz(&options, &args),
+    }?;
+    replay::finish();
+    Ok(())
+}
+
 // --- Main Application Logic ---
-fn main() -> Result<(), Box<dyn Error>> {
-    println!("{}", "=".repeat(40).cyan());
-    println!("{}", " Daily Metrics Logger ".bold().cyan());
-    println!("{}", "=".repeat(40).cyan());
+fn run_log(options: &GlobalOptions, args: &LogArgs) -> Result<(), AppError> {
+    // `--defaults <file>` pre-fills the prompts; `--yes` skips them entirely;
+    // `--if-missing` does nothing (exit code 3) once today is logged, for reminder popups
+    let yes = args.yes;
+    let units = DisplayUnits::from_config(&options.config);
+    let mut defaults = match &args.defaults {
+        Some(path) => LogDefaults::load(Path::new(path), &units)?,
+        None => LogDefaults::default(),
+    };
 
-    let csv_info = read_csv_info(DATA_FILE)?;
+    let data_file = options.data_file();
+    let store = store::open(&data_file);
+    let store_info = store.info()?;
     let today = Utc::now().date_naive();
 
-    // Determine if it's the first entry of the day
-    let is_first_entry_today = match csv_info.last_entry_date {
-        Some(last_date) => last_date != today,
-        None => true, // No previous entries means this is the first
-    };
+    let logged_today = store_info.last_entry_date == Some(today);
+    if args.if_missing && logged_today {
+        return Err(AppError::AlreadyLogged);
+    }
+    if args.correction && !logged_today {
+        return Err(AppError::UsageError(
+            "Nothing logged today to correct".to_string(),
+        ));
+    }
+    // A second entry is never added by accident from a script
+    if logged_today && yes && !args.follow_up && !args.correction {
+        return Err(AppError::UsageError(
+            "Today is already logged: add --follow-up for another reading or --correction to replace the last one".to_string(),
+        ));
+    }
+
+    // An entry cancelled last time picks up where it stopped, unless preset answers were given
+    let mut resumed = false;
+    if args.defaults.is_none() && !yes {
+        if let Some(draft) = cancel::offer_draft(&data_file, &units)? {
+            defaults = draft;
+            resumed = true;
+        }
+    }
 
     // Determine the first ever entry date (or today if none)
-    let first_ever_date = csv_info.first_entry_date.unwrap_or(today);
+    let first_ever_date = store_info.first_entry_date.unwrap_or(today);
 
     // Calculate day count
     let day_count = (today - first_ever_date).num_days() + 1; // +1 because day 1 is the first day
 
-    println!("Current Date: {}", today.format("%Y-%m-%d"));
-    println!(
-        "Logging Day: {} / {} (Goal)",
-        day_count.to_string().yellow(),
-        GOAL_DAYS.to_string().green()
-    );
-    println!("{}", "-".repeat(40).cyan());
+    // The goal counts from the start of the current phase; day_count keeps the whole history
+    let phase_start = options.goal_start.unwrap_or(first_ever_date);
+    let goal_day = (today - phase_start).num_days() + 1;
+    let questions = phases::questions_for(options, phase_start)?;
+
+    // The banner is only a reminder, so unreadable data doesn't stop the entry
+    let challenge_lines = challenges::banner(options).unwrap_or_else(|e| {
+        eprintln!("Warning: Could not show challenge progress: {}", e);
+        Vec::new()
+    });
+    let compact = layout::compact();
+    if compact {
+        // One line instead of the boxed banner
+        let who: Vec<&str> = options
+            .user
+            .iter()
+            .chain(&options.profile)
+            .map(String::as_str)
+            .collect();
+        println!(
+            "{} day {}/{}{}",
+            today.format("%Y-%m-%d"),
+            goal_day.to_string().yellow(),
+            options.goal_days,
+            if who.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", who.join("/"))
+            }
+        );
+        for line in &challenge_lines {
+            println!("{}", line.dimmed());
+        }
+    } else {
+        println!("{}", layout::rule('=').cyan());
+        println!("{}", " Daily Metrics Logger ".bold().cyan());
+        if let Some(user) = &options.user {
+            println!(" User: {}", user.magenta());
+        }
+        if let Some(profile) = &options.profile {
+            println!(" Profile: {}", profile.magenta());
+        }
+        println!("{}", layout::rule('=').cyan());
+        println!("Current Date: {}", today.format("%Y-%m-%d"));
+        println!(
+            "Logging Day: {} / {} (Goal)",
+            goal_day.to_string().yellow(),
+            options.goal_days.to_string().green()
+        );
+        for line in &challenge_lines {
+            println!("{}", line);
+        }
+        println!("{}", layout::rule('-').cyan());
+    }
+
+    // A day that already has an entry gets a follow-up or a correction of its last entry
+    let kind = if !logged_today {
+        EntryKind::FirstOfDay
+    } else if args.correction {
+        EntryKind::Correction
+    } else if args.follow_up || yes {
+        EntryKind::FollowUp
+    } else {
+        ask_entry_kind()?
+    };
+    let today_entries = store.read_range(today, today)?;
+    let replaced = (kind == EntryKind::Correction)
+        .then(|| today_entries.last().cloned())
+        .flatten();
+    if let Some(old) = &replaced {
+        // The corrected answers start from the entry being replaced
+        defaults.fill_from(old);
+        defaults.remarks.get_or_insert_with(|| old.remarks.clone());
+    }
+    // Correcting the day's only entry asks for the night's sleep again
+    let is_first_entry_today =
+        kind == EntryKind::FirstOfDay || (replaced.is_some() && today_entries.len() == 1);
 
     // --- Collect Data ---
-    let sleep_hours: Option<f32>;
-    let sleep_quality: Option<f32>;
-    if is_first_entry_today {
+    let record_session = options.config.session_meta.unwrap_or(false) && !yes;
+    let started = Instant::now();
+    let edits = Cell::new(0); // Bumped by every rejected answer
+    let mut session = cancel::PromptSession::start(&data_file, &units);
+    if resumed {
+        session.answered = defaults.clone();
+    }
+    if yes {
+        // Whatever the defaults file leaves out comes from the previous entry
+        if let Some(previous) = store.last_entry()? {
+            defaults.fill_from(&previous);
+        }
+        let sleep_metrics = if is_first_entry_today {
+            &METRIC_NAMES[..2]
+        } else {
+            &[]
+        };
+        let missing: Vec<&str> = sleep_metrics
+            .iter()
+            .chain(&METRIC_NAMES[2..])
+            .copied()
+            .filter(|m| defaults.get(m).is_none() && phases::asks(&questions, m))
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::UsageError(format!(
+                "--yes needs a value for {} (add it to the defaults file)",
+                missing.join(", ")
+            )));
+        }
+        println!("{}", "Scripted entry: no prompts.".dimmed());
+    }
+    let mut sleep_hours: Option<f32> = None;
+    let mut sleep_quality: Option<f32> = None;
+    if is_first_entry_today && yes {
+        sleep_hours = defaults.get("sleep_hours").map(|h| h as f32);
+        sleep_quality = defaults.get("sleep_quality").map(|q| q as f32);
+    } else if is_first_entry_today {
         println!("{}", "First log of the day!".bright_blue());
-        sleep_hours = Some(
-            Input::with_theme(&*THEME)
-                .with_prompt("How many hours did you sleep last night?")
+    } else {
+        println!("{}", "Follow-up log for today.".dimmed());
+        // Copy sleep values from last entry
+        sleep_hours = store_info.last_sleep_hours;
+        sleep_quality = store_info.last_sleep_quality;
+        if sleep_hours.is_some() || sleep_quality.is_some() {
+            println!(
+                "{}",
+                format!(
+                    "Copied sleep data: {} {}, quality rating: {}", 
+                    sleep_hours.map_or("N/A".to_string(), |h| units
+                        .convert("sleep_hours", f64::from(h))
+                        .to_string()),
+                    units.unit("sleep_hours"),
+                    sleep_quality.map_or("N/A".to_string(), |q| q.to_string())
+                ).dimmed()
+            );
+        }
+    }
+
+    // Sleep is asked in its display unit (hours unless configured otherwise)
+    let ask_sleep_hours = |session: &cancel::PromptSession| -> Result<Option<f32>, AppError> {
+        let sleep_unit = units.unit("sleep_hours").to_string();
+        let max_sleep = units.convert("sleep_hours", 12.0);
+        let sleep_prompt = match options.config.metric_prompt("sleep_hours") {
+            _ if compact => format!("Sleep ({})", sleep_unit),
+            Some(prompt) => prompt.to_string(),
+            None => format!("How many {} did you sleep last night?", sleep_unit),
+        };
+        let sleep_default = units
+            .convert(
+                "sleep_hours",
+                defaults
+                    .get("sleep_hours")
+                    .or(options.config.default_sleep_hours)
+                    .unwrap_or(8.0),
+            )
+            .to_string(); // Sensible default
+        let answer = session.ask("sleep_hours", || {
+            Input::with_theme(&**THEME)
+                .with_prompt(&sleep_prompt)
                 .validate_with(|input: &String| -> Result<(), String> {
                     match input.parse::<f32>() {
                         Ok(val) => {
-                            if val <= 12.0 {
+                            if f64::from(val) <= max_sleep {
                                 // Max 12 hours, min is implicitly 0 for u8
                                 Ok(())
                             } else {
-                                Err("Please enter a number between 0 and 12".to_string())
+                                Err(format!(
+                                    "Please enter a number between 0 and {} {}",
+                                    max_sleep, sleep_unit
+                                ))
                             }
                         }
                         Err(_) => Err("Please enter a valid number".to_string()),
                     }
+                    .inspect_err(|_| edits.set(edits.get() + 1))
                 })
-                .default("8".to_string()) // Sensible default
+                .default(sleep_default.clone())
                 .interact_text()
-                .map_err(|_| AppError::DialogCancelled)? // Handle potential cancel
-                .parse::<f32>()?, // Parse validated input
-        );
-        sleep_quality = Some(
-            Input::with_theme(&*THEME)
-                .with_prompt("Rate sleep quality (1.0=Poor, 10.0=Excellent)")
-                .validate_with(|input: &String| -> Result<(), String> {
-                    match input.parse::<f32>() {
-                        Ok(val) => {
-                            if (1.0..=10.0).contains(&val) {
-                                Ok(())
-                            } else {
-                                Err("Please enter a value between 1.0 and 10.0".to_string())
+        })?;
+        Ok(match answer {
+            Some(answer) => {
+                let hours = units.to_stored("sleep_hours", f64::from(answer.parse::<f32>()?));
+                Some(((hours * 100.0).round() / 100.0) as f32)
+            }
+            None => None,
+        })
+    };
+    let ask_sleep_quality = |session: &cancel::PromptSession| -> Result<Option<f32>, AppError> {
+        let quality_default = defaults
+            .get("sleep_quality")
+            .map_or("7.5".to_string(), |q| q.to_string());
+        let (quality_min, quality_max) = metric_range("sleep_quality");
+        Ok(session
+            .ask("sleep_quality", || {
+                Input::with_theme(&**THEME)
+                    .with_prompt(if compact {
+                        format!("Sleep quality {}-{}", quality_min, quality_max)
+                    } else {
+                        format!(
+                            "{} ({:.1}=Poor, {:.1}=Excellent)",
+                            options
+                                .config
+                                .metric_prompt("sleep_quality")
+                                .unwrap_or("Rate sleep quality"),
+                            quality_min,
+                            quality_max
+                        )
+                    })
+                    .validate_with(|input: &String| -> Result<(), String> {
+                        match input.parse::<f32>() {
+                            Ok(val) => {
+                                if (quality_min..=quality_max).contains(&f64::from(val)) {
+                                    Ok(())
+                                } else {
+                                    Err(format!(
+                                        "Please enter a value between {:.1} and {:.1}",
+                                        quality_min, quality_max
+                                    ))
+                                }
                             }
+                            Err(_) => Err("Please enter a valid float (e.g. 7.5)".to_string()),
                         }
-                        Err(_) => Err("Please enter a valid float (e.g. 7.5)".to_string()),
-                    }
-                })
-                .default("7.5".to_string())
-                .interact_text()
-                .map_err(|_| AppError::DialogCancelled)?
-                .parse::<f32>()?, // Parse validated input
-        );
-    } else {
-        println!("{}", "Follow-up log for today.".dimmed());
-        // Copy sleep values from last entry
-        sleep_hours = csv_info.last_sleep_hours;
-        sleep_quality = csv_info.last_sleep_quality;
-        if sleep_hours.is_some() || sleep_quality.is_some() {
-            println!(
-                "{}",
-                format!(
-                    "Copied sleep data: {} hours, quality rating: {}", 
-                    sleep_hours.map_or("N/A".to_string(), |h| h.to_string()),
-                    sleep_quality.map_or("N/A".to_string(), |q| q.to_string())
-                ).dimmed()
-            );
-        }
-    }
+                        .inspect_err(|_| edits.set(edits.get() + 1))
+                    })
+                    .default(quality_default.clone())
+                    .interact_text()
+            })?
+            .map(|answer: String| answer.parse::<f32>()) // Parse validated input
+            .transpose()?)
+    };
 
-    let sleepiness = ask_rating("Sleepiness/Grogginess (1=Low, 10=High)")?;
-    let zonkedness = ask_rating("Zonked-ness (1=Low, 10=High)")?;
-    let mid_vibes = ask_rating("Mid Vibes (1=Low, 10=High)")?;
-    let energy = ask_rating("Energy Levels (1=Low, 10=High)")?;
-    let strength = ask_rating("Physical Strength (1=Low, 10=High)")?;
-    let focus = ask_rating("Focus (1=Low, 10=High)")?;
-    let intelligence = ask_rating("Perceived Intelligence (1=Low, 10=High)")?; // Wording change for clarity
+    // Ratings mention their scale, and their unit when one is configured. The question is
+    // the metric's label unless the config rewords it.
+    let rating_prompt = |metric: &str| {
+        let label = options.config.metric_prompt(metric).unwrap_or_else(|| {
+            metric_info(&options.config, metric).map_or(metric, |info| info.label)
+        });
+        let (min, max) = metric_range(metric);
+        match units.unit(metric) {
+            _ if compact => format!("{} {}-{}", column_name(metric), min, max),
+            "" => format!("{} ({}=Low, {}=High)", label, min, max),
+            unit => format!("{} ({}=Low, {}=High, in {})", label, min, max, unit),
+        }
+    };
+    let rate =
+        |session: &cancel::PromptSession, metric: &'static str| -> Result<Option<u8>, AppError> {
+            let preset = defaults.get(metric).map(|v| v.round() as u8);
+            match preset {
+                Some(value) if yes => Ok(Some(value)), // Checked above: --yes always has a value
+                _ => ask_rating(
+                    session,
+                    metric,
+                    &rating_prompt(metric),
+                    metric_range(metric),
+                    preset,
+                    &edits,
+                ),
+            }
+        };
 
-    let workout_today: bool;
+    // Whether a question gets a prompt, so a section's header only shows above real ones
+    let prompted = |question: Question| match question {
+        _ if yes => false,
+        Question::Metric(metric @ ("sleep_hours" | "sleep_quality")) => {
+            is_first_entry_today && phases::asks(&questions, metric)
+        }
+        Question::Metric(metric) => phases::asks(&questions, metric),
+        Question::Custom(name) => custom::asked(name, is_first_entry_today),
+        Question::Workout => !store_info.workout_logged_today,
+    };
 
-    if !csv_info.workout_logged_today {
-        // Only ask if no 'yes' workout has been logged today yet
-        println!("{}", "Checking workout status...".blue()); // Info message
-        workout_today = Confirm::with_theme(&*THEME)
-            .with_prompt("Did you (or will you) workout today?")
-            .interact()
-            .map_err(|_| AppError::DialogCancelled)?; // Handle potential cancel
-        if workout_today {
-            println!("{}", " -> Awesome!".yellow());
-        } else {
-            println!("{}", " -> Ok, maybe later.".dimmed());
+    let mut ratings: BTreeMap<&'static str, u8> = BTreeMap::new();
+    let mut custom = BTreeMap::new();
+    let mut workout_today = false;
+    for section in sections::order(&options.config) {
+        let mut title = section.title;
+        for question in section.questions {
+            if prompted(question) {
+                if let Some(title) = title.take() {
+                    println!("{}", format!("-- {} --", title).bold().cyan());
+                }
+            }
+            match question {
+                Question::Metric("sleep_hours") => {
+                    if prompted(question) {
+                        sleep_hours = ask_sleep_hours(&session)?;
+                    }
+                    if let Some(hours) = sleep_hours.filter(|_| is_first_entry_today) {
+                        session.answered.set("sleep_hours", f64::from(hours));
+                    }
+                }
+                Question::Metric("sleep_quality") => {
+                    if prompted(question) {
+                        sleep_quality = ask_sleep_quality(&session)?;
+                    }
+                    if let Some(quality) = sleep_quality.filter(|_| is_first_entry_today) {
+                        session.answered.set("sleep_quality", f64::from(quality));
+                    }
+                }
+                Question::Metric(metric) => {
+                    if !phases::asks(&questions, metric) {
+                        continue;
+                    }
+                    if let Some(value) = rate(&session, metric)? {
+                        session.answered.set(metric, f64::from(value));
+                        ratings.insert(metric, value);
+                    }
+                }
+                Question::Custom(name) => {
+                    if yes {
+                        continue;
+                    }
+                    if let Some(value) = custom::ask(&session, name, is_first_entry_today)? {
+                        custom.insert(name.to_string(), value);
+                    }
+                }
+                Question::Workout => {
+                    workout_today =
+                        ask_workout(&session, &store_info, &defaults, yes, compact, &edits)?;
+                    session.answered.workout = Some(workout_today);
+                }
+            }
         }
-    } else {
-        // A 'yes' was already logged today, so don't ask again.
-        println!(
-            "{}",
-            "Workout already logged as 'yes' earlier today.".dimmed()
-        );
-        workout_today = true; // Assume 'true' for this follow-up entry as well
     }
+    let [sleepiness, zonkedness, mid_vibes, energy, strength, focus, intelligence] = [
+        "sleepiness",
+        "zonkedness",
+        "mid_vibes",
+        "energy",
+        "strength",
+        "focus",
+        "intelligence",
+    ]
+    .map(|metric| ratings.get(metric).copied());
 
-    let remarks: String = Input::with_theme(&*THEME)
-        .with_prompt("Any remarks?")
-        .allow_empty(true) // Allow empty remarks
-        .interact_text()
-        .map_err(|_| AppError::DialogCancelled)?; // Handle potential cancel
+    let remarks: String = if yes {
+        defaults.remarks.clone().unwrap_or_default()
+    } else {
+        let mut input = Input::with_theme(&**THEME)
+            .with_prompt(if compact { "Notes" } else { "Any remarks?" })
+            .allow_empty(true); // Allow empty remarks
+        if let Some(remarks) = &defaults.remarks {
+            input = input.default(remarks.clone());
+        }
+        session
+            .ask("remarks", || input.clone().interact_text())?
+            .unwrap_or_default()
+    };
 
     let timestamp = Utc::now(); // Record time after all questions are answered
 
     // --- Create Log Entry ---
-    let entry = LogEntry {
+    let mut entry = LogEntry {
         timestamp: timestamp.to_rfc3339(), // ISO 8601 format
         day_count,
         sleep_hours,
@@ -204,13 +1355,84 @@ fn main() -> Result<(), Box<dyn Error>> {
         intelligence,
         workout_today,
         remarks,
+        session_seconds: record_session.then(|| started.elapsed().as_secs() as u32),
+        session_edits: record_session.then(|| edits.get()),
+        provenance: if yes { "defaults" } else { "interactive" }.to_string(),
+        entry_kind: Some(kind),
+        custom,
     };
 
-    // --- Write to CSV ---
-    append_to_csv(DATA_FILE, &entry)?;
+    // --- Last look at the answers before anything is written ---
+    if !yes && options.config.review.unwrap_or(true) {
+        // In the order they were asked
+        let asked: Vec<&'static str> = sections::order(&options.config)
+            .iter()
+            .flat_map(|section| &section.questions)
+            .filter_map(|question| match question {
+                Question::Metric(metric) if phases::asks(&questions, metric) => Some(*metric),
+                _ => None,
+            })
+            .collect();
+        match edit::review(&units, &asked, entry.clone()) {
+            Ok(Some(reviewed)) => entry = reviewed,
+            Ok(None) => {
+                cancel::discard_draft(&data_file);
+                println!("{}", "Entry discarded.".dimmed());
+                return Ok(());
+            }
+            Err(AppError::DialogCancelled) => return Err(session.cancel_entry(&entry)),
+            Err(e) => return Err(e),
+        }
+    }
+    drop(session); // Ctrl+C quits normally again
 
-    println!("{}", "\n----------------------------------------".green());
+    // --- Write to CSV (or just show the row in a dry run) ---
+    if options.dry_run {
+        constraints::validate(std::slice::from_ref(&entry))?;
+        println!("\n{}", layout::rule('-').yellow());
+        println!(
+            "{}",
+            format!(" Dry run: nothing written to {}", data_file)
+                .bold()
+                .yellow()
+        );
+        if let Some(old) = &replaced {
+            println!(" Would move {} to the trash", old.timestamp);
+        }
+        println!(" Would append:");
+        match args.preview {
+            ExportFormat::Csv => print!("{}", row_preview(&data_file, &entry)?),
+            ExportFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&export::json_entry(&entry)?)?
+            ),
+        }
+        println!("{}", layout::rule('-').yellow());
+        return Ok(());
+    }
+    let trash_id = match &replaced {
+        Some(old) => Some(trash::move_to_trash(
+            &data_file,
+            vec![old.clone()],
+            "correction",
+            |entries| entries.push(entry.clone()),
+        )?),
+        None => {
+            store.append(&entry)?;
+            None
+        }
+    };
+    termux::clear_reminder();
+    cancel::discard_draft(&data_file);
+
+    println!("\n{}", layout::rule('-').green());
     println!("{}", " Entry successfully logged!".bold().green());
+    if let Some(id) = trash_id {
+        println!(
+            " Replaced the previous entry (undo with: trash restore {})",
+            id
+        );
+    }
     println!(
         " Timestamp: {}",
         timestamp
@@ -218,183 +1440,495 @@ fn main() -> Result<(), Box<dyn Error>> {
             .to_string()
             .dimmed()
     );
-    println!("{}", "----------------------------------------".green());
+    if let Err(e) = summary::print_block(options, &entry) {
+        eprintln!("Warning: Could not summarize the entry: {}", e);
+    }
+    println!("{}", layout::rule('-').green());
 
+    push_saved_entry(options, &entry);
+    if let Err(e) = baseline::alert(options, &entry) {
+        eprintln!("Warning: Could not compare with the usual values: {}", e);
+    }
+
+    // Scripted entries never stop for questionnaires or the goal prompt
+    if kind == EntryKind::FirstOfDay && !yes {
+        bundles::ask_due(options, today)?;
+        let previous_day = store_info
+            .last_entry_date
+            .map(|date| (date - phase_start).num_days() + 1);
+        goals::check_goal(options, phase_start, goal_day, previous_day)?;
+        challenges::check_finished(options, store_info.last_entry_date)?;
+    }
     Ok(())
 }
 
-// --- Helper function to ask for a 1-10 rating ---
-fn ask_rating(prompt: &str) -> Result<u8, AppError> {
-    Input::with_theme(&*THEME)
-        .with_prompt(prompt)
-        .validate_with(|input: &String| -> Result<(), String> {
-            match input.parse::<u8>() {
-                Ok(val) => {
-                    if (1..=10).contains(&val) {
-                        Ok(())
-                    } else {
-                        Err("Please enter a number between 1 and 10".to_string())
-                    }
+// The entry is already saved, so a monitoring server being down is only a warning
+fn push_saved_entry(options: &GlobalOptions, entry: &LogEntry) {
+    if let Some(push) = &options.config.push {
+        match push::push_entry(push, entry) {
+            Ok(count) => println!(
+                "{}",
+                format!("Pushed {} values to {}", count, push.address).dimmed()
+            ),
+            Err(e) => eprintln!(
+                "Warning: Could not push to {} at {}: {}",
+                push.protocol, push.address, e
+            ),
+        }
+    }
+}
+
+// --- Today already has an entry: is this one another reading or a fix? ---
+fn ask_entry_kind() -> Result<EntryKind, AppError> {
+    const PROMPT: &str = "Today is already logged. This entry is";
+    let choice = replay::answer(PROMPT, || {
+        Select::with_theme(&**THEME)
+            .with_prompt(PROMPT)
+            .items(&[
+                "A follow-up: another reading today",
+                "A correction: replaces today's last entry",
+            ])
+            .default(0)
+            .interact()
+    })?;
+    Ok(if choice == 0 {
+        EntryKind::FollowUp
+    } else {
+        EntryKind::Correction
+    })
+}
+
+// --- The workout question: asked until a 'yes' is logged for the day ---
+fn ask_workout(
+    session: &cancel::PromptSession,
+    store_info: &store::StoreInfo,
+    defaults: &LogDefaults,
+    yes: bool,
+    compact: bool,
+    edits: &Cell<u32>,
+) -> Result<bool, AppError> {
+    if store_info.workout_logged_today {
+        // A 'yes' was already logged today, so don't ask again.
+        println!(
+            "{}",
+            "Workout already logged as 'yes' earlier today.".dimmed()
+        );
+        return Ok(true); // Assume 'true' for this follow-up entry as well
+    }
+    if yes {
+        return Ok(defaults.workout.unwrap_or(false));
+    }
+    if compact {
+        // A number keeps the phone keyboard on its digit row
+        return ask_workout_number(session, defaults.workout, edits);
+    }
+    println!("{}", "Checking workout status...".blue()); // Info message
+    let mut confirm =
+        Confirm::with_theme(&**THEME).with_prompt("Did you (or will you) workout today?");
+    if let Some(workout) = defaults.workout {
+        confirm = confirm.default(workout);
+    }
+    // A skipped question counts as no workout
+    let workout_today = session
+        .ask("workout_today", || confirm.clone().interact())?
+        .unwrap_or(false);
+    if workout_today {
+        println!("{}", " -> Awesome!".yellow());
+    } else {
+        println!("{}", " -> Ok, maybe later.".dimmed());
+    }
+    Ok(workout_today)
+}
+
+// --- Helper function to ask for a rating on its scale (1-10 unless changed) ---
+fn ask_rating(
+    session: &cancel::PromptSession,
+    metric: &str,
+    prompt: &str,
+    (min, max): (f64, f64),
+    default: Option<u8>,
+    edits: &Cell<u32>,
+) -> Result<Option<u8>, AppError> {
+    let mut input = Input::with_theme(&**THEME).with_prompt(prompt);
+    if let Some(value) = default {
+        input = input.default(value.to_string());
+    }
+    let validated = input.validate_with(|input: &String| -> Result<(), String> {
+        match input.parse::<u8>() {
+            Ok(val) => {
+                if (min..=max).contains(&f64::from(val)) {
+                    Ok(())
+                } else {
+                    Err(format!("Please enter a number between {} and {}", min, max))
                 }
-                Err(_) => Err("Please enter a valid number".to_string()),
             }
-        })
-        .interact_text()
-        .map_err(|_| AppError::DialogCancelled)? // Handle potential cancel
-        .parse::<u8>() // We know it's valid u8 due to validator
+            Err(_) => Err("Please enter a valid number".to_string()),
+        }
+        .inspect_err(|_| edits.set(edits.get() + 1))
+    });
+    session
+        .ask(metric, || validated.clone().interact_text())?
+        .map(|answer: String| answer.parse::<u8>()) // We know it's valid u8 due to validator
+        .transpose()
         .map_err(|e| AppError::IoError(std::io::Error::new(std::io::ErrorKind::InvalidInput, e)))
 }
 
-// --- Helper function to read first and last date from CSV ---
-fn read_csv_info(file_path: &str) -> Result<CsvInfo, AppError> {
-    let mut first_date: Option<NaiveDate> = None;
-    let mut last_date: Option<NaiveDate> = None;
-    let mut workout_today_logged = false; // Initialize flag for the new logic
-    let mut last_sleep_hours: Option<f32> = None;
-    let mut last_sleep_quality: Option<f32> = None;
-
-    let today = Utc::now().date_naive(); // Get today's date once
-
-    if Path::new(file_path).exists() {
-        let file = File::open(file_path)?;
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(true) // Expect a header row
-            .from_reader(file);
-
-        // Define the expected header name for robustness check
-        const TIMESTAMP_HEADER: &str = "timestamp";
-        // Define column indices (0-based)
-        const WORKOUT_COLUMN_INDEX: usize = 11;
-        const SLEEP_HOURS_COLUMN_INDEX: usize = 2; 
-        const SLEEP_QUALITY_COLUMN_INDEX: usize = 3;
-
-        for result in rdr.records() {
-            let record = match result {
-                Ok(rec) => rec,
-                Err(e) => {
-                    eprintln!("Warning: Skipping corrupted CSV record: {}", e);
-                    continue; // Skip this record
-                }
-            };
-
-            // Get timestamp string
-            if let Some(ts_str) = record.get(0) {
-                // Safety check: Skip if we somehow got the header row
-                if ts_str.trim() == TIMESTAMP_HEADER {
-                    eprintln!("Warning: Skipping potential header row accidentally read as data.");
-                    continue;
-                }
+// --- Compact mode's workout question: 1 = yes, 0 = no ---
+fn ask_workout_number(
+    session: &cancel::PromptSession,
+    default: Option<bool>,
+    edits: &Cell<u32>,
+) -> Result<bool, AppError> {
+    let mut input = Input::with_theme(&**THEME).with_prompt("Workout 1=yes 0=no");
+    if let Some(workout) = default {
+        input = input.default(if workout { "1" } else { "0" }.to_string());
+    }
+    let input = input.validate_with(|input: &String| -> Result<(), String> {
+        match input.trim() {
+            "0" | "1" => Ok(()),
+            _ => Err("Enter 1 or 0".to_string()),
+        }
+        .inspect_err(|_| edits.set(edits.get() + 1))
+    });
+    // A skipped question counts as no workout
+    let answer = session.ask("workout_today", || input.clone().interact_text())?;
+    Ok(answer.is_some_and(|a: String| a.trim() == "1"))
+}
 
-                // Parse timestamp
-                let dt = DateTime::parse_from_rfc3339(ts_str)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .map_err(|e| {
-                        eprintln!(
-                            "Warning: Could not parse timestamp '{}' in data row: {}. Skipping record.", // Changed log level
-                            ts_str, e
-                        );
-                        // Don't return AppError here, just skip the record for date finding purposes
-                        // AppError::DateParseError(e)
-                    });
-
-                // Proceed only if date parsing was successful
-                if let Ok(dt) = dt {
-                    let current_date = dt.date_naive();
-
-                    // Update first date logic
-                    if first_date.is_none() || current_date < first_date.unwrap() {
-                        first_date = Some(current_date);
-                    }
-                    // Update last date (always override with the latest processed valid record)
-                    last_date = Some(current_date);
-
-                    // --- Check workout status for today's entries ---
-                    if current_date == today {
-                        if let Some(workout_str) = record.get(WORKOUT_COLUMN_INDEX) {
-                            // Check if workout was logged as 'true' case-insensitively
-                            if workout_str.trim().eq_ignore_ascii_case("true") {
-                                workout_today_logged = true;
-                                // Optimization note: We could potentially break early if we only needed this flag,
-                                // but we still need to loop through all records to find the *last* date reliably.
-                            }
-                        } else {
-                            eprintln!("Warning: Record for today ({}) is missing workout column (index {}).", current_date, WORKOUT_COLUMN_INDEX);
-                        }
-                    }
-                    
-                    // Update sleep values from the most recent entry
-                    if last_date.is_none() || current_date >= last_date.unwrap() {
-                        // Try to read sleep hours
-                        if let Some(sleep_hours_str) = record.get(SLEEP_HOURS_COLUMN_INDEX) {
-                            if !sleep_hours_str.trim().is_empty() {
-                                if let Ok(hours) = sleep_hours_str.trim().parse::<f32>() {
-                                    last_sleep_hours = Some(hours);
-                                }
-                            }
-                        }
-                        
-                        // Try to read sleep quality
-                        if let Some(sleep_quality_str) = record.get(SLEEP_QUALITY_COLUMN_INDEX) {
-                            if !sleep_quality_str.trim().is_empty() {
-                                if let Ok(quality) = sleep_quality_str.trim().parse::<f32>() {
-                                    last_sleep_quality = Some(quality);
-                                }
-                            }
-                        }
-                    }
-                } // End if Ok(dt)
-            } else {
-                eprintln!("Warning: Skipping record with missing timestamp column.");
-            }
-        } // End for loop
+// --- Helper function to render the text an append would produce ---
+// (a JSON line when the data file is JSONL or MessagePack; CSV gets its header when the
+// file is new)
+fn row_preview(file_path: &str, entry: &LogEntry) -> Result<String, AppError> {
+    if jsonl::is_jsonl(file_path) || msgpack::is_msgpack(file_path) {
+        return jsonl::line(entry);
+    }
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(vec![]);
+    if !Path::new(file_path).exists() {
+        wtr.write_record(&csv_header())?;
     }
+    wtr.write_record(&store::entry_record(entry)?)?;
+    let bytes = wtr
+        .into_inner()
+        .map_err(|e| AppError::IoError(e.into_error()))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
 
-    Ok(CsvInfo {
-        first_entry_date: first_date,
-        last_entry_date: last_date,
-        workout_logged_today: workout_today_logged,
-        last_sleep_hours,
-        last_sleep_quality,
-    })
+// --- CSV header row, using the current names of any renamed metrics ---
+// Order matches the LogEntry struct fields, with the custom metrics' columns last.
+fn csv_header() -> StringRecord {
+    [
+        "timestamp",
+        "day_count",
+        column_name("sleep_hours"),
+        column_name("sleep_quality"),
+        column_name("sleepiness"),
+        column_name("zonkedness"),
+        column_name("mid_vibes"),
+        column_name("energy"),
+        column_name("strength"),
+        column_name("focus"),
+        column_name("intelligence"),
+        "workout_today",
+        "remarks",
+        "session_seconds",
+        "session_edits",
+        "provenance",
+        "entry_kind",
+    ]
+    .into_iter()
+    .chain(custom::columns())
+    .collect()
+}
+
+// --- Every entry in the data file, whichever format it is stored in ---
+fn read_entries(file_path: &str) -> Result<Vec<LogEntry>, AppError> {
+    store::open(file_path).all_entries()
+}
+
+// --- Rewrite the whole data file (used when merging into existing rows) ---
+fn write_entries(file_path: &str, entries: &[LogEntry]) -> Result<(), AppError> {
+    store::open(file_path).write_all(entries)
 }
 
-// --- Helper function to append data to CSV ---
-fn append_to_csv(file_path: &str, entry: &LogEntry) -> Result<(), AppError> {
-    let file_exists = Path::new(file_path).exists();
-
-    let file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(file_path)?;
-
-    let mut wtr = WriterBuilder::new()
-        .has_headers(!file_exists) // Write headers only if file is new
-        .from_writer(file);
-
-    // Write header if it's a new file
-    if !file_exists {
-        // Manually create header record from struct field names
-        // Note: Order must match LogEntry struct fields for clarity, though serde handles it
-        let headers = StringRecord::from(vec![
-            "timestamp",
-            "day_count",
-            "sleep_hours",
-            "sleep_quality",
-            "sleepiness",
-            "zonkedness",
-            "mid_vibes",
-            "energy",
-            "strength",
-            "focus",
-            "intelligence",
-            "workout_today",
-            "remarks",
-        ]);
-        wtr.write_record(&headers)?;
-    }
-
-    // Serialize and write the data record
-    wtr.serialize(entry)?;
-    wtr.flush()?; // Ensure data is written to disk
+// --- Group entries by day (same UTC day boundary the logging flow uses for follow-ups) ---
+fn group_by_day(entries: Vec<LogEntry>) -> BTreeMap<NaiveDate, Vec<LogEntry>> {
+    let mut days: BTreeMap<NaiveDate, Vec<LogEntry>> = BTreeMap::new();
+    for entry in entries {
+        if let Some(ts) = entry.parsed_timestamp() {
+            days.entry(ts.date_naive()).or_default().push(entry);
+        }
+    }
+    days
+}
+
+// --- Hour-of-day analysis: how a metric varies across the (local) day ---
+fn run_by_hour(options: &GlobalOptions, metric: &str) -> Result<(), AppError> {
+    if !METRIC_NAMES.contains(&metric) {
+        return Err(AppError::UsageError(format!(
+            "Unknown metric '{}'. Choose one of: {}",
+            metric,
+            METRIC_NAMES.join(", ")
+        )));
+    }
+
+    // (sum, count) per local hour
+    let mut buckets = [(0.0_f64, 0_usize); 24];
+    for entry in options.analysis_entries()? {
+        let (Some(ts), Some(value)) = (entry.parsed_timestamp(), entry.metric(metric)) else {
+            continue;
+        };
+        // Backfilled days carry a made-up time, so they say nothing about the hour
+        if entry.entry_kind == Some(EntryKind::Backfill) {
+            continue;
+        }
+        let hour = ts.with_timezone(&Local).hour() as usize;
+        buckets[hour].0 += value;
+        buckets[hour].1 += 1;
+    }
+
+    if buckets.iter().all(|(_, n)| *n == 0) {
+        println!("{}", "No entries logged yet.".dimmed());
+        return Ok(());
+    }
+
+    let max_mean = buckets
+        .iter()
+        .filter(|(_, n)| *n > 0)
+        .map(|(sum, n)| sum / *n as f64)
+        .fold(f64::MIN, f64::max);
+
+    let units = DisplayUnits::from_config(&options.config);
+    let title = match units.unit(metric) {
+        "" => format!(" {} by hour of day ", column_name(metric)),
+        unit => format!(" {} ({}) by hour of day ", column_name(metric), unit),
+    };
+    println!("{}", title.bold().cyan());
+    println!("{}", layout::rule('-').cyan());
+    layout::row(&format!("{:>5}  {:>7}  {:>5}", "Hour", "Entries", "Mean"));
+    for (hour, (sum, n)) in buckets.iter().enumerate() {
+        if *n == 0 {
+            continue;
+        }
+        let mean = sum / *n as f64;
+        let ratio = mean / max_mean.max(1.0);
+        let bar = "█".repeat((ratio * 20.0).round() as usize);
+        // Colour the bar like a heatmap: low values red, high values green
+        let bar = if ratio >= 0.75 {
+            bar.green()
+        } else if ratio >= 0.5 {
+            bar.yellow()
+        } else {
+            bar.red()
+        };
+        layout::row(&format!(
+            "{:>3}:00  {:>7}  {:>5.1}  {}",
+            hour,
+            n,
+            units.convert(metric, mean),
+            bar
+        ));
+    }
+    Ok(())
+}
+
+// --- Intra-day trend: change between the first and last entry of each day ---
+fn run_intraday(options: &GlobalOptions) -> Result<(), AppError> {
+    let mut entries = options.analysis_entries()?;
+    entries.retain(|e| e.entry_kind != Some(EntryKind::Backfill));
+    let days = group_by_day(entries);
+
+    let multi_entry_days: Vec<&Vec<LogEntry>> = days.values().filter(|d| d.len() >= 2).collect();
+    if multi_entry_days.is_empty() {
+        println!(
+            "{}",
+            "No days with follow-up entries yet; log more than once a day to see intra-day trends."
+                .dimmed()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        " Intra-day change (first vs last entry) ".bold().cyan()
+    );
+    println!("{}", layout::rule('-').cyan());
+    println!(
+        "Days with follow-ups: {}",
+        multi_entry_days.len().to_string().yellow()
+    );
+    println!();
+
+    // Sleep values are copied to follow-ups, so only the ratings can change during a day
+    for metric in METRIC_NAMES.iter().filter(|m| !m.starts_with("sleep_")) {
+        let deltas: Vec<f64> = multi_entry_days
+            .iter()
+            .filter_map(|day| {
+                let mut sorted: Vec<&LogEntry> = day.iter().collect();
+                sorted.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+                let first = sorted.first()?.metric(metric)?;
+                let last = sorted.last()?.metric(metric)?;
+                Some(last - first)
+            })
+            .collect();
+        if deltas.is_empty() {
+            continue;
+        }
+
+        let avg = deltas.iter().sum::<f64>() / deltas.len() as f64;
+        let description = if avg.abs() < 0.05 {
+            "stays flat".dimmed()
+        } else if avg < 0.0 {
+            format!("drops on average {:.1} points", -avg).red()
+        } else {
+            format!("rises on average {:.1} points", avg).green()
+        };
+        layout::row(&format!(
+            "{:<13} {} between first and last entry",
+            column_name(metric),
+            description
+        ));
+    }
+    Ok(())
+}
+
+// --- Summary statistics for one metric ---
+struct MetricSummary {
+    count: usize,
+    mean: f64,
+    min: f64,
+    max: f64,
+}
+
+impl MetricSummary {
+    fn from_values(values: &[f64]) -> Option<MetricSummary> {
+        if values.is_empty() {
+            return None;
+        }
+        Some(MetricSummary {
+            count: values.len(),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            min: values.iter().copied().fold(f64::MAX, f64::min),
+            max: values.iter().copied().fold(f64::MIN, f64::max),
+        })
+    }
+
+    // One value per day, combined the way the metric's `aggregate` setting says
+    fn for_days(points: &[DailyPoint], metric: &str) -> Option<MetricSummary> {
+        let values: Vec<f64> = points
+            .iter()
+            .filter_map(|p| p.values.get(metric).copied())
+            .collect();
+        MetricSummary::from_values(&values)
+    }
+
+    // Same summary expressed in the metric's display unit
+    fn converted(&self, units: &DisplayUnits, metric: &str) -> MetricSummary {
+        MetricSummary {
+            count: self.count,
+            mean: units.convert(metric, self.mean),
+            min: units.convert(metric, self.min),
+            max: units.convert(metric, self.max),
+        }
+    }
+}
+
+// --- Read-only comparison of several people's exported files ---
+fn run_compare(options: &GlobalOptions, files: &[String]) -> Result<(), AppError> {
+    if files.is_empty() {
+        return Err(AppError::UsageError(
+            "Usage: compare <export.csv> [<export.csv> ...]".to_string(),
+        ));
+    }
+
+    // Label each dataset by its file name (without extension)
+    let mut datasets: Vec<(String, usize, Vec<DailyPoint>)> = Vec::new();
+    for file in files {
+        if !Path::new(file).exists() {
+            eprintln!("Warning: '{}' does not exist, skipping.", file);
+            continue;
+        }
+        let label = Path::new(file)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file.clone());
+        let entries = read_entries(file)?;
+        datasets.push((label, entries.len(), daily_points(entries)));
+    }
+    if datasets.is_empty() {
+        return Err(AppError::UsageError("No readable files given.".to_string()));
+    }
+
+    // Everyone's numbers are shown in this user's display units
+    let units = DisplayUnits::from_config(&options.config);
+
+    // Individual reports
+    for (label, entry_count, points) in &datasets {
+        println!("{}", layout::rule('=').cyan());
+        println!("{}", format!(" {} ", label).bold().cyan());
+        println!("{}", layout::rule('=').cyan());
+
+        match (points.first(), points.last()) {
+            (Some(first), Some(last)) => println!(
+                "Entries: {}  Days logged: {}  ({} to {})",
+                entry_count.to_string().yellow(),
+                points.len().to_string().yellow(),
+                first.date,
+                last.date
+            ),
+            _ => {
+                println!("{}", "No entries.".dimmed());
+                continue;
+            }
+        }
+        let workout_days = points.iter().filter(|p| p.workout).count();
+        println!(
+            "Workout days: {} of {}",
+            workout_days.to_string().green(),
+            points.len()
+        );
+
+        layout::row(&format!(
+            "{:<14} {:>5} {:>6} {:>5} {:>5}  Unit",
+            "Metric", "N", "Mean", "Min", "Max"
+        ));
+        for metric in METRIC_NAMES {
+            if let Some(summary) = MetricSummary::for_days(points, metric) {
+                let summary = summary.converted(&units, metric);
+                layout::row(&format!(
+                    "{:<14} {:>5} {:>6.2} {:>5.1} {:>5.1}  {}",
+                    column_name(metric),
+                    summary.count,
+                    summary.mean,
+                    summary.min,
+                    summary.max,
+                    units.unit(metric).dimmed()
+                ));
+            }
+        }
+        println!();
+    }
+
+    // Side-by-side comparison of means
+    if datasets.len() > 1 {
+        println!("{}", " Comparison (mean per metric) ".bold().cyan());
+        println!("{}", layout::rule('-').cyan());
+        let mut header = format!("{:<14}", "Metric");
+        for (label, _, _) in &datasets {
+            header.push_str(&format!(" {:>12.12}", label));
+        }
+        layout::row(&header);
+        for metric in METRIC_NAMES {
+            let mut line = format!("{:<14}", column_name(metric));
+            for (_, _, points) in &datasets {
+                match MetricSummary::for_days(points, metric) {
+                    Some(summary) => {
+                        line.push_str(&format!(" {:>12.2}", units.convert(metric, summary.mean)))
+                    }
+                    None => line.push_str(&format!(" {:>12}", "-")),
+                }
+            }
+            layout::row(&line);
+        }
+    }
     Ok(())
 }
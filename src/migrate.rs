@@ -0,0 +1,223 @@
+use crate::audit;
+use crate::backup;
+use crate::cli::MigrateArgs;
+use crate::constraints::{constraint, CONSTRAINTS};
+use crate::custom;
+use crate::jsonl;
+use crate::layout;
+use crate::lock;
+use crate::msgpack;
+use crate::schema::{canonical_metric, header_version, SCHEMA_VERSION};
+use crate::store;
+use crate::{AppError, GlobalOptions};
+use colored::*;
+use csv::{ReaderBuilder, StringRecord};
+use std::path::PathBuf;
+
+// --- What bringing one CSV file up to the current layout involves ---
+struct Plan {
+    version: Option<u32>, // None: not one of the layouts
+    renamed: Vec<(String, &'static str)>,
+    added: Vec<&'static str>,
+    unknown: Vec<String>,
+    reordered: bool,
+}
+
+impl Plan {
+    fn up_to_date(&self) -> bool {
+        self.version == Some(SCHEMA_VERSION) && self.renamed.is_empty()
+    }
+}
+
+// The column a name refers to: a metric (or one of its aliases) or any other column
+fn known_column(name: &str) -> Option<&'static str> {
+    canonical_metric(name).or_else(|| constraint(name).map(|c| c.column))
+}
+
+// `--rename OLD=NEW` pairs, NEW resolved to the column it means
+fn parse_renames(args: &MigrateArgs) -> Result<Vec<(String, &'static str)>, AppError> {
+    args.rename
+        .iter()
+        .map(|pair| {
+            let (old, new) = pair.split_once('=').ok_or_else(|| {
+                AppError::UsageError(format!("--rename takes OLD=NEW, got {}", pair))
+            })?;
+            let column = known_column(new.trim()).ok_or_else(|| {
+                AppError::UsageError(format!(
+                    "{} isn't a column; see `schema` for the columns",
+                    new.trim()
+                ))
+            })?;
+            Ok((old.trim().to_string(), column))
+        })
+        .collect()
+}
+
+fn plan(
+    file: &str,
+    header: &StringRecord,
+    renames: &[(String, &'static str)],
+) -> Result<Plan, AppError> {
+    let mut plan = Plan {
+        version: header_version(header),
+        renamed: Vec::new(),
+        added: Vec::new(),
+        unknown: Vec::new(),
+        reordered: false,
+    };
+    let mut present: Vec<&'static str> = Vec::new();
+    for name in header.iter().map(str::trim) {
+        let column = match renames.iter().find(|(old, _)| old == name) {
+            Some((_, column)) => {
+                plan.renamed.push((name.to_string(), column));
+                *column
+            }
+            None => match known_column(name) {
+                Some(column) => column,
+                // Declared with [custom.<name>] in the config, kept after the built-in ones
+                None if custom::columns().any(|c| c == name) => continue,
+                None => {
+                    plan.unknown.push(name.to_string());
+                    continue;
+                }
+            },
+        };
+        if present.contains(&column) {
+            return Err(AppError::UsageError(format!(
+                "{}: more than one column would be read as {}",
+                file, column
+            )));
+        }
+        present.push(column);
+    }
+    plan.added = CONSTRAINTS
+        .iter()
+        .map(|c| c.column)
+        .filter(|column| !present.contains(column))
+        .collect();
+    let positions: Vec<usize> = present
+        .iter()
+        .filter_map(|column| CONSTRAINTS.iter().position(|c| c.column == *column))
+        .collect();
+    plan.reordered = !positions.is_sorted();
+    Ok(plan)
+}
+
+fn print_plan(file: &str, plan: &Plan, drop_unknown: bool) {
+    let from = plan
+        .version
+        .map_or("a custom layout".to_string(), |v| format!("version {}", v));
+    println!("{}: {} → version {}", file.bold(), from, SCHEMA_VERSION);
+    for (old, column) in &plan.renamed {
+        layout::row(&format!("  rename {} → {}", old, column));
+    }
+    if !plan.added.is_empty() {
+        layout::row(&format!("  add {}", plan.added.join(", ")));
+    }
+    if plan.reordered {
+        layout::row("  put the columns back in order");
+    }
+    if drop_unknown && !plan.unknown.is_empty() {
+        layout::row(
+            &format!("  drop {}", plan.unknown.join(", "))
+                .red()
+                .to_string(),
+        );
+    }
+}
+
+// --- `migrate` command: rewrite CSV data files in the current column layout ---
+// Rows are appended by position, so a file whose header isn't one of the layouts in
+// schema.rs can't be appended to until it's migrated. JSON Lines and MessagePack files
+// store field names with every entry and never need it.
+pub fn run_migrate(options: &GlobalOptions, args: &MigrateArgs) -> Result<(), AppError> {
+    let data_file = options.data_file();
+    let renames = parse_renames(args)?;
+    let files: Vec<PathBuf> = store::files(&data_file)
+        .into_iter()
+        .filter(|path| {
+            let path = path.to_string_lossy();
+            !jsonl::is_jsonl(&path) && !msgpack::is_msgpack(&path)
+        })
+        .collect();
+    if files.is_empty() {
+        println!(
+            "{}",
+            format!("Nothing to migrate: {} isn't a CSV data file.", data_file).dimmed()
+        );
+        return Ok(());
+    }
+
+    let mut pending: Vec<(String, Plan)> = Vec::new();
+    for path in files {
+        let file = path.to_string_lossy().to_string();
+        let Some(header) = ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(&path)?
+            .records()
+            .next()
+            .transpose()?
+        else {
+            continue; // Empty: the next write gives it the current header
+        };
+        let plan = plan(&file, &header, &renames)?;
+        if plan.up_to_date() {
+            println!(
+                "{}",
+                format!("{}: already at schema version {}", file, SCHEMA_VERSION).dimmed()
+            );
+            continue;
+        }
+        print_plan(&file, &plan, args.drop_unknown);
+        if !plan.unknown.is_empty() && !args.drop_unknown {
+            return Err(AppError::UsageError(format!(
+                "{} has columns that aren't part of any layout: {}. Map them with --rename OLD=NEW, or drop them with --drop-unknown",
+                file,
+                plan.unknown.join(", ")
+            )));
+        }
+        pending.push((file, plan));
+    }
+    if pending.is_empty() {
+        return Ok(());
+    }
+    if options.dry_run {
+        println!("{}", "Dry run: nothing rewritten".yellow());
+        return Ok(());
+    }
+
+    // Each file is read and rewritten under the data file's lock, backed up first like
+    // any other write, so an entry logged meanwhile can't be written over
+    let _lock = lock::write_lock(&data_file)?;
+    backup::snapshot(&data_file)?;
+    for (file, plan) in &pending {
+        let entries = store::read_csv_renaming(file, &renames)?;
+        store::open_file(file).write_all(&entries)?;
+        let mut details = vec![format!(
+            "{} to version {}",
+            plan.version
+                .map_or("custom layout".to_string(), |v| format!("version {}", v)),
+            SCHEMA_VERSION
+        )];
+        details.extend(
+            plan.renamed
+                .iter()
+                .map(|(old, column)| format!("renamed {} to {}", old, column)),
+        );
+        if !plan.unknown.is_empty() {
+            details.push(format!("dropped {}", plan.unknown.join(", ")));
+        }
+        audit::record(&data_file, "migrate", &details.join("; "), Vec::new())?;
+    }
+    println!(
+        "{}",
+        format!(
+            "Migrated {} file{} to schema version {}.",
+            pending.len(),
+            if pending.len() == 1 { "" } else { "s" },
+            SCHEMA_VERSION
+        )
+        .green()
+    );
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use crate::audit;
+use crate::cli::AnnotateArgs;
+use crate::daily::DailyPoint;
+use crate::layout;
+use crate::{AppError, GlobalOptions, MetricSummary};
+use chrono::{NaiveDate, Utc};
+use colored::*;
+use csv::{ReaderBuilder, WriterBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const WINDOW_DAYS: i64 = 14; // Before/after comparisons use this many days on each side
+
+// --- A dated marker such as "started new medication", kept beside the data ---
+// Reports draw them on the charts; the entries themselves are untouched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Milestone {
+    pub date: NaiveDate,
+    pub label: String,
+}
+
+fn milestones_file(data_file: &str) -> PathBuf {
+    Path::new(data_file).with_extension("milestones.csv")
+}
+
+// Every milestone, oldest first (none when the file doesn't exist yet)
+pub fn read_milestones(data_file: &str) -> Result<Vec<Milestone>, AppError> {
+    let path = milestones_file(data_file);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut rdr = ReaderBuilder::new().from_path(path)?;
+    let mut milestones = rdr.deserialize().collect::<Result<Vec<Milestone>, _>>()?;
+    milestones.sort_by_key(|m| m.date);
+    Ok(milestones)
+}
+
+fn write_milestones(data_file: &str, milestones: &[Milestone]) -> Result<(), AppError> {
+    let mut wtr = WriterBuilder::new().from_path(milestones_file(data_file))?;
+    for milestone in milestones {
+        wtr.serialize(milestone)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Mean daily value of a metric in the window before the date and from the date on
+pub fn before_after(points: &[DailyPoint], date: NaiveDate, metric: &str) -> [Option<f64>; 2] {
+    let mean = |range: std::ops::Range<i64>| {
+        let values: Vec<f64> = points
+            .iter()
+            .filter(|p| range.contains(&(p.date - date).num_days()))
+            .filter_map(|p| p.values.get(metric).copied())
+            .collect();
+        MetricSummary::from_values(&values).map(|s| s.mean)
+    };
+    [mean(-WINDOW_DAYS..0), mean(0..WINDOW_DAYS)]
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, AppError> {
+    let today = Utc::now().date_naive(); // Entries are dated in UTC too
+    match text {
+        "today" => Ok(today),
+        "yesterday" => Ok(today - chrono::Duration::days(1)),
+        _ => NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| {
+            AppError::UsageError(format!(
+                "Can't read '{}': use YYYY-MM-DD, today or yesterday",
+                text
+            ))
+        }),
+    }
+}
+
+// --- `annotate` command: add, list or remove milestones ---
+pub fn run_annotate(options: &GlobalOptions, args: &AnnotateArgs) -> Result<(), AppError> {
+    let data_file = options.data_file();
+    let mut milestones = read_milestones(&data_file)?;
+
+    if let Some(date) = &args.remove {
+        let date = parse_date(date)?;
+        let before = milestones.len();
+        milestones.retain(|m| m.date != date);
+        if milestones.len() == before {
+            println!("{}", format!("No milestone on {}.", date).dimmed());
+            return Ok(());
+        }
+        if options.dry_run {
+            println!("{}", format!("Dry run: would remove {}", date).yellow());
+            return Ok(());
+        }
+        write_milestones(&data_file, &milestones)?;
+        audit::record(&data_file, "annotate remove", &date.to_string(), Vec::new())?;
+        println!("{}", format!("Removed the milestone on {}", date).green());
+        return Ok(());
+    }
+
+    let Some(date) = &args.date else {
+        if milestones.is_empty() {
+            println!(
+                "{}",
+                "No milestones yet. Add one with: annotate 2024-05-10 \"started new medication\""
+                    .dimmed()
+            );
+        }
+        for milestone in &milestones {
+            layout::row(&format!(
+                "{}  {}",
+                milestone.date.to_string().yellow(),
+                milestone.label
+            ));
+        }
+        return Ok(());
+    };
+    let date = parse_date(date)?;
+    let label = args.label.join(" ");
+    if label.trim().is_empty() {
+        return Err(AppError::UsageError(
+            "Give the milestone a label, e.g. annotate 2024-05-10 \"started new medication\""
+                .to_string(),
+        ));
+    }
+    let milestone = Milestone {
+        date,
+        label: label.trim().to_string(),
+    };
+    if options.dry_run {
+        println!(
+            "{}",
+            format!("Dry run: would add {} {}", milestone.date, milestone.label).yellow()
+        );
+        return Ok(());
+    }
+    let details = format!("{} {}", milestone.date, milestone.label);
+    milestones.push(milestone);
+    milestones.sort_by_key(|m| m.date);
+    write_milestones(&data_file, &milestones)?;
+    audit::record(&data_file, "annotate", &details, Vec::new())?;
+    println!("{}", format!("Milestone added on {}", date).green());
+    Ok(())
+}
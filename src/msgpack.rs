@@ -0,0 +1,126 @@
+use crate::store::{self, MetricStore, StoreInfo};
+use crate::{constraints, AppError, LogEntry};
+use chrono::Utc;
+use serde::Deserialize;
+use std::fs::OpenOptions;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+// --- MessagePack storage for a data file ending in .msgpack ---
+// Entries are encoded one after another as compact arrays in field order, so appending
+// stays a single write and nothing is parsed as text on startup. Fields added to LogEntry
+// later have serde defaults, which older (shorter) arrays fall back to. `export` turns
+// the file back into CSV.
+pub fn is_msgpack(file_path: &str) -> bool {
+    Path::new(file_path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("msgpack"))
+}
+
+fn encode(entry: &LogEntry) -> Result<Vec<u8>, AppError> {
+    rmp_serde::to_vec(entry)
+        .map_err(|e| AppError::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+}
+
+fn read_entries(file_path: &str) -> Result<Vec<LogEntry>, AppError> {
+    let mut entries = Vec::new();
+    if !Path::new(file_path).exists() {
+        return Ok(entries);
+    }
+    let bytes = std::fs::read(file_path)?;
+    let mut cursor = Cursor::new(bytes.as_slice());
+    while (cursor.position() as usize) < bytes.len() {
+        let offset = cursor.position();
+        let mut de = rmp_serde::Deserializer::new(&mut cursor);
+        match LogEntry::deserialize(&mut de) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                // Without line breaks there's no next record to resync on
+                eprintln!(
+                    "Warning: Skipping the rest of {} from byte {}: {}",
+                    file_path, offset, e
+                );
+                break;
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn append(file_path: &str, entry: &LogEntry) -> Result<(), AppError> {
+    constraints::validate(std::slice::from_ref(entry))?;
+    if let Some(parent) = Path::new(file_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    file.write_all(&encode(entry)?)?;
+    file.sync_data()?;
+    Ok(())
+}
+
+fn write_entries(file_path: &str, entries: &[LogEntry]) -> Result<(), AppError> {
+    let mut bytes = Vec::new();
+    for entry in entries {
+        bytes.extend(encode(entry)?);
+    }
+    store::replace_file(file_path, &bytes)
+}
+
+pub struct MsgpackStore {
+    path: String,
+}
+
+impl MsgpackStore {
+    pub fn new(path: &str) -> MsgpackStore {
+        MsgpackStore {
+            path: path.to_string(),
+        }
+    }
+}
+
+impl MetricStore for MsgpackStore {
+    fn append(&self, entry: &LogEntry) -> Result<(), AppError> {
+        append(&self.path, entry)
+    }
+
+    fn all_entries(&self) -> Result<Vec<LogEntry>, AppError> {
+        read_entries(&self.path)
+    }
+
+    fn write_all(&self, entries: &[LogEntry]) -> Result<(), AppError> {
+        write_entries(&self.path, entries)
+    }
+
+    // Entries are in time order, so only the first one and the tail back to the
+    // last sleep answer need their timestamps parsed
+    fn info(&self) -> Result<StoreInfo, AppError> {
+        let entries = self.all_entries()?;
+        let today = Utc::now().date_naive();
+        let date = |entry: &LogEntry| entry.parsed_timestamp().map(|ts| ts.date_naive());
+        let mut info = StoreInfo {
+            first_entry_date: entries.iter().find_map(date),
+            last_entry_date: entries.iter().rev().find_map(date),
+            workout_logged_today: false,
+            last_sleep_hours: None,
+            last_sleep_quality: None,
+        };
+        for entry in entries.iter().rev() {
+            let day = date(entry);
+            if day == Some(today) {
+                info.workout_logged_today |= entry.workout_today;
+            }
+            info.last_sleep_hours = info.last_sleep_hours.or(entry.sleep_hours);
+            info.last_sleep_quality = info.last_sleep_quality.or(entry.sleep_quality);
+            let past_today = day.is_some_and(|d| d < today);
+            if past_today && info.last_sleep_hours.is_some() && info.last_sleep_quality.is_some() {
+                break;
+            }
+        }
+        Ok(info)
+    }
+}
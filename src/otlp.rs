@@ -0,0 +1,185 @@
+// --- OpenTelemetry export: daily summaries as OTLP gauges ---
+// Sends OTLP/HTTP with JSON encoding to a collector on the local network:
+//   otlp = { endpoint = "http://192.168.1.10:4318", service_name = "daily-metrics" }
+// Every metric becomes a gauge named daily_metrics.<metric> (the built-in name, so
+// renames don't break dashboards) with one point per day, stamped at midday UTC.
+use crate::cli::OtlpArgs;
+use crate::config::OtlpConfig;
+use crate::daily::{self, DailyPoint};
+use crate::units::DisplayUnits;
+use crate::{AppError, GlobalOptions, METRIC_NAMES};
+use chrono::{Duration, NaiveTime, Utc};
+use colored::*;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+const DEFAULT_DAYS: i64 = 7;
+const METRIC_PREFIX: &str = "daily_metrics";
+
+fn time_unix_nano(point: &DailyPoint) -> String {
+    let midday = point
+        .date
+        .and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    midday
+        .and_utc()
+        .timestamp_nanos_opt()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn gauge(name: &str, unit: &str, points: Vec<Value>) -> Value {
+    json!({
+        "name": format!("{}.{}", METRIC_PREFIX, name),
+        "unit": unit,
+        "gauge": { "dataPoints": points },
+    })
+}
+
+// The ExportMetricsServiceRequest body
+fn payload(options: &GlobalOptions, config: &OtlpConfig, points: &[DailyPoint]) -> Value {
+    let units = DisplayUnits::from_config(&options.config);
+    let mut attributes = Vec::new();
+    for (key, value) in [("user", &options.user), ("profile", &options.profile)] {
+        if let Some(value) = value {
+            attributes.push(json!({ "key": key, "value": { "stringValue": value } }));
+        }
+    }
+    let data_point = |point: &DailyPoint, value: f64| {
+        json!({
+            "timeUnixNano": time_unix_nano(point),
+            "asDouble": value,
+            "attributes": attributes,
+        })
+    };
+
+    let mut metrics: Vec<Value> = METRIC_NAMES
+        .iter()
+        .filter_map(|metric| {
+            let values: Vec<Value> = points
+                .iter()
+                .filter_map(|p| {
+                    let value = p.values.get(metric)?;
+                    Some(data_point(p, units.convert(metric, *value)))
+                })
+                .collect();
+            (!values.is_empty()).then(|| gauge(metric, units.unit(metric), values))
+        })
+        .collect();
+    let workouts = points
+        .iter()
+        .map(|p| data_point(p, if p.workout { 1.0 } else { 0.0 }))
+        .collect();
+    metrics.push(gauge("workout_today", "1", workouts));
+    let entries = points
+        .iter()
+        .map(|p| data_point(p, p.entries as f64))
+        .collect();
+    metrics.push(gauge("entries", "{entry}", entries));
+
+    let service = config.service_name.as_deref().unwrap_or(METRIC_PREFIX);
+    json!({
+        "resourceMetrics": [{
+            "resource": {
+                "attributes": [{ "key": "service.name", "value": { "stringValue": service } }],
+            },
+            "scopeMetrics": [{
+                "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                "metrics": metrics,
+            }],
+        }],
+    })
+}
+
+// --- Minimal HTTP/1.1 POST; collectors at home rarely sit behind TLS ---
+fn post(config: &OtlpConfig, body: &str) -> Result<(), AppError> {
+    let error = |msg: String| AppError::ConfigError(format!("otlp: {}", msg));
+    let Some(rest) = config.endpoint.strip_prefix("http://") else {
+        return Err(error(format!(
+            "endpoint '{}' must start with http:// (put a TLS proxy in front for https)",
+            config.endpoint
+        )));
+    };
+    let (host, base) = rest.split_once('/').unwrap_or((rest, ""));
+    let base = base.trim_end_matches('/');
+    let path = if base.is_empty() {
+        "/v1/metrics".to_string()
+    } else {
+        format!("/{}/v1/metrics", base)
+    };
+    let address = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        path,
+        host,
+        body.len()
+    );
+    for (name, value) in &config.headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+
+    let mut stream = TcpStream::connect(&address)?;
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(30)))?;
+    stream.write_all(request.as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status_line = response.lines().next().unwrap_or("");
+    let status = status_line.split_whitespace().nth(1).unwrap_or("");
+    if !status.starts_with('2') {
+        return Err(error(format!(
+            "collector at {} answered '{}'",
+            config.endpoint, status_line
+        )));
+    }
+    Ok(())
+}
+
+// --- `otlp` command: push the last few days' summaries to the configured collector ---
+pub fn run_otlp(options: &GlobalOptions, args: &OtlpArgs) -> Result<(), AppError> {
+    let days = if args.all {
+        None
+    } else {
+        Some(args.days.unwrap_or(DEFAULT_DAYS))
+    };
+    let Some(config) = &options.config.otlp else {
+        return Err(AppError::UsageError(
+            "No collector configured. Add e.g. to config.toml:\n  otlp = { endpoint = \"http://localhost:4318\" }"
+                .to_string(),
+        ));
+    };
+
+    let since = days.map(|n| Utc::now().date_naive() - Duration::days(n - 1));
+    let points: Vec<DailyPoint> = daily::load(options)?
+        .into_iter()
+        .filter(|p| since.is_none_or(|since| p.date >= since))
+        .collect();
+    if points.is_empty() {
+        println!("{}", "No days to export.".dimmed());
+        return Ok(());
+    }
+    let body = payload(options, config, &points).to_string();
+    if options.dry_run {
+        println!(
+            "{}",
+            format!(" Dry run: would send to {}", config.endpoint)
+                .bold()
+                .yellow()
+        );
+        println!("{}", body);
+        return Ok(());
+    }
+    post(config, &body)?;
+    println!(
+        "Exported {} day(s) to {}",
+        points.len().to_string().green(),
+        config.endpoint
+    );
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use crate::config::Config;
+use crate::constraints::configured_range;
+use crate::schema::{column_name, metric_info};
+use crate::store;
+use crate::{AppError, GlobalOptions, METRIC_NAMES};
+use chrono::{Duration, NaiveDate};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+// --- The question set of each goal phase, frozen when the phase starts ---
+// Config changes to which metrics are asked, or how they are worded and scaled, take
+// effect with the next phase, so one phase's answers all mean the same thing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Question {
+    pub metric: String,
+    pub name: String, // Column name at the time; a rename alone doesn't change the question
+    pub description: String,
+    pub low: String,
+    pub high: String,
+    pub unit: Option<String>,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Question {
+    fn same_as(&self, other: &Question) -> bool {
+        self.metric == other.metric
+            && self.description == other.description
+            && self.low == other.low
+            && self.high == other.high
+            && self.unit == other.unit
+            && self.min == other.min
+            && self.max == other.max
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Phase {
+    pub start: NaiveDate,
+    pub goal_days: i64,
+    pub questions: Vec<Question>,
+}
+
+fn phases_file(data_file: &str) -> PathBuf {
+    Path::new(data_file).with_extension("phases.json")
+}
+
+// Every recorded phase, oldest first
+pub fn read_phases(data_file: &str) -> Result<Vec<Phase>, AppError> {
+    let path = phases_file(data_file);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut phases: Vec<Phase> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    phases.sort_by_key(|p| p.start);
+    Ok(phases)
+}
+
+fn write_phases(data_file: &str, phases: &[Phase]) -> Result<(), AppError> {
+    let text = serde_json::to_string_pretty(phases)? + "\n";
+    store::replace_file(&phases_file(data_file).to_string_lossy(), text.as_bytes())
+}
+
+// The metrics the config asks about right now, with their wording and scale
+fn current_questions(config: &Config) -> Vec<Question> {
+    METRIC_NAMES
+        .iter()
+        .filter(|m| config.metric_enabled(m))
+        .filter_map(|m| {
+            let info = metric_info(config, m)?;
+            let (min, max) = configured_range(config, m)?;
+            Some(Question {
+                metric: m.to_string(),
+                name: column_name(m).to_string(),
+                description: info.description,
+                low: info.low,
+                high: info.high,
+                unit: info.unit,
+                min,
+                max,
+            })
+        })
+        .collect()
+}
+
+fn same_questions(a: &[Question], b: &[Question]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.same_as(y))
+}
+
+// Record the question set of a phase that starts now (replacing one with the same start)
+pub fn record(
+    data_file: &str,
+    config: &Config,
+    start: NaiveDate,
+    goal_days: i64,
+) -> Result<(), AppError> {
+    let mut phases = read_phases(data_file)?;
+    phases.retain(|p| p.start != start);
+    phases.push(Phase {
+        start,
+        goal_days,
+        questions: current_questions(config),
+    });
+    phases.sort_by_key(|p| p.start);
+    write_phases(data_file, &phases)
+}
+
+// The questions to ask in the phase starting at `start`: the recorded ones, or the
+// config's (recorded now) for a phase seen for the first time
+pub fn questions_for(options: &GlobalOptions, start: NaiveDate) -> Result<Vec<Question>, AppError> {
+    let data_file = options.data_file();
+    let current = current_questions(&options.config);
+    let Some(phase) = read_phases(&data_file)?
+        .into_iter()
+        .find(|p| p.start == start)
+    else {
+        if !options.dry_run {
+            record(&data_file, &options.config, start, options.goal_days)?;
+        }
+        return Ok(current);
+    };
+    if !same_questions(&phase.questions, &current) {
+        println!(
+            "{}",
+            format!(
+                "The questions are locked for the phase that began {}; config changes apply from the next phase.",
+                start
+            )
+            .dimmed()
+        );
+    }
+    Ok(phase.questions)
+}
+
+pub fn asks(questions: &[Question], metric: &str) -> bool {
+    questions.iter().any(|q| q.metric == metric)
+}
+
+// A note for analyses over from..=to when it spans phases asked different questions
+pub fn mixed_note(
+    data_file: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Option<String>, AppError> {
+    let phases = read_phases(data_file)?;
+    let mut covered: Vec<&Phase> = Vec::new();
+    for (i, phase) in phases.iter().enumerate() {
+        let ends_after_from = phases.get(i + 1).is_none_or(|next| next.start > from);
+        if phase.start <= to && ends_after_from {
+            covered.push(phase);
+        }
+    }
+    let changes: Vec<String> = covered
+        .windows(2)
+        .filter(|pair| !same_questions(&pair[0].questions, &pair[1].questions))
+        .map(|pair| pair[1].start.to_string())
+        .collect();
+    if changes.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "The questions changed with the phases starting {}: values from before and after may not compare.",
+        changes.join(", ")
+    )))
+}
+
+// --- `--phase`: analyses over one goal phase ---
+pub enum Which {
+    Number(usize), // Counted from 1, oldest first
+    Current,
+}
+
+impl Which {
+    pub fn parse(text: &str) -> Result<Which, String> {
+        let text = text.trim();
+        if text.eq_ignore_ascii_case("current") {
+            return Ok(Which::Current);
+        }
+        match text.parse::<usize>() {
+            Ok(number) if number > 0 => Ok(Which::Number(number)),
+            _ => Err(format!(
+                "--phase takes a phase number (1 is the first) or current, got '{}'",
+                text
+            )),
+        }
+    }
+}
+
+// The days of the phase chosen with --phase, from its start to the day before the next
+// one (or to the last possible day for the current phase); None without --phase
+pub fn selected_days(
+    options: &GlobalOptions,
+) -> Result<Option<RangeInclusive<NaiveDate>>, AppError> {
+    let Some(which) = &options.phase else {
+        return Ok(None);
+    };
+    let phases = read_phases(&options.data_file())?;
+    if phases.is_empty() {
+        return Err(AppError::UsageError(
+            "No goal phases are recorded yet (the first one is recorded when you log)".to_string(),
+        ));
+    }
+    let index = match which {
+        Which::Current => phases.len() - 1,
+        Which::Number(number) if *number <= phases.len() => number - 1,
+        Which::Number(number) => {
+            return Err(AppError::UsageError(format!(
+                "There is no phase {}: {} phase(s) are recorded",
+                number,
+                phases.len()
+            )))
+        }
+    };
+    let end = phases
+        .get(index + 1)
+        .map_or(NaiveDate::MAX, |next| next.start - Duration::days(1));
+    Ok(Some(phases[index].start..=end))
+}
@@ -0,0 +1,212 @@
+use crate::cli::{ProfileAction, ProfileArgs};
+use crate::config::{profile_config_file, Config, CONFIG_FILE};
+use crate::layout;
+use crate::{read_entries, validate_name, AppError, GlobalOptions};
+use colored::*;
+use std::collections::BTreeSet;
+use std::fs;
+use toml_edit::DocumentMut;
+
+// Settings a new profile must not share with the config it's copied from: its own data
+// file and goal counter, and the other profiles' sections
+const NOT_COPIED: [&str; 4] = ["data_file", "goal_start", "profile", "read_only"];
+
+// Profiles that exist in the data directory: a [profile.<name>] section in config.toml,
+// a config_<name>.toml or a daily_metrics_<name>.csv
+fn known_profiles(options: &GlobalOptions, base: &Config) -> BTreeSet<String> {
+    let mut names: BTreeSet<String> = base.profile.keys().cloned().collect();
+    let dir = options.data_dir();
+    let dir = if dir.as_os_str().is_empty() {
+        std::path::PathBuf::from(".")
+    } else {
+        dir
+    };
+    let Ok(listing) = fs::read_dir(dir) else {
+        return names;
+    };
+    for item in listing.flatten() {
+        let file_name = item.file_name().to_string_lossy().into_owned();
+        let name = file_name
+            .strip_prefix("config_")
+            .and_then(|rest| rest.strip_suffix(".toml"))
+            .or_else(|| {
+                file_name
+                    .strip_prefix("daily_metrics_")
+                    .and_then(|rest| rest.strip_suffix(".csv"))
+            });
+        // Year files (daily_metrics_2025.csv, daily_metrics_work_2025.csv) aren't profiles
+        let year_file = |n: &str| {
+            let year = n.rsplit('_').next().unwrap_or(n);
+            year.len() == 4 && year.chars().all(|c| c.is_ascii_digit())
+        };
+        if let Some(name) = name.filter(|n| !year_file(n)) {
+            if validate_name("profile", name).is_ok() {
+                names.insert(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+// A profile's data file: from its own config, its [profile.<name>] section, or the default
+fn profile_data_file(
+    options: &GlobalOptions,
+    base: &Config,
+    name: Option<&str>,
+) -> Result<String, AppError> {
+    let own = name.and_then(|n| options.own_config_file(n));
+    let own_config = own.as_deref().map(Config::load).transpose()?;
+    let config = own_config.as_ref().unwrap_or(base);
+    Ok(options
+        .configured_data_file(config, name, own.is_some())
+        .unwrap_or_else(|| options.default_data_file(name)))
+}
+
+// Another profile's data file, for analyses that join it with the selected one by date
+pub fn joined_data_file(options: &GlobalOptions, name: &str) -> Result<String, AppError> {
+    validate_name("profile", name).map_err(AppError::UsageError)?;
+    if options.profile.as_deref() == Some(name) {
+        return Err(AppError::UsageError(format!(
+            "'{}' is the selected profile; join it from another one",
+            name
+        )));
+    }
+    let base = Config::load(&options.data_dir().join(CONFIG_FILE))?;
+    if !known_profiles(options, &base).contains(name) {
+        return Err(AppError::UsageError(format!(
+            "No profile named '{}' (`profile list` shows them)",
+            name
+        )));
+    }
+    profile_data_file(options, &base, Some(name))
+}
+
+fn list(options: &GlobalOptions) -> Result<(), AppError> {
+    let base = Config::load(&options.data_dir().join(CONFIG_FILE))?;
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Profiles ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    layout::row(&format!(
+        "  {:<12} {:<20} {:>7} {:<10}  Data file",
+        "Profile", "Settings", "Entries", "Last day"
+    ));
+    let names = std::iter::once(None).chain(known_profiles(options, &base).into_iter().map(Some));
+    for name in names {
+        let own = name.as_deref().and_then(|n| options.own_config_file(n));
+        let settings = match (&own, name.as_deref()) {
+            (Some(path), _) => path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            (None, Some(n)) if base.profile.contains_key(n) => format!("[profile.{}]", n),
+            _ => CONFIG_FILE.to_string(),
+        };
+        let data_file = profile_data_file(options, &base, name.as_deref())?;
+        let entries = read_entries(&data_file)?;
+        let last_day = entries
+            .iter()
+            .filter_map(|e| e.parsed_timestamp())
+            .map(|ts| ts.date_naive())
+            .max()
+            .map_or("-".to_string(), |d| d.to_string());
+        let selected = name == options.profile;
+        layout::row(&format!(
+            "{} {:<12} {:<20} {:>7} {:<10}  {}",
+            if selected { "*" } else { " " },
+            name.as_deref().unwrap_or("(default)"),
+            settings,
+            entries.len(),
+            last_day,
+            data_file.dimmed()
+        ));
+    }
+    println!();
+    println!(
+        "{}",
+        "Use one with --profile <name>; `profile new <name>` gives it a config of its own."
+            .dimmed()
+    );
+    Ok(())
+}
+
+// A config_<name>.toml copied from config.toml, so the profile starts with the same
+// questions and settings and changes them without touching anyone else's. Settings under
+// its [profile.<name>] section move to the top level of the new file.
+fn new(options: &GlobalOptions, name: &str) -> Result<(), AppError> {
+    validate_name("profile", name).map_err(AppError::UsageError)?;
+    let path = options.data_dir().join(profile_config_file(name));
+    if path.exists() {
+        return Err(AppError::UsageError(format!(
+            "{} already exists",
+            path.display()
+        )));
+    }
+    let base = options.data_dir().join(CONFIG_FILE);
+    let text = if base.exists() {
+        fs::read_to_string(&base)?
+    } else {
+        String::new()
+    };
+    let mut doc: DocumentMut = text
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("{}: {}", base.display(), e)))?;
+    let section = doc
+        .get("profile")
+        .and_then(|p| p.get(name))
+        .and_then(|s| s.as_table_like())
+        .map(|s| {
+            s.iter()
+                .map(|(key, item)| (key.to_string(), item.clone()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    for key in NOT_COPIED {
+        doc.remove(key);
+    }
+    for (key, item) in section {
+        doc.insert(&key, item);
+    }
+    let text = format!(
+        "# Settings for the {} profile (--profile {}); config.toml isn't read for it\n{}",
+        name, name, doc
+    );
+    if options.dry_run {
+        println!(
+            "{}",
+            format!("Dry run: would write {}", path.display()).yellow()
+        );
+        return Ok(());
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, text)?;
+    let config = Config::load(&path)?;
+    let data_file = options
+        .configured_data_file(&config, Some(name), true)
+        .unwrap_or_else(|| options.default_data_file(Some(name)));
+    println!("{}", format!("Created {}", path.display()).green());
+    println!(
+        "{}",
+        format!(
+            "Its entries go to {}; log with --profile {}.",
+            data_file, name
+        )
+        .dimmed()
+    );
+    Ok(())
+}
+
+// --- `profile` command: the profiles on this machine, or a new one with its own config ---
+pub fn run_profile(options: &GlobalOptions, args: &ProfileArgs) -> Result<(), AppError> {
+    match args.action {
+        ProfileAction::List => list(options),
+        ProfileAction::New => match &args.name {
+            Some(name) => new(options, name),
+            None => Err(AppError::UsageError(
+                "profile new needs a name, e.g. profile new partner".to_string(),
+            )),
+        },
+    }
+}
@@ -0,0 +1,56 @@
+// --- statsd / Graphite push after each log ---
+// push = { protocol = "statsd", address = "127.0.0.1:8125", prefix = "personal" }
+// Every answered metric of the new entry goes out as <prefix>.<metric> (the built-in
+// name, so renames keep the same paths), plus workout_today as 0 or 1. statsd gets
+// gauges over UDP; Graphite gets its plaintext protocol over TCP (usually port 2003).
+use crate::config::PushConfig;
+use crate::{AppError, LogEntry, METRIC_NAMES};
+use chrono::Utc;
+use std::io::Write;
+use std::net::{TcpStream, UdpSocket};
+use std::time::Duration;
+
+pub const PROTOCOLS: [&str; 2] = ["statsd", "graphite"];
+const DEFAULT_PREFIX: &str = "personal";
+
+fn values(entry: &LogEntry) -> Vec<(&'static str, f64)> {
+    let mut values: Vec<(&'static str, f64)> = METRIC_NAMES
+        .iter()
+        .filter_map(|m| Some((*m, entry.metric(m)?)))
+        .collect();
+    values.push(("workout_today", if entry.workout_today { 1.0 } else { 0.0 }));
+    values
+}
+
+pub fn push_entry(config: &PushConfig, entry: &LogEntry) -> Result<usize, AppError> {
+    let prefix = config.prefix.as_deref().unwrap_or(DEFAULT_PREFIX);
+    let path = |metric: &str| match prefix {
+        "" => metric.to_string(),
+        prefix => format!("{}.{}", prefix, metric),
+    };
+    let values = values(entry);
+    match config.protocol.as_str() {
+        "statsd" => {
+            let socket = UdpSocket::bind("0.0.0.0:0")?;
+            let lines: Vec<String> = values
+                .iter()
+                .map(|(metric, value)| format!("{}:{}|g", path(metric), value))
+                .collect();
+            socket.send_to(lines.join("\n").as_bytes(), &config.address)?;
+        }
+        _ => {
+            let timestamp = entry
+                .parsed_timestamp()
+                .unwrap_or_else(Utc::now)
+                .timestamp();
+            let mut text = String::new();
+            for (metric, value) in &values {
+                text.push_str(&format!("{} {} {}\n", path(metric), value, timestamp));
+            }
+            let mut stream = TcpStream::connect(&config.address)?;
+            stream.set_write_timeout(Some(Duration::from_secs(10)))?;
+            stream.write_all(text.as_bytes())?;
+        }
+    }
+    Ok(values.len())
+}
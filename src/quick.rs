@@ -0,0 +1,244 @@
+use crate::baseline;
+use crate::cli::QuickArgs;
+use crate::layout;
+use crate::schema::{canonical_metric, column_name};
+use crate::trash;
+use crate::units::DisplayUnits;
+use crate::{
+    metric_range, push_saved_entry, row_preview, store, termux, AppError, EntryKind, GlobalOptions,
+    LogEntry,
+};
+use chrono::Utc;
+use colored::*;
+
+// Short keys for `quick`; full metric names (or their aliases) work as well
+const KEYS: [(&str, &str); 10] = [
+    ("sh", "sleep_hours"),
+    ("sq", "sleep_quality"),
+    ("s", "sleepiness"),
+    ("z", "zonkedness"),
+    ("m", "mid_vibes"),
+    ("e", "energy"),
+    ("st", "strength"),
+    ("f", "focus"),
+    ("i", "intelligence"),
+    ("w", "workout_today"),
+];
+
+// Split the line like a shell would for double quotes, so the whole answer can also be
+// passed as one argument: quick 's3 e7 "good day"'
+fn tokens(args: &[String]) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for arg in args {
+        let mut current = String::new();
+        let mut quoted = false;
+        for ch in arg.chars() {
+            match ch {
+                '"' => quoted = !quoted,
+                ' ' if !quoted => {
+                    if !current.is_empty() {
+                        tokens.push(std::mem::take(&mut current));
+                    }
+                }
+                _ => current.push(ch),
+            }
+        }
+        if !current.is_empty() {
+            tokens.push(current);
+        }
+    }
+    tokens
+}
+
+// "st6" -> ("strength", 6.0); None for a word of the remarks. A word followed by a number
+// or '=' is meant as an answer, so an unknown key or a bad value is an error rather than
+// a remark: a typo would otherwise save an entry without the value.
+fn answer(token: &str) -> Result<Option<(&'static str, f64)>, AppError> {
+    let Some(split) = token.find(|c: char| !(c.is_ascii_alphabetic() || c == '_')) else {
+        return Ok(None);
+    };
+    let (key, number) = token.split_at(split);
+    if key.is_empty() || !number.starts_with(|c: char| c == '=' || c.is_ascii_digit()) {
+        return Ok(None);
+    }
+    let metric = KEYS
+        .iter()
+        .find(|(short, _)| short.eq_ignore_ascii_case(key))
+        .map(|(_, metric)| *metric)
+        .or_else(|| canonical_metric(key))
+        .or_else(|| (key == "workout_today").then_some("workout_today"))
+        .ok_or_else(|| {
+            AppError::UsageError(format!(
+                "'{}': unknown key '{}' (use {} or a metric name)",
+                token,
+                key,
+                KEYS.map(|(short, _)| short).join(" ")
+            ))
+        })?;
+    let number = number
+        .trim_start_matches('=')
+        .parse()
+        .map_err(|_| AppError::UsageError(format!("'{}': the value isn't a number", token)))?;
+    Ok(Some((metric, number)))
+}
+
+// --- `quick` command: a whole entry from one line, e.g. quick s3 z2 e7 st6 f8 i7 w1 "good day" ---
+// Anything not given stays empty, except that follow-ups copy the day's sleep and workout
+// and corrections start from the entry they replace, the way `log` does.
+pub fn run_quick(options: &GlobalOptions, args: &QuickArgs) -> Result<(), AppError> {
+    let units = DisplayUnits::from_config(&options.config);
+    let data_file = options.data_file();
+    let store = store::open(&data_file);
+    let store_info = store.info()?;
+    let now = Utc::now();
+    let today = now.date_naive();
+
+    let logged_today = store_info.last_entry_date == Some(today);
+    if args.correction && !logged_today {
+        return Err(AppError::UsageError(
+            "Nothing logged today to correct".to_string(),
+        ));
+    }
+    if logged_today && !args.follow_up && !args.correction {
+        return Err(AppError::UsageError(
+            "Today is already logged: add --follow-up for another reading or --correction to replace the last one".to_string(),
+        ));
+    }
+    let mut today_entries = if logged_today {
+        store.read_range(today, today)?
+    } else {
+        Vec::new()
+    };
+    let replaced = if args.correction {
+        today_entries.pop()
+    } else {
+        None
+    };
+
+    let mut entry = match &replaced {
+        Some(old) => LogEntry {
+            timestamp: now.to_rfc3339(),
+            session_seconds: None,
+            session_edits: None,
+            ..old.clone()
+        },
+        None => LogEntry::empty_at(now),
+    };
+    let first_ever_date = store_info.first_entry_date.unwrap_or(today);
+    entry.day_count = (today - first_ever_date).num_days() + 1;
+    entry.provenance = "quick".to_string();
+    entry.entry_kind = Some(if !logged_today {
+        EntryKind::FirstOfDay
+    } else if args.correction {
+        EntryKind::Correction
+    } else {
+        EntryKind::FollowUp
+    });
+    let mut remarks = Vec::new();
+    let mut answered = false;
+    for token in tokens(&args.answers) {
+        let Some((metric, value)) = answer(&token)? else {
+            remarks.push(token);
+            continue;
+        };
+        answered = true;
+        if !options.config.metric_enabled(metric) {
+            return Err(AppError::UsageError(format!(
+                "{} is turned off in the config (metric on {} turns it back on)",
+                column_name(metric),
+                column_name(metric)
+            )));
+        }
+        let stored = units.to_stored(metric, value);
+        let (min, max) = metric_range(metric);
+        if !(min..=max).contains(&stored) {
+            return Err(AppError::UsageError(format!(
+                "{} = {} is outside {} to {}",
+                token,
+                value,
+                units.convert(metric, min),
+                units.convert(metric, max)
+            )));
+        }
+        if metric == "workout_today" {
+            entry.workout_today = stored >= 0.5;
+        } else {
+            entry.set_metric(metric, stored);
+        }
+    }
+    if replaced.is_none() && !answered {
+        return Err(AppError::UsageError(
+            "Nothing to log: give at least one answer, e.g. quick e7".to_string(),
+        ));
+    }
+    if replaced.is_none() || !remarks.is_empty() {
+        entry.remarks = remarks.join(" ");
+    }
+
+    // Only today's own entries: an earlier day's sleep isn't this night's
+    if entry.entry_kind == Some(EntryKind::FollowUp) {
+        let sleep =
+            |value: fn(&LogEntry) -> Option<f32>| today_entries.iter().rev().find_map(value);
+        entry.sleep_hours = entry.sleep_hours.or(sleep(|e| e.sleep_hours));
+        entry.sleep_quality = entry.sleep_quality.or(sleep(|e| e.sleep_quality));
+        entry.workout_today |= today_entries.iter().any(|e| e.workout_today);
+    }
+
+    let summary: Vec<String> = KEYS
+        .iter()
+        .filter_map(|(_, metric)| {
+            let value = entry.metric(metric)?;
+            Some(format!(
+                "{} {}",
+                column_name(metric),
+                units.convert(metric, value)
+            ))
+        })
+        .chain(entry.workout_today.then(|| "workout".to_string()))
+        .collect();
+
+    if options.dry_run {
+        crate::constraints::validate(std::slice::from_ref(&entry))?;
+        println!(
+            "{}",
+            format!(" Dry run: nothing written to {}", data_file)
+                .bold()
+                .yellow()
+        );
+        print!("{}", row_preview(&data_file, &entry)?);
+        return Ok(());
+    }
+    let trash_id = match replaced {
+        Some(old) => Some(trash::move_to_trash(
+            &data_file,
+            vec![old],
+            "correction",
+            |entries| entries.push(entry.clone()),
+        )?),
+        None => {
+            store.append(&entry)?;
+            None
+        }
+    };
+    termux::clear_reminder();
+    layout::row(&format!(
+        "{} {}",
+        "Logged:".bold().green(),
+        summary.join(", ")
+    ));
+    if let Some(id) = trash_id {
+        println!(
+            "{}",
+            format!(
+                "Replaced the previous entry (undo with: trash restore {})",
+                id
+            )
+            .dimmed()
+        );
+    }
+    push_saved_entry(options, &entry);
+    if let Err(e) = baseline::alert(options, &entry) {
+        eprintln!("Warning: Could not compare with the usual values: {}", e);
+    }
+    Ok(())
+}
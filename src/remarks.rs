@@ -0,0 +1,343 @@
+use crate::analysis::{correlation, metric_series, Series};
+use crate::cli::{OutputFormat, Period, RemarksArgs};
+use crate::daily;
+use crate::layout;
+use crate::{AppError, GlobalOptions, LogEntry};
+use chrono::{Datelike, NaiveDate};
+use colored::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+const PERIOD_COLUMNS: usize = 6; // Most recent weeks or months shown in the keyword table
+
+// Small built-in lexicons: enough to tell a good day's note from a bad one
+const POSITIVE: [&str; 36] = [
+    "good",
+    "great",
+    "awesome",
+    "amazing",
+    "happy",
+    "calm",
+    "relaxed",
+    "rested",
+    "energized",
+    "energetic",
+    "productive",
+    "focused",
+    "sharp",
+    "fresh",
+    "strong",
+    "fun",
+    "nice",
+    "excellent",
+    "better",
+    "best",
+    "love",
+    "loved",
+    "enjoyed",
+    "grateful",
+    "motivated",
+    "peaceful",
+    "proud",
+    "clear",
+    "easy",
+    "solid",
+    "fantastic",
+    "refreshed",
+    "content",
+    "excited",
+    "progress",
+    "win",
+];
+const NEGATIVE: [&str; 36] = [
+    "bad",
+    "awful",
+    "terrible",
+    "tired",
+    "exhausted",
+    "sick",
+    "ill",
+    "sad",
+    "stressed",
+    "stress",
+    "anxious",
+    "anxiety",
+    "angry",
+    "annoyed",
+    "groggy",
+    "foggy",
+    "headache",
+    "pain",
+    "sore",
+    "hungover",
+    "lazy",
+    "worse",
+    "worst",
+    "hard",
+    "rough",
+    "poor",
+    "drained",
+    "burnt",
+    "burned",
+    "overwhelmed",
+    "lonely",
+    "bored",
+    "frustrated",
+    "cold",
+    "nauseous",
+    "insomnia",
+];
+const NEGATIONS: [&str; 5] = ["not", "no", "never", "didnt", "wasnt"];
+// Words too common to say anything about the day
+const STOPWORDS: [&str; 48] = [
+    "the", "and", "but", "for", "with", "was", "were", "had", "have", "has", "did", "this", "that",
+    "then", "than", "from", "into", "just", "very", "really", "today", "day", "got", "some", "all",
+    "too", "out", "after", "before", "about", "again", "also", "still", "bit", "its", "it's",
+    "been", "are", "not", "didnt", "wasnt", "a", "an", "of", "to", "in", "on", "at",
+];
+
+fn words(remarks: &str) -> Vec<String> {
+    remarks
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.trim_matches('\'').replace('\'', "").to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+// Tone of one note from -1 (only negative words) to +1 (only positive ones); None when
+// it has no lexicon words. A negation right before a word flips it ("not good").
+fn sentiment(remarks: &str) -> Option<f64> {
+    let words = words(remarks);
+    let (mut positive, mut negative) = (0.0, 0.0);
+    for (i, word) in words.iter().enumerate() {
+        let negated = i > 0 && NEGATIONS.contains(&words[i - 1].as_str());
+        let sign = match word.as_str() {
+            w if POSITIVE.contains(&w) => 1.0,
+            w if NEGATIVE.contains(&w) => -1.0,
+            _ => continue,
+        };
+        if sign * if negated { -1.0 } else { 1.0 } > 0.0 {
+            positive += 1.0;
+        } else {
+            negative += 1.0;
+        }
+    }
+    (positive + negative > 0.0).then(|| (positive - negative) / (positive + negative))
+}
+
+fn keywords(remarks: &str) -> impl Iterator<Item = String> {
+    words(remarks)
+        .into_iter()
+        .filter(|w| w.len() >= 3 && !w.chars().all(|c| c.is_ascii_digit()))
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+}
+
+fn period(date: NaiveDate, by: Period) -> String {
+    match by {
+        Period::Week => {
+            let week = date.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        }
+        Period::Month => date.format("%Y-%m").to_string(),
+    }
+}
+
+// One row per logged day with remarks: the tone next to the metrics, for plotting
+#[derive(Serialize)]
+struct DayRow {
+    date: NaiveDate,
+    sentiment: Option<f64>,
+    keywords: String, // Space separated, most frequent first
+    #[serde(flatten)]
+    metrics: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct KeywordRow {
+    keyword: String,
+    total: usize,
+    periods: BTreeMap<String, usize>,
+}
+
+// --- `remarks-analysis` command: keyword trends and the tone of the remarks ---
+pub fn run_remarks_analysis(options: &GlobalOptions, args: &RemarksArgs) -> Result<(), AppError> {
+    let entries: Vec<LogEntry> = options
+        .analysis_entries()?
+        .into_iter()
+        .filter(|e| !e.remarks.trim().is_empty())
+        .collect();
+
+    // Per-day tone (mean over the notes that have one) and keyword counts
+    let mut tones: BTreeMap<NaiveDate, Vec<f64>> = BTreeMap::new();
+    let mut day_words: BTreeMap<NaiveDate, HashMap<String, usize>> = BTreeMap::new();
+    let mut totals: HashMap<String, usize> = HashMap::new();
+    let mut by_period: HashMap<String, BTreeMap<String, usize>> = HashMap::new();
+    for entry in &entries {
+        let Some(date) = entry.parsed_timestamp().map(|ts| ts.date_naive()) else {
+            continue;
+        };
+        let tone = tones.entry(date).or_default();
+        tone.extend(sentiment(&entry.remarks));
+        for word in keywords(&entry.remarks) {
+            *day_words
+                .entry(date)
+                .or_default()
+                .entry(word.clone())
+                .or_default() += 1;
+            *by_period
+                .entry(word.clone())
+                .or_default()
+                .entry(period(date, args.by))
+                .or_default() += 1;
+            *totals.entry(word).or_default() += 1;
+        }
+    }
+    let tone: Series = tones
+        .iter()
+        .filter(|(_, scores)| !scores.is_empty())
+        .map(|(date, scores)| (*date, scores.iter().sum::<f64>() / scores.len() as f64))
+        .collect();
+    let mut top: Vec<(String, usize)> = totals.into_iter().collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top.truncate(args.top);
+    let keyword_rows: Vec<KeywordRow> = top
+        .iter()
+        .map(|(word, total)| KeywordRow {
+            keyword: word.clone(),
+            total: *total,
+            periods: by_period.remove(word).unwrap_or_default(),
+        })
+        .collect();
+
+    let series = metric_series(&daily::load(options)?);
+    if options.format != OutputFormat::Table {
+        let days: Vec<DayRow> = tones
+            .keys()
+            .map(|date| {
+                let mut counted: Vec<(&String, &usize)> =
+                    day_words.get(date).into_iter().flatten().collect();
+                counted.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                let metrics = series
+                    .iter()
+                    .map(|(name, values)| {
+                        let value = values.get(date).map_or(Value::Null, |v| Value::from(*v));
+                        (name.clone(), value)
+                    })
+                    .collect();
+                DayRow {
+                    date: *date,
+                    sentiment: tone.get(date).copied(),
+                    keywords: counted
+                        .iter()
+                        .map(|(w, _)| w.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    metrics,
+                }
+            })
+            .collect();
+        if options.format == OutputFormat::Json {
+            let report = serde_json::json!({ "days": days, "keywords": keyword_rows });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            // Flattened maps can't go through serialize(), so the header is written by hand
+            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+            let mut header = vec!["date", "sentiment", "keywords"];
+            header.extend(series.iter().map(|(name, _)| name.as_str()));
+            wtr.write_record(&header)?;
+            for day in &days {
+                let mut record = vec![
+                    day.date.to_string(),
+                    day.sentiment.map_or(String::new(), |s| format!("{:.3}", s)),
+                    day.keywords.clone(),
+                ];
+                record.extend(series.iter().map(|(name, _)| match day.metrics.get(name) {
+                    Some(Value::Null) | None => String::new(),
+                    Some(value) => value.to_string(),
+                }));
+                wtr.write_record(&record)?;
+            }
+            wtr.flush()?;
+        }
+        return Ok(());
+    }
+
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Remarks ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    if entries.is_empty() {
+        println!("{}", "No remarks yet.".dimmed());
+        return Ok(());
+    }
+    let scored: Vec<f64> = entries
+        .iter()
+        .filter_map(|e| sentiment(&e.remarks))
+        .collect();
+    println!(
+        "Entries with remarks: {}  Days: {}",
+        entries.len().to_string().yellow(),
+        tones.len().to_string().yellow()
+    );
+    if !scored.is_empty() {
+        let mean = scored.iter().sum::<f64>() / scored.len() as f64;
+        let shown = format!("{:+.2}", mean);
+        println!(
+            "Mean tone: {} {}",
+            if mean >= 0.0 {
+                shown.green()
+            } else {
+                shown.red()
+            },
+            format!("(-1 to +1, from {} notes with mood words)", scored.len()).dimmed()
+        );
+    }
+
+    println!();
+    println!("{}", "Tone alongside the metrics".bold());
+    let mut links: Vec<(&String, f64, usize)> = series
+        .iter()
+        .filter_map(|(name, values)| {
+            let (r, days) = correlation(&tone, values)?;
+            Some((name, r, days))
+        })
+        .collect();
+    links.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+    if links.is_empty() {
+        println!("{}", "  Not enough days with mood words yet.".dimmed());
+    }
+    for (name, r, days) in &links {
+        layout::row(&format!(
+            "  {:<14} r = {:+.2} {}",
+            name,
+            r,
+            format!("({} days)", days).dimmed()
+        ));
+    }
+
+    println!();
+    let periods: Vec<String> = {
+        let mut all: Vec<String> = tones.keys().map(|d| period(*d, args.by)).collect();
+        all.dedup();
+        all.split_off(all.len().saturating_sub(PERIOD_COLUMNS))
+    };
+    let mut line = format!("{:<14} {:>5}", "Keyword", "Total");
+    for p in &periods {
+        line.push_str(&format!(" {:>8}", p));
+    }
+    layout::row(&line.bold().to_string());
+    for row in &keyword_rows {
+        let mut line = format!("{:<14} {:>5}", row.keyword, row.total);
+        for p in &periods {
+            let count = row.periods.get(p).copied().unwrap_or(0);
+            let cell = format!(" {:>8}", count);
+            line.push_str(&if count == 0 {
+                cell.dimmed().to_string()
+            } else {
+                cell
+            });
+        }
+        layout::row(&line);
+    }
+    Ok(())
+}
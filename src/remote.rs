@@ -0,0 +1,294 @@
+use crate::store::{self, MetricStore, StoreInfo};
+use crate::{AppError, LogEntry};
+use chrono::NaiveDate;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+// --- Data file on another machine (`--remote user@host[:path]`) ---
+// Everything goes through the system `ssh`, so hosts, keys, jump hosts and connection
+// sharing (ControlMaster) come from ~/.ssh/config. The entries are fetched once per
+// command into a local copy beside the other files, and every write locks the remote
+// file, checks it hasn't changed since it was read and uploads the whole file again.
+// Milestones, the audit log and other sidecar files stay next to the local copy.
+#[derive(Clone)]
+pub struct Remote {
+    pub host: String, // Anything `ssh` accepts: user@host or a Host alias
+    pub path: String, // Relative paths start in the remote home directory
+}
+
+impl Remote {
+    // "user@host:logs/daily_metrics.csv", or just "user@host" for the default file name
+    pub fn parse(spec: &str, default_file: &str) -> Result<Remote, String> {
+        let (host, path) = match spec.split_once(':') {
+            Some((host, path)) => (host, path.trim()),
+            None => (spec, ""),
+        };
+        let host = host.trim();
+        if host.is_empty() || host.starts_with('-') {
+            return Err(format!("--remote needs user@host[:path], got '{}'", spec));
+        }
+        let path = path.strip_prefix("~/").unwrap_or(path);
+        Ok(Remote {
+            host: host.to_string(),
+            path: if path.is_empty() {
+                default_file.to_string()
+            } else {
+                path.to_string()
+            },
+        })
+    }
+
+    // Where the local copy lives, e.g. remote/me@server/daily_metrics.csv
+    pub fn cache_file(&self, data_dir: &Path) -> PathBuf {
+        let host: String = self
+            .host
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || "@.-_".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let name = Path::new(&self.path)
+            .file_name()
+            .map_or_else(|| self.path.clone().into(), |n| n.to_os_string());
+        data_dir.join("remote").join(host).join(name)
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{}:{}", self.host, self.path)
+    }
+}
+
+struct Installed {
+    remote: Remote,
+    cache: String,
+    fetched: Mutex<Option<Vec<u8>>>, // The remote file as last read (empty when missing)
+}
+
+static REMOTE: OnceLock<Installed> = OnceLock::new();
+
+// Called once at startup when --remote is given; `cache` is the local data file
+pub fn install(remote: Remote, cache: &str) {
+    let _ = REMOTE.set(Installed {
+        remote,
+        cache: cache.to_string(),
+        fetched: Mutex::new(None),
+    });
+}
+
+// The store for the data file when it's the local copy of a remote one
+pub fn store_for(file_path: &str) -> Option<Box<dyn MetricStore>> {
+    let installed = REMOTE.get().filter(|r| r.cache == file_path)?;
+    Some(Box::new(RemoteStore { installed }))
+}
+
+// Quote for the remote shell: single quotes, with embedded ones closed and escaped
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn ssh(remote: &Remote, command: &str, input: Option<&[u8]>) -> Result<Output, AppError> {
+    let mut child = Command::new("ssh")
+        .arg("--")
+        .arg(&remote.host)
+        .arg(command)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::inherit() // A password or host key prompt can still be answered
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::RemoteError(format!("could not run ssh: {}", e)))?;
+    if let (Some(bytes), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(bytes)?;
+    }
+    Ok(child.wait_with_output()?)
+}
+
+fn failed(remote: &Remote, action: &str, output: &Output) -> AppError {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    AppError::RemoteError(format!(
+        "{} {} failed: {}",
+        action,
+        remote.describe(),
+        stderr.trim()
+    ))
+}
+
+// The remote file's contents (empty when it doesn't exist yet)
+fn fetch(remote: &Remote) -> Result<Vec<u8>, AppError> {
+    let path = quote(&remote.path);
+    let output = ssh(
+        remote,
+        &format!("if [ -e {} ]; then cat {}; fi", path, path),
+        None,
+    )?;
+    if !output.status.success() {
+        return Err(failed(remote, "reading", &output));
+    }
+    Ok(output.stdout)
+}
+
+// Written beside the file and renamed over it, like a local rewrite
+fn upload(remote: &Remote, bytes: &[u8]) -> Result<(), AppError> {
+    let path = quote(&remote.path);
+    let tmp = quote(&format!("{}.tmp", remote.path));
+    let output = ssh(
+        remote,
+        &format!("cat > {} && mv {} {}", tmp, tmp, path),
+        Some(bytes),
+    )?;
+    if !output.status.success() {
+        return Err(failed(remote, "writing", &output));
+    }
+    Ok(())
+}
+
+// --- Lock on the remote file: a directory, since mkdir either creates it or fails ---
+struct RemoteLock<'a> {
+    remote: &'a Remote,
+}
+
+fn lock(remote: &Remote) -> Result<RemoteLock<'_>, AppError> {
+    let dir = quote(&format!("{}.lock", remote.path));
+    // Exit status 3 when the lock is already there; anything else is a real failure
+    let output = ssh(
+        remote,
+        &format!(
+            "mkdir {} 2>/dev/null || {{ [ -d {} ] && exit 3; mkdir {}; }}",
+            dir, dir, dir
+        ),
+        None,
+    )?;
+    if output.status.code() == Some(3) {
+        return Err(AppError::RemoteError(format!(
+            "{} is being written by another session (if none is running, remove {}.lock on {})",
+            remote.describe(),
+            remote.path,
+            remote.host
+        )));
+    }
+    if !output.status.success() {
+        return Err(failed(remote, "locking", &output));
+    }
+    Ok(RemoteLock { remote })
+}
+
+impl Drop for RemoteLock<'_> {
+    fn drop(&mut self) {
+        let dir = quote(&format!("{}.lock", self.remote.path));
+        if !matches!(ssh(self.remote, &format!("rmdir {}", dir), None), Ok(o) if o.status.success())
+        {
+            eprintln!(
+                "Warning: Could not remove {}.lock on {}",
+                self.remote.path, self.remote.host
+            );
+        }
+    }
+}
+
+struct RemoteStore {
+    installed: &'static Installed,
+}
+
+impl RemoteStore {
+    // The local copy's own store reads and writes the bytes in the file's format
+    fn local(&self) -> Box<dyn MetricStore> {
+        store::open_file(&self.installed.cache)
+    }
+
+    fn save_local(&self, bytes: &[u8]) -> Result<(), AppError> {
+        if let Some(parent) = Path::new(&self.installed.cache).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if bytes.is_empty() {
+            // A new remote file: no local copy, so the first write starts a new file
+            if Path::new(&self.installed.cache).exists() {
+                fs::remove_file(&self.installed.cache)?;
+            }
+            return Ok(());
+        }
+        store::replace_file(&self.installed.cache, bytes)
+    }
+
+    fn fetched(&self) -> std::sync::MutexGuard<'_, Option<Vec<u8>>> {
+        self.installed
+            .fetched
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Fetch once per command; later reads use the local copy
+    fn refresh(&self) -> Result<(), AppError> {
+        let mut fetched = self.fetched();
+        if fetched.is_none() {
+            let bytes = fetch(&self.installed.remote)?;
+            self.save_local(&bytes)?;
+            *fetched = Some(bytes);
+        }
+        Ok(())
+    }
+
+    // Change the local copy under the remote lock, then upload it. A rewrite is refused
+    // when the remote file changed after it was read, since it would undo that change.
+    fn write(
+        &self,
+        check_unchanged: bool,
+        change: impl FnOnce(&dyn MetricStore) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        let remote = &self.installed.remote;
+        let _lock = lock(remote)?;
+        let current = fetch(remote)?;
+        let mut fetched = self.fetched();
+        if check_unchanged && fetched.as_ref().is_some_and(|seen| *seen != current) {
+            return Err(AppError::RemoteError(format!(
+                "{} changed on the server while this command ran; run it again",
+                remote.describe()
+            )));
+        }
+        self.save_local(&current)?;
+        change(&*self.local())?;
+        let bytes = fs::read(&self.installed.cache)?;
+        upload(remote, &bytes)?;
+        *fetched = Some(bytes);
+        Ok(())
+    }
+}
+
+impl MetricStore for RemoteStore {
+    fn append(&self, entry: &LogEntry) -> Result<(), AppError> {
+        self.write(false, |local| local.append(entry))
+    }
+
+    fn all_entries(&self) -> Result<Vec<LogEntry>, AppError> {
+        self.refresh()?;
+        self.local().all_entries()
+    }
+
+    fn write_all(&self, entries: &[LogEntry]) -> Result<(), AppError> {
+        self.write(true, |local| local.write_all(entries))
+    }
+
+    fn read_range(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<LogEntry>, AppError> {
+        self.refresh()?;
+        self.local().read_range(from, to)
+    }
+
+    fn last_entry(&self) -> Result<Option<LogEntry>, AppError> {
+        self.refresh()?;
+        self.local().last_entry()
+    }
+
+    fn info(&self) -> Result<StoreInfo, AppError> {
+        self.refresh()?;
+        self.local().info()
+    }
+}
@@ -0,0 +1,160 @@
+use crate::AppError;
+use colored::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+
+// --- Recording and replaying the answers to every prompt ---
+// `--record session.json` saves each answer as it is given, so a session that runs into
+// a bug is on disk up to that point. `--replay session.json` answers the same questions
+// from the file without a terminal and stops as soon as a question differs from the
+// recording: conditional questionnaires can be tested end to end, and a bug report can
+// come with the exact session that triggered it.
+#[derive(Serialize, Deserialize)]
+struct Session {
+    args: Vec<String>, // The command it was recorded with, for whoever replays it
+    answers: Vec<Answer>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Answer {
+    prompt: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<Value>, // None: the question was cancelled (Ctrl+C or Esc)
+}
+
+enum Mode {
+    Record {
+        path: String,
+        session: Session,
+    },
+    Replay {
+        path: String,
+        answers: VecDeque<Answer>,
+    },
+}
+
+static MODE: OnceLock<Mutex<Mode>> = OnceLock::new();
+
+// Called once at startup with the --record/--replay paths
+pub fn install(
+    record: Option<&str>,
+    replay: Option<&str>,
+    args: &[String],
+) -> Result<(), AppError> {
+    let mode = match (record, replay) {
+        (None, None) => return Ok(()),
+        (Some(_), Some(_)) => {
+            return Err(AppError::UsageError(
+                "Use either --record or --replay, not both".to_string(),
+            ))
+        }
+        (Some(path), None) => Mode::Record {
+            path: path.to_string(),
+            session: Session {
+                args: args.to_vec(),
+                answers: Vec::new(),
+            },
+        },
+        (None, Some(path)) => {
+            let session: Session = serde_json::from_str(&fs::read_to_string(path)?)?;
+            Mode::Replay {
+                path: path.to_string(),
+                answers: session.answers.into(),
+            }
+        }
+    };
+    let _ = MODE.set(Mutex::new(mode));
+    Ok(())
+}
+
+fn mode() -> Option<std::sync::MutexGuard<'static, Mode>> {
+    MODE.get()
+        .map(|mode| mode.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+// A replay that no longer matches can't go on: every later answer would be off by one
+fn replay_failed(path: &str, message: String) -> AppError {
+    AppError::UsageError(format!("replaying {}: {}", path, message))
+}
+
+// Ask one question through `ask`, or take its answer from the replayed session. A
+// cancelled question is DialogCancelled.
+pub fn answer<T: Serialize + DeserializeOwned>(
+    prompt: &str,
+    ask: impl FnOnce() -> dialoguer::Result<T>,
+) -> Result<T, AppError> {
+    let replaying = matches!(mode().as_deref(), Some(Mode::Replay { .. }));
+    if !replaying {
+        let result = ask();
+        if let Some(Mode::Record { path, session }) = mode().as_deref_mut() {
+            session.answers.push(Answer {
+                prompt: prompt.to_string(),
+                value: result
+                    .as_ref()
+                    .ok()
+                    .and_then(|value| serde_json::to_value(value).ok()),
+            });
+            let saved = serde_json::to_string_pretty(session)
+                .map_err(std::io::Error::other)
+                .and_then(|text| fs::write(&*path, text + "\n"));
+            if let Err(e) = saved {
+                eprintln!("Warning: Could not save the recording to {}: {}", path, e);
+            }
+        }
+        return result.map_err(|_| AppError::DialogCancelled);
+    }
+
+    let mut guard = mode();
+    let Some(Mode::Replay { path, answers }) = guard.as_deref_mut() else {
+        unreachable!("checked above");
+    };
+    let Some(next) = answers.pop_front() else {
+        return Err(replay_failed(
+            path,
+            format!("no recorded answer for '{}'", prompt),
+        ));
+    };
+    if next.prompt != prompt {
+        return Err(replay_failed(
+            path,
+            format!("expected '{}' but was asked '{}'", next.prompt, prompt),
+        ));
+    }
+    let Some(value) = next.value else {
+        println!("{} {}", prompt.bold(), "(cancelled)".dimmed());
+        return Err(AppError::DialogCancelled);
+    };
+    let shown = match &value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    println!("{} {}", prompt.bold(), shown.cyan());
+    match serde_json::from_value(value) {
+        Ok(answer) => Ok(answer),
+        Err(e) => Err(replay_failed(
+            path,
+            format!("the answer to '{}' doesn't fit the question: {}", prompt, e),
+        )),
+    }
+}
+
+// After the command: a replay should have used up every recorded answer
+pub fn finish() {
+    if let Some(Mode::Replay { answers, .. }) = mode().as_deref() {
+        if !answers.is_empty() {
+            eprintln!(
+                "{}",
+                format!(
+                    "Warning: {} recorded answers were never asked for (first: '{}')",
+                    answers.len(),
+                    answers[0].prompt
+                )
+                .yellow()
+            );
+        }
+    }
+}
@@ -0,0 +1,440 @@
+use crate::cli::ReportArgs;
+use crate::clipboard::copy_to_clipboard;
+use crate::daily::{daily_points, DailyPoint};
+use crate::layout;
+use crate::milestones::{before_after, read_milestones, Milestone, WINDOW_DAYS};
+use crate::scales;
+use crate::schema::column_name;
+use crate::templates::{render_style, render_template_file, ReportContext};
+use crate::units::DisplayUnits;
+use crate::{metric_range, AppError, GlobalOptions, LogEntry, MetricSummary, METRIC_NAMES};
+use chrono::{Datelike, NaiveDate};
+use colored::*;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::path::Path;
+
+// --- `report` command: text summary, a user template, or a static site with `--bundle <dir>` ---
+pub fn run_report(options: &GlobalOptions, args: &ReportArgs) -> Result<(), AppError> {
+    let entries = options.analysis_entries()?;
+    if entries.is_empty() {
+        println!("{}", "No entries logged yet.".dimmed());
+        return Ok(());
+    }
+
+    let units = DisplayUnits::from_config(&options.config);
+    // Scale changes are marked on the charts like milestones
+    let mut milestones = read_milestones(&options.data_file())?;
+    milestones.extend(scales::markers());
+    milestones.sort_by_key(|m| m.date);
+    match &args.bundle {
+        Some(dir) => {
+            write_bundle(Path::new(dir), entries, &milestones)?;
+            println!(
+                "{}",
+                format!(" Report bundle written to {}/index.html", dir)
+                    .bold()
+                    .green()
+            );
+        }
+        None => {
+            let plain = args.template.is_none() && args.style.is_none();
+            let text = match (&args.template, &args.style) {
+                (Some(path), _) => {
+                    let context =
+                        ReportContext::build(entries, options.goal_days, &units, &milestones);
+                    render_template_file(Path::new(path), &context)?
+                }
+                (None, Some(name)) => {
+                    let context =
+                        ReportContext::build(entries, options.goal_days, &units, &milestones);
+                    render_style(name, &context)?
+                }
+                (None, None) => format_text_report(entries, &units, &milestones)?,
+            };
+            if plain {
+                layout::print_block(&text);
+            } else {
+                print!("{}", text); // Markup is printed untouched
+            }
+            if args.copy {
+                copy_to_clipboard(&text)?;
+                println!("{}", "Report copied to clipboard.".dimmed());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn format_text_report(
+    entries: Vec<LogEntry>,
+    units: &DisplayUnits,
+    milestones: &[Milestone],
+) -> Result<String, fmt::Error> {
+    let mut out = String::new();
+    let total_entries = entries.len();
+    let points = scales::normalized(daily_points(entries));
+    let overall: Vec<(&str, MetricSummary)> = METRIC_NAMES
+        .iter()
+        .filter_map(|m| MetricSummary::for_days(&points, m).map(|s| (*m, s.converted(units, m))))
+        .collect();
+
+    writeln!(out, "{}", layout::rule('=').cyan())?;
+    writeln!(out, "{}", " Progress Report ".bold().cyan())?;
+    writeln!(out, "{}", layout::rule('=').cyan())?;
+    if let (Some(first), Some(last)) = (points.first(), points.last()) {
+        writeln!(out, "Period: {} to {}", first.date, last.date)?;
+    }
+    writeln!(
+        out,
+        "Entries: {}  Days logged: {}  Workout days: {}",
+        total_entries.to_string().yellow(),
+        points.len().to_string().yellow(),
+        points
+            .iter()
+            .filter(|p| p.workout)
+            .count()
+            .to_string()
+            .green()
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<14} {:>6} {:>5} {:>5}  Unit",
+        "Metric", "Mean", "Min", "Max"
+    )?;
+    for (metric, summary) in &overall {
+        writeln!(
+            out,
+            "{:<14} {:>6.2} {:>5.1} {:>5.1}  {}",
+            column_name(metric),
+            summary.mean,
+            summary.min,
+            summary.max,
+            units.unit(metric).dimmed()
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "{}", " Monthly means ".bold().cyan())?;
+    writeln!(out, "{}", layout::rule('-').cyan())?;
+    for (month, days) in by_month(&points) {
+        write!(out, "{}", month.bold())?;
+        for metric in ["sleep_hours", "energy", "focus"] {
+            match mean_of(&days, metric) {
+                Some(mean) => write!(
+                    out,
+                    "  {} {:.1}",
+                    column_name(metric),
+                    units.convert(metric, mean)
+                )?,
+                None => write!(out, "  {} -", column_name(metric))?,
+            }
+        }
+        writeln!(out, "  ({} days)", days.len())?;
+    }
+
+    // Each milestone with the same metrics before and after it
+    let shown = in_range(&points, milestones);
+    if !shown.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "{}", " Milestones ".bold().cyan())?;
+        writeln!(out, "{}", layout::rule('-').cyan())?;
+        writeln!(
+            out,
+            "{}",
+            format!("Means over {} days before -> after", WINDOW_DAYS).dimmed()
+        )?;
+    }
+    for milestone in shown {
+        writeln!(
+            out,
+            "{} {}",
+            milestone.date.to_string().bold(),
+            milestone.label
+        )?;
+        for metric in ["sleep_hours", "energy", "focus"] {
+            let [before, after] = before_after(&points, milestone.date, metric).map(|v| {
+                v.map_or("-".to_string(), |v| {
+                    format!("{:.1}", units.convert(metric, v))
+                })
+            });
+            write!(out, "  {} {} -> {}", column_name(metric), before, after)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(out)
+}
+
+// Milestones that fall within the days a report covers
+pub fn in_range<'a>(points: &[DailyPoint], milestones: &'a [Milestone]) -> Vec<&'a Milestone> {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return Vec::new();
+    };
+    milestones
+        .iter()
+        .filter(|m| (first.date..=last.date).contains(&m.date))
+        .collect()
+}
+
+// --- Helpers shared by the text and HTML reports ---
+pub fn by_month(points: &[DailyPoint]) -> BTreeMap<String, Vec<&DailyPoint>> {
+    let mut months: BTreeMap<String, Vec<&DailyPoint>> = BTreeMap::new();
+    for point in points {
+        let key = format!("{:04}-{:02}", point.date.year(), point.date.month());
+        months.entry(key).or_default().push(point);
+    }
+    months
+}
+
+fn mean_of(points: &[&DailyPoint], metric: &str) -> Option<f64> {
+    let values: Vec<f64> = points
+        .iter()
+        .filter_map(|p| p.values.get(metric).copied())
+        .collect();
+    MetricSummary::from_values(&values).map(|s| s.mean)
+}
+
+// Upper bound of a metric's scale, used to size chart axes
+fn scale_max(metric: &str) -> f64 {
+    metric_range(metric).1
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// --- Static site bundle ---
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:860px;margin:2em auto;padding:0 1em;color:#222}\
+h1,h2{color:#0a7c86}table{border-collapse:collapse;margin:1em 0}\
+td,th{border:1px solid #ccc;padding:4px 8px;text-align:right}th{background:#eef7f8}\
+td:first-child,th:first-child{text-align:left}svg{background:#fafafa;border:1px solid #ddd}\
+nav a{margin-right:1em}.muted{color:#888}";
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        escape_html(title),
+        STYLE,
+        body
+    )
+}
+
+// Inline SVG line chart of one metric's daily means, with milestones as dashed lines
+fn svg_chart(points: &[&DailyPoint], metric: &str, milestones: &[Milestone]) -> String {
+    const WIDTH: f64 = 800.0;
+    const HEIGHT: f64 = 160.0;
+    const PAD: f64 = 24.0;
+
+    let values: Vec<(NaiveDate, f64)> = points
+        .iter()
+        .filter_map(|p| p.values.get(metric).map(|v| (p.date, *v)))
+        .collect();
+    let (Some(first), Some(last)) = (values.first(), values.last()) else {
+        return String::from("<p class=\"muted\">No data.</p>");
+    };
+
+    let span = (last.0 - first.0).num_days().max(1) as f64;
+    let max = scale_max(metric);
+    let coords: Vec<(f64, f64)> = values
+        .iter()
+        .map(|(date, value)| {
+            let x = PAD + (*date - first.0).num_days() as f64 / span * (WIDTH - 2.0 * PAD);
+            let y = HEIGHT - PAD - (value / max).clamp(0.0, 1.0) * (HEIGHT - 2.0 * PAD);
+            (x, y)
+        })
+        .collect();
+    let polyline: Vec<String> = coords
+        .iter()
+        .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+        .collect();
+    let dots: String = coords
+        .iter()
+        .map(|(x, y)| {
+            format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2.5\" fill=\"#0a7c86\"/>",
+                x, y
+            )
+        })
+        .collect();
+    let markers: String = milestones
+        .iter()
+        .filter(|m| (first.0..=last.0).contains(&m.date))
+        .map(|m| {
+            let x = PAD + (m.date - first.0).num_days() as f64 / span * (WIDTH - 2.0 * PAD);
+            format!(
+                "<line x1=\"{x:.1}\" x2=\"{x:.1}\" y1=\"{top}\" y2=\"{bottom}\" stroke=\"#c0392b\" \
+                 stroke-dasharray=\"4 3\"><title>{date} {label}</title></line>\
+                 <text x=\"{tx:.1}\" y=\"{top}\" font-size=\"10\" fill=\"#c0392b\">{label}</text>",
+                x = x,
+                tx = x + 3.0,
+                top = PAD,
+                bottom = HEIGHT - PAD,
+                date = m.date,
+                label = escape_html(&m.label),
+            )
+        })
+        .collect();
+
+    format!(
+        "<svg viewBox=\"0 0 {w} {h}\" width=\"100%\" role=\"img\" aria-label=\"{m}\">\
+         <text x=\"4\" y=\"14\" font-size=\"12\" fill=\"#888\">{max}</text>\
+         <text x=\"4\" y=\"{bottom}\" font-size=\"12\" fill=\"#888\">0</text>\
+         <text x=\"{pad}\" y=\"{h}\" font-size=\"11\" fill=\"#888\">{start}</text>\
+         <text x=\"{end_x}\" y=\"{h}\" font-size=\"11\" fill=\"#888\" text-anchor=\"end\">{end}</text>\
+         {markers}<polyline fill=\"none\" stroke=\"#0a7c86\" stroke-width=\"2\" points=\"{points}\"/>{dots}</svg>",
+        w = WIDTH,
+        h = HEIGHT,
+        m = column_name(metric),
+        max = max,
+        bottom = HEIGHT - PAD,
+        pad = PAD,
+        start = first.0,
+        end_x = WIDTH - PAD,
+        end = last.0,
+        points = polyline.join(" "),
+        dots = dots,
+        markers = markers,
+    )
+}
+
+fn summary_table(points: &[&DailyPoint]) -> String {
+    let mut html =
+        String::from("<table><tr><th>Metric</th><th>Mean</th><th>Min</th><th>Max</th></tr>");
+    for metric in METRIC_NAMES {
+        let values: Vec<f64> = points
+            .iter()
+            .filter_map(|p| p.values.get(metric).copied())
+            .collect();
+        if let Some(s) = MetricSummary::from_values(&values) {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{:.2}</td><td>{:.1}</td><td>{:.1}</td></tr>",
+                column_name(metric),
+                s.mean,
+                s.min,
+                s.max
+            ));
+        }
+    }
+    html.push_str("</table>");
+    html
+}
+
+pub fn write_bundle(
+    dir: &Path,
+    entries: Vec<LogEntry>,
+    milestones: &[Milestone],
+) -> Result<(), AppError> {
+    fs::create_dir_all(dir)?;
+
+    // Remarks are only shown on the month pages, keyed by day
+    let mut remarks: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+    for entry in &entries {
+        if let Some(ts) = entry.parsed_timestamp() {
+            if !entry.remarks.trim().is_empty() {
+                remarks
+                    .entry(ts.date_naive())
+                    .or_default()
+                    .push(entry.remarks.clone());
+            }
+        }
+    }
+
+    let total_entries = entries.len();
+    let points = scales::normalized(daily_points(entries));
+    let all: Vec<&DailyPoint> = points.iter().collect();
+    let months = by_month(&points);
+
+    // Index page
+    let mut body = String::from("<h1>Daily Metrics Report</h1>");
+    if let (Some(first), Some(last)) = (points.first(), points.last()) {
+        body.push_str(&format!(
+            "<p>{} to {} &middot; {} entries over {} days &middot; {} workout days</p>",
+            first.date,
+            last.date,
+            total_entries,
+            points.len(),
+            points.iter().filter(|p| p.workout).count()
+        ));
+    }
+    body.push_str("<nav><strong>Months:</strong> ");
+    for month in months.keys() {
+        body.push_str(&format!("<a href=\"{0}.html\">{0}</a>", month));
+    }
+    body.push_str("</nav><h2>Overall</h2>");
+    body.push_str(&summary_table(&all));
+    let shown = in_range(&points, milestones);
+    if !shown.is_empty() {
+        body.push_str("<h2>Milestones</h2><ul>");
+        for m in shown {
+            body.push_str(&format!(
+                "<li><strong>{}</strong> {}</li>",
+                m.date,
+                escape_html(&m.label)
+            ));
+        }
+        body.push_str("</ul>");
+    }
+    for metric in METRIC_NAMES {
+        body.push_str(&format!("<h2>{}</h2>", column_name(metric)));
+        body.push_str(&svg_chart(&all, metric, milestones));
+    }
+    fs::write(dir.join("index.html"), page("Daily Metrics Report", &body))?;
+
+    // One page per month
+    for (month, days) in &months {
+        let mut body = format!(
+            "<nav><a href=\"index.html\">&larr; Overview</a></nav><h1>{}</h1>",
+            month
+        );
+        body.push_str(&summary_table(days));
+        body.push_str("<h2>Days</h2><table><tr><th>Date</th><th>Entries</th>");
+        for metric in METRIC_NAMES {
+            body.push_str(&format!("<th>{}</th>", metric));
+        }
+        body.push_str("<th>Workout</th></tr>");
+        for day in days {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td>",
+                day.date, day.entries
+            ));
+            for metric in METRIC_NAMES {
+                match day.values.get(metric) {
+                    Some(v) => body.push_str(&format!("<td>{:.1}</td>", v)),
+                    None => body.push_str("<td class=\"muted\">-</td>"),
+                }
+            }
+            body.push_str(&format!(
+                "<td>{}</td></tr>",
+                if day.workout { "yes" } else { "no" }
+            ));
+        }
+        body.push_str("</table>");
+        for metric in METRIC_NAMES {
+            body.push_str(&format!("<h2>{}</h2>", column_name(metric)));
+            body.push_str(&svg_chart(days, metric, milestones));
+        }
+
+        let month_remarks: Vec<String> = days
+            .iter()
+            .filter_map(|d| remarks.get(&d.date).map(|r| (d.date, r)))
+            .flat_map(|(date, rs)| {
+                rs.iter()
+                    .map(move |r| format!("<li><strong>{}</strong> {}</li>", date, escape_html(r)))
+            })
+            .collect();
+        if !month_remarks.is_empty() {
+            body.push_str("<h2>Remarks</h2><ul>");
+            body.push_str(&month_remarks.concat());
+            body.push_str("</ul>");
+        }
+        fs::write(dir.join(format!("{}.html", month)), page(month, &body))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,390 @@
+use crate::audit;
+use crate::baseline;
+use crate::config::{edit_config, set_value, table_at, Config};
+use crate::constraints::{configured_range, constraint, is_rating};
+use crate::daily::DailyPoint;
+use crate::milestones::Milestone;
+use crate::phases;
+use crate::replay;
+use crate::schema::{canonical_metric, column_name};
+use crate::store;
+use crate::{metric_range, read_entries, AppError, GlobalOptions, METRIC_NAMES, THEME};
+use chrono::{Duration, NaiveDate, Utc};
+use colored::*;
+use csv::{ReaderBuilder, WriterBuilder};
+use dialoguer::Select;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml_edit::{value, Array};
+
+// --- Rating scale changes, e.g. energy from 1-10 to 1-5 ---
+// Recorded beside the data by `metric scale`. Earlier values are either rewritten onto
+// the new scale, or kept as entered and converted whenever days are analysed, so stats,
+// reports and correlations never average the two scales together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaleChange {
+    pub date: NaiveDate, // First day on the new scale
+    pub metric: String,
+    pub old_min: f64,
+    pub old_max: f64,
+    pub new_min: f64,
+    pub new_max: f64,
+    pub rescaled: bool, // The earlier values in the data file were rewritten
+}
+
+impl ScaleChange {
+    fn convert(&self, value: f64) -> f64 {
+        let share = (value - self.old_min) / (self.old_max - self.old_min);
+        self.new_min + share * (self.new_max - self.new_min)
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} {}-{} → {}-{}",
+            column_name(&self.metric),
+            self.old_min,
+            self.old_max,
+            self.new_min,
+            self.new_max
+        )
+    }
+}
+
+fn scales_file(data_file: &str) -> PathBuf {
+    Path::new(data_file).with_extension("scales.csv")
+}
+
+// Every recorded change, oldest first
+pub fn read_changes(data_file: &str) -> Result<Vec<ScaleChange>, AppError> {
+    let path = scales_file(data_file);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut rdr = ReaderBuilder::new().from_path(path)?;
+    let mut changes = rdr.deserialize().collect::<Result<Vec<ScaleChange>, _>>()?;
+    changes.sort_by_key(|c| c.date);
+    Ok(changes)
+}
+
+fn write_changes(data_file: &str, changes: &[ScaleChange]) -> Result<(), AppError> {
+    let mut wtr = WriterBuilder::new().from_path(scales_file(data_file))?;
+    for change in changes {
+        wtr.serialize(change)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+static CHANGES: OnceLock<Vec<ScaleChange>> = OnceLock::new();
+
+// Called once at startup, before the scales are installed (a change can still hold the
+// old one); an unreadable file only costs the conversion, with a warning
+pub fn install(data_file: &str) {
+    let changes = read_changes(data_file).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: Ignoring {}: {}",
+            scales_file(data_file).display(),
+            e
+        );
+        Vec::new()
+    });
+    let _ = CHANGES.set(changes);
+}
+
+// A scale edited by hand in config.toml has no change recorded, so earlier days would be
+// read as if they were on the new scale. Checked once the scales are installed.
+pub fn check_recorded(data_file: &str) {
+    if !Path::new(data_file).exists() {
+        return;
+    }
+    for metric in METRIC_NAMES
+        .iter()
+        .filter(|m| is_rating(m) && held_range(m).is_none())
+    {
+        let (min, max) = metric_range(metric);
+        let (recorded_min, recorded_max) = recorded_range(metric);
+        if (min, max) != (recorded_min, recorded_max) {
+            eprintln!(
+                "{}",
+                format!(
+                    "Warning: {} is rated {}-{} in the config, but the data was recorded on {}-{}; \
+                     run `metric scale {} {} {}` so earlier days are converted",
+                    column_name(metric),
+                    min,
+                    max,
+                    recorded_min,
+                    recorded_max,
+                    column_name(metric),
+                    min,
+                    max
+                )
+                .yellow()
+            );
+        }
+    }
+}
+
+// The scale the data was last recorded on: the latest change, else the built-in one
+fn recorded_range(metric: &str) -> (f64, f64) {
+    CHANGES
+        .get()
+        .into_iter()
+        .flatten()
+        .rfind(|c| c.metric == metric)
+        .map(|c| (c.new_min, c.new_max))
+        .or_else(|| constraint(metric).and_then(|c| c.range))
+        .unwrap_or((1.0, 10.0))
+}
+
+fn kept_changes() -> impl Iterator<Item = &'static ScaleChange> {
+    CHANGES.get().into_iter().flatten().filter(|c| !c.rescaled)
+}
+
+// The old scale while a kept change hasn't started yet. A change made on a day that
+// already had an answer starts the next day, so answers given later that day are still
+// checked on the old scale, and converted with the rest of the day.
+pub fn held_range(metric: &str) -> Option<(f64, f64)> {
+    let today = Utc::now().date_naive();
+    kept_changes()
+        .find(|c| c.metric == metric && c.date > today)
+        .map(|c| (c.old_min, c.old_max))
+}
+
+// Days from before each change whose values were kept, moved onto the scale that
+// followed it. Changes apply oldest first, so values cross several changes in turn.
+pub fn normalized(mut points: Vec<DailyPoint>) -> Vec<DailyPoint> {
+    for change in kept_changes() {
+        for point in points.iter_mut().filter(|p| p.date < change.date) {
+            if let Some(value) = point.values.get_mut(change.metric.as_str()) {
+                *value = change.convert(*value);
+            }
+        }
+    }
+    points
+}
+
+// One line per change that converts some of the days from `first` on, for `stats`
+pub fn notes(first: NaiveDate) -> Vec<String> {
+    kept_changes()
+        .filter(|c| first < c.date)
+        .map(|c| {
+            format!(
+                "{} changed scale on {}: earlier days are converted from {}-{} to {}-{}.",
+                column_name(&c.metric),
+                c.date,
+                c.old_min,
+                c.old_max,
+                c.new_min,
+                c.new_max
+            )
+        })
+        .collect()
+}
+
+// Every change as a chart marker
+pub fn markers() -> Vec<Milestone> {
+    CHANGES
+        .get()
+        .into_iter()
+        .flatten()
+        .map(|c| Milestone {
+            date: c.date,
+            label: format!("scale {}", c.describe()),
+        })
+        .collect()
+}
+
+// --- `metric scale <metric> <min> <max> [--rescale | --keep]` ---
+// Saves the new scale to the config and records the change; the earlier values are
+// rewritten (rounded to whole numbers) with --rescale or kept as entered with --keep.
+pub fn run_scale(
+    options: &GlobalOptions,
+    name: &str,
+    (min, max): (u8, u8),
+    rescale: bool,
+    keep: bool,
+) -> Result<(), AppError> {
+    let metric = canonical_metric(name)
+        .filter(|m| is_rating(m))
+        .ok_or_else(|| AppError::UsageError(format!("{} isn't a 1-10 rating", name)))?;
+    let (new_min, new_max) = (f64::from(min), f64::from(max));
+    if new_min >= new_max {
+        return Err(AppError::UsageError(
+            "The scale needs the lower end first, e.g. 1 5".to_string(),
+        ));
+    }
+    // From the recorded scale, which is also right when config.toml was edited by hand
+    let (old_min, old_max) = recorded_range(metric);
+    if (old_min, old_max) == (new_min, new_max)
+        && configured_range(&options.config, metric) == Some((new_min, new_max))
+    {
+        println!(
+            "{}",
+            format!(
+                "{} is already on {}-{}.",
+                column_name(metric),
+                new_min,
+                new_max
+            )
+            .dimmed()
+        );
+        return Ok(());
+    }
+
+    let data_file = options.data_file();
+    let entries = read_entries(&data_file)?;
+    let earlier = entries
+        .iter()
+        .filter(|e| e.metric(metric).is_some())
+        .count();
+    let today = Utc::now().date_naive();
+    let answered_today = entries.iter().any(|e| {
+        e.metric(metric).is_some() && e.parsed_timestamp().map(|ts| ts.date_naive()) == Some(today)
+    });
+    let change = ScaleChange {
+        date: today,
+        metric: metric.to_string(),
+        old_min,
+        old_max,
+        new_min,
+        new_max,
+        rescaled: false,
+    };
+    println!(
+        "{}",
+        format!(" {} ({} earlier values)", change.describe(), earlier).bold()
+    );
+    let rescaled = match (rescale, keep) {
+        _ if earlier == 0 => false,
+        (true, _) => true,
+        (_, true) => false,
+        _ => {
+            let items = [
+                format!(
+                    "Rescale them to {}-{} in the data file (rounded to whole numbers)",
+                    new_min, new_max
+                ),
+                "Keep them as entered; analyses convert them to the new scale".to_string(),
+            ];
+            const PROMPT: &str = "What should happen to the earlier values?";
+            replay::answer(PROMPT, || {
+                Select::with_theme(&**THEME)
+                    .with_prompt(PROMPT)
+                    .items(&items)
+                    .default(1)
+                    .interact()
+            })? == 0
+        }
+    };
+    // A day already answered on the old scale stays on it, and so do the answers still to
+    // come that day: the new scale starts tomorrow. Rescaled values are all on the new one.
+    let date = if answered_today && !rescaled {
+        today + Duration::days(1)
+    } else {
+        today
+    };
+    let change = ScaleChange {
+        date,
+        rescaled,
+        ..change
+    };
+
+    if options.dry_run {
+        println!(
+            "{}",
+            format!(
+                " Dry run: would set the scale in {}{}",
+                options.config_path().display(),
+                if rescaled {
+                    format!(" and rescale {} values in {}", earlier, data_file)
+                } else {
+                    String::new()
+                }
+            )
+            .bold()
+            .yellow()
+        );
+        return Ok(());
+    }
+
+    if rescaled {
+        store::open(&data_file).modify(&mut |entries| {
+            for entry in entries.iter_mut() {
+                if let Some(value) = entry.metric(metric) {
+                    entry.set_metric(metric, change.convert(value));
+                }
+            }
+            Ok(())
+        })?;
+    }
+    // The seed baseline is compared with new answers, so it always moves to the new scale
+    baseline::rescale(&data_file, metric, |value| change.convert(value))?;
+
+    let config_path = options.config_path();
+    edit_config(&config_path, |doc| {
+        let settings = table_at(doc, &["metric", metric]).ok_or_else(|| {
+            AppError::ConfigError(format!(
+                "{}: [metric] must be a table",
+                config_path.display()
+            ))
+        })?;
+        if (new_min, new_max) == (1.0, 10.0) {
+            settings.remove("scale");
+        } else {
+            let scale: Array = [new_min as i64, new_max as i64].into_iter().collect();
+            set_value(settings, "scale", value(scale));
+        }
+        Ok(())
+    })?;
+    let mut changes = read_changes(&data_file)?;
+    changes.push(change.clone());
+    write_changes(&data_file, &changes)?;
+    // The running phase asks on the new scale from now on
+    let config = Config::load(&config_path)?;
+    if let Some(phase) = phases::read_phases(&data_file)?.pop() {
+        phases::record(&data_file, &config, phase.start, phase.goal_days)?;
+    }
+    audit::record(
+        &data_file,
+        "metric scale",
+        &format!(
+            "{}{}",
+            change.describe(),
+            if rescaled {
+                ", earlier values rescaled"
+            } else {
+                ""
+            }
+        ),
+        Vec::new(),
+    )?;
+
+    println!(
+        "{}",
+        format!(
+            " {} is rated {}-{} from {}",
+            column_name(metric),
+            new_min,
+            new_max,
+            change.date
+        )
+        .bold()
+        .green()
+    );
+    println!(
+        "{}",
+        if rescaled {
+            format!("Rescaled {} earlier values.", earlier)
+        } else if earlier > 0 {
+            format!(
+                "The earlier values stay as entered; stats and reports convert days before {} to the new scale.",
+                change.date
+            )
+        } else {
+            "There were no earlier values to convert.".to_string()
+        }
+        .dimmed()
+    );
+    Ok(())
+}
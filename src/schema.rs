@@ -0,0 +1,672 @@
+use crate::audit;
+use crate::backup;
+use crate::cli::{DescribeArgs, MetricAction, MetricArgs, OutputFormat};
+use crate::config::{edit_config, set_value, table_at, Config};
+use crate::constraints::{configured_range, CONSTRAINTS};
+use crate::custom;
+use crate::layout;
+use crate::lock;
+use crate::scales;
+use crate::store;
+use crate::{group_by_day, metric_range, read_entries, AppError, GlobalOptions, METRIC_NAMES};
+use chrono::NaiveDate;
+use colored::*;
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use toml_edit::{value, Array, Item};
+
+// --- Built-in metric definitions: (name, label, description, low anchor, high anchor, unit) ---
+const BUILTIN_METRICS: [(&str, &str, &str, &str, &str, &str); 9] = [
+    (
+        "sleep_hours",
+        "Sleep Hours",
+        "Hours slept the night before",
+        "No sleep",
+        "12+ hours",
+        "hours",
+    ),
+    (
+        "sleep_quality",
+        "Sleep Quality",
+        "How restful last night's sleep felt",
+        "Poor",
+        "Excellent",
+        "",
+    ),
+    (
+        "sleepiness",
+        "Sleepiness/Grogginess",
+        "How sleepy or groggy you feel right now",
+        "Wide awake",
+        "Can barely keep eyes open",
+        "",
+    ),
+    (
+        "zonkedness",
+        "Zonked-ness",
+        "Mental exhaustion: drained, fried, running on empty (distinct from sleepiness)",
+        "Fresh",
+        "Completely fried",
+        "",
+    ),
+    (
+        "mid_vibes",
+        "Mid Vibes",
+        "Overall mood and outlook",
+        "Awful",
+        "Great",
+        "",
+    ),
+    (
+        "energy",
+        "Energy Levels",
+        "Physical and mental drive to get things done",
+        "Flat",
+        "Buzzing",
+        "",
+    ),
+    (
+        "strength",
+        "Physical Strength",
+        "How strong and capable your body feels",
+        "Weak",
+        "Peak",
+        "",
+    ),
+    (
+        "focus",
+        "Focus",
+        "Ability to stay on one task without drifting",
+        "Scattered",
+        "Locked in",
+        "",
+    ),
+    (
+        "intelligence",
+        "Perceived Intelligence",
+        "How sharp and quick your thinking feels",
+        "Foggy",
+        "Sharp",
+        "",
+    ),
+];
+
+// --- Column names after `metric rename` ---
+// Installed once at startup so every CSV read and write sees the same names.
+struct ColumnNames {
+    current: BTreeMap<&'static str, String>, // metric -> column name in the data file
+    known: BTreeMap<String, &'static str>,   // any current or former name -> metric
+}
+
+static COLUMN_NAMES: OnceLock<ColumnNames> = OnceLock::new();
+
+pub fn install_column_names(config: &Config) {
+    let mut current = BTreeMap::new();
+    let mut known = BTreeMap::new();
+    for metric in METRIC_NAMES {
+        known.insert(metric.to_string(), metric);
+        if let Some(settings) = config.metric.get(metric) {
+            for alias in &settings.aliases {
+                known.insert(alias.clone(), metric);
+            }
+            if let Some(name) = &settings.name {
+                known.insert(name.clone(), metric);
+                current.insert(metric, name.clone());
+            }
+        }
+    }
+    let _ = COLUMN_NAMES.set(ColumnNames { current, known });
+}
+
+// Resolve a current, former or original metric name to the built-in metric
+pub fn canonical_metric(name: &str) -> Option<&'static str> {
+    match COLUMN_NAMES.get() {
+        Some(names) => names.known.get(name).copied(),
+        None => METRIC_NAMES.iter().find(|m| **m == name).copied(),
+    }
+}
+
+// Column name a metric is written under
+pub fn column_name(metric: &str) -> &str {
+    COLUMN_NAMES
+        .get()
+        .and_then(|names| names.current.get(metric))
+        .map_or(metric, String::as_str)
+}
+
+// --- A metric's definition after applying `[metric.<name>]` config overrides ---
+pub struct MetricInfo {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub description: String,
+    pub low: String,
+    pub high: String,
+    pub unit: Option<String>,
+    pub display_unit: Option<String>,
+    pub prompt: Option<String>, // Set when the config rewords the question
+}
+
+pub fn metric_info(config: &Config, name: &str) -> Option<MetricInfo> {
+    let (name, label, description, low, high, unit) = BUILTIN_METRICS
+        .iter()
+        .find(|(metric, ..)| *metric == name)
+        .copied()?;
+    let overrides = config.metric.get(name).cloned().unwrap_or_default();
+    Some(MetricInfo {
+        name,
+        label,
+        description: overrides
+            .description
+            .unwrap_or_else(|| description.to_string()),
+        low: overrides.low.unwrap_or_else(|| low.to_string()),
+        high: overrides.high.unwrap_or_else(|| high.to_string()),
+        unit: overrides
+            .unit
+            .or_else(|| (!unit.is_empty()).then(|| unit.to_string())),
+        display_unit: overrides.display_unit,
+        prompt: overrides.prompt,
+    })
+}
+
+// --- `describe` command: data dictionary built from the schema plus the recorded data ---
+pub fn run_describe(options: &GlobalOptions, args: &DescribeArgs) -> Result<(), AppError> {
+    let args = &args.metrics;
+    if let Some(name) = args.iter().find(|name| canonical_metric(name).is_none()) {
+        return Err(AppError::UsageError(format!(
+            "Unknown metric '{}' (choose from {})",
+            name,
+            METRIC_NAMES.join(", ")
+        )));
+    }
+    let selected: Vec<&str> = METRIC_NAMES
+        .into_iter()
+        .filter(|name| {
+            args.is_empty() || args.iter().any(|arg| canonical_metric(arg) == Some(name))
+        })
+        .collect();
+
+    let days = group_by_day(read_entries(&options.data_file())?);
+
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Metric Dictionary ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    for name in selected {
+        let Some(info) = metric_info(&options.config, name) else {
+            continue;
+        };
+        let recorded: Vec<NaiveDate> = days
+            .iter()
+            .filter(|(_, entries)| entries.iter().any(|e| e.metric(name).is_some()))
+            .map(|(date, _)| *date)
+            .collect();
+        let (min, max) = metric_range(name);
+
+        println!(
+            "{} ({})",
+            info.label.bold(),
+            column_name(info.name).dimmed()
+        );
+        println!("  {}", info.description);
+        if let Some(prompt) = &info.prompt {
+            println!("  Asked as: \"{}\"", prompt);
+        }
+        if let Some(settings) = options
+            .config
+            .metric
+            .get(name)
+            .filter(|s| !s.aliases.is_empty())
+        {
+            println!("  Formerly: {}", settings.aliases.join(", "));
+        }
+        println!(
+            "  Scale: {} = {}, {} = {}",
+            min,
+            info.low.yellow(),
+            max,
+            info.high.yellow()
+        );
+        match (&info.unit, &info.display_unit) {
+            (Some(unit), Some(display)) => println!("  Unit: {} (shown as {})", unit, display),
+            (Some(unit), None) => println!("  Unit: {}", unit),
+            _ => {}
+        }
+        match (recorded.first(), recorded.last()) {
+            (Some(first), Some(last)) => println!(
+                "  Recorded: {} to {}, fill rate {:.0}% ({} of {} logged days)",
+                first,
+                last,
+                recorded.len() as f64 / days.len() as f64 * 100.0,
+                recorded.len(),
+                days.len()
+            ),
+            _ => println!("  {}", "Never recorded".dimmed()),
+        }
+        println!("{}", layout::rule('-').cyan());
+    }
+    Ok(())
+}
+
+// --- Versions of the CSV layout ---
+// Add a version to LAYOUTS (and bump SCHEMA_VERSION) when a column is added, removed or
+// changes type, so consumers can tell which layout they're reading and `migrate` can
+// bring older files up to date. Every layout so far is a prefix of CONSTRAINTS.
+pub const SCHEMA_VERSION: u32 = 4;
+const LAYOUTS: [(u32, usize); 4] = [
+    (1, 13), // timestamp .. remarks
+    (2, 15), // + session_seconds, session_edits
+    (3, 16), // + provenance
+    (4, 17), // + entry_kind
+];
+
+// The version of a CSV header (renamed metrics count as themselves), or None when it
+// isn't one of the layouts: columns reordered, missing or unknown
+pub fn header_version(header: &StringRecord) -> Option<u32> {
+    let mut columns: Vec<&str> = header
+        .iter()
+        .map(|h| canonical_metric(h.trim()).unwrap_or(h.trim()))
+        .collect();
+    // The custom metrics' columns follow the current layout
+    if let Some(count) = custom::trailing_columns(&columns) {
+        columns.truncate(columns.len() - count);
+        return (columns.len() == CONSTRAINTS.len()
+            && columns
+                .iter()
+                .copied()
+                .eq(CONSTRAINTS.iter().map(|c| c.column)))
+        .then_some(SCHEMA_VERSION);
+    }
+    LAYOUTS.iter().find_map(|(version, count)| {
+        let expected = CONSTRAINTS[..*count].iter().map(|c| c.column);
+        (columns.len() == *count && columns.iter().copied().eq(expected)).then_some(*version)
+    })
+}
+
+// --- `schema` command: every column as it appears in the data file and exports ---
+const ENTRY_KINDS: [&str; 4] = ["first-of-day", "follow-up", "backfill", "correction"];
+
+#[derive(Serialize)]
+struct ColumnSchema {
+    name: String,        // Header in CSV exports and key in JSON (renames applied)
+    field: &'static str, // Built-in name, stable across renames
+    #[serde(rename = "type")]
+    kind: &'static str,
+    nullable: bool,
+    min: Option<f64>,
+    max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    values: Option<Vec<&'static str>>, // The only values a text column takes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metric: Option<MetricSchema>,
+}
+
+#[derive(Serialize)]
+struct MetricSchema {
+    label: &'static str,
+    description: String,
+    low: String,
+    high: String,
+    unit: Option<String>,
+    display_unit: Option<String>,
+    aliases: Vec<String>,
+    enabled: bool,
+}
+
+fn columns(config: &Config) -> Vec<ColumnSchema> {
+    CONSTRAINTS
+        .iter()
+        .map(|c| {
+            let metric = metric_info(config, c.column).map(|info| MetricSchema {
+                label: info.label,
+                description: info.description,
+                low: info.low,
+                high: info.high,
+                unit: info.unit,
+                display_unit: info.display_unit,
+                aliases: config
+                    .metric
+                    .get(c.column)
+                    .map(|m| m.aliases.clone())
+                    .unwrap_or_default(),
+                enabled: config.metric_enabled(c.column),
+            });
+            ColumnSchema {
+                name: column_name(c.column).to_string(),
+                field: c.column,
+                kind: c.kind.name(),
+                nullable: c.nullable,
+                min: configured_range(config, c.column).map(|(min, _)| min),
+                max: configured_range(config, c.column)
+                    .map(|(_, max)| max)
+                    .filter(|max| *max < f64::MAX),
+                values: (c.column == "entry_kind").then(|| ENTRY_KINDS.to_vec()),
+                metric,
+            }
+        })
+        .chain(custom::metrics().iter().map(|(name, metric)| {
+            let range = custom::range(metric);
+            ColumnSchema {
+                name: name.clone(),
+                field: name.as_str(),
+                kind: custom::column_type(metric.kind).name(),
+                nullable: true,
+                min: range.map(|(min, _)| min),
+                max: range.map(|(_, max)| max),
+                values: None,
+                metric: None,
+            }
+        }))
+        .collect()
+}
+
+// One flat row per column for `--format csv`
+#[derive(Serialize)]
+struct ColumnRow<'a> {
+    name: &'a str,
+    field: &'a str,
+    #[serde(rename = "type")]
+    kind: &'a str,
+    nullable: bool,
+    min: Option<f64>,
+    max: Option<f64>,
+    unit: Option<&'a str>,
+}
+
+pub fn run_schema(options: &GlobalOptions) -> Result<(), AppError> {
+    let columns = columns(&options.config);
+    if options.format == OutputFormat::Csv {
+        let mut wtr = csv::Writer::from_writer(std::io::stdout());
+        for column in &columns {
+            wtr.serialize(ColumnRow {
+                name: &column.name,
+                field: column.field,
+                kind: column.kind,
+                nullable: column.nullable,
+                min: column.min,
+                max: column.max,
+                unit: column.metric.as_ref().and_then(|m| m.unit.as_deref()),
+            })?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+    if options.json || options.format == OutputFormat::Json {
+        let schema = serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "columns": columns,
+        });
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+
+    println!("{}", layout::rule('=').cyan());
+    println!(
+        "{}",
+        format!(" Schema (version {}) ", SCHEMA_VERSION)
+            .bold()
+            .cyan()
+    );
+    println!("{}", layout::rule('=').cyan());
+    layout::row(
+        &format!(
+            "{:<16} {:<9} {:<10} {:<8} Null",
+            "Column", "Type", "Range", "Unit"
+        )
+        .bold()
+        .to_string(),
+    );
+    for column in &columns {
+        let range = match (column.min, column.max) {
+            (Some(min), Some(max)) => format!("{}-{}", min, max),
+            (Some(min), None) => format!(">= {}", min),
+            _ => String::new(),
+        };
+        let unit = column
+            .metric
+            .as_ref()
+            .and_then(|m| m.unit.clone())
+            .unwrap_or_default();
+        layout::row(&format!(
+            "{:<16} {:<9} {:<10} {:<8} {}",
+            column.name,
+            column.kind,
+            range,
+            unit,
+            if column.nullable { "yes" } else { "no" }
+        ));
+    }
+    Ok(())
+}
+
+// A new column name must be CSV-safe and not already belong to another metric
+pub fn check_new_name(metric: &str, new: &str) -> Result<(), String> {
+    let valid = !new.is_empty() && new.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid metric name '{}': use letters, digits or '_'",
+            new
+        ));
+    }
+    if let Some(other) = canonical_metric(new).filter(|other| *other != metric) {
+        return Err(format!("'{}' is already a name of {}", new, other));
+    }
+    Ok(())
+}
+
+// --- `metric` command: schema maintenance (`metric rename <old> <new>`, `metric scale`) ---
+pub fn run_metric(options: &GlobalOptions, args: &MetricArgs) -> Result<(), AppError> {
+    let (old, new) = match &args.action {
+        MetricAction::Rename { old, new } => (old.as_str(), new.as_str()),
+        MetricAction::Scale {
+            metric,
+            min,
+            max,
+            rescale,
+            keep,
+        } => return scales::run_scale(options, metric, (*min, *max), *rescale, *keep),
+        MetricAction::On { metric } => return set_enabled(options, metric, true),
+        MetricAction::Off { metric } => return set_enabled(options, metric, false),
+    };
+    let Some(metric) = canonical_metric(old) else {
+        return Err(AppError::UsageError(format!(
+            "Unknown metric '{}' (choose from {})",
+            old,
+            METRIC_NAMES.join(", ")
+        )));
+    };
+    if let Err(msg) = check_new_name(metric, new) {
+        return Err(AppError::UsageError(msg));
+    }
+    let current = column_name(metric).to_string();
+    if current == new {
+        println!(
+            "{}",
+            format!("{} is already called {}.", metric, new).dimmed()
+        );
+        return Ok(());
+    }
+    // Only the local copy's header could be rewritten, and the next fetch would undo it
+    if let Some(remote) = &options.remote {
+        return Err(AppError::UsageError(format!(
+            "metric rename rewrites the data file's header, which can't be done through --remote; run it where {} is",
+            remote.describe()
+        )));
+    }
+
+    let config_path = options.config_path();
+    let data_file = options.data_file();
+    if options.dry_run {
+        println!(
+            "{}",
+            format!(
+                " Dry run: would rename column '{}' to '{}' in {} and record the alias in {}",
+                current,
+                new,
+                data_file,
+                config_path.display()
+            )
+            .bold()
+            .yellow()
+        );
+        return Ok(());
+    }
+
+    // Record the new name first: if the CSV rewrite fails, reads still resolve the old header
+    edit_config(&config_path, |doc| {
+        let settings = table_at(doc, &["metric", metric]).ok_or_else(|| {
+            AppError::ConfigError(format!(
+                "{}: [metric] must be a table",
+                config_path.display()
+            ))
+        })?;
+        set_value(settings, "name", value(new));
+        let mut aliases: Array = settings
+            .get("aliases")
+            .and_then(Item::as_array)
+            .cloned()
+            .unwrap_or_default();
+        if !aliases
+            .iter()
+            .any(|alias| alias.as_str() == Some(current.as_str()))
+        {
+            aliases.push(current.as_str());
+        }
+        set_value(settings, "aliases", value(aliases));
+        Ok(())
+    })?;
+
+    // Every year's file when the data is split per year, all under the data file's lock
+    // and backed up first like any other rewrite
+    let mut renamed = Vec::new();
+    {
+        let _lock = lock::write_lock(&data_file)?;
+        backup::snapshot(&data_file)?;
+        for file in store::files(&data_file) {
+            if rename_csv_column(&file, &current, new)? {
+                renamed.push(file.display().to_string());
+            }
+        }
+    }
+    audit::record(
+        &data_file,
+        "metric rename",
+        &format!("{} -> {}", current, new),
+        Vec::new(),
+    )?;
+    println!(
+        "{}",
+        format!(" Renamed {} to {}", current, new).bold().green()
+    );
+    if !renamed.is_empty() {
+        println!("Updated the header of {}", renamed.join(", "));
+    }
+    println!(
+        "{}",
+        format!(
+            "'{}' is kept as an alias, so older exports and commands still work.",
+            current
+        )
+        .dimmed()
+    );
+    Ok(())
+}
+
+// `metric off <metric>`: stop asking it while logging. The column stays in the data file
+// and exports, past values are kept, and new entries leave it empty.
+fn set_enabled(options: &GlobalOptions, name: &str, enabled: bool) -> Result<(), AppError> {
+    let Some(metric) = canonical_metric(name) else {
+        return Err(AppError::UsageError(format!(
+            "Unknown metric '{}' (choose from {})",
+            name,
+            METRIC_NAMES.join(", ")
+        )));
+    };
+    let state = if enabled { "on" } else { "off" };
+    if options.config.metric_enabled(metric) == enabled {
+        println!(
+            "{}",
+            format!("{} is already {}.", column_name(metric), state).dimmed()
+        );
+        return Ok(());
+    }
+    let config_path = options.config_path();
+    if options.dry_run {
+        println!(
+            "{}",
+            format!(
+                " Dry run: would turn {} {} in {}",
+                column_name(metric),
+                state,
+                config_path.display()
+            )
+            .bold()
+            .yellow()
+        );
+        return Ok(());
+    }
+    edit_config(&config_path, |doc| {
+        let settings = table_at(doc, &["metric", metric]).ok_or_else(|| {
+            AppError::ConfigError(format!(
+                "{}: [metric] must be a table",
+                config_path.display()
+            ))
+        })?;
+        if enabled {
+            settings.remove("enabled");
+        } else {
+            set_value(settings, "enabled", value(false));
+        }
+        Ok(())
+    })?;
+    println!(
+        "{}",
+        format!(" Turned {} {}", column_name(metric), state)
+            .bold()
+            .green()
+    );
+    if !enabled {
+        println!(
+            "{}",
+            "`log` no longer asks it; the column and its past values stay.".dimmed()
+        );
+    }
+    Ok(())
+}
+
+// Rewrite just the header row of a data file, returning whether the column was found
+// (JSONL and MessagePack files key by field name, so they never need it). The caller
+// holds the data file's write lock.
+fn rename_csv_column(path: &Path, old: &str, new: &str) -> Result<bool, AppError> {
+    let name = path.to_string_lossy();
+    if !path.exists() || crate::jsonl::is_jsonl(&name) || crate::msgpack::is_msgpack(&name) {
+        return Ok(false);
+    }
+    let text = fs::read_to_string(path)?;
+    let (header, body) = text.split_once('\n').unwrap_or((text.as_str(), ""));
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(header.as_bytes());
+    let Some(record) = rdr.records().next().transpose()? else {
+        return Ok(false);
+    };
+    if !record.iter().any(|h| h == old) {
+        return Ok(false);
+    }
+    let renamed: StringRecord = record
+        .iter()
+        .map(|h| if h == old { new } else { h })
+        .collect();
+    let mut wtr = WriterBuilder::new().from_writer(vec![]);
+    wtr.write_record(&renamed)?;
+    let mut out = wtr
+        .into_inner()
+        .map_err(|e| AppError::IoError(e.into_error()))?;
+    out.extend_from_slice(body.as_bytes());
+
+    store::replace_file(&name, &out)?;
+    Ok(true)
+}
@@ -0,0 +1,59 @@
+use crate::config::{Config, WORKOUT_QUESTION};
+use crate::custom;
+use crate::schema::canonical_metric;
+use crate::METRIC_NAMES;
+
+// --- The order of the questions while logging, grouped by `[[sections]]` ---
+// Sleep is still only asked with the day's first entry, wherever its section is, and
+// the remarks always come last.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Question {
+    Metric(&'static str),
+    Custom(&'static str),
+    Workout,
+}
+
+pub struct Section {
+    pub title: Option<String>, // None for the questions no section mentions
+    pub questions: Vec<Question>,
+}
+
+// Built-in metrics, custom metrics, then the workout: the order without sections
+fn usual_order() -> impl Iterator<Item = Question> {
+    METRIC_NAMES
+        .iter()
+        .map(|m| Question::Metric(m))
+        .chain(custom::columns().map(Question::Custom))
+        .chain(std::iter::once(Question::Workout))
+}
+
+fn question(name: &str) -> Option<Question> {
+    if name == WORKOUT_QUESTION {
+        return Some(Question::Workout);
+    }
+    canonical_metric(name)
+        .map(Question::Metric)
+        .or_else(|| custom::columns().find(|c| *c == name).map(Question::Custom))
+}
+
+// The configured sections (names were checked when the config was loaded), then the rest
+pub fn order(config: &Config) -> Vec<Section> {
+    let mut sections: Vec<Section> = config
+        .sections
+        .iter()
+        .map(|section| Section {
+            title: Some(section.title.clone()),
+            questions: section.metrics.iter().filter_map(|m| question(m)).collect(),
+        })
+        .collect();
+    let rest: Vec<Question> = usual_order()
+        .filter(|q| !sections.iter().any(|s| s.questions.contains(q)))
+        .collect();
+    if !rest.is_empty() {
+        sections.push(Section {
+            title: None,
+            questions: rest,
+        });
+    }
+    sections
+}
@@ -0,0 +1,186 @@
+use crate::baseline;
+use crate::cli::OutputFormat;
+use crate::daily::{self, DailyPoint};
+use crate::layout;
+use crate::phases;
+use crate::scales;
+use crate::schema::column_name;
+use crate::units::DisplayUnits;
+use crate::{AppError, EntryKind, GlobalOptions, MetricSummary, METRIC_NAMES};
+use chrono::NaiveDate;
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const RECENT_DAYS: usize = 7; // The "Last 7" column: the most recent logged days
+
+// One metric's row: shown as a table, or written as JSON/CSV in display units
+#[derive(Serialize)]
+struct MetricRow {
+    metric: String,
+    days: usize,
+    mean: f64,
+    min: f64,
+    max: f64,
+    last_7: Option<f64>,   // Mean of the most recent logged days
+    baseline: Option<f64>, // Seed value, while too few days are logged to go without it
+    unit: String,
+}
+
+#[derive(Serialize)]
+struct Stats {
+    first_day: Option<NaiveDate>,
+    last_day: Option<NaiveDate>,
+    entries: usize,
+    days_logged: usize,
+    workout_days: usize,
+    entry_kinds: BTreeMap<&'static str, usize>,
+    seed_baseline: bool, // Fewer than 14 days logged and `baseline set` was run
+    metrics: Vec<MetricRow>,
+}
+
+fn collect(options: &GlobalOptions) -> Result<Stats, AppError> {
+    let units = DisplayUnits::from_config(&options.config);
+    let points = daily::load(options)?;
+    // Older rows have no kind, so the breakdown only covers the ones that do
+    let mut entry_kinds = BTreeMap::new();
+    for entry in options.analysis_entries()? {
+        let kind = match entry.entry_kind {
+            Some(EntryKind::FirstOfDay) => "first of day",
+            Some(EntryKind::FollowUp) => "follow-up",
+            Some(EntryKind::Backfill) => "backfill",
+            Some(EntryKind::Correction) => "correction",
+            None => continue,
+        };
+        *entry_kinds.entry(kind).or_default() += 1;
+    }
+    let seed = baseline::seed(options, &points)?;
+    let recent: &[DailyPoint] = &points[points.len().saturating_sub(RECENT_DAYS)..];
+    let metrics = METRIC_NAMES
+        .iter()
+        .filter_map(|metric| {
+            let summary = MetricSummary::for_days(&points, metric)?.converted(&units, metric);
+            Some(MetricRow {
+                metric: column_name(metric).to_string(),
+                days: summary.count,
+                mean: summary.mean,
+                min: summary.min,
+                max: summary.max,
+                last_7: MetricSummary::for_days(recent, metric)
+                    .map(|s| units.convert(metric, s.mean)),
+                baseline: seed
+                    .as_ref()
+                    .and_then(|seed| seed.get(*metric))
+                    .map(|value| units.convert(metric, *value)),
+                unit: units.unit(metric).to_string(),
+            })
+        })
+        .collect();
+    Ok(Stats {
+        first_day: points.first().map(|p| p.date),
+        last_day: points.last().map(|p| p.date),
+        entries: points.iter().map(|p| p.entries).sum(),
+        days_logged: points.len(),
+        workout_days: points.iter().filter(|p| p.workout).count(),
+        entry_kinds,
+        seed_baseline: seed.is_some(),
+        metrics,
+    })
+}
+
+// --- `stats` command: one row per metric over the daily values ---
+// `--format json` writes everything below; `--format csv` just the metric rows.
+pub fn run_stats(options: &GlobalOptions) -> Result<(), AppError> {
+    let stats = collect(options)?;
+    match options.format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+            return Ok(());
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+            for row in &stats.metrics {
+                wtr.serialize(row)?;
+            }
+            wtr.flush()?;
+            return Ok(());
+        }
+        OutputFormat::Table | OutputFormat::Parquet => {}
+    }
+
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Statistics ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    let (Some(first), Some(last)) = (stats.first_day, stats.last_day) else {
+        println!("{}", "No entries yet. Run `log` to add one.".dimmed());
+        return Ok(());
+    };
+    println!("Period: {} to {}", first, last);
+    if let Some(note) = phases::mixed_note(&options.data_file(), first, last)? {
+        println!("{}", note.yellow());
+    }
+    for note in scales::notes(first) {
+        println!("{}", note.yellow());
+    }
+    println!(
+        "Entries: {}  Days logged: {}  Workout days: {}",
+        stats.entries.to_string().yellow(),
+        stats.days_logged.to_string().yellow(),
+        stats.workout_days.to_string().green()
+    );
+    if !stats.entry_kinds.is_empty() {
+        let counts: Vec<String> = stats
+            .entry_kinds
+            .iter()
+            .map(|(kind, count)| format!("{} {}", kind, count.to_string().yellow()))
+            .collect();
+        println!("Entry kinds: {}", counts.join("  "));
+    }
+    if stats.seed_baseline {
+        println!(
+            "{}",
+            format!(
+                "Fewer than {} days logged: the Baseline column is your seed baseline.",
+                baseline::SEED_DAYS
+            )
+            .dimmed()
+        );
+    }
+    println!();
+
+    // The Baseline column only appears while the seed baseline is in use
+    let baseline_cell = |value: Option<f64>| match (stats.seed_baseline, value) {
+        (false, _) => String::new(),
+        (true, Some(value)) => format!(" {:>8.1}", value),
+        (true, None) => format!(" {:>8}", "-"),
+    };
+    layout::row(&format!(
+        "{:<14} {:>5} {:>6} {:>5} {:>5} {:>7}{}  Unit",
+        "Metric",
+        "Days",
+        "Mean",
+        "Min",
+        "Max",
+        "Last 7",
+        if stats.seed_baseline {
+            format!(" {:>8}", "Baseline")
+        } else {
+            String::new()
+        }
+    ));
+    for row in &stats.metrics {
+        layout::row(&format!(
+            "{:<14} {:>5} {:>6.2} {:>5.1} {:>5.1} {:>7}{}  {}",
+            row.metric,
+            row.days,
+            row.mean,
+            row.min,
+            row.max,
+            row.last_7
+                .map_or("-".to_string(), |mean| format!("{:.2}", mean)),
+            baseline_cell(row.baseline),
+            row.unit.dimmed()
+        ));
+    }
+    Ok(())
+}
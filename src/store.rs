@@ -0,0 +1,789 @@
+use crate::backup;
+use crate::custom;
+use crate::jsonl::{self, JsonlStore};
+use crate::lock;
+use crate::msgpack::{self, MsgpackStore};
+use crate::remote;
+use crate::schema::{canonical_metric, header_version};
+use crate::{constraints, csv_header, AppError, LogEntry};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// Data file split per year (`yearly_files = true`), installed once at startup
+static YEARLY_FILE: OnceLock<String> = OnceLock::new();
+
+// --- What the logging flow needs to know about the entries so far ---
+pub struct StoreInfo {
+    pub first_entry_date: Option<NaiveDate>,
+    pub last_entry_date: Option<NaiveDate>,
+    pub workout_logged_today: bool,
+    pub last_sleep_hours: Option<f32>,
+    pub last_sleep_quality: Option<f32>,
+}
+
+// --- Where entries live: CSV by default, JSON Lines or MessagePack by file extension ---
+// Commands go through this instead of a file format, so another backend (or an
+// in-memory one for checks) only has to implement the first three methods.
+pub trait MetricStore {
+    fn append(&self, entry: &LogEntry) -> Result<(), AppError>;
+    fn all_entries(&self) -> Result<Vec<LogEntry>, AppError>;
+    // Replace everything, e.g. after an edit or a merge
+    fn write_all(&self, entries: &[LogEntry]) -> Result<(), AppError>;
+
+    // Read, change and write back the entries as one step. Rewrites go through this rather
+    // than all_entries + write_all, so the opened store can hold its write lock throughout
+    // and an entry appended in between isn't written over.
+    fn modify(
+        &self,
+        change: &mut dyn FnMut(&mut Vec<LogEntry>) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        let mut entries = self.all_entries()?;
+        change(&mut entries)?;
+        self.write_all(&entries)
+    }
+
+    // Entries dated from..=to (UTC days, like the rest of the logging flow)
+    fn read_range(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<LogEntry>, AppError> {
+        Ok(self
+            .all_entries()?
+            .into_iter()
+            .filter(|e| {
+                e.parsed_timestamp()
+                    .is_some_and(|ts| (from..=to).contains(&ts.date_naive()))
+            })
+            .collect())
+    }
+
+    // The entry written last (entries are kept in time order)
+    fn last_entry(&self) -> Result<Option<LogEntry>, AppError> {
+        Ok(self.all_entries()?.pop())
+    }
+
+    fn info(&self) -> Result<StoreInfo, AppError> {
+        let today = Utc::now().date_naive();
+        let mut info = StoreInfo {
+            first_entry_date: None,
+            last_entry_date: None,
+            workout_logged_today: false,
+            last_sleep_hours: None,
+            last_sleep_quality: None,
+        };
+        for entry in self.all_entries()? {
+            let Some(date) = entry.parsed_timestamp().map(|ts| ts.date_naive()) else {
+                continue;
+            };
+            if info.first_entry_date.is_none_or(|first| date < first) {
+                info.first_entry_date = Some(date);
+            }
+            info.last_entry_date = Some(date);
+            info.workout_logged_today |= date == today && entry.workout_today;
+            info.last_sleep_hours = entry.sleep_hours.or(info.last_sleep_hours);
+            info.last_sleep_quality = entry.sleep_quality.or(info.last_sleep_quality);
+        }
+        Ok(info)
+    }
+}
+
+pub fn install(data_file: &str, yearly_files: bool) {
+    if yearly_files {
+        let _ = YEARLY_FILE.set(data_file.to_string());
+    }
+}
+
+// The store for a data file, picked by its extension. Year files are read whenever
+// they exist in place of the single file, so turning `yearly_files` off hides nothing.
+pub fn open(file_path: &str) -> Box<dyn MetricStore> {
+    let yearly = YEARLY_FILE.get().is_some_and(|f| f == file_path)
+        || (!Path::new(file_path).exists() && !year_files(file_path).is_empty());
+    let inner = if let Some(remote) = remote::store_for(file_path) {
+        remote
+    } else if yearly {
+        Box::new(YearlyStore {
+            path: file_path.to_string(),
+        })
+    } else {
+        open_file(file_path)
+    };
+    Box::new(GuardedStore {
+        path: file_path.to_string(),
+        inner,
+    })
+}
+
+// --- Any store, locked while it writes (and while a rewrite reads), with the data copied
+// to backups/ first ---
+struct GuardedStore {
+    path: String,
+    inner: Box<dyn MetricStore>,
+}
+
+impl MetricStore for GuardedStore {
+    fn append(&self, entry: &LogEntry) -> Result<(), AppError> {
+        let _lock = lock::write_lock(&self.path)?;
+        backup::snapshot(&self.path)?;
+        self.inner.append(entry)
+    }
+
+    fn all_entries(&self) -> Result<Vec<LogEntry>, AppError> {
+        self.inner.all_entries()
+    }
+
+    fn write_all(&self, entries: &[LogEntry]) -> Result<(), AppError> {
+        let _lock = lock::write_lock(&self.path)?;
+        backup::snapshot(&self.path)?;
+        self.inner.write_all(entries)
+    }
+
+    fn modify(
+        &self,
+        change: &mut dyn FnMut(&mut Vec<LogEntry>) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        let _lock = lock::write_lock(&self.path)?;
+        backup::snapshot(&self.path)?;
+        self.inner.modify(change)
+    }
+
+    // The rest go straight through, so each backend keeps its faster versions
+    fn read_range(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<LogEntry>, AppError> {
+        self.inner.read_range(from, to)
+    }
+
+    fn last_entry(&self) -> Result<Option<LogEntry>, AppError> {
+        self.inner.last_entry()
+    }
+
+    fn info(&self) -> Result<StoreInfo, AppError> {
+        self.inner.info()
+    }
+}
+
+pub fn open_file(file_path: &str) -> Box<dyn MetricStore> {
+    if jsonl::is_jsonl(file_path) {
+        Box::new(JsonlStore::new(file_path))
+    } else if msgpack::is_msgpack(file_path) {
+        Box::new(MsgpackStore::new(file_path))
+    } else {
+        Box::new(CsvStore::new(file_path))
+    }
+}
+
+pub struct CsvStore {
+    path: String,
+}
+
+impl CsvStore {
+    pub fn new(path: &str) -> CsvStore {
+        CsvStore {
+            path: path.to_string(),
+        }
+    }
+}
+
+impl MetricStore for CsvStore {
+    fn append(&self, entry: &LogEntry) -> Result<(), AppError> {
+        append_csv(&self.path, entry)
+    }
+
+    fn all_entries(&self) -> Result<Vec<LogEntry>, AppError> {
+        read_csv(&self.path)
+    }
+
+    fn write_all(&self, entries: &[LogEntry]) -> Result<(), AppError> {
+        write_csv(&self.path, entries)
+    }
+
+    // Reads the raw columns, so rows too damaged to load still count for the dates
+    fn info(&self) -> Result<StoreInfo, AppError> {
+        csv_info(&self.path)
+    }
+}
+
+// --- One file per year next to the data file: daily_metrics_2024.csv, daily_metrics_2025.csv ---
+// Entries go to the file of their (UTC) year. A single file from before the split is
+// still read, and the first write after turning the split on moves its entries over.
+pub struct YearlyStore {
+    path: String,
+}
+
+// daily_metrics.csv -> daily_metrics_2025.csv
+fn year_path(file_path: &str, year: i32) -> PathBuf {
+    let path = Path::new(file_path);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}_{}.{}", stem, year, ext.to_string_lossy()),
+        None => format!("{}_{}", stem, year),
+    };
+    path.with_file_name(name)
+}
+
+// The year files that exist, oldest first
+fn year_files(file_path: &str) -> Vec<(i32, PathBuf)> {
+    let path = Path::new(file_path);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let Ok(listing) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let prefix = format!("{}_", stem);
+    let mut found: Vec<(i32, PathBuf)> = listing
+        .filter_map(|item| {
+            let name = item.ok()?.file_name();
+            let year: i32 = name
+                .to_str()?
+                .strip_prefix(&prefix)?
+                .get(..4)?
+                .parse()
+                .ok()?;
+            let candidate = year_path(file_path, year);
+            (candidate.file_name() == Some(name.as_os_str())).then_some((year, candidate))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+// Every file holding entries: the single file (if it's still there), then the years
+pub fn files(file_path: &str) -> Vec<PathBuf> {
+    let single = Path::new(file_path);
+    let mut files: Vec<PathBuf> = Vec::new();
+    if single.exists() {
+        files.push(single.to_path_buf());
+    }
+    files.extend(year_files(file_path).into_iter().map(|(_, path)| path));
+    files
+}
+
+fn entry_year(entry: &LogEntry) -> i32 {
+    entry
+        .parsed_timestamp()
+        .map_or_else(|| Utc::now().year(), |ts| ts.year())
+}
+
+impl YearlyStore {
+    fn year_store(&self, year: i32) -> Box<dyn MetricStore> {
+        open_file(&year_path(&self.path, year).to_string_lossy())
+    }
+}
+
+impl MetricStore for YearlyStore {
+    fn append(&self, entry: &LogEntry) -> Result<(), AppError> {
+        if Path::new(&self.path).exists() {
+            let mut entries = self.all_entries()?;
+            entries.push(entry.clone());
+            return self.write_all(&entries);
+        }
+        self.year_store(entry_year(entry)).append(entry)
+    }
+
+    fn all_entries(&self) -> Result<Vec<LogEntry>, AppError> {
+        let mut entries = Vec::new();
+        for file in files(&self.path) {
+            entries.extend(open_file(&file.to_string_lossy()).all_entries()?);
+        }
+        Ok(entries)
+    }
+
+    fn write_all(&self, entries: &[LogEntry]) -> Result<(), AppError> {
+        let mut by_year: std::collections::BTreeMap<i32, Vec<LogEntry>> = Default::default();
+        for entry in entries {
+            by_year
+                .entry(entry_year(entry))
+                .or_default()
+                .push(entry.clone());
+        }
+        if by_year.is_empty() {
+            by_year.insert(Utc::now().year(), Vec::new()); // Keeps a file with the header
+        }
+        for (year, entries) in &by_year {
+            self.year_store(*year).write_all(entries)?;
+        }
+        // Years left without entries (say after a delete) and the old single file go
+        for (year, path) in year_files(&self.path) {
+            if !by_year.contains_key(&year) {
+                fs::remove_file(path)?;
+            }
+        }
+        if Path::new(&self.path).exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    // Only the years in the range are read
+    fn read_range(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<LogEntry>, AppError> {
+        let mut entries = if Path::new(&self.path).exists() {
+            open_file(&self.path).read_range(from, to)?
+        } else {
+            Vec::new()
+        };
+        for (year, path) in year_files(&self.path) {
+            if (from.year()..=to.year()).contains(&year) {
+                entries.extend(open_file(&path.to_string_lossy()).read_range(from, to)?);
+            }
+        }
+        Ok(entries)
+    }
+
+    fn last_entry(&self) -> Result<Option<LogEntry>, AppError> {
+        match files(&self.path).last() {
+            Some(path) => open_file(&path.to_string_lossy()).last_entry(),
+            None => Ok(None),
+        }
+    }
+
+    // The first date comes from the oldest file, everything else from the newest
+    fn info(&self) -> Result<StoreInfo, AppError> {
+        let files = files(&self.path);
+        let (Some(oldest), Some(newest)) = (files.first(), files.last()) else {
+            return open_file(&self.path).info();
+        };
+        let mut info = open_file(&newest.to_string_lossy()).info()?;
+        if oldest != newest {
+            let first = open_file(&oldest.to_string_lossy()).info()?;
+            info.first_entry_date = first.first_entry_date.or(info.first_entry_date);
+        }
+        Ok(info)
+    }
+}
+
+// --- Entries held in memory only: checks run against it without touching files ---
+#[cfg(any(test, feature = "test-support"))]
+#[derive(Default)]
+pub struct MemoryStore {
+    entries: std::cell::RefCell<Vec<LogEntry>>,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl MetricStore for MemoryStore {
+    fn append(&self, entry: &LogEntry) -> Result<(), AppError> {
+        constraints::validate(std::slice::from_ref(entry))?;
+        self.entries.borrow_mut().push(entry.clone());
+        Ok(())
+    }
+
+    fn all_entries(&self) -> Result<Vec<LogEntry>, AppError> {
+        Ok(self.entries.borrow().clone())
+    }
+
+    fn write_all(&self, entries: &[LogEntry]) -> Result<(), AppError> {
+        *self.entries.borrow_mut() = entries.to_vec();
+        Ok(())
+    }
+}
+
+// --- Helper function to read first and last date from CSV ---
+fn csv_info(file_path: &str) -> Result<StoreInfo, AppError> {
+    let mut first_date: Option<NaiveDate> = None;
+    let mut last_date: Option<NaiveDate> = None;
+    let mut workout_today_logged = false; // Initialize flag for the new logic
+    let mut last_sleep_hours: Option<f32> = None;
+    let mut last_sleep_quality: Option<f32> = None;
+
+    let today = Utc::now().date_naive(); // Get today's date once
+
+    if Path::new(file_path).exists() {
+        let file = File::open(file_path)?;
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(true) // Expect a header row
+            .from_reader(file);
+
+        // Define the expected header name for robustness check
+        const TIMESTAMP_HEADER: &str = "timestamp";
+        // Columns are found by name, so older, renamed or reordered headers still read right
+        let headers = rdr.headers()?.clone();
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|h| canonical_metric(h).unwrap_or(h) == name)
+        };
+        let timestamp_column = column(TIMESTAMP_HEADER).unwrap_or(0);
+        let workout_column = column("workout_today");
+        let sleep_hours_column = column("sleep_hours");
+        let sleep_quality_column = column("sleep_quality");
+
+        for result in rdr.records() {
+            let record = match result {
+                Ok(rec) => rec,
+                Err(e) => {
+                    eprintln!("Warning: Skipping corrupted CSV record: {}", e);
+                    continue; // Skip this record
+                }
+            };
+
+            // Get timestamp string
+            if let Some(ts_str) = record.get(timestamp_column) {
+                // Safety check: Skip if we somehow got the header row
+                if ts_str.trim() == TIMESTAMP_HEADER {
+                    eprintln!("Warning: Skipping potential header row accidentally read as data.");
+                    continue;
+                }
+
+                // Parse timestamp
+                let dt = DateTime::parse_from_rfc3339(ts_str)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .map_err(|e| {
+                        eprintln!(
+                            "Warning: Could not parse timestamp '{}' in data row: {}. Skipping record.", // Changed log level
+                            ts_str, e
+                        );
+                        // Don't return AppError here, just skip the record for date finding purposes
+                        // AppError::DateParseError(e)
+                    });
+
+                // Proceed only if date parsing was successful
+                if let Ok(dt) = dt {
+                    let current_date = dt.date_naive();
+
+                    // Update first date logic
+                    if first_date.is_none() || current_date < first_date.unwrap() {
+                        first_date = Some(current_date);
+                    }
+                    // Update last date (always override with the latest processed valid record)
+                    last_date = Some(current_date);
+
+                    // --- Check workout status for today's entries ---
+                    if current_date == today {
+                        if let Some(workout_str) = workout_column.and_then(|i| record.get(i)) {
+                            // Check if workout was logged as 'true' case-insensitively
+                            if workout_str.trim().eq_ignore_ascii_case("true") {
+                                workout_today_logged = true;
+                                // Optimization note: We could potentially break early if we only needed this flag,
+                                // but we still need to loop through all records to find the *last* date reliably.
+                            }
+                        } else {
+                            eprintln!(
+                                "Warning: Record for today ({}) has no workout_today value.",
+                                current_date
+                            );
+                        }
+                    }
+                    
+                    // Update sleep values from the most recent entry
+                    if last_date.is_none() || current_date >= last_date.unwrap() {
+                        // Try to read sleep hours
+                        if let Some(sleep_hours_str) =
+                            sleep_hours_column.and_then(|i| record.get(i))
+                        {
+                            if !sleep_hours_str.trim().is_empty() {
+                                if let Ok(hours) = sleep_hours_str.trim().parse::<f32>() {
+                                    last_sleep_hours = Some(hours);
+                                }
+                            }
+                        }
+                        
+                        // Try to read sleep quality
+                        if let Some(sleep_quality_str) =
+                            sleep_quality_column.and_then(|i| record.get(i))
+                        {
+                            if !sleep_quality_str.trim().is_empty() {
+                                if let Ok(quality) = sleep_quality_str.trim().parse::<f32>() {
+                                    last_sleep_quality = Some(quality);
+                                }
+                            }
+                        }
+                    }
+                } // End if Ok(dt)
+            } else {
+                eprintln!("Warning: Skipping record with missing timestamp column.");
+            }
+        } // End for loop
+    }
+
+    Ok(StoreInfo {
+        first_entry_date: first_date,
+        last_entry_date: last_date,
+        workout_logged_today: workout_today_logged,
+        last_sleep_hours,
+        last_sleep_quality,
+    })
+}
+
+// --- Helper function to append data to CSV ---
+fn append_csv(file_path: &str, entry: &LogEntry) -> Result<(), AppError> {
+    constraints::validate(std::slice::from_ref(entry))?;
+    let file_exists = Path::new(file_path).exists();
+
+    // Files from before the session and provenance columns existed are widened only once
+    // there is data for them to keep; until then new rows match the old header
+    let header = if file_exists {
+        ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(file_path)?
+            .records()
+            .next()
+            .transpose()?
+    } else {
+        None
+    };
+    // Rows are written by position, which only lines up with a known layout
+    if header.as_ref().is_some_and(|h| header_version(h).is_none()) {
+        return Err(AppError::UsageError(format!(
+            "{} has columns in an unexpected order or unknown columns; run `migrate` first",
+            file_path
+        )));
+    }
+    let existing_columns = header
+        .map(|header| header.len())
+        .filter(|columns| *columns < csv_header().len());
+    if existing_columns.is_some()
+        && (entry.session_seconds.is_some()
+            || !entry.provenance.is_empty()
+            || entry.entry_kind.is_some()
+            || !entry.custom.is_empty())
+    {
+        let mut entries = read_csv(file_path)?;
+        entries.push(entry.clone());
+        return write_csv(file_path, &entries);
+    }
+
+    // Per-user data lives in a subdirectory that may not exist yet
+    if let Some(parent) = Path::new(file_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+
+    let mut wtr = WriterBuilder::new()
+        .has_headers(false) // The header is written by hand below
+        .from_writer(file);
+
+    // Write header if it's a new file
+    if !file_exists {
+        let headers = csv_header();
+        wtr.write_record(&headers)?;
+    }
+
+    // Serialize and write the data record, cut to the columns an older file has
+    let mut record = entry_record(entry)?;
+    if let Some(columns) = existing_columns {
+        record.truncate(columns);
+    }
+    wtr.write_record(&record)?;
+    wtr.flush()?;
+    // Through to the disk, not just the OS cache, before saying the entry is logged
+    wtr.get_ref().sync_data()?;
+    Ok(())
+}
+
+// --- One entry as a CSV record, in csv_header() order ---
+pub fn entry_record(entry: &LogEntry) -> Result<StringRecord, AppError> {
+    // The custom values go in columns of their own, which serde can't write
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(vec![]);
+    wtr.serialize(LogEntry {
+        custom: BTreeMap::new(),
+        ..entry.clone()
+    })?;
+    let bytes = wtr
+        .into_inner()
+        .map_err(|e| AppError::IoError(e.into_error()))?;
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(bytes.as_slice());
+    let mut record = rdr.records().next().transpose()?.unwrap_or_default();
+    record.extend(custom::record_values(entry));
+    Ok(record)
+}
+
+// --- Replace a file's contents all at once ---
+// Writes a temporary file, syncs it to disk and renames it over the original, so an
+// interrupted write or a power cut leaves either the old file or the new one, never
+// half of each.
+pub fn replace_file(file_path: &str, bytes: &[u8]) -> Result<(), AppError> {
+    let tmp_path = format!("{}.tmp", file_path);
+    {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, file_path)?;
+    // The rename itself is only durable once the directory is synced (not possible on Windows)
+    #[cfg(unix)]
+    if let Some(parent) = Path::new(file_path).parent() {
+        let dir = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+// --- Helper function to rewrite the whole CSV ---
+fn write_csv(file_path: &str, entries: &[LogEntry]) -> Result<(), AppError> {
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(vec![]);
+    wtr.write_record(&csv_header())?;
+    for entry in entries {
+        wtr.write_record(&entry_record(entry)?)?;
+    }
+    let bytes = wtr
+        .into_inner()
+        .map_err(|e| AppError::IoError(e.into_error()))?;
+    replace_file(file_path, &bytes)
+}
+
+// --- Helper function to read every entry from CSV ---
+fn read_csv(file_path: &str) -> Result<Vec<LogEntry>, AppError> {
+    read_csv_renaming(file_path, &[])
+}
+
+// Also reads each column named in `renames` (old name, field) as that field; `migrate`
+// uses it for columns that were renamed outside the config
+pub fn read_csv_renaming(
+    file_path: &str,
+    renames: &[(String, &'static str)],
+) -> Result<Vec<LogEntry>, AppError> {
+    let mut entries = Vec::new();
+    if !Path::new(file_path).exists() {
+        return Ok(entries);
+    }
+
+    let file = File::open(file_path)?;
+    let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(file);
+    // Renamed metric columns are read back under their original field names
+    let headers: StringRecord = rdr
+        .headers()?
+        .iter()
+        .map(|h| {
+            renames
+                .iter()
+                .find(|(old, _)| old == h)
+                .map(|(_, field)| *field)
+                .or_else(|| canonical_metric(h))
+                .unwrap_or(h)
+        })
+        .collect();
+    rdr.set_headers(headers.clone());
+    for result in rdr.records() {
+        match result.and_then(|record| {
+            let mut entry: LogEntry = record.deserialize(Some(&headers))?;
+            entry.custom = custom::read_values(&headers, &record);
+            Ok(entry)
+        }) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => eprintln!("Warning: Skipping corrupted CSV record: {}", e),
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::time::Duration;
+
+    fn entry_at(day: u32, hour: u32, energy: u8) -> LogEntry {
+        let timestamp = Utc.with_ymd_and_hms(2025, 3, day, hour, 0, 0).unwrap();
+        let mut entry = LogEntry::empty_at(timestamp);
+        entry.energy = Some(energy);
+        entry
+    }
+
+    // A data file in a directory of its own under the system temp directory
+    fn temp_data_file(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("dml-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("daily_metrics.csv").to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn memory_store_round_trip() {
+        let store = MemoryStore::default();
+        let entries = [entry_at(1, 8, 5), entry_at(2, 8, 6), entry_at(2, 20, 4)];
+        for entry in &entries {
+            store.append(entry).unwrap();
+        }
+        assert_eq!(store.all_entries().unwrap(), entries);
+        let day = NaiveDate::from_ymd_opt(2025, 3, 2).unwrap();
+        assert_eq!(store.read_range(day, day).unwrap(), entries[1..]);
+        assert_eq!(store.last_entry().unwrap(), Some(entries[2].clone()));
+
+        let info = store.info().unwrap();
+        assert_eq!(info.first_entry_date, NaiveDate::from_ymd_opt(2025, 3, 1));
+        assert_eq!(info.last_entry_date, Some(day));
+    }
+
+    #[test]
+    fn memory_store_refuses_out_of_range_values() {
+        let store = MemoryStore::default();
+        assert!(store.append(&entry_at(1, 8, 11)).is_err());
+        assert!(store.all_entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn modify_writes_back_the_changed_entries() {
+        let store = MemoryStore::default();
+        store
+            .write_all(&[entry_at(1, 8, 5), entry_at(2, 8, 6)])
+            .unwrap();
+        store
+            .modify(&mut |entries| {
+                entries.retain(|e| e.energy != Some(5));
+                entries.push(entry_at(3, 8, 7));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            store.all_entries().unwrap(),
+            [entry_at(2, 8, 6), entry_at(3, 8, 7)]
+        );
+    }
+
+    #[test]
+    fn csv_file_round_trip() {
+        let path = temp_data_file("csv-round-trip");
+        let store = open_file(&path);
+        let memory = MemoryStore::default();
+        for entry in [entry_at(1, 8, 5), entry_at(1, 14, 3), entry_at(2, 8, 9)] {
+            store.append(&entry).unwrap();
+            memory.append(&entry).unwrap();
+        }
+        assert_eq!(store.all_entries().unwrap(), memory.all_entries().unwrap());
+        store.write_all(&memory.all_entries().unwrap()[1..]).unwrap();
+        assert_eq!(
+            store.all_entries().unwrap(),
+            memory.all_entries().unwrap()[1..]
+        );
+    }
+
+    #[test]
+    fn rewrite_keeps_an_entry_appended_while_it_runs() {
+        let path = temp_data_file("rewrite-lock");
+        open(&path).append(&entry_at(1, 8, 5)).unwrap();
+
+        let mut appender = None;
+        open(&path)
+            .modify(&mut |entries| {
+                // A `quick` from another process, started after the rewrite read the file
+                let path = path.clone();
+                appender = Some(std::thread::spawn(move || {
+                    open(&path).append(&entry_at(2, 8, 6))
+                }));
+                std::thread::sleep(Duration::from_millis(200));
+                entries[0].energy = Some(7);
+                Ok(())
+            })
+            .unwrap();
+        appender.unwrap().join().unwrap().unwrap();
+
+        assert_eq!(
+            open(&path).all_entries().unwrap(),
+            [entry_at(1, 8, 7), entry_at(2, 8, 6)]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::analysis::{metric_series, pair_links};
+use crate::daily::{self, DailyPoint};
+use crate::layout;
+use crate::schema::column_name;
+use crate::units::DisplayUnits;
+use crate::{AppError, GlobalOptions, LogEntry, MetricSummary, METRIC_NAMES};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use colored::*;
+
+const CLOSE: f64 = 0.5; // Answers within this of the average count as "about usual"
+
+// Consecutive logged days up to and including the last one
+fn current_streak(points: &[DailyPoint]) -> usize {
+    let mut streak = 0;
+    let mut expected: Option<NaiveDate> = None;
+    for point in points.iter().rev() {
+        if expected.is_some_and(|date| point.date != date) {
+            break;
+        }
+        streak += 1;
+        expected = Some(point.date - Duration::days(1));
+    }
+    streak
+}
+
+// Each answer next to the metric's average over the earlier days
+fn averages(options: &GlobalOptions, entry: &LogEntry, earlier: &[DailyPoint]) {
+    let units = DisplayUnits::from_config(&options.config);
+    for metric in METRIC_NAMES {
+        let (Some(value), Some(summary)) = (
+            entry.metric(metric),
+            MetricSummary::for_days(earlier, metric),
+        ) else {
+            continue;
+        };
+        let (value, mean) = (
+            units.convert(metric, value),
+            units.convert(metric, summary.mean),
+        );
+        let difference = match value - mean {
+            d if d.abs() < 0.05 => 0.0, // No "-0.0"
+            d => d,
+        };
+        let shown = format!("{:+.1}", difference);
+        layout::row(&format!(
+            " {:<14} {:>5} {} {}",
+            column_name(metric),
+            value,
+            format!("avg {:.1}", mean).dimmed(),
+            if difference.abs() < CLOSE {
+                shown.dimmed()
+            } else if difference > 0.0 {
+                shown.green()
+            } else {
+                shown.red()
+            }
+        ));
+    }
+}
+
+// The strongest link between two metrics, in words
+fn insight(points: &[DailyPoint]) -> Option<String> {
+    let (a, b, r, days) = pair_links(&metric_series(points)).into_iter().next()?;
+    Some(format!(
+        "Days with more {} tend to have {} {} (r = {:+.2}, {} days)",
+        a,
+        if r > 0.0 { "more" } else { "less" },
+        b,
+        r,
+        days
+    ))
+}
+
+// --- Block shown after `log` saves an entry ---
+// Each part can be turned off under [summary] in config.toml.
+pub fn print_block(options: &GlobalOptions, entry: &LogEntry) -> Result<(), AppError> {
+    let shown = options.config.summary.clone().unwrap_or_default();
+    let points = daily::load(options)?;
+    let today = entry.parsed_timestamp().map(|ts| ts.date_naive());
+
+    if shown.streak.unwrap_or(true) {
+        let streak = current_streak(&points);
+        println!(
+            " Streak: {} {}",
+            streak.to_string().bold().yellow(),
+            if streak == 1 { "day" } else { "days in a row" }
+        );
+    }
+    if shown.averages.unwrap_or(true) {
+        // Days are in date order, so today's is the last one
+        let earlier = match points.split_last() {
+            Some((last, rest)) if Some(last.date) == today => rest,
+            _ => &points[..],
+        };
+        averages(options, entry, earlier);
+    }
+    if shown.insight.unwrap_or(true) {
+        if let Some(text) = insight(&points) {
+            println!(" {}", text.cyan());
+        }
+    }
+    if shown.reminder.unwrap_or(true) {
+        // Validated as HH:MM when the config was loaded
+        let time = options
+            .config
+            .reminder_time
+            .as_deref()
+            .and_then(|t| NaiveTime::parse_from_str(t, "%H:%M").ok());
+        if let Some(time) = time {
+            println!(
+                " {}",
+                format!("Next reminder: tomorrow at {}", time.format("%H:%M")).dimmed()
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use crate::daily::daily_points;
+use crate::milestones::Milestone;
+use crate::report::{by_month, in_range};
+use crate::scales;
+use crate::schema::column_name;
+use crate::units::DisplayUnits;
+use crate::{AppError, LogEntry, MetricSummary, METRIC_NAMES};
+use chrono::{NaiveDate, Utc};
+use handlebars::{no_escape, Handlebars};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// --- Data handed to report templates ---
+// Numbers are rounded to two decimals so templates can print them directly.
+#[derive(Serialize)]
+pub struct ReportContext {
+    generated_at: String,
+    first_date: Option<String>,
+    last_date: Option<String>,
+    goal_days: i64,
+    entries: usize,
+    days_logged: usize,
+    workout_days: usize,
+    metrics: Vec<MetricContext>,
+    months: Vec<MonthContext>,
+    days: Vec<DayContext>,
+    milestones: Vec<Milestone>, // Those within the report's period, oldest first
+}
+
+#[derive(Serialize)]
+struct MetricContext {
+    name: String, // Current column name (differs from the built-in name after `metric rename`)
+    unit: String, // Display unit, "" when the metric has none
+    count: usize,
+    mean: f64,
+    min: f64,
+    max: f64,
+    stddev: f64,
+    trend: String, // Last 7 logged days vs the first 7, e.g. "+0.4"; "n/a" under 14 days
+}
+
+#[derive(Serialize)]
+struct MonthContext {
+    month: String,
+    days_logged: usize,
+    workout_days: usize,
+    means: BTreeMap<&'static str, f64>,
+}
+
+#[derive(Serialize)]
+struct DayContext {
+    date: String,
+    entries: usize,
+    workout: bool,
+    means: BTreeMap<&'static str, f64>,
+    remarks: Vec<String>,
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+impl ReportContext {
+    // Values are converted to each metric's display unit
+    pub fn build(
+        entries: Vec<LogEntry>,
+        goal_days: i64,
+        units: &DisplayUnits,
+        milestones: &[Milestone],
+    ) -> ReportContext {
+        let mut remarks: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+        for entry in &entries {
+            if let Some(ts) = entry.parsed_timestamp() {
+                if !entry.remarks.trim().is_empty() {
+                    remarks
+                        .entry(ts.date_naive())
+                        .or_default()
+                        .push(entry.remarks.clone());
+                }
+            }
+        }
+
+        let total_entries = entries.len();
+        let points = scales::normalized(daily_points(entries));
+
+        // Summaries are over daily values, so a day logged twice counts once
+        let metrics = METRIC_NAMES
+            .iter()
+            .filter_map(|name| {
+                let daily: Vec<f64> = points
+                    .iter()
+                    .filter_map(|p| p.values.get(name).map(|v| units.convert(name, *v)))
+                    .collect();
+                let s = MetricSummary::from_values(&daily)?;
+                let variance =
+                    daily.iter().map(|v| (v - s.mean).powi(2)).sum::<f64>() / daily.len() as f64;
+                let trend = if daily.len() >= 14 {
+                    let first: f64 = daily[..7].iter().sum::<f64>() / 7.0;
+                    let last: f64 = daily[daily.len() - 7..].iter().sum::<f64>() / 7.0;
+                    format!("{:+.2}", last - first)
+                } else {
+                    "n/a".to_string()
+                };
+                Some(MetricContext {
+                    name: column_name(name).to_string(),
+                    unit: units.unit(name).to_string(),
+                    count: s.count,
+                    mean: round2(s.mean),
+                    min: round2(s.min),
+                    max: round2(s.max),
+                    stddev: round2(variance.sqrt()),
+                    trend,
+                })
+            })
+            .collect();
+        let months = by_month(&points)
+            .into_iter()
+            .map(|(month, days)| {
+                let mut means = BTreeMap::new();
+                for metric in METRIC_NAMES {
+                    let values: Vec<f64> = days
+                        .iter()
+                        .filter_map(|d| d.values.get(metric).map(|v| units.convert(metric, *v)))
+                        .collect();
+                    if let Some(s) = MetricSummary::from_values(&values) {
+                        means.insert(metric, round2(s.mean));
+                    }
+                }
+                MonthContext {
+                    month,
+                    days_logged: days.len(),
+                    workout_days: days.iter().filter(|d| d.workout).count(),
+                    means,
+                }
+            })
+            .collect();
+        let days = points
+            .iter()
+            .map(|p| DayContext {
+                date: p.date.to_string(),
+                entries: p.entries,
+                workout: p.workout,
+                means: p
+                    .values
+                    .iter()
+                    .map(|(k, v)| (*k, round2(units.convert(k, *v))))
+                    .collect(),
+                remarks: remarks.remove(&p.date).unwrap_or_default(),
+            })
+            .collect();
+
+        let milestones = in_range(&points, milestones).into_iter().cloned().collect();
+        ReportContext {
+            generated_at: Utc::now().to_rfc3339(),
+            first_date: points.first().map(|p| p.date.to_string()),
+            last_date: points.last().map(|p| p.date.to_string()),
+            goal_days,
+            entries: total_entries,
+            days_logged: points.len(),
+            workout_days: points.iter().filter(|p| p.workout).count(),
+            metrics,
+            months,
+            days,
+            milestones,
+        }
+    }
+}
+
+// --- Built-in report styles (`report --style <name>`) ---
+// Each is an ordinary template, so they double as examples for `--template`.
+pub const STYLES: [(&str, &str, bool); 4] = [
+    ("minimal", include_str!("templates/minimal.hbs"), false),
+    ("detailed", include_str!("templates/detailed.hbs"), false),
+    ("coach", include_str!("templates/coach.hbs"), false),
+    (
+        "printable",
+        include_str!("templates/printable.html.hbs"),
+        true,
+    ),
+];
+
+pub fn render_style(name: &str, context: &ReportContext) -> Result<String, AppError> {
+    let Some((_, source, html)) = STYLES.iter().find(|(style, _, _)| *style == name) else {
+        let names: Vec<&str> = STYLES.iter().map(|(style, _, _)| *style).collect();
+        return Err(AppError::TemplateError(format!(
+            "unknown style '{}' (choose from {})",
+            name,
+            names.join(", ")
+        )));
+    };
+    render_template(source, *html, context)
+        .map_err(|e| AppError::TemplateError(format!("style '{}': {}", name, e)))
+}
+
+// --- Render a Handlebars template file with the report context ---
+// HTML templates (*.html, *.html.hbs) get HTML escaping; anything else is plain text.
+pub fn render_template_file(path: &Path, context: &ReportContext) -> Result<String, AppError> {
+    let source = fs::read_to_string(path)?;
+    let is_html = path
+        .to_string_lossy()
+        .to_ascii_lowercase()
+        .trim_end_matches(".hbs")
+        .ends_with(".html");
+    render_template(&source, is_html, context)
+        .map_err(|e| AppError::TemplateError(format!("{}: {}", path.display(), e)))
+}
+
+fn render_template(
+    source: &str,
+    html: bool,
+    context: &ReportContext,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut handlebars = Handlebars::new();
+    if !html {
+        handlebars.register_escape_fn(no_escape);
+    }
+    Ok(handlebars.render_template(source, context)?)
+}
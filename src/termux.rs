@@ -0,0 +1,111 @@
+use crate::cli::RemindArgs;
+use crate::store;
+use crate::windows;
+use crate::{AppError, GlobalOptions};
+use chrono::{NaiveTime, Utc};
+use colored::*;
+use std::process::Command;
+
+// --- Termux (Android) support: detection and Termux:API notifications ---
+// Notifications need the Termux:API app plus `pkg install termux-api`.
+const REMINDER_ID: &str = "daily-metrics-reminder"; // Replaced, not stacked, on every reminder
+const DEFAULT_REMINDER_TIME: &str = "21:00";
+
+// Termux sets this in every session it starts
+pub fn detected() -> bool {
+    std::env::var_os("TERMUX_VERSION").is_some()
+}
+
+fn termux_api(command: &str, args: &[&str]) -> Result<(), AppError> {
+    let status = Command::new(command).args(args).status().map_err(|e| {
+        AppError::IoError(std::io::Error::new(
+            e.kind(),
+            format!(
+                "{} is not available ({}); install it with: pkg install termux-api",
+                command, e
+            ),
+        ))
+    })?;
+    if !status.success() {
+        return Err(AppError::IoError(std::io::Error::other(format!(
+            "{} failed ({})",
+            command, status
+        ))));
+    }
+    Ok(())
+}
+
+fn notify(id: &str, title: &str, content: &str) -> Result<(), AppError> {
+    termux_api(
+        "termux-notification",
+        &["--id", id, "--title", title, "--content", content],
+    )
+}
+
+// A toast on Windows, else a Termux notification. Posting again with the same id
+// replaces the earlier one instead of stacking another.
+pub fn post(id: &str, title: &str, content: &str) -> Result<(), AppError> {
+    if windows::detected() {
+        windows::notify(id, title, content)
+    } else {
+        notify(id, title, content)
+    }
+}
+
+// Called after a successful log; nothing to do off Termux or without a reminder showing
+pub fn clear_reminder() {
+    if detected() {
+        let _ = termux_api("termux-notification-remove", &[REMINDER_ID]);
+    }
+}
+
+// --- `remind` command: post a notification when today has no entry yet ---
+// Meant for termux-job-scheduler or cron, e.g. every evening at 21:00; on Windows
+// `remind --install` schedules it with Task Scheduler and the notification is a toast.
+pub fn run_remind(options: &GlobalOptions, args: &RemindArgs) -> Result<(), AppError> {
+    if args.install {
+        if !windows::detected() {
+            return Err(AppError::UsageError(
+                "--install sets up Windows Task Scheduler; elsewhere run `remind` from \
+                 termux-job-scheduler or cron"
+                    .to_string(),
+            ));
+        }
+        let at = args
+            .at
+            .as_deref()
+            .or(options.config.reminder_time.as_deref())
+            .unwrap_or(DEFAULT_REMINDER_TIME);
+        let at = NaiveTime::parse_from_str(at, "%H:%M")
+            .map_err(|_| AppError::UsageError(format!("Can't read --at '{}': use HH:MM", at)))?;
+        if options.dry_run {
+            println!(
+                "{}",
+                format!(
+                    "Dry run: would schedule a reminder at {}",
+                    at.format("%H:%M")
+                )
+                .yellow()
+            );
+            return Ok(());
+        }
+        let task = windows::install_task(&options.data_file(), &at.format("%H:%M").to_string())?;
+        println!(
+            "{}",
+            format!("Scheduled \"{}\" every day at {}", task, at.format("%H:%M")).green()
+        );
+        return Ok(());
+    }
+    let today = Utc::now().date_naive();
+    let logged = !store::open(&options.data_file())
+        .read_range(today, today)?
+        .is_empty();
+    if logged {
+        println!("Already logged today; no reminder needed.");
+        return Ok(());
+    }
+    let (title, content) = ("Daily metrics", "Nothing logged yet today. Run: log");
+    post(REMINDER_ID, title, content)?;
+    println!("Reminder posted.");
+    Ok(())
+}
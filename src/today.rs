@@ -0,0 +1,128 @@
+use crate::cli::TodayArgs;
+use crate::clipboard::copy_to_clipboard;
+use crate::layout;
+use crate::{read_entries, AppError, GlobalOptions, LogEntry, MetricSummary, METRIC_NAMES};
+use chrono::Utc;
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+
+// --- Snapshot of today's entries, also used as the QR code payload ---
+#[derive(Serialize)]
+struct TodaySummary {
+    date: String,
+    day_count: i64,
+    entries: usize,
+    workout: bool,
+    means: BTreeMap<&'static str, f64>, // Rounded to one decimal to keep the QR code small
+    remarks: Vec<String>,
+}
+
+fn summarize_today(entries: &[LogEntry]) -> Option<TodaySummary> {
+    let today = Utc::now().date_naive();
+    let todays: Vec<&LogEntry> = entries
+        .iter()
+        .filter(|e| e.parsed_timestamp().map(|ts| ts.date_naive()) == Some(today))
+        .collect();
+    let last = todays.last()?;
+
+    let mut means = BTreeMap::new();
+    for metric in METRIC_NAMES {
+        let values: Vec<f64> = todays.iter().filter_map(|e| e.metric(metric)).collect();
+        if let Some(summary) = MetricSummary::from_values(&values) {
+            means.insert(metric, (summary.mean * 10.0).round() / 10.0);
+        }
+    }
+
+    Some(TodaySummary {
+        date: today.format("%Y-%m-%d").to_string(),
+        day_count: last.day_count,
+        entries: todays.len(),
+        workout: todays.iter().any(|e| e.workout_today),
+        means,
+        remarks: todays
+            .iter()
+            .filter(|e| !e.remarks.trim().is_empty())
+            .map(|e| e.remarks.clone())
+            .collect(),
+    })
+}
+
+// --- `today` command: print today's summary, optionally as a QR code or to the clipboard ---
+pub fn run_today(options: &GlobalOptions, args: &TodayArgs) -> Result<(), AppError> {
+    let entries = read_entries(&options.data_file())?;
+    let Some(summary) = summarize_today(&entries) else {
+        println!("{}", "Nothing logged today yet.".dimmed());
+        return Ok(());
+    };
+
+    if args.qr {
+        let payload = serde_json::to_string(&summary)?;
+        println!("{}", qr_code(&payload)?);
+        println!("{}", "Scan to copy today's summary (JSON).".dimmed());
+        return Ok(());
+    }
+
+    let text = format_today(&summary)?;
+    layout::print_block(&text);
+    if args.copy {
+        copy_to_clipboard(&text)?;
+        println!("{}", "Summary copied to clipboard.".dimmed());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "qr")]
+fn qr_code(payload: &str) -> Result<String, AppError> {
+    use qrcode::render::unicode::Dense1x2;
+    use qrcode::QrCode;
+
+    let code = QrCode::new(payload.as_bytes())?;
+    // Light-on-dark so the code scans from a typical dark terminal
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+#[cfg(not(feature = "qr"))]
+fn qr_code(_payload: &str) -> Result<String, AppError> {
+    Err(AppError::UsageError(
+        "This build has no QR code support (rebuild with --features qr)".to_string(),
+    ))
+}
+
+fn format_today(summary: &TodaySummary) -> Result<String, fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, "{}", layout::rule('=').cyan())?;
+    writeln!(
+        out,
+        "{}",
+        format!(" Today: {} (Day {}) ", summary.date, summary.day_count)
+            .bold()
+            .cyan()
+    )?;
+    writeln!(out, "{}", layout::rule('=').cyan())?;
+    writeln!(
+        out,
+        "Entries: {}  Workout: {}",
+        summary.entries.to_string().yellow(),
+        if summary.workout {
+            "yes".green()
+        } else {
+            "no".dimmed()
+        }
+    )?;
+    for metric in METRIC_NAMES {
+        if let Some(mean) = summary.means.get(metric) {
+            writeln!(out, "{:<14} {:>5.1}", metric, mean)?;
+        }
+    }
+    for remark in &summary.remarks {
+        writeln!(out, "{} {}", "-".dimmed(), remark)?;
+    }
+    Ok(out)
+}
@@ -0,0 +1,204 @@
+use crate::layout;
+use crate::replay;
+use crate::{AppError, GlobalOptions, ENV_PREFIX, THEME};
+use chrono::{Duration, Utc};
+use colored::*;
+use dialoguer::Select;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const SAMPLE_DAYS: u32 = 90;
+const SAMPLE_SEED: u32 = 7; // The same sample data on every tour
+
+// --- One stop on the tour: what it shows, then the command itself ---
+struct Step {
+    title: &'static str,
+    about: &'static str,
+    args: Vec<String>,
+}
+
+fn step(title: &'static str, about: &'static str, args: &[&str]) -> Step {
+    Step {
+        title,
+        about,
+        args: args.iter().map(|a| a.to_string()).collect(),
+    }
+}
+
+fn steps() -> Vec<Step> {
+    let milestone = (Utc::now().date_naive() - Duration::days(30)).to_string();
+    vec![
+        step(
+            "Sample data",
+            "The tour works on made-up history, so nothing here touches your own log.",
+            &[
+                "generate",
+                "--days",
+                &SAMPLE_DAYS.to_string(),
+                "--seed",
+                &SAMPLE_SEED.to_string(),
+            ],
+        ),
+        step(
+            "Logging in one line",
+            "`log` asks every question in turn; `quick` takes the answers on the command line.",
+            &[
+                "quick", "s3", "z2", "e7", "st6", "f8", "i7", "w1", "tour day",
+            ],
+        ),
+        step(
+            "Today at a glance",
+            "What was logged today, and how it compares with your usual day.",
+            &["today"],
+        ),
+        step(
+            "Recent entries",
+            "The latest entries as a table; --format csv or json for scripts.",
+            &["view"],
+        ),
+        step(
+            "Statistics",
+            "One row per metric over the daily values.",
+            &["stats"],
+        ),
+        step(
+            "Calendar",
+            "Which days of the month were logged; move with the arrow keys, q to go on.",
+            &["calendar"],
+        ),
+        step(
+            "Through the day",
+            "Average energy by the hour it was logged at.",
+            &["by-hour", "energy"],
+        ),
+        step(
+            "What goes together",
+            "The strongest links between metrics, e.g. sleep and energy.",
+            &["correlate"],
+        ),
+        step(
+            "Milestones",
+            "Mark the day something changed...",
+            &["annotate", &milestone, "started running"],
+        ),
+        step(
+            "Before and after",
+            "...and see how each metric moved around it.",
+            &["effect", "running"],
+        ),
+        step(
+            "Progress report",
+            "Means, monthly trends and milestones in one report (--style for HTML, --bundle for a site).",
+            &["report"],
+        ),
+    ]
+}
+
+// The arguments as they'd be typed, quoting the ones with spaces
+fn shown_args(args: &[String]) -> String {
+    args.iter()
+        .map(|a| {
+            if a.contains(' ') {
+                format!("\"{}\"", a)
+            } else {
+                a.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Runs this program again on the sample files, with a config of its own and none of the
+// DML_ settings, so the tour shows the commands exactly as they are typed
+fn run_step(dir: &Path, args: &[String]) -> Result<(), AppError> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .arg("--data-file")
+        .arg(dir.join("daily_metrics.csv"))
+        .arg("--config")
+        .arg(dir.join("config.toml"))
+        .args(args)
+        .env_remove("DAILY_METRICS_FILE");
+    for (key, _) in std::env::vars_os() {
+        if key.to_string_lossy().starts_with(ENV_PREFIX) {
+            command.env_remove(key);
+        }
+    }
+    let status = command.status()?;
+    if !status.success() {
+        eprintln!("{}", format!("(exited with {})", status).dimmed());
+    }
+    Ok(())
+}
+
+// --- `tour` command: the main features, one at a time, on sample data ---
+pub fn run_tour(options: &GlobalOptions) -> Result<(), AppError> {
+    let dir = std::env::temp_dir().join(format!("dml-tour-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    println!("{}", layout::rule('=').cyan());
+    println!("{}", " Tour ".bold().cyan());
+    println!("{}", layout::rule('=').cyan());
+    println!(
+        "{}",
+        format!(
+            "Each stop runs one command on {} days of sample data in {}.",
+            SAMPLE_DAYS,
+            dir.display()
+        )
+        .dimmed()
+    );
+    let steps = steps();
+    let result = (|| -> Result<(), AppError> {
+        for (index, step) in steps.iter().enumerate() {
+            println!();
+            println!(
+                "{} {}",
+                format!("[{}/{}]", index + 1, steps.len()).cyan(),
+                step.title.bold()
+            );
+            println!("{}", step.about);
+            println!(
+                "{}",
+                format!("$ {} {}", env!("CARGO_PKG_NAME"), shown_args(&step.args)).yellow()
+            );
+            // The sample data has to exist for the other stops, so it's never skipped
+            if index > 0 {
+                const PROMPT: &str = "Run it?";
+                let choice = replay::answer(PROMPT, || {
+                    Select::with_theme(&**THEME)
+                        .with_prompt(PROMPT)
+                        .items(&["Run it", "Skip", "End the tour"])
+                        .default(0)
+                        .interact()
+                })?;
+                match choice {
+                    1 => continue,
+                    2 => break,
+                    _ => {}
+                }
+            }
+            run_step(&dir, &step.args)?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = fs::remove_dir_all(&dir) {
+        eprintln!("Warning: Could not remove {}: {}", dir.display(), e);
+    }
+    result?;
+    println!();
+    println!(
+        "{}",
+        format!(
+            "That's the tour. Start logging with `{}`{}; `--help` lists every command.",
+            env!("CARGO_PKG_NAME"),
+            if options.config_path().exists() {
+                ""
+            } else {
+                " or set things up with `init`"
+            }
+        )
+        .green()
+    );
+    Ok(())
+}
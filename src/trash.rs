@@ -0,0 +1,272 @@
+use crate::audit;
+use crate::cli::{DeleteArgs, TrashAction, TrashArgs};
+use crate::import::{new_timestamps, save_sorted};
+use crate::layout;
+use crate::replay;
+use crate::store;
+use crate::{read_entries, AppError, GlobalOptions, LogEntry, THEME};
+use chrono::{DateTime, NaiveDate, Utc};
+use colored::*;
+use dialoguer::Confirm;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+// --- One `delete` call's worth of rows, restorable as a unit ---
+#[derive(Serialize, Deserialize)]
+struct TrashItem {
+    id: u64,
+    deleted_at: String,
+    entries: Vec<LogEntry>,
+}
+
+// Trash lives next to the data file: daily_metrics.csv -> daily_metrics.trash.jsonl
+fn trash_file(data_file: &str) -> PathBuf {
+    Path::new(data_file).with_extension("trash.jsonl")
+}
+
+fn read_trash(path: &Path) -> Result<Vec<TrashItem>, AppError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut items = Vec::new();
+    for line in fs::read_to_string(path)?.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(item) => items.push(item),
+            Err(e) => eprintln!("Warning: Skipping unreadable trash record: {}", e),
+        }
+    }
+    Ok(items)
+}
+
+fn write_trash(path: &Path, items: &[TrashItem]) -> Result<(), AppError> {
+    let mut text = String::new();
+    for item in items {
+        text.push_str(&serde_json::to_string(item)?);
+        text.push('\n');
+    }
+    store::replace_file(&path.to_string_lossy(), text.as_bytes())
+}
+
+fn describe_entry(entry: &LogEntry) -> String {
+    let remarks = if entry.remarks.trim().is_empty() {
+        String::new()
+    } else {
+        format!("  \"{}\"", entry.remarks)
+    };
+    format!("{} (Day {}){}", entry.timestamp, entry.day_count, remarks)
+}
+
+// --- `delete` command: move entries to the trash ---
+// Selects `last`, every entry on a date (YYYY-MM-DD), or one exact timestamp.
+pub fn run_delete(options: &GlobalOptions, args: &DeleteArgs) -> Result<(), AppError> {
+    let selector = &args.selector;
+
+    let data_file = options.data_file();
+    let entries = read_entries(&data_file)?;
+    let selected: HashSet<usize> = if selector == "last" {
+        entries.len().checked_sub(1).into_iter().collect()
+    } else if let Ok(date) = NaiveDate::parse_from_str(selector, "%Y-%m-%d") {
+        entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.parsed_timestamp().map(|ts| ts.date_naive()) == Some(date))
+            .map(|(i, _)| i)
+            .collect()
+    } else if let Ok(ts) = DateTime::parse_from_rfc3339(selector) {
+        let ts = ts.with_timezone(&Utc);
+        entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.parsed_timestamp() == Some(ts))
+            .map(|(i, _)| i)
+            .collect()
+    } else {
+        return Err(AppError::UsageError(format!(
+            "Can't read '{}': use last, a date (YYYY-MM-DD) or a full timestamp",
+            selector
+        )));
+    };
+    if selected.is_empty() {
+        println!("{}", "No matching entries.".dimmed());
+        return Ok(());
+    }
+
+    let removed: Vec<LogEntry> = entries
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| selected.contains(i))
+        .map(|(_, e)| e)
+        .collect();
+
+    println!("{}", " Moving to trash ".bold().cyan());
+    println!("{}", layout::rule('-').cyan());
+    for entry in &removed {
+        println!("{}", describe_entry(entry));
+    }
+    if options.dry_run {
+        println!(
+            "{}",
+            format!(" Dry run: nothing moved ({} entries)", removed.len())
+                .bold()
+                .yellow()
+        );
+        return Ok(());
+    }
+
+    let count = removed.len();
+    let id = move_to_trash(&data_file, removed, "delete", |_| {})?;
+    println!(
+        "{}",
+        format!(" Moved {} entries to trash (id {})", count, id)
+            .bold()
+            .green()
+    );
+    println!("{}", format!("Undo with: trash restore {}", id).dimmed());
+    Ok(())
+}
+
+// --- Trash `removed`, take it out of the data and apply `change` to the rest, returning
+// the trash item's id ---
+// Trash first, so an interrupted delete never loses rows.
+pub fn move_to_trash(
+    data_file: &str,
+    removed: Vec<LogEntry>,
+    action: &str,
+    mut change: impl FnMut(&mut Vec<LogEntry>),
+) -> Result<u64, AppError> {
+    let trash_path = trash_file(data_file);
+    let id = read_trash(&trash_path)?
+        .iter()
+        .map(|item| item.id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let item = TrashItem {
+        id,
+        deleted_at: Utc::now().to_rfc3339(),
+        entries: removed,
+    };
+    let mut trash = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&trash_path)?;
+    writeln!(trash, "{}", serde_json::to_string(&item)?)?;
+    save_sorted(data_file, action, |entries| {
+        entries.retain(|e| !item.entries.contains(e));
+        change(entries);
+    })?;
+    Ok(id)
+}
+
+// --- `trash` command: list, restore or empty deleted entries ---
+pub fn run_trash(options: &GlobalOptions, args: &TrashArgs) -> Result<(), AppError> {
+    let data_file = options.data_file();
+    let trash_path = trash_file(&data_file);
+    let mut items = read_trash(&trash_path)?;
+
+    match args.action {
+        TrashAction::List => {
+            if items.is_empty() {
+                println!("{}", "Trash is empty.".dimmed());
+                return Ok(());
+            }
+            for item in &items {
+                println!(
+                    "{} {} {}",
+                    format!("[{}]", item.id).yellow(),
+                    item.deleted_at.dimmed(),
+                    format!("{} entries", item.entries.len()).bold()
+                );
+                for entry in &item.entries {
+                    println!("    {}", describe_entry(entry));
+                }
+            }
+        }
+        TrashAction::Restore => {
+            let Some(id) = args.id else {
+                return Err(AppError::UsageError(
+                    "Usage: trash restore <id>".to_string(),
+                ));
+            };
+            let Some(pos) = items.iter().position(|item| item.id == id) else {
+                return Err(AppError::UsageError(format!(
+                    "No trash item with id {}",
+                    id
+                )));
+            };
+            let item = items.remove(pos);
+
+            let total = item.entries.len();
+            let fresh = new_timestamps(&read_entries(&data_file)?, &item.entries);
+            if fresh.len() < total {
+                eprintln!(
+                    "Warning: {} entries already exist again and were not restored.",
+                    total - fresh.len()
+                );
+            }
+            if options.dry_run {
+                println!(
+                    "{}",
+                    format!(" Dry run: would restore {} entries", fresh.len())
+                        .bold()
+                        .yellow()
+                );
+                return Ok(());
+            }
+            let restored = fresh.len();
+            save_sorted(&data_file, "trash restore", |entries| {
+                let fresh = new_timestamps(entries, &item.entries);
+                entries.extend(fresh);
+            })?;
+            write_trash(&trash_path, &items)?;
+            println!(
+                "{}",
+                format!(" Restored {} entries from trash item {}", restored, id)
+                    .bold()
+                    .green()
+            );
+        }
+        TrashAction::Empty => {
+            if items.is_empty() {
+                println!("{}", "Trash is empty.".dimmed());
+                return Ok(());
+            }
+            let count: usize = items.iter().map(|item| item.entries.len()).sum();
+            if options.dry_run {
+                println!(
+                    "{}",
+                    format!(" Dry run: would purge {} entries", count)
+                        .bold()
+                        .yellow()
+                );
+                return Ok(());
+            }
+            let confirmed = args.yes
+                || replay::answer("Permanently delete the trash?", || {
+                    Confirm::with_theme(&**THEME)
+                        .with_prompt(format!("Permanently delete {} trashed entries?", count))
+                        .default(false)
+                        .interact()
+                })?;
+            if !confirmed {
+                println!("{}", "Trash kept.".dimmed());
+                return Ok(());
+            }
+            fs::remove_file(&trash_path)?;
+            audit::record(
+                &data_file,
+                "trash empty",
+                &format!("purged {} entries", count),
+                Vec::new(),
+            )?;
+            println!("{}", format!(" Purged {} entries", count).bold().green());
+        }
+    }
+    Ok(())
+}
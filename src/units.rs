@@ -0,0 +1,90 @@
+use crate::config::Config;
+use crate::schema::metric_info;
+use crate::METRIC_NAMES;
+use std::collections::BTreeMap;
+
+// --- Known units: (aliases, dimension, size in the dimension's base unit) ---
+// Units outside this table are fine as labels but cannot be converted.
+const UNITS: [(&[&str], &str, f64); 11] = [
+    (&["hours", "hour", "h", "hr", "hrs"], "time", 3600.0),
+    (&["minutes", "minute", "min", "mins"], "time", 60.0),
+    (&["seconds", "second", "s", "sec", "secs"], "time", 1.0),
+    (&["kg", "kilograms", "kilogram"], "mass", 1000.0),
+    (&["lbs", "lb", "pounds", "pound"], "mass", 453.592_37),
+    (&["g", "grams", "gram"], "mass", 1.0),
+    (&["mg", "milligrams", "milligram"], "mass", 0.001),
+    (&["km", "kilometers", "kilometres"], "distance", 1000.0),
+    (&["miles", "mile", "mi"], "distance", 1609.344),
+    (&["l", "liters", "litres"], "volume", 1000.0),
+    (&["ml", "milliliters", "millilitres"], "volume", 1.0),
+];
+
+fn lookup(unit: &str) -> Option<(&'static str, f64)> {
+    let unit = unit.trim().to_ascii_lowercase();
+    UNITS
+        .iter()
+        .find(|(aliases, _, _)| aliases.contains(&unit.as_str()))
+        .map(|(_, dimension, size)| (*dimension, *size))
+}
+
+// Multiply a value in `from` by this to get it in `to` (None when the units don't convert)
+pub fn conversion_factor(from: &str, to: &str) -> Option<f64> {
+    if from.trim().eq_ignore_ascii_case(to.trim()) {
+        return Some(1.0);
+    }
+    match (lookup(from)?, lookup(to)?) {
+        ((from_dim, from_size), (to_dim, to_size)) if from_dim == to_dim => {
+            Some(from_size / to_size)
+        }
+        _ => None,
+    }
+}
+
+// --- Units each metric is displayed in, after any `display_unit` conversion ---
+pub struct DisplayUnits {
+    metrics: BTreeMap<&'static str, (f64, String)>, // metric -> (factor, unit shown)
+}
+
+impl DisplayUnits {
+    pub fn from_config(config: &Config) -> DisplayUnits {
+        let mut metrics = BTreeMap::new();
+        for name in METRIC_NAMES {
+            let Some(info) = metric_info(config, name) else {
+                continue;
+            };
+            match (info.unit, info.display_unit) {
+                (Some(unit), Some(display)) => {
+                    // Config validation guarantees the units convert
+                    let factor = conversion_factor(&unit, &display).unwrap_or(1.0);
+                    metrics.insert(name, (factor, display));
+                }
+                (Some(unit), None) => {
+                    metrics.insert(name, (1.0, unit));
+                }
+                _ => {}
+            }
+        }
+        DisplayUnits { metrics }
+    }
+
+    // Convert a stored value into the display unit
+    pub fn convert(&self, metric: &str, value: f64) -> f64 {
+        self.metrics
+            .get(metric)
+            .map_or(value, |(factor, _)| value * factor)
+    }
+
+    // Convert a value typed in the display unit back into the stored unit
+    pub fn to_stored(&self, metric: &str, value: f64) -> f64 {
+        self.metrics
+            .get(metric)
+            .map_or(value, |(factor, _)| value / factor)
+    }
+
+    // Unit label for a metric ("" when it has none)
+    pub fn unit(&self, metric: &str) -> &str {
+        self.metrics
+            .get(metric)
+            .map_or("", |(_, unit)| unit.as_str())
+    }
+}
@@ -0,0 +1,86 @@
+use crate::cli::ViewArgs;
+use crate::export;
+use crate::layout;
+use crate::schema::column_name;
+use crate::units::DisplayUnits;
+use crate::{read_entries, AppError, GlobalOptions, LogEntry, METRIC_NAMES};
+use chrono::Local;
+use colored::*;
+
+const MAX_COLUMN: usize = 8; // Metric headers longer than this are cut, e.g. "intelli…"
+
+// Header for a metric column, cut to fit (the `describe` command has the full names)
+fn header(metric: &str) -> String {
+    let name = column_name(metric);
+    let tail = if layout::compact() { "~" } else { "…" };
+    console::truncate_str(name, MAX_COLUMN, tail).into_owned()
+}
+
+// Ratings are coloured by how good they are; sleep hours are left plain
+fn cell(metric: &str, value: Option<f64>, units: &DisplayUnits, width: usize) -> String {
+    let Some(value) = value else {
+        return format!("{:>width$}", "-").dimmed().to_string();
+    };
+    let shown = (units.convert(metric, value) * 10.0).round() / 10.0;
+    let text = format!("{:>width$}", shown);
+    match metric {
+        "sleep_hours" => text,
+        _ if value >= 7.0 => text.green().to_string(),
+        _ if value <= 4.0 => text.red().to_string(),
+        _ => text,
+    }
+}
+
+fn time(entry: &LogEntry) -> String {
+    entry
+        .parsed_timestamp()
+        .map(|ts| {
+            ts.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| entry.timestamp.clone())
+}
+
+// --- `view` command: the latest entries as a table, newest last ---
+// Remarks come last so a narrow terminal cuts them rather than the numbers.
+pub fn run_view(options: &GlobalOptions, args: &ViewArgs) -> Result<(), AppError> {
+    let units = DisplayUnits::from_config(&options.config);
+    let entries = read_entries(&options.data_file())?;
+    let recent = &entries[entries.len().saturating_sub(args.last)..];
+    // The same rows `export` writes, so scripts see stored units and every column
+    if let Some(format) = options.format.rows() {
+        print!("{}", export::render(recent, format)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("{}", "No entries yet. Run `log` to add one.".dimmed());
+        return Ok(());
+    }
+    let headers: Vec<String> = METRIC_NAMES.iter().map(|m| header(m)).collect();
+    let widths: Vec<usize> = headers
+        .iter()
+        .map(|h| console::measure_text_width(h).max(3))
+        .collect();
+    let time_width = recent.iter().map(|e| time(e).len()).max().unwrap_or(0);
+
+    let mut line = format!("{:<time_width$}", "Time");
+    for (header, width) in headers.iter().zip(&widths) {
+        line.push_str(&format!(" {:>width$}", header));
+    }
+    line.push_str(" Workout  Remarks");
+    layout::row(&line.bold().to_string());
+
+    for entry in recent {
+        let mut line = format!("{:<time_width$}", time(entry)).yellow().to_string();
+        for (metric, width) in METRIC_NAMES.iter().zip(&widths) {
+            line.push(' ');
+            line.push_str(&cell(metric, entry.metric(metric), &units, *width));
+        }
+        let workout = if entry.workout_today { "yes" } else { "" };
+        line.push_str(&format!(" {:<7}  ", workout).green().to_string());
+        line.push_str(&entry.remarks.dimmed().to_string());
+        layout::row(&line);
+    }
+    Ok(())
+}
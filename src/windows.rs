@@ -0,0 +1,99 @@
+use crate::AppError;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// --- Windows support: AppData storage, console colours, toasts and Task Scheduler ---
+// Toasts and scheduled tasks go through PowerShell and schtasks, which every Windows
+// install has, so no extra dependencies are needed.
+const APP_DIR: &str = "daily_metrics_logger"; // Folder under %APPDATA%
+const TASK_NAME: &str = "Daily metrics reminder";
+
+// Toasts need a registered app id; PowerShell's is always there
+const POWERSHELL_APP_ID: &str =
+    "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe";
+
+pub fn detected() -> bool {
+    cfg!(windows)
+}
+
+// %APPDATA%\daily_metrics_logger, where the data lives unless the current directory
+// already has it (older setups kept everything next to the program)
+pub fn app_data_dir() -> Option<PathBuf> {
+    if !detected() {
+        return None;
+    }
+    std::env::var_os("APPDATA")
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(&dir).join(APP_DIR))
+}
+
+// Older consoles (cmd.exe, PowerShell 5) print escape codes literally until asked not to;
+// where that can't be turned on, output stays plain
+pub fn enable_colors() {
+    #[cfg(windows)]
+    if colored::control::set_virtual_terminal(true).is_err() {
+        colored::control::set_override(false);
+    }
+}
+
+fn run(program: &str, args: &[&str], envs: &[(&str, &str)]) -> Result<(), AppError> {
+    let output = Command::new(program)
+        .args(args)
+        .envs(envs.iter().copied())
+        .output()
+        .map_err(|e| {
+            AppError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("{} is not available ({})", program, e),
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(AppError::IoError(std::io::Error::other(format!(
+            "{} failed ({}): {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))));
+    }
+    Ok(())
+}
+
+// Title and text travel in environment variables, so quotes in them can't break the script
+pub fn notify(tag: &str, title: &str, content: &str) -> Result<(), AppError> {
+    let script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null
+$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $xml.GetElementsByTagName('text')
+$text.Item(0).AppendChild($xml.CreateTextNode($env:DML_TOAST_TITLE)) > $null
+$text.Item(1).AppendChild($xml.CreateTextNode($env:DML_TOAST_TEXT)) > $null
+$toast = [Windows.UI.Notifications.ToastNotification]::new($xml)
+$toast.Tag = '{}'
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{}').Show($toast)",
+        tag, POWERSHELL_APP_ID
+    );
+    run(
+        "powershell",
+        &["-NoProfile", "-NonInteractive", "-Command", &script],
+        &[("DML_TOAST_TITLE", title), ("DML_TOAST_TEXT", content)],
+    )
+}
+
+// A daily Task Scheduler entry that runs `remind` for this data file at the given time.
+// Tasks start in System32, so the data file is made absolute first.
+pub fn install_task(data_file: &str, at: &str) -> Result<String, AppError> {
+    let exe = std::env::current_exe()?;
+    let data_file = std::env::current_dir()?.join(data_file);
+    let command = format!(
+        "\"{}\" --data-file \"{}\" remind",
+        exe.display(),
+        data_file.display()
+    );
+    run(
+        "schtasks",
+        &[
+            "/Create", "/F", "/SC", "DAILY", "/ST", at, "/TN", TASK_NAME, "/TR", &command,
+        ],
+        &[],
+    )?;
+    Ok(TASK_NAME.to_string())
+}
@@ -0,0 +1,524 @@
+use crate::import::merge_entries;
+use crate::layout;
+use crate::replay;
+use crate::{metric_range, AppError, GlobalOptions, LogEntry, METRIC_NAMES, THEME};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use colored::*;
+use csv::{ReaderBuilder, StringRecord};
+use dialoguer::{Confirm, Input, Select};
+use std::path::Path;
+
+// Timestamp layouts seen in Google Forms/Sheets response exports
+const FORMS_TIMESTAMP_FORMATS: [&str; 5] = [
+    "%m/%d/%Y %H:%M:%S",
+    "%d/%m/%Y %H:%M:%S",
+    "%Y/%m/%d %I:%M:%S %p",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+];
+
+// Extra layouts offered by the generic wizard (date-only formats import at midday)
+const WIZARD_DATE_FORMATS: [&str; 5] = ["%Y-%m-%d", "%d.%m.%Y", "%m/%d/%Y", "%d/%m/%Y", "rfc3339"];
+
+const SAMPLE_SIZE: usize = 20; // Rows inspected for type inference and format detection
+const PREVIEW_ROWS: usize = 10; // Converted rows shown before confirming an import
+
+// --- Where a source column ends up ---
+#[derive(Clone, Copy, PartialEq)]
+enum Target {
+    Metric(&'static str),
+    Workout,
+    Remarks,
+    Skip,
+}
+
+impl Target {
+    fn label(&self) -> String {
+        match self {
+            Target::Metric(name) => name.to_string(),
+            Target::Workout => "workout_today".to_string(),
+            Target::Remarks => "remarks".to_string(),
+            Target::Skip => "(skip this column)".to_string(),
+        }
+    }
+
+    fn all() -> Vec<Target> {
+        let mut targets: Vec<Target> = METRIC_NAMES.iter().map(|m| Target::Metric(m)).collect();
+        targets.extend([Target::Workout, Target::Remarks, Target::Skip]);
+        targets
+    }
+
+    // Guess a target from the question text of a column
+    fn suggest(header: &str, kind: &ColumnKind) -> Target {
+        let h = header.to_ascii_lowercase();
+        let guess = if h.contains("sleep") && h.contains("quality") {
+            Target::Metric("sleep_quality")
+        } else if h.contains("sleep") && (h.contains("hour") || h.contains("long")) {
+            Target::Metric("sleep_hours")
+        } else if h.contains("workout") || h.contains("work out") || h.contains("exercise") {
+            Target::Workout
+        } else if h.contains("remark") || h.contains("note") || h.contains("comment") {
+            Target::Remarks
+        } else if h.contains("groggy") || h.contains("sleepiness") {
+            Target::Metric("sleepiness")
+        } else if h.contains("zonk") {
+            Target::Metric("zonkedness")
+        } else if h.contains("vibe") || h.contains("mood") {
+            Target::Metric("mid_vibes")
+        } else if let Some(metric) = METRIC_NAMES.iter().find(|m| h.contains(*m)) {
+            Target::Metric(metric)
+        } else {
+            Target::Skip
+        };
+        // Don't suggest a numeric metric for a column that clearly isn't numeric
+        match (guess, kind) {
+            (Target::Metric(_), ColumnKind::Text) => Target::Skip,
+            _ => guess,
+        }
+    }
+}
+
+// --- Inferred type of a source column ---
+enum ColumnKind {
+    Empty,
+    Number { min: f64, max: f64 },
+    Boolean,
+    Text,
+}
+
+impl ColumnKind {
+    fn infer(values: &[&str]) -> ColumnKind {
+        let values: Vec<&str> = values
+            .iter()
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .collect();
+        if values.is_empty() {
+            return ColumnKind::Empty;
+        }
+        let numbers: Vec<f64> = values.iter().filter_map(|v| v.parse().ok()).collect();
+        if numbers.len() == values.len() {
+            return ColumnKind::Number {
+                min: numbers.iter().copied().fold(f64::MAX, f64::min),
+                max: numbers.iter().copied().fold(f64::MIN, f64::max),
+            };
+        }
+        if values.iter().all(|v| parse_bool(v).is_some()) {
+            return ColumnKind::Boolean;
+        }
+        ColumnKind::Text
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            ColumnKind::Empty => "empty".to_string(),
+            ColumnKind::Number { min, max } => format!("number, {} to {}", min, max),
+            ColumnKind::Boolean => "yes/no".to_string(),
+            ColumnKind::Text => "text".to_string(),
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "yes" | "y" | "true" | "1" => Some(true),
+        "no" | "n" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+// --- One mapped column ---
+struct ColumnPlan {
+    index: usize,
+    target: Target,
+    scale: f64,
+}
+
+// --- Timestamp parsing ---
+// Forms exports may end in a zone such as "GMT+2" or "GMT-5:30"; otherwise the
+// time is the form owner's local time.
+pub fn parse_timestamp(value: &str, format: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if format == "rfc3339" {
+        return DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc));
+    }
+    let (text, offset) = match value.rsplit_once(" GMT") {
+        Some((text, zone)) => (text, Some(parse_gmt_offset(zone)?)),
+        None => (value, None),
+    };
+
+    let naive = match NaiveDateTime::parse_from_str(text, format) {
+        Ok(dt) => dt,
+        // Date-only formats land at midday, like other imported days
+        Err(_) => NaiveDate::parse_from_str(text, format)
+            .ok()?
+            .and_time(NaiveTime::from_hms_opt(12, 0, 0)?),
+    };
+    match offset {
+        Some(offset) => offset
+            .from_local_datetime(&naive)
+            .single()
+            .map(|dt| dt.with_timezone(&Utc)),
+        None => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+    }
+}
+
+// "+2", "-5:30" or "" (plain GMT) -> offset from UTC
+fn parse_gmt_offset(zone: &str) -> Option<FixedOffset> {
+    let zone = zone.trim();
+    if zone.is_empty() {
+        return FixedOffset::east_opt(0);
+    }
+    let (sign, rest) = match zone.split_at(1) {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let seconds = hours.parse::<i32>().ok()? * 3600 + minutes.parse::<i32>().ok()? * 60;
+    FixedOffset::east_opt(sign * seconds)
+}
+
+pub fn detect_timestamp_format(samples: &[&str]) -> Option<&'static str> {
+    let formats = FORMS_TIMESTAMP_FORMATS
+        .iter()
+        .chain(WIZARD_DATE_FORMATS.iter());
+    formats.copied().find(|format| {
+        samples
+            .iter()
+            .all(|value| parse_timestamp(value, format).is_some())
+    })
+}
+
+// --- Reading the source file ---
+fn read_csv(path: &Path) -> Result<(Vec<String>, Vec<StringRecord>), AppError> {
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(path)?;
+    let headers = rdr
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let mut rows = Vec::new();
+    for result in rdr.records() {
+        match result {
+            Ok(record) => rows.push(record),
+            Err(e) => eprintln!("Warning: Skipping corrupted CSV record: {}", e),
+        }
+    }
+    Ok((headers, rows))
+}
+
+fn column_values(rows: &[StringRecord], index: usize) -> Vec<&str> {
+    rows.iter().filter_map(|r| r.get(index)).collect()
+}
+
+// --- Interactive mapping of every non-timestamp column ---
+fn plan_columns(
+    headers: &[String],
+    rows: &[StringRecord],
+    timestamp_col: usize,
+) -> Result<Vec<ColumnPlan>, AppError> {
+    let targets = Target::all();
+    let labels: Vec<String> = targets.iter().map(Target::label).collect();
+    let mut plans = Vec::new();
+
+    for (index, header) in headers.iter().enumerate() {
+        if index == timestamp_col {
+            continue;
+        }
+        let values = column_values(rows, index);
+        let sample = &values[..values.len().min(SAMPLE_SIZE)];
+        let kind = ColumnKind::infer(sample);
+        let mut examples: Vec<&str> = Vec::new();
+        for value in sample.iter().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+            if !examples.contains(&value) && examples.len() < 3 {
+                examples.push(value);
+            }
+        }
+
+        println!();
+        println!("{} {}", "Column:".bold(), header.bold().cyan());
+        println!(
+            "  {} {}  {} {}",
+            "type:".dimmed(),
+            kind.describe(),
+            "e.g.".dimmed(),
+            examples.join(" | ")
+        );
+        let suggestion = Target::suggest(header, &kind);
+        let default = targets.iter().position(|t| *t == suggestion).unwrap_or(0);
+        let choice = replay::answer(header, || {
+            Select::with_theme(&**THEME)
+                .with_prompt("Import this column as")
+                .items(&labels)
+                .default(default)
+                .interact()
+        })?;
+        let target = targets[choice];
+
+        let mut scale = 1.0;
+        if let Target::Metric(metric) = target {
+            let (min, max) = metric_range(metric);
+            if let ColumnKind::Number { max: seen_max, .. } = kind {
+                // A 1-5 question mapped onto a 1-10 rating
+                if metric != "sleep_hours"
+                    && seen_max <= 5.0
+                    && replay::answer("Double the 1-5 values?", || {
+                        Confirm::with_theme(&**THEME)
+                            .with_prompt("Values look like a 1-5 scale. Double them to fit 1-10?")
+                            .default(true)
+                            .interact()
+                    })?
+                {
+                    scale = 2.0;
+                }
+            }
+            let out_of_range = values
+                .iter()
+                .filter_map(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| !(min..=max).contains(&(v * scale)))
+                .count();
+            let unreadable = values
+                .iter()
+                .filter(|v| !v.trim().is_empty() && v.trim().parse::<f64>().is_err())
+                .count();
+            if out_of_range > 0 || unreadable > 0 {
+                println!(
+                    "{}",
+                    format!(
+                        "  {} value(s) outside {}..={} and {} non-numeric value(s) will be left empty.",
+                        out_of_range, min, max, unreadable
+                    )
+                    .yellow()
+                );
+            }
+        }
+        if target != Target::Skip {
+            plans.push(ColumnPlan {
+                index,
+                target,
+                scale,
+            });
+        }
+    }
+    Ok(plans)
+}
+
+// --- Turn one source row into a log entry ---
+fn convert_row(row: &StringRecord, timestamp: DateTime<Utc>, plans: &[ColumnPlan]) -> LogEntry {
+    let mut entry = LogEntry::empty_at(timestamp);
+    let mut remarks: Vec<&str> = Vec::new();
+    for plan in plans {
+        let value = row.get(plan.index).unwrap_or("").trim();
+        if value.is_empty() {
+            continue;
+        }
+        match plan.target {
+            Target::Metric(metric) => {
+                if let Ok(number) = value.parse::<f64>() {
+                    let scaled = number * plan.scale;
+                    let (min, max) = metric_range(metric);
+                    if (min..=max).contains(&scaled) {
+                        entry.set_metric(metric, scaled);
+                    }
+                }
+            }
+            Target::Workout => entry.workout_today = parse_bool(value).unwrap_or(false),
+            Target::Remarks => remarks.push(value),
+            Target::Skip => {}
+        }
+    }
+    entry.remarks = remarks.join("; ");
+    entry
+}
+
+fn print_preview(entries: &[LogEntry], plans: &[ColumnPlan], limit: usize) {
+    println!();
+    println!(
+        "{}",
+        format!(" Preview (first {} rows) ", limit).bold().cyan()
+    );
+    println!("{}", layout::rule('-').cyan());
+    for entry in entries.iter().take(limit) {
+        let fields: Vec<String> = plans
+            .iter()
+            .map(|plan| match plan.target {
+                Target::Metric(metric) => format!(
+                    "{}={}",
+                    metric,
+                    entry
+                        .metric(metric)
+                        .map_or("-".to_string(), |v| v.to_string())
+                ),
+                Target::Workout => format!("workout_today={}", entry.workout_today),
+                Target::Remarks => format!("remarks={:?}", entry.remarks),
+                Target::Skip => String::new(),
+            })
+            .collect();
+        println!("{}  {}", entry.timestamp.dimmed(), fields.join(" "));
+    }
+}
+
+// --- `import --google-forms responses.csv` ---
+pub fn run_forms_import(options: &GlobalOptions, path: &Path) -> Result<(), AppError> {
+    let (headers, rows) = read_csv(path)?;
+    let Some(timestamp_col) = headers
+        .iter()
+        .position(|h| h.eq_ignore_ascii_case("timestamp"))
+    else {
+        return Err(AppError::ImportError(
+            "expected a 'Timestamp' column in the Google Forms responses".to_string(),
+        ));
+    };
+    if rows.is_empty() {
+        println!("{}", "The responses file has no rows.".dimmed());
+        return Ok(());
+    }
+
+    let timestamps = column_values(&rows, timestamp_col);
+    let samples: Vec<&str> = timestamps.iter().take(SAMPLE_SIZE).copied().collect();
+    let Some(format) = detect_timestamp_format(&samples) else {
+        return Err(AppError::ImportError(format!(
+            "could not recognise the timestamp format (e.g. '{}')",
+            samples.first().unwrap_or(&"")
+        )));
+    };
+
+    println!("{}", " Google Forms import ".bold().cyan());
+    println!("{}", layout::rule('-').cyan());
+    println!(
+        "{} responses, timestamps read as {}",
+        rows.len().to_string().yellow(),
+        format.dimmed()
+    );
+
+    let plans = plan_columns(&headers, &rows, timestamp_col)?;
+    convert_and_merge(
+        options,
+        &rows,
+        timestamp_col,
+        format,
+        &plans,
+        "Google Forms",
+    )
+}
+
+// --- `import --wizard file.csv`: guided import of an arbitrary CSV ---
+pub fn run_wizard_import(options: &GlobalOptions, path: &Path) -> Result<(), AppError> {
+    let (headers, rows) = read_csv(path)?;
+    if headers.is_empty() || rows.is_empty() {
+        println!("{}", "The file has no data rows.".dimmed());
+        return Ok(());
+    }
+
+    println!("{}", " Import wizard ".bold().cyan());
+    println!("{}", layout::rule('-').cyan());
+    println!(
+        "{} rows, {} columns. First rows:",
+        rows.len().to_string().yellow(),
+        headers.len().to_string().yellow()
+    );
+    println!("{}", headers.join(" | ").bold());
+    for row in rows.iter().take(5) {
+        println!("{}", row.iter().collect::<Vec<_>>().join(" | ").dimmed());
+    }
+    println!();
+
+    // Which column holds the date or timestamp
+    let default_col = headers
+        .iter()
+        .position(|h| {
+            let h = h.to_ascii_lowercase();
+            h.contains("date") || h.contains("time")
+        })
+        .unwrap_or(0);
+    let timestamp_col = replay::answer("Which column holds the date/time?", || {
+        Select::with_theme(&**THEME)
+            .with_prompt("Which column holds the date/time?")
+            .items(&headers)
+            .default(default_col)
+            .interact()
+    })?;
+
+    // How to parse it: suggest a known format, let the user type any chrono format
+    let timestamps = column_values(&rows, timestamp_col);
+    let samples: Vec<&str> = timestamps.iter().take(SAMPLE_SIZE).copied().collect();
+    let detected = detect_timestamp_format(&samples).unwrap_or(WIZARD_DATE_FORMATS[0]);
+    println!(
+        "{}",
+        "Formats use chrono syntax, e.g. %Y-%m-%d or %d.%m.%Y %H:%M; 'rfc3339' for ISO timestamps."
+            .dimmed()
+    );
+    let format: String = replay::answer("Date format", || {
+        Input::with_theme(&**THEME)
+            .with_prompt(format!("Date format (e.g. '{}')", samples[0].trim()))
+            .default(detected.to_string())
+            .validate_with(|input: &String| -> Result<(), String> {
+                let failures = samples
+                    .iter()
+                    .filter(|v| parse_timestamp(v, input).is_none())
+                    .count();
+                if failures == 0 {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{} of {} sample values don't match this format",
+                        failures,
+                        samples.len()
+                    ))
+                }
+            })
+            .interact_text()
+    })?;
+
+    let plans = plan_columns(&headers, &rows, timestamp_col)?;
+    convert_and_merge(options, &rows, timestamp_col, &format, &plans, "Wizard")
+}
+
+// --- Shared tail of the guided importers: convert, preview, confirm, merge ---
+fn convert_and_merge(
+    options: &GlobalOptions,
+    rows: &[StringRecord],
+    timestamp_col: usize,
+    format: &str,
+    plans: &[ColumnPlan],
+    source: &str,
+) -> Result<(), AppError> {
+    if plans.is_empty() {
+        println!("{}", "No columns selected; nothing to import.".dimmed());
+        return Ok(());
+    }
+
+    let mut entries = Vec::new();
+    for row in rows {
+        match row
+            .get(timestamp_col)
+            .and_then(|ts| parse_timestamp(ts, format))
+        {
+            Some(ts) => entries.push(convert_row(row, ts, plans)),
+            None => eprintln!("Warning: Skipping row with unreadable date/time."),
+        }
+    }
+    print_preview(&entries, plans, PREVIEW_ROWS);
+
+    let proceed = replay::answer("Import the entries?", || {
+        Confirm::with_theme(&**THEME)
+            .with_prompt(format!("Import {} entries?", entries.len()))
+            .default(true)
+            .interact()
+    })?;
+    if !proceed {
+        println!("{}", "Import cancelled.".dimmed());
+        return Ok(());
+    }
+    merge_entries(options, entries, source)?;
+    Ok(())
+}